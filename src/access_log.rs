@@ -0,0 +1,75 @@
+use anyhow::Result;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// 请求ID（沿用客户端提供的 X-Request-Id，否则随机生成）
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let provided = headers
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.is_empty() && v.len() <= 128 && v.chars().all(|c| c.is_ascii_graphic()));
+        match provided {
+            Some(id) => Self(id.to_string()),
+            None => Self(uuid::Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// 访问日志记录（每行一个JSON对象）
+#[derive(Debug, Serialize)]
+pub struct AccessLogEntry {
+    pub time: DateTime<Utc>,
+    pub request_id: String,
+    pub principal: String,
+    pub remote_addr: String,
+    pub method: String,
+    pub path: String,
+    pub bucket_name: Option<String>,
+    pub key: Option<String>,
+    pub status: u16,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub latency_ms: f64,
+}
+
+/// JSON访问日志，输出到标准输出或追加到文件
+#[derive(Clone)]
+pub struct AccessLog {
+    sink: Option<Arc<Mutex<Box<dyn Write + Send>>>>,
+}
+
+impl AccessLog {
+    /// target 为 "stdout"、"off" 或日志文件路径
+    pub fn open(target: &str) -> Result<Self> {
+        let sink: Option<Box<dyn Write + Send>> = match target {
+            "off" | "" => None,
+            "stdout" => Some(Box::new(std::io::stdout())),
+            path => Some(Box::new(OpenOptions::new().create(true).append(true).open(path)?)),
+        };
+        Ok(Self { sink: sink.map(|sink| Arc::new(Mutex::new(sink))) })
+    }
+
+    /// 写入一条访问日志（失败只记录警告）
+    pub fn record(&self, entry: &AccessLogEntry) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+
+        let result = serde_json::to_string(entry).map_err(anyhow::Error::from).and_then(|line| {
+            let mut sink = sink.lock().map_err(|_| anyhow::anyhow!("Access log lock poisoned"))?;
+            writeln!(sink, "{}", line)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to write access log: {}", e);
+        }
+    }
+}
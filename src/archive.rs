@@ -0,0 +1,651 @@
+use crate::models::{ArchiveEntryError, ArchiveExtractReport, ObjectMetadata};
+use crate::policy::{PolicyService, Requester};
+use crate::services::{DeduplicationMode, ObjectService, StorageService};
+use crate::utils::{get_mime_type, validate_object_key};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::{DeflateEncoder, GzEncoder};
+use futures_util::Stream;
+use std::cell::Cell;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// 每个归档最多解出的条目数
+pub const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+/// 不使用 ZIP64 时的偏移上限
+const ZIP_MAX_OFFSET: u64 = u32::MAX as u64;
+const TAR_BLOCK: u64 = 512;
+/// 两个文件条目之间允许的扩展头（PAX、GNU 长文件名）总大小，避免恶意的超大头部
+const TAR_MAX_METADATA: u64 = 1024 * 1024;
+
+/// 归档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "zip" => Ok(Self::Zip),
+            "tar" => Ok(Self::Tar),
+            "tar.gz" | "tgz" => Ok(Self::TarGz),
+            other => Err(anyhow!("Unsupported archive format '{}', expected zip, tar or tar.gz", other)),
+        }
+    }
+
+    /// 按文件头识别归档格式
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if data.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::TarGz)
+        } else if data.get(257..262) == Some(b"ustar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Zip => "application/zip",
+            Self::Tar => "application/x-tar",
+            Self::TarGz => "application/gzip",
+        }
+    }
+}
+
+/// 把归档中的条目路径规范化为相对路径。
+/// `sanitize_path` 只折叠 `..`，折叠后的路径仍可能指向前缀之外（如 `../x`），
+/// 这里直接拒绝绝对路径、盘符、`..` 和控制字符，并统一分隔符
+pub fn normalize_entry_path(name: &str) -> Result<String, String> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {
+        return Err("absolute paths are not allowed".to_string());
+    }
+    let mut chars = name.chars();
+    if let (Some(drive), Some(':')) = (chars.next(), chars.next()) {
+        if drive.is_ascii_alphabetic() {
+            return Err("drive-qualified paths are not allowed".to_string());
+        }
+    }
+    if name.chars().any(|c| c.is_control()) {
+        return Err("control characters are not allowed".to_string());
+    }
+
+    let mut components = Vec::new();
+    for component in name.split('/') {
+        match component {
+            "" | "." => continue,
+            ".." => return Err("parent directory references are not allowed".to_string()),
+            component => components.push(component),
+        }
+    }
+    if components.is_empty() {
+        return Err("empty path".to_string());
+    }
+    Ok(components.join("/"))
+}
+
+/// 归档中读出的条目
+enum ArchiveItem {
+    File { name: String, data: Vec<u8> },
+    /// 跳过的条目（链接、设备文件、超过大小限制等）
+    Rejected { name: String, reason: String },
+}
+
+/// 归档服务 - 把上传的 zip/tar/tar.gz 解包为对象，以及把前缀下的对象边生成边下载为 zip/tar.gz
+#[derive(Clone)]
+pub struct ArchiveService {
+    storage: StorageService,
+    object_service: ObjectService,
+    policy_service: PolicyService,
+    max_entry_size: u64,
+}
+
+impl ArchiveService {
+    /// `max_entry_size` 为解包时单个条目的最大字节数（防止压缩炸弹）
+    pub fn new(storage: StorageService, object_service: ObjectService, policy_service: PolicyService, max_entry_size: u64) -> Self {
+        Self { storage, object_service, policy_service, max_entry_size }
+    }
+
+    /// 把归档解包到前缀下（未指定格式时按文件头识别）；请求者无权写入或无法写入的条目记录在 errors 中，其余条目继续写入
+    pub async fn extract(&self, bucket_name: &str, prefix: &str, format: Option<ArchiveFormat>, data: bytes::Bytes, requester: &Requester) -> Result<ArchiveExtractReport> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        let format = match format {
+            Some(format) => format,
+            None => ArchiveFormat::detect(&data).ok_or_else(|| anyhow!("Unrecognized archive format, specify format=zip, tar or tar.gz"))?,
+        };
+
+        // 解压在阻塞线程中进行，条目逐个交给写入端，内存中最多同时保留少量条目
+        let (tx, mut rx) = mpsc::channel::<Result<ArchiveItem>>(2);
+        let max_entry_size = self.max_entry_size;
+        let reader = tokio::task::spawn_blocking(move || {
+            let result = read_archive(format, &data, max_entry_size, &mut |item| {
+                tx.blocking_send(Ok(item)).map_err(|_| anyhow!("Archive extraction was cancelled"))
+            });
+            if let Err(e) = result {
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+
+        let mut report = ArchiveExtractReport {
+            prefix: prefix.to_string(),
+            format: format.name().to_string(),
+            extracted: Vec::new(),
+            errors: Vec::new(),
+            total_bytes: 0,
+        };
+        while let Some(item) = rx.recv().await {
+            let (name, data) = match item? {
+                ArchiveItem::File { name, data } => (name, data),
+                ArchiveItem::Rejected { name, reason } => {
+                    report.errors.push(ArchiveEntryError { path: name, message: reason });
+                    continue;
+                }
+            };
+
+            let key = match normalize_entry_path(&name) {
+                Ok(path) => format!("{}{}", prefix, path),
+                Err(reason) => {
+                    report.errors.push(ArchiveEntryError { path: name, message: format!("Rejected entry path: {}", reason) });
+                    continue;
+                }
+            };
+            if let Err(e) = validate_object_key(&key) {
+                report.errors.push(ArchiveEntryError { path: name, message: e });
+                continue;
+            }
+            if let Some(message) = self.policy_service.authorize_key(requester, "sevino:PutObject", bucket_name, &key).await? {
+                report.errors.push(ArchiveEntryError { path: name, message });
+                continue;
+            }
+
+            let size = data.len() as u64;
+            match self.object_service.put_object_with_deduplication(
+                bucket_name,
+                &key,
+                data,
+                &get_mime_type(&key),
+                Default::default(),
+                DeduplicationMode::Allow,
+            ).await {
+                Ok(object) => {
+                    report.total_bytes += size;
+                    report.extracted.push(object);
+                }
+                Err(e) => report.errors.push(ArchiveEntryError { path: name, message: e.to_string() }),
+            }
+        }
+        reader.await.map_err(|e| anyhow!("Archive reader failed: {}", e))?;
+
+        Ok(report)
+    }
+
+    /// 前缀下请求者有权读取的当前对象（按key排序）；SSE-C 加密的对象没有密钥无法读取，不包含在归档中
+    pub async fn list_prefix(&self, bucket_name: &str, prefix: &str, format: ArchiveFormat, requester: &Requester) -> Result<Vec<ObjectMetadata>> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        let records = self.storage.list_object_metadata(bucket_name).await?
+            .into_iter()
+            .filter(|m| m.key.starts_with(prefix) && m.customer_encryption.is_none())
+            .collect();
+        let current = self.storage.retain_current_records(bucket_name, records).await;
+        let mut records = Vec::with_capacity(current.len());
+        for metadata in current {
+            if self.policy_service.authorize_key(requester, "sevino:GetObject", bucket_name, &metadata.key).await?.is_none() {
+                records.push(metadata);
+            }
+        }
+        records.sort_by(|a, b| a.key.cmp(&b.key));
+
+        if records.len() > MAX_ARCHIVE_ENTRIES {
+            return Err(anyhow!("Prefix contains {} objects, at most {} can be archived at once", records.len(), MAX_ARCHIVE_ENTRIES));
+        }
+        // 不使用 ZIP64：数据按存储方式计算的上限也不能超过 4GB
+        if format == ArchiveFormat::Zip {
+            let total: u64 = records.iter()
+                .map(|m| 30 + 46 + 2 * entry_name(&m.key, prefix).len() as u64 + m.size)
+                .sum();
+            if total > ZIP_MAX_OFFSET {
+                return Err(anyhow!("Prefix is too large for a zip archive, use format=tar.gz instead"));
+            }
+        }
+        Ok(records)
+    }
+
+    /// 边读取对象边生成归档，不在磁盘上暂存；读取失败时中断响应流
+    pub fn stream(&self, bucket_name: &str, prefix: &str, format: ArchiveFormat, records: Vec<ObjectMetadata>) -> impl Stream<Item = std::io::Result<bytes::Bytes>> {
+        let (tx, rx) = mpsc::channel::<std::io::Result<bytes::Bytes>>(4);
+        let object_service = self.object_service.clone();
+        let bucket_name = bucket_name.to_string();
+        let prefix = prefix.to_string();
+
+        tokio::spawn(async move {
+            let mut writer = ArchiveWriter::new(format);
+            for record in records {
+                let chunk = match object_service.get_object(&bucket_name, &record.key).await {
+                    Ok((data, metadata)) => writer.add(&entry_name(&record.key, &prefix), &data, metadata.last_modified),
+                    Err(e) => Err(e),
+                };
+                let chunk = chunk.map_err(|e| std::io::Error::other(format!("Failed to archive '{}': {}", record.key, e)));
+                let failed = chunk.is_err();
+                if tx.send(chunk.map(bytes::Bytes::from)).await.is_err() || failed {
+                    return;
+                }
+            }
+            let chunk = writer.finish().map_err(|e| std::io::Error::other(e.to_string()));
+            let _ = tx.send(chunk.map(bytes::Bytes::from)).await;
+        });
+
+        futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        })
+    }
+}
+
+/// 归档中的条目名：去掉前缀后的key；key 等于前缀时使用最后一段
+fn entry_name(key: &str, prefix: &str) -> String {
+    let name = key.strip_prefix(prefix).unwrap_or(key).trim_start_matches('/');
+    if name.is_empty() {
+        key.rsplit('/').find(|s| !s.is_empty()).unwrap_or(key).to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// 读取归档的全部条目（目录条目被忽略）
+fn read_archive(format: ArchiveFormat, data: &[u8], max_entry_size: u64, emit: &mut dyn FnMut(ArchiveItem) -> Result<()>) -> Result<()> {
+    match format {
+        ArchiveFormat::Zip => read_zip(data, max_entry_size, emit),
+        ArchiveFormat::Tar => read_tar(data, max_entry_size, emit),
+        ArchiveFormat::TarGz => read_tar(GzDecoder::new(data), max_entry_size, emit),
+    }
+}
+
+fn read_zip(data: &[u8], max_entry_size: u64, emit: &mut dyn FnMut(ArchiveItem) -> Result<()>) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|e| anyhow!("Invalid zip archive: {}", e))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err(anyhow!("Archive contains {} entries, at most {} are allowed", archive.len(), MAX_ARCHIVE_ENTRIES));
+    }
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| anyhow!("Invalid zip archive: {}", e))?;
+        let name = file.name().to_string();
+        if file.is_dir() {
+            continue;
+        }
+        // 符号链接（Unix 模式 S_IFLNK）
+        if file.unix_mode().is_some_and(|mode| mode & 0o170000 == 0o120000) {
+            emit(ArchiveItem::Rejected { name, reason: "Symbolic links are not supported".to_string() })?;
+            continue;
+        }
+        if file.size() > max_entry_size {
+            emit(ArchiveItem::Rejected { name, reason: format!("Entry exceeds the maximum size of {} bytes", max_entry_size) })?;
+            continue;
+        }
+
+        // 声明的大小可能与实际解压结果不符，读取时再限制一次
+        let mut content = Vec::with_capacity(file.size() as usize);
+        (&mut file).take(max_entry_size + 1).read_to_end(&mut content)
+            .map_err(|e| anyhow!("Failed to read zip entry '{}': {}", name, e))?;
+        if content.len() as u64 > max_entry_size {
+            emit(ArchiveItem::Rejected { name, reason: format!("Entry exceeds the maximum size of {} bytes", max_entry_size) })?;
+            continue;
+        }
+        emit(ArchiveItem::File { name, data: content })?;
+    }
+    Ok(())
+}
+
+/// 读取 ustar/GNU/PAX 格式的 tar 流
+fn read_tar(reader: impl Read, max_entry_size: u64, emit: &mut dyn FnMut(ArchiveItem) -> Result<()>) -> Result<()> {
+    let budget = Rc::new(Cell::new(TAR_MAX_METADATA));
+    let mut archive = tar::Archive::new(BudgetReader { inner: reader, budget: budget.clone() });
+    let mut entries = 0;
+
+    for entry in archive.entries().map_err(|e| anyhow!("Invalid tar archive: {}", e))? {
+        let mut entry = entry.map_err(|e| anyhow!("Invalid tar archive: {}", e))?;
+        let size = entry.size();
+        // 下一个条目之前可以读取本条目的数据（含块填充）、头部和扩展头
+        budget.set(size.div_ceil(TAR_BLOCK) * TAR_BLOCK + TAR_BLOCK + TAR_MAX_METADATA);
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+
+        match entry.header().entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                entries += 1;
+                if entries > MAX_ARCHIVE_ENTRIES {
+                    return Err(anyhow!("Archive contains more than {} entries", MAX_ARCHIVE_ENTRIES));
+                }
+                if name.ends_with('/') {
+                    continue;
+                }
+                if size > max_entry_size {
+                    emit(ArchiveItem::Rejected { name, reason: format!("Entry exceeds the maximum size of {} bytes", max_entry_size) })?;
+                    continue;
+                }
+                let mut content = Vec::with_capacity(size as usize);
+                entry.read_to_end(&mut content).map_err(|e| anyhow!("Failed to read tar entry '{}': {}", name, e))?;
+                if content.len() as u64 != size {
+                    return Err(anyhow!("Unexpected end of tar archive"));
+                }
+                emit(ArchiveItem::File { name, data: content })?;
+            }
+            tar::EntryType::Directory | tar::EntryType::XGlobalHeader => {}
+            kind => {
+                let reason = match kind {
+                    tar::EntryType::Link => "Hard links are not supported",
+                    tar::EntryType::Symlink => "Symbolic links are not supported",
+                    _ => "Special files are not supported",
+                };
+                emit(ArchiveItem::Rejected { name, reason: reason.to_string() })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 限制两次重置之间可读取的字节数：tar 会把扩展头整个读入内存，压缩的恶意归档可以声明极大的扩展头
+struct BudgetReader<R> {
+    inner: R,
+    budget: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for BudgetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.budget.get();
+        if remaining == 0 {
+            return Err(std::io::Error::other("Tar extended header is too large"));
+        }
+        let limit = buf.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..limit])?;
+        self.budget.set(remaining - n as u64);
+        Ok(n)
+    }
+}
+
+/// 流式归档写入器：每添加一个条目返回可以立即发送的字节
+enum ArchiveWriter {
+    Zip(StreamingZip),
+    Tar(tar::Builder<Vec<u8>>),
+    TarGz(tar::Builder<GzEncoder<Vec<u8>>>),
+}
+
+impl ArchiveWriter {
+    fn new(format: ArchiveFormat) -> Self {
+        match format {
+            ArchiveFormat::Zip => Self::Zip(StreamingZip::new()),
+            ArchiveFormat::Tar => Self::Tar(tar::Builder::new(Vec::new())),
+            ArchiveFormat::TarGz => Self::TarGz(tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()))),
+        }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8], modified: DateTime<Utc>) -> Result<Vec<u8>> {
+        match self {
+            Self::Zip(writer) => writer.add(name, data, modified),
+            Self::Tar(builder) => {
+                builder.append_data(&mut tar_header(data.len() as u64, modified), name, data)?;
+                Ok(std::mem::take(builder.get_mut()))
+            }
+            Self::TarGz(builder) => {
+                builder.append_data(&mut tar_header(data.len() as u64, modified), name, data)?;
+                Ok(std::mem::take(builder.get_mut().get_mut()))
+            }
+        }
+    }
+
+    fn finish(self) -> Result<Vec<u8>> {
+        match self {
+            Self::Zip(writer) => writer.finish(),
+            Self::Tar(builder) => Ok(builder.into_inner()?),
+            Self::TarGz(builder) => Ok(builder.into_inner()?.finish()?),
+        }
+    }
+}
+
+/// GNU 格式的文件头：超长名称写为 GNU 长文件名扩展，大小不受 ustar 的 8GB 限制
+fn tar_header(size: u64, modified: DateTime<Utc>) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(modified.timestamp().max(0) as u64);
+    header
+}
+
+/// 边生成边发送的 zip：`zip::ZipWriter` 完成一个条目时会回到其本地头写入 CRC 和大小，
+/// 因此每个条目在下一个条目开始后才发送
+struct StreamingZip {
+    writer: zip::ZipWriter<ZipSink>,
+    sink: ZipSink,
+}
+
+impl StreamingZip {
+    fn new() -> Self {
+        let sink = ZipSink::default();
+        Self { writer: zip::ZipWriter::new(sink.clone()), sink }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8], modified: DateTime<Utc>) -> Result<Vec<u8>> {
+        let entry_start = self.sink.end();
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip_method(data))
+            .last_modified_time(zip_time(modified))
+            .unix_permissions(0o644);
+        self.writer.start_file(name, options)?;
+        self.writer.write_all(data)?;
+        Ok(self.sink.take_before(entry_start))
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>> {
+        self.writer.finish()?;
+        Ok(self.sink.take_before(self.sink.end()))
+    }
+}
+
+/// zip 写入器的输出：只保留尚未发送的部分，允许在这部分之内回写
+#[derive(Clone, Default)]
+struct ZipSink(Arc<Mutex<ZipBuffer>>);
+
+#[derive(Default)]
+struct ZipBuffer {
+    /// buf[0] 在归档中的偏移（之前的字节都已发送）
+    sent: u64,
+    buf: Vec<u8>,
+    position: u64,
+}
+
+impl ZipSink {
+    fn end(&self) -> u64 {
+        let buffer = self.0.lock().unwrap();
+        buffer.sent + buffer.buf.len() as u64
+    }
+
+    /// 取出 offset 之前的字节用于发送
+    fn take_before(&self, offset: u64) -> Vec<u8> {
+        let mut buffer = self.0.lock().unwrap();
+        let count = (offset.saturating_sub(buffer.sent) as usize).min(buffer.buf.len());
+        buffer.sent += count as u64;
+        buffer.buf.drain(..count).collect()
+    }
+}
+
+impl Write for ZipSink {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let mut buffer = self.0.lock().unwrap();
+        let start = (buffer.position - buffer.sent) as usize;
+        let end = start + data.len();
+        if end > buffer.buf.len() {
+            buffer.buf.resize(end, 0);
+        }
+        buffer.buf[start..end].copy_from_slice(data);
+        buffer.position += data.len() as u64;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for ZipSink {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let mut buffer = self.0.lock().unwrap();
+        let end = buffer.sent + buffer.buf.len() as u64;
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => end.checked_add_signed(delta),
+            SeekFrom::Current(delta) => buffer.position.checked_add_signed(delta),
+        };
+        match target {
+            Some(target) if (buffer.sent..=end).contains(&target) => {
+                buffer.position = target;
+                Ok(target)
+            }
+            _ => Err(std::io::Error::other("Cannot seek into the part of the zip archive that was already sent")),
+        }
+    }
+}
+
+/// 已压缩的内容（图片、视频等）直接存储：用开头一段数据试压缩，压不小就不压缩
+fn zip_method(data: &[u8]) -> zip::CompressionMethod {
+    let sample = &data[..data.len().min(64 * 1024)];
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    match encoder.write_all(sample).and_then(|_| encoder.finish()) {
+        Ok(deflated) if deflated.len() < sample.len() => zip::CompressionMethod::Deflated,
+        _ => zip::CompressionMethod::Stored,
+    }
+}
+
+/// MS-DOS 日期时间（1980 年之前的时间按 1980-01-01 处理）
+fn zip_time(time: DateTime<Utc>) -> zip::DateTime {
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).unwrap_or(0),
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    ).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_entry_path_keeps_relative_paths() {
+        assert_eq!(normalize_entry_path("docs/readme.txt").unwrap(), "docs/readme.txt");
+        assert_eq!(normalize_entry_path("./docs//a/./b.txt").unwrap(), "docs/a/b.txt");
+        assert_eq!(normalize_entry_path("docs/dir/").unwrap(), "docs/dir");
+        assert_eq!(normalize_entry_path("..data/x..y").unwrap(), "..data/x..y");
+    }
+
+    #[test]
+    fn normalize_entry_path_rejects_parent_references() {
+        assert!(normalize_entry_path("../etc/passwd").is_err());
+        assert!(normalize_entry_path("docs/../../etc/passwd").is_err());
+        assert!(normalize_entry_path("docs/../readme.txt").is_err());
+        assert!(normalize_entry_path("..").is_err());
+    }
+
+    #[test]
+    fn normalize_entry_path_rejects_absolute_paths() {
+        assert!(normalize_entry_path("/etc/passwd").is_err());
+        assert!(normalize_entry_path("//server/share/file").is_err());
+        assert!(normalize_entry_path("C:/Windows/system.ini").is_err());
+        assert!(normalize_entry_path("c:file.txt").is_err());
+    }
+
+    #[test]
+    fn normalize_entry_path_converts_backslashes() {
+        assert_eq!(normalize_entry_path("docs\\sub\\file.txt").unwrap(), "docs/sub/file.txt");
+        assert!(normalize_entry_path("..\\..\\windows\\win.ini").is_err());
+        assert!(normalize_entry_path("docs\\..\\..\\secret").is_err());
+        assert!(normalize_entry_path("\\\\server\\share\\file").is_err());
+        assert!(normalize_entry_path("C:\\Windows\\system.ini").is_err());
+    }
+
+    #[test]
+    fn normalize_entry_path_rejects_empty_and_control_characters() {
+        assert!(normalize_entry_path("").is_err());
+        assert!(normalize_entry_path("./").is_err());
+        assert!(normalize_entry_path("a\u{0}b").is_err());
+        assert!(normalize_entry_path("line\nbreak").is_err());
+    }
+
+    fn round_trip(format: ArchiveFormat, entries: &[(&str, Vec<u8>)]) -> Vec<(String, Vec<u8>)> {
+        let mut writer = ArchiveWriter::new(format);
+        let mut archive = Vec::new();
+        for (name, data) in entries {
+            archive.extend(writer.add(name, data, Utc::now()).unwrap());
+        }
+        archive.extend(writer.finish().unwrap());
+        assert_eq!(ArchiveFormat::detect(&archive), Some(format));
+
+        let mut files = Vec::new();
+        read_archive(format, &archive, 1024 * 1024, &mut |item| {
+            match item {
+                ArchiveItem::File { name, data } => files.push((name, data)),
+                ArchiveItem::Rejected { name, reason } => panic!("{} rejected: {}", name, reason),
+            }
+            Ok(())
+        }).unwrap();
+        files
+    }
+
+    #[test]
+    fn archives_round_trip() {
+        let long_name = format!("{}/{}.txt", "d".repeat(120), "f".repeat(110));
+        let entries = vec![
+            ("a.txt", b"hello".to_vec()),
+            ("empty", Vec::new()),
+            ("图片/数据.bin", (0..=255u8).cycle().take(100_000).collect()),
+            (long_name.as_str(), b"long".repeat(1000)),
+        ];
+        for format in [ArchiveFormat::Zip, ArchiveFormat::Tar, ArchiveFormat::TarGz] {
+            let files = round_trip(format, &entries);
+            assert_eq!(files.len(), entries.len(), "{:?}", format);
+            for ((name, data), (expected_name, expected_data)) in files.iter().zip(&entries) {
+                assert_eq!(name, expected_name, "{:?}", format);
+                assert_eq!(data, expected_data, "{:?}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_tar_entries_are_rejected() {
+        let mut writer = ArchiveWriter::new(ArchiveFormat::Tar);
+        let mut archive = writer.add("big", &[1u8; 2000], Utc::now()).unwrap();
+        archive.extend(writer.add("small", b"ok", Utc::now()).unwrap());
+        archive.extend(writer.finish().unwrap());
+
+        let mut items = Vec::new();
+        read_archive(ArchiveFormat::Tar, &archive, 1000, &mut |item| {
+            items.push(match item {
+                ArchiveItem::File { name, .. } => format!("file {}", name),
+                ArchiveItem::Rejected { name, .. } => format!("rejected {}", name),
+            });
+            Ok(())
+        }).unwrap();
+        assert_eq!(items, ["rejected big", "file small"]);
+    }
+}
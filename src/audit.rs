@@ -0,0 +1,395 @@
+use crate::utils::sha256_hash;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 当前审计日志文件名（位于 {data_dir}/.sevino.audit 目录下）
+const AUDIT_LOG_FILE: &str = "audit.log";
+/// 哈希链起点
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// 后台任务（如生命周期规则）的操作主体
+pub const SYSTEM_PRINCIPAL: &str = "system";
+
+/// 请求上下文（由HTTP中间件设置，供审计日志记录操作者）
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub request_id: String,
+    pub principal: String,
+}
+
+tokio::task_local! {
+    static REQUEST_CONTEXT: RequestContext;
+}
+
+/// 在请求上下文中执行
+pub async fn with_request_context<F: Future>(context: RequestContext, future: F) -> F::Output {
+    REQUEST_CONTEXT.scope(context, future).await
+}
+
+/// 审计记录内容（哈希覆盖除 hash 以外的所有字段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuditEntry {
+    seq: u64,
+    time: DateTime<Utc>,
+    request_id: Option<String>,
+    principal: String,
+    action: String,
+    bucket_name: String,
+    key: Option<String>,
+    version_id: Option<String>,
+    details: Option<String>,
+    prev_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuditRecord {
+    #[serde(flatten)]
+    entry: AuditEntry,
+    hash: String,
+}
+
+impl AuditEntry {
+    fn hash(&self) -> Result<String> {
+        Ok(sha256_hash(&serde_json::to_vec(self)?))
+    }
+}
+
+/// 审计日志写入状态
+struct AuditState {
+    last_seq: u64,
+    last_hash: String,
+    /// 当前文件的大小和第一条记录的序号
+    current_size: u64,
+    current_first_seq: Option<u64>,
+}
+
+/// 哈希链审计日志：每条记录包含上一条记录的哈希，当前文件超过大小上限时轮转为 audit-{首条序号}.log
+#[derive(Clone)]
+pub struct AuditLog {
+    dir: PathBuf,
+    max_bytes: u64,
+    state: Arc<Mutex<AuditState>>,
+}
+
+/// 审计日志校验结果
+#[derive(Debug, Default)]
+pub struct AuditVerification {
+    pub files: usize,
+    pub entries: u64,
+    /// 第一处不一致（文件、行号、原因）
+    pub error: Option<String>,
+}
+
+impl AuditLog {
+    /// 打开审计日志，从最后一条记录恢复哈希链
+    pub fn open(data_dir: &str, max_bytes: u64) -> Result<Self> {
+        let dir = PathBuf::from(data_dir).join(".sevino.audit");
+        fs::create_dir_all(&dir)?;
+
+        let mut state = AuditState {
+            last_seq: 0,
+            last_hash: GENESIS_HASH.to_string(),
+            current_size: 0,
+            current_first_seq: None,
+        };
+
+        // 从最后一个非空文件恢复链尾；无法解析的行（如写入中断）留给 verify 报告
+        let current = dir.join(AUDIT_LOG_FILE);
+        for path in log_files(&dir)?.into_iter().rev() {
+            let records = read_records(&path)?;
+            if path == current {
+                state.current_size = fs::metadata(&current)?.len();
+                state.current_first_seq = records.first().map(|r| r.entry.seq);
+            }
+            if let Some(last) = records.last() {
+                state.last_seq = last.entry.seq;
+                state.last_hash = last.hash.clone();
+                break;
+            }
+        }
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// 追加一条审计记录（操作者取自当前请求上下文，没有上下文时为 system）
+    pub fn record(&self, action: &str, bucket_name: &str, key: Option<&str>, version_id: Option<&str>, details: Option<String>) -> Result<()> {
+        let context = REQUEST_CONTEXT.try_with(|context| context.clone()).ok();
+        let mut state = self.state.lock().map_err(|_| anyhow!("Audit log lock poisoned"))?;
+
+        let entry = AuditEntry {
+            seq: state.last_seq + 1,
+            time: Utc::now(),
+            request_id: context.as_ref().map(|c| c.request_id.clone()),
+            principal: context.map(|c| c.principal).unwrap_or_else(|| SYSTEM_PRINCIPAL.to_string()),
+            action: action.to_string(),
+            bucket_name: bucket_name.to_string(),
+            key: key.map(str::to_string),
+            version_id: version_id.map(str::to_string),
+            details,
+            prev_hash: state.last_hash.clone(),
+        };
+        let record = AuditRecord { hash: entry.hash()?, entry };
+        let line = format!("{}\n", serde_json::to_string(&record)?);
+
+        let current = self.dir.join(AUDIT_LOG_FILE);
+        if state.current_size > 0 && state.current_size + line.len() as u64 > self.max_bytes {
+            let first_seq = state.current_first_seq.unwrap_or(record.entry.seq);
+            fs::rename(&current, self.dir.join(format!("audit-{:012}.log", first_seq)))?;
+            state.current_size = 0;
+            state.current_first_seq = None;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&current)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+
+        state.current_size += line.len() as u64;
+        state.current_first_seq.get_or_insert(record.entry.seq);
+        state.last_seq = record.entry.seq;
+        state.last_hash = record.hash;
+        Ok(())
+    }
+}
+
+/// 校验 {data_dir}/.sevino.audit 下所有审计日志的哈希链
+pub fn verify(data_dir: &str) -> Result<AuditVerification> {
+    let dir = PathBuf::from(data_dir).join(".sevino.audit");
+    let mut verification = AuditVerification::default();
+    let mut expected_seq = 1;
+    let mut expected_prev = GENESIS_HASH.to_string();
+
+    for path in log_files(&dir)? {
+        verification.files += 1;
+        let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let reader = BufReader::new(File::open(&path)?);
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let location = format!("{}:{}", file_name, index + 1);
+            let record: AuditRecord = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(e) => {
+                    verification.error = Some(format!("{}: unreadable entry ({})", location, e));
+                    return Ok(verification);
+                }
+            };
+
+            let problem = if record.entry.seq != expected_seq {
+                Some(format!("expected sequence {} but found {}", expected_seq, record.entry.seq))
+            } else if record.entry.prev_hash != expected_prev {
+                Some("previous hash does not match the preceding entry".to_string())
+            } else if record.entry.hash()? != record.hash {
+                Some("entry hash does not match its contents".to_string())
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                verification.error = Some(format!("{}: {}", location, problem));
+                return Ok(verification);
+            }
+
+            verification.entries += 1;
+            expected_seq += 1;
+            expected_prev = record.hash;
+        }
+    }
+
+    Ok(verification)
+}
+
+/// 按时间顺序列出审计日志文件（已轮转的文件在前，当前文件最后）
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut rotated: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with("audit-") && n.ends_with(".log"))
+                .unwrap_or(false)
+        })
+        .collect();
+    // 文件名中的序号定长补零，按名称排序即按序号排序
+    rotated.sort();
+
+    let current = dir.join(AUDIT_LOG_FILE);
+    if current.exists() {
+        rotated.push(current);
+    }
+    Ok(rotated)
+}
+
+fn read_records(path: &Path) -> Result<Vec<AuditRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        if let Ok(record) = serde_json::from_str(&line?) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dir() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().into_owned();
+        (dir, path)
+    }
+
+    fn record_actions(log: &AuditLog, actions: &[&str]) {
+        for action in actions {
+            log.record(action, "bucket", Some("key"), None, None).unwrap();
+        }
+    }
+
+    fn current_log(data_dir: &str) -> PathBuf {
+        PathBuf::from(data_dir).join(".sevino.audit").join(AUDIT_LOG_FILE)
+    }
+
+    fn rewrite_current(data_dir: &str, change: impl FnOnce(Vec<String>) -> Vec<String>) {
+        let path = current_log(data_dir);
+        let lines = fs::read_to_string(&path).unwrap().lines().map(str::to_string).collect();
+        fs::write(&path, change(lines).join("\n") + "\n").unwrap();
+    }
+
+    #[test]
+    fn verify_accepts_an_intact_chain() {
+        let (_dir, data_dir) = data_dir();
+        let log = AuditLog::open(&data_dir, 1024 * 1024).unwrap();
+        record_actions(&log, &["CreateBucket", "PutObject", "DeleteObject"]);
+
+        let verification = verify(&data_dir).unwrap();
+        assert_eq!(verification.files, 1);
+        assert_eq!(verification.entries, 3);
+        assert!(verification.error.is_none());
+
+        let records = read_records(&current_log(&data_dir)).unwrap();
+        assert_eq!(records[0].entry.prev_hash, GENESIS_HASH);
+        assert_eq!(records[0].entry.principal, SYSTEM_PRINCIPAL);
+        assert_eq!(records[1].entry.prev_hash, records[0].hash);
+        assert_eq!(records[2].entry.seq, 3);
+    }
+
+    #[test]
+    fn verify_of_missing_directory_is_empty() {
+        let (_dir, data_dir) = data_dir();
+        let verification = verify(&data_dir).unwrap();
+        assert_eq!((verification.files, verification.entries), (0, 0));
+        assert!(verification.error.is_none());
+    }
+
+    #[test]
+    fn rotation_continues_the_chain_across_files() {
+        let (_dir, data_dir) = data_dir();
+        // 每条记录约300字节，每个文件只能放下一到两条
+        let log = AuditLog::open(&data_dir, 600).unwrap();
+        record_actions(&log, &["A", "B", "C", "D", "E"]);
+
+        let files = log_files(&PathBuf::from(&data_dir).join(".sevino.audit")).unwrap();
+        assert!(files.len() >= 3);
+        assert!(files[0].file_name().unwrap().to_str().unwrap().starts_with("audit-000000000001"));
+        assert_eq!(files.last().unwrap(), &current_log(&data_dir));
+
+        let verification = verify(&data_dir).unwrap();
+        assert_eq!(verification.files, files.len());
+        assert_eq!(verification.entries, 5);
+        assert!(verification.error.is_none());
+    }
+
+    #[test]
+    fn reopening_resumes_the_chain() {
+        let (_dir, data_dir) = data_dir();
+        record_actions(&AuditLog::open(&data_dir, 600).unwrap(), &["A", "B", "C"]);
+        record_actions(&AuditLog::open(&data_dir, 600).unwrap(), &["D", "E"]);
+
+        let verification = verify(&data_dir).unwrap();
+        assert_eq!(verification.entries, 5);
+        assert!(verification.error.is_none());
+    }
+
+    #[test]
+    fn verify_detects_modified_entries() {
+        let (_dir, data_dir) = data_dir();
+        record_actions(&AuditLog::open(&data_dir, 1024 * 1024).unwrap(), &["A", "B", "C"]);
+        rewrite_current(&data_dir, |mut lines| {
+            lines[1] = lines[1].replace("\"action\":\"B\"", "\"action\":\"X\"");
+            lines
+        });
+
+        let verification = verify(&data_dir).unwrap();
+        assert_eq!(verification.entries, 1);
+        assert_eq!(verification.error.unwrap(), "audit.log:2: entry hash does not match its contents");
+    }
+
+    #[test]
+    fn verify_detects_removed_entries() {
+        let (_dir, data_dir) = data_dir();
+        record_actions(&AuditLog::open(&data_dir, 1024 * 1024).unwrap(), &["A", "B", "C"]);
+        rewrite_current(&data_dir, |mut lines| {
+            lines.remove(1);
+            lines
+        });
+
+        let error = verify(&data_dir).unwrap().error.unwrap();
+        assert_eq!(error, "audit.log:2: expected sequence 2 but found 3");
+    }
+
+    #[test]
+    fn verify_detects_rehashed_entries_that_break_the_link() {
+        let (_dir, data_dir) = data_dir();
+        record_actions(&AuditLog::open(&data_dir, 1024 * 1024).unwrap(), &["A", "B"]);
+        // 修改第一条记录并重新计算它的哈希，第二条记录的 prev_hash 不再匹配
+        rewrite_current(&data_dir, |mut lines| {
+            let mut record: AuditRecord = serde_json::from_str(&lines[0]).unwrap();
+            record.entry.principal = "mallory".to_string();
+            record.hash = record.entry.hash().unwrap();
+            lines[0] = serde_json::to_string(&record).unwrap();
+            lines
+        });
+
+        let error = verify(&data_dir).unwrap().error.unwrap();
+        assert_eq!(error, "audit.log:2: previous hash does not match the preceding entry");
+    }
+
+    #[test]
+    fn verify_reports_unreadable_lines() {
+        let (_dir, data_dir) = data_dir();
+        record_actions(&AuditLog::open(&data_dir, 1024 * 1024).unwrap(), &["A"]);
+        rewrite_current(&data_dir, |mut lines| {
+            lines.push("{\"seq\":2,".to_string());
+            lines
+        });
+
+        let error = verify(&data_dir).unwrap().error.unwrap();
+        assert!(error.starts_with("audit.log:2: unreadable entry"));
+    }
+
+    #[tokio::test]
+    async fn records_use_the_request_context() {
+        let (_dir, data_dir) = data_dir();
+        let log = AuditLog::open(&data_dir, 1024 * 1024).unwrap();
+        let context = RequestContext { request_id: "req-1".to_string(), principal: "alice".to_string() };
+        with_request_context(context, async { record_actions(&log, &["PutObject"]) }).await;
+
+        let record = &read_records(&current_log(&data_dir)).unwrap()[0];
+        assert_eq!(record.entry.principal, "alice");
+        assert_eq!(record.entry.request_id.as_deref(), Some("req-1"));
+    }
+}
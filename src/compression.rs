@@ -0,0 +1,91 @@
+use crate::models::{CompressionAlgorithm, CompressionConfiguration, ObjectCompression};
+use crate::utils::wildcard_match;
+use anyhow::{Result, anyhow};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{Read, Write};
+
+/// 桶压缩配置文件名（位于 .sevino.meta 目录下）
+pub const COMPRESSION_CONFIG: &str = "compression";
+
+/// 校验桶压缩配置
+pub fn validate_configuration(config: &CompressionConfiguration) -> Result<()> {
+    if let Some(level) = config.level {
+        let valid = match config.algorithm {
+            CompressionAlgorithm::Off => true,
+            CompressionAlgorithm::Gzip => (0..=9).contains(&level),
+            CompressionAlgorithm::Zstd => (1..=22).contains(&level),
+        };
+        if !valid {
+            return Err(anyhow!("Invalid compression level {} for {:?}", level, config.algorithm));
+        }
+    }
+
+    for pattern in config.include_content_types.iter().chain(&config.exclude_content_types) {
+        if pattern.trim().is_empty() {
+            return Err(anyhow!("Content type patterns must not be empty"));
+        }
+    }
+
+    Ok(())
+}
+
+/// 判断给定内容类型的对象是否需要压缩
+pub fn should_compress(config: &CompressionConfiguration, content_type: &str) -> bool {
+    if config.algorithm == CompressionAlgorithm::Off {
+        return false;
+    }
+
+    // 忽略 charset 等参数
+    let content_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    let matches = |pattern: &String| wildcard_match(&pattern.to_ascii_lowercase(), &content_type);
+
+    if config.exclude_content_types.iter().any(matches) {
+        return false;
+    }
+    config.include_content_types.is_empty() || config.include_content_types.iter().any(matches)
+}
+
+/// 压缩数据；压缩后没有变小时返回 None，按原样存储
+pub fn compress(config: &CompressionConfiguration, data: &[u8]) -> Result<Option<(Vec<u8>, ObjectCompression)>> {
+    let compressed = match config.algorithm {
+        CompressionAlgorithm::Off => return Ok(None),
+        CompressionAlgorithm::Gzip => {
+            let level = config.level.map(|l| Compression::new(l as u32)).unwrap_or_default();
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionAlgorithm::Zstd => zstd::encode_all(data, config.level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))?,
+    };
+
+    if compressed.len() >= data.len() {
+        return Ok(None);
+    }
+
+    let info = ObjectCompression {
+        algorithm: config.algorithm,
+        compressed_size: compressed.len() as u64,
+    };
+    Ok(Some((compressed, info)))
+}
+
+/// 解压数据并校验解压后的大小
+pub fn decompress(info: &ObjectCompression, data: &[u8], expected_size: u64) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(expected_size as usize);
+    match info.algorithm {
+        CompressionAlgorithm::Off => output.extend_from_slice(data),
+        CompressionAlgorithm::Gzip => {
+            GzDecoder::new(data).read_to_end(&mut output)?;
+        }
+        CompressionAlgorithm::Zstd => {
+            zstd::stream::read::Decoder::new(data)?.read_to_end(&mut output)?;
+        }
+    }
+
+    if output.len() as u64 != expected_size {
+        return Err(anyhow!("Decompressed size {} does not match object size {}", output.len(), expected_size));
+    }
+    Ok(output)
+}
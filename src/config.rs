@@ -1,110 +1,387 @@
-use serde::{Deserialize, Serialize};
-use std::env;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Settings {
-    pub host: String,
-    pub port: u16,
-    pub data_dir: String,
-    pub max_file_size: u64,
-    pub enable_cors: bool,
-    pub cors_origins: Vec<String>,
-    pub cors_methods: Vec<String>,
-    pub cors_headers: Vec<String>,
-    pub cors_allow_credentials: bool,
-}
-
-impl Default for Settings {
-    fn default() -> Self {
-        Self {
-            host: "127.0.0.1".to_string(),
-            port: 8000,
-            data_dir: "./data".to_string(),
-            max_file_size: 100 * 1024 * 1024, // 100MB
-            enable_cors: true,
-            cors_origins: vec![
-                "http://localhost:3000".to_string(),
-                "http://127.0.0.1:3000".to_string(),
-                "http://localhost:8080".to_string(),
-                "http://127.0.0.1:8080".to_string(),
-                "*".to_string(), // 允许所有域名（开发环境）
-            ],
-            cors_methods: vec![
-                "GET".to_string(),
-                "POST".to_string(),
-                "PUT".to_string(),
-                "DELETE".to_string(),
-                "OPTIONS".to_string(),
-            ],
-            cors_headers: vec![
-                "Content-Type".to_string(),
-                "Authorization".to_string(),
-                "X-Requested-With".to_string(),
-                "Accept".to_string(),
-                "Origin".to_string(),
-            ],
-            cors_allow_credentials: false,
-        }
-    }
-}
-
-impl Settings {
-    pub fn from_env() -> Self {
-        let mut settings = Self::default();
-        
-        if let Ok(host) = env::var("SEVINO_HOST") {
-            settings.host = host;
-        }
-        
-        if let Ok(port) = env::var("SEVINO_PORT") {
-            if let Ok(port_num) = port.parse() {
-                settings.port = port_num;
-            }
-        }
-        
-        if let Ok(data_dir) = env::var("SEVINO_DATA_DIR") {
-            settings.data_dir = data_dir;
-        }
-        
-        if let Ok(max_file_size) = env::var("SEVINO_MAX_FILE_SIZE") {
-            if let Ok(size) = max_file_size.parse() {
-                settings.max_file_size = size;
-            }
-        }
-        
-        if let Ok(enable_cors) = env::var("SEVINO_ENABLE_CORS") {
-            settings.enable_cors = enable_cors.to_lowercase() == "true";
-        }
-        
-        // CORS配置
-        if let Ok(cors_origins) = env::var("SEVINO_CORS_ORIGINS") {
-            settings.cors_origins = cors_origins
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-        }
-        
-        if let Ok(cors_methods) = env::var("SEVINO_CORS_METHODS") {
-            settings.cors_methods = cors_methods
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-        }
-        
-        if let Ok(cors_headers) = env::var("SEVINO_CORS_HEADERS") {
-            settings.cors_headers = cors_headers
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-        }
-        
-        if let Ok(allow_credentials) = env::var("SEVINO_CORS_ALLOW_CREDENTIALS") {
-            settings.cors_allow_credentials = allow_credentials.to_lowercase() == "true";
-        }
-        
-        settings
-    }
-} 
\ No newline at end of file
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    pub host: String,
+    pub port: u16,
+    pub data_dir: String,
+    pub max_file_size: u64,
+    /// 图片变换允许解码的源图最大像素数
+    pub image_max_pixels: u64,
+    pub enable_cors: bool,
+    pub cors_origins: Vec<String>,
+    pub cors_methods: Vec<String>,
+    pub cors_headers: Vec<String>,
+    pub cors_allow_credentials: bool,
+    pub webhook_secret: String,
+    pub webhook_max_attempts: u32,
+    pub webhook_timeout_secs: u64,
+    /// 每个 Webhook 地址同时进行的最大投递数
+    pub webhook_concurrency: usize,
+    /// 允许投递的内网地址（IP 或 CIDR）：默认拒绝向回环、链路本地和私有地址投递 Webhook
+    pub webhook_allowed_networks: Vec<String>,
+    pub lifecycle_interval_secs: u64,
+    pub admin_token: Option<String>,
+    /// 主体名称 -> 访问密钥
+    pub access_keys: HashMap<String, String>,
+    pub share_link_secret: String,
+    pub upload_policy_secret: String,
+    pub master_key: Option<String>,
+    pub master_key_file: Option<String>,
+    /// 访问日志输出（stdout、off 或文件路径）
+    pub access_log: String,
+    pub audit_log_max_bytes: u64,
+    /// 每个客户端（访问密钥或IP）的请求数和带宽上限，0 表示不限制
+    pub client_requests_per_sec: f64,
+    pub client_bytes_per_sec: u64,
+    /// 每个桶的默认请求数和带宽上限，0 表示不限制
+    pub bucket_requests_per_sec: f64,
+    pub bucket_bytes_per_sec: u64,
+    /// 桶名称 -> 覆盖默认值的限速配置
+    pub bucket_rate_limits: HashMap<String, BucketRateLimit>,
+    /// 静态网站域名：Host 为 {桶名}.{域名} 的请求按桶的网站配置处理
+    pub website_domain: Option<String>,
+    /// 受信任的反向代理（IP 或 CIDR）：只有来自这些地址的 X-Forwarded-Proto 才被采信
+    pub trusted_proxies: Vec<String>,
+    /// 是否按 Accept-Encoding 压缩 API 响应
+    pub response_compression: bool,
+    /// 是否同时压缩对象下载的响应体（只压缩可压缩的内容类型）
+    pub object_response_compression: bool,
+    /// 小于该字节数的响应不压缩
+    pub response_compression_min_size: u64,
+    /// 支持的响应编码，按服务端优先顺序排列（zstd、br、gzip）
+    pub response_compression_encodings: Vec<String>,
+}
+
+/// 单个桶的限速配置，0 表示不限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketRateLimit {
+    pub requests_per_sec: f64,
+    pub bytes_per_sec: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8000,
+            data_dir: "./data".to_string(),
+            max_file_size: 100 * 1024 * 1024, // 100MB
+            image_max_pixels: 40_000_000,
+            enable_cors: true,
+            cors_origins: vec![
+                "http://localhost:3000".to_string(),
+                "http://127.0.0.1:3000".to_string(),
+                "http://localhost:8080".to_string(),
+                "http://127.0.0.1:8080".to_string(),
+                "*".to_string(), // 允许所有域名（开发环境）
+            ],
+            cors_methods: vec![
+                "GET".to_string(),
+                "POST".to_string(),
+                "PUT".to_string(),
+                "DELETE".to_string(),
+                "OPTIONS".to_string(),
+            ],
+            cors_headers: vec![
+                "Content-Type".to_string(),
+                "Authorization".to_string(),
+                "X-Requested-With".to_string(),
+                "Accept".to_string(),
+                "Origin".to_string(),
+            ],
+            cors_allow_credentials: false,
+            webhook_secret: String::new(),
+            webhook_max_attempts: 10,
+            webhook_timeout_secs: 10,
+            webhook_concurrency: 4,
+            webhook_allowed_networks: Vec::new(),
+            lifecycle_interval_secs: 3600,
+            admin_token: None,
+            access_keys: HashMap::new(),
+            share_link_secret: String::new(),
+            upload_policy_secret: String::new(),
+            master_key: None,
+            master_key_file: None,
+            access_log: "stdout".to_string(),
+            audit_log_max_bytes: 10 * 1024 * 1024, // 10MB
+            client_requests_per_sec: 0.0,
+            client_bytes_per_sec: 0,
+            bucket_requests_per_sec: 0.0,
+            bucket_bytes_per_sec: 0,
+            bucket_rate_limits: HashMap::new(),
+            website_domain: None,
+            trusted_proxies: Vec::new(),
+            response_compression: true,
+            object_response_compression: false,
+            response_compression_min_size: 1024,
+            response_compression_encodings: vec![
+                "zstd".to_string(),
+                "br".to_string(),
+                "gzip".to_string(),
+            ],
+        }
+    }
+}
+
+impl Settings {
+    /// 隐藏敏感字段后的配置（用于日志输出）
+    pub fn redacted(&self) -> Self {
+        let mut settings = self.clone();
+        if !settings.webhook_secret.is_empty() {
+            settings.webhook_secret = "***".to_string();
+        }
+        if settings.admin_token.is_some() {
+            settings.admin_token = Some("***".to_string());
+        }
+        if !settings.share_link_secret.is_empty() {
+            settings.share_link_secret = "***".to_string();
+        }
+        if !settings.upload_policy_secret.is_empty() {
+            settings.upload_policy_secret = "***".to_string();
+        }
+        if settings.master_key.is_some() {
+            settings.master_key = Some("***".to_string());
+        }
+        for key in settings.access_keys.values_mut() {
+            *key = "***".to_string();
+        }
+        settings
+    }
+    
+    pub fn from_env() -> Self {
+        let mut settings = Self::default();
+        
+        if let Ok(host) = env::var("SEVINO_HOST") {
+            settings.host = host;
+        }
+        
+        if let Ok(port) = env::var("SEVINO_PORT") {
+            if let Ok(port_num) = port.parse() {
+                settings.port = port_num;
+            }
+        }
+        
+        if let Ok(data_dir) = env::var("SEVINO_DATA_DIR") {
+            settings.data_dir = data_dir;
+        }
+        
+        if let Ok(max_file_size) = env::var("SEVINO_MAX_FILE_SIZE") {
+            if let Ok(size) = max_file_size.parse() {
+                settings.max_file_size = size;
+            }
+        }
+        
+        if let Ok(max_pixels) = env::var("SEVINO_IMAGE_MAX_PIXELS") {
+            if let Ok(max_pixels) = max_pixels.parse() {
+                settings.image_max_pixels = max_pixels;
+            }
+        }
+        
+        if let Ok(enable_cors) = env::var("SEVINO_ENABLE_CORS") {
+            settings.enable_cors = enable_cors.to_lowercase() == "true";
+        }
+        
+        // CORS配置
+        if let Ok(cors_origins) = env::var("SEVINO_CORS_ORIGINS") {
+            settings.cors_origins = cors_origins
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        if let Ok(cors_methods) = env::var("SEVINO_CORS_METHODS") {
+            settings.cors_methods = cors_methods
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        if let Ok(cors_headers) = env::var("SEVINO_CORS_HEADERS") {
+            settings.cors_headers = cors_headers
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        if let Ok(allow_credentials) = env::var("SEVINO_CORS_ALLOW_CREDENTIALS") {
+            settings.cors_allow_credentials = allow_credentials.to_lowercase() == "true";
+        }
+        
+        // 事件通知配置
+        if let Ok(secret) = env::var("SEVINO_WEBHOOK_SECRET") {
+            settings.webhook_secret = secret;
+        }
+        
+        if let Ok(max_attempts) = env::var("SEVINO_WEBHOOK_MAX_ATTEMPTS") {
+            if let Ok(attempts) = max_attempts.parse() {
+                settings.webhook_max_attempts = attempts;
+            }
+        }
+        
+        if let Ok(timeout) = env::var("SEVINO_WEBHOOK_TIMEOUT_SECS") {
+            if let Ok(secs) = timeout.parse() {
+                settings.webhook_timeout_secs = secs;
+            }
+        }
+        
+        if let Ok(concurrency) = env::var("SEVINO_WEBHOOK_CONCURRENCY") {
+            if let Ok(concurrency) = concurrency.parse::<usize>() {
+                settings.webhook_concurrency = concurrency.max(1);
+            }
+        }
+        
+        // 允许投递的内网地址（逗号分隔的 IP 或 CIDR）
+        if let Ok(networks) = env::var("SEVINO_WEBHOOK_ALLOWED_NETWORKS") {
+            settings.webhook_allowed_networks = networks
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        // 生命周期配置
+        if let Ok(interval) = env::var("SEVINO_LIFECYCLE_INTERVAL_SECS") {
+            if let Ok(secs) = interval.parse() {
+                settings.lifecycle_interval_secs = secs;
+            }
+        }
+        
+        // 管理员令牌（用于绕过治理模式保留等特权操作）
+        if let Ok(token) = env::var("SEVINO_ADMIN_TOKEN") {
+            if !token.is_empty() {
+                settings.admin_token = Some(token);
+            }
+        }
+        
+        // 访问密钥（格式：principal:key,principal:key）
+        if let Ok(access_keys) = env::var("SEVINO_ACCESS_KEYS") {
+            settings.access_keys = access_keys
+                .split(',')
+                .filter_map(|pair| pair.split_once(':'))
+                .map(|(principal, key)| (principal.trim().to_string(), key.trim().to_string()))
+                .filter(|(principal, key)| !key.is_empty() && !principal.is_empty())
+                .collect();
+        }
+        
+        // 分享链接签名密钥（未设置时自动生成并保存在数据目录中）
+        if let Ok(secret) = env::var("SEVINO_SHARE_LINK_SECRET") {
+            settings.share_link_secret = secret;
+        }
+        
+        // 表单上传策略签名密钥（未设置时自动生成并保存在数据目录中）
+        if let Ok(secret) = env::var("SEVINO_UPLOAD_POLICY_SECRET") {
+            settings.upload_policy_secret = secret;
+        }
+        
+        // 服务端加密主密钥（base64编码的32字节，直接配置或从密钥文件读取）
+        if let Ok(key) = env::var("SEVINO_MASTER_KEY") {
+            if !key.is_empty() {
+                settings.master_key = Some(key);
+            }
+        }
+        
+        if let Ok(path) = env::var("SEVINO_MASTER_KEY_FILE") {
+            if !path.is_empty() {
+                settings.master_key_file = Some(path);
+            }
+        }
+        
+        // 访问日志和审计日志
+        if let Ok(access_log) = env::var("SEVINO_ACCESS_LOG") {
+            settings.access_log = access_log;
+        }
+        
+        if let Ok(max_bytes) = env::var("SEVINO_AUDIT_LOG_MAX_BYTES") {
+            if let Ok(bytes) = max_bytes.parse() {
+                settings.audit_log_max_bytes = bytes;
+            }
+        }
+        
+        // 限速配置
+        if let Ok(rps) = env::var("SEVINO_RATE_LIMIT_CLIENT_RPS") {
+            if let Ok(rps) = rps.parse() {
+                settings.client_requests_per_sec = rps;
+            }
+        }
+        
+        if let Ok(bps) = env::var("SEVINO_RATE_LIMIT_CLIENT_BPS") {
+            if let Ok(bps) = bps.parse() {
+                settings.client_bytes_per_sec = bps;
+            }
+        }
+        
+        if let Ok(rps) = env::var("SEVINO_RATE_LIMIT_BUCKET_RPS") {
+            if let Ok(rps) = rps.parse() {
+                settings.bucket_requests_per_sec = rps;
+            }
+        }
+        
+        if let Ok(bps) = env::var("SEVINO_RATE_LIMIT_BUCKET_BPS") {
+            if let Ok(bps) = bps.parse() {
+                settings.bucket_bytes_per_sec = bps;
+            }
+        }
+        
+        // 单桶限速（格式：bucket:rps:bps,bucket:rps:bps）
+        if let Ok(limits) = env::var("SEVINO_RATE_LIMIT_BUCKETS") {
+            settings.bucket_rate_limits = limits
+                .split(',')
+                .filter_map(|entry| {
+                    let mut parts = entry.split(':').map(str::trim);
+                    let bucket = parts.next().filter(|b| !b.is_empty())?;
+                    let requests_per_sec = parts.next()?.parse().ok()?;
+                    let bytes_per_sec = parts.next()?.parse().ok()?;
+                    Some((bucket.to_string(), BucketRateLimit { requests_per_sec, bytes_per_sec }))
+                })
+                .collect();
+        }
+        
+        // 静态网站域名
+        if let Ok(domain) = env::var("SEVINO_WEBSITE_DOMAIN") {
+            let domain = domain.trim().trim_start_matches('.').to_lowercase();
+            if !domain.is_empty() {
+                settings.website_domain = Some(domain);
+            }
+        }
+        
+        // 受信任的反向代理（逗号分隔的 IP 或 CIDR）
+        if let Ok(proxies) = env::var("SEVINO_TRUSTED_PROXIES") {
+            settings.trusted_proxies = proxies
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        // 响应压缩配置
+        if let Ok(enabled) = env::var("SEVINO_RESPONSE_COMPRESSION") {
+            settings.response_compression = enabled.to_lowercase() == "true";
+        }
+        
+        if let Ok(enabled) = env::var("SEVINO_OBJECT_RESPONSE_COMPRESSION") {
+            settings.object_response_compression = enabled.to_lowercase() == "true";
+        }
+        
+        if let Ok(min_size) = env::var("SEVINO_RESPONSE_COMPRESSION_MIN_SIZE") {
+            if let Ok(size) = min_size.parse() {
+                settings.response_compression_min_size = size;
+            }
+        }
+        
+        if let Ok(encodings) = env::var("SEVINO_RESPONSE_COMPRESSION_ENCODINGS") {
+            settings.response_compression_encodings = encodings
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        settings
+    }
+} 
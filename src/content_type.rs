@@ -0,0 +1,645 @@
+use crate::models::{ContentTypeConfiguration, ContentTypeMode};
+
+/// 桶内容类型检测配置文件名（位于 .sevino.meta 目录下）
+pub const CONTENT_TYPE_CONFIG: &str = "content-type";
+
+/// 未声明内容类型时的默认值
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// 嗅探时检查的前缀长度
+const SNIFF_LEN: usize = 8192;
+
+/// 声明的内容类型与文件内容不符
+#[derive(Debug, thiserror::Error)]
+#[error("Declared content type '{declared}' does not match the detected content type '{detected}'")]
+pub struct ContentTypeMismatch {
+    pub declared: String,
+    pub detected: String,
+}
+
+/// 嗅探结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detected {
+    pub content_type: &'static str,
+    /// 是否由二进制签名识别（文本类的识别只是推测，不作为拒绝上传的依据）
+    pub from_signature: bool,
+}
+
+/// 按扩展名查找内容类型
+pub fn from_extension(filename: &str) -> Option<&'static str> {
+    mime_guess::from_path(filename).first_raw()
+}
+
+/// 按文件内容的前几个字节识别内容类型
+pub fn sniff(data: &[u8]) -> Option<Detected> {
+    let data = &data[..data.len().min(SNIFF_LEN)];
+    let signature = |content_type| Some(Detected { content_type, from_signature: true });
+    let at = |offset: usize, magic: &[u8]| data.get(offset..offset + magic.len()) == Some(magic);
+
+    if at(0, b"\x89PNG\r\n\x1a\n") {
+        return signature("image/png");
+    }
+    if at(0, b"\xff\xd8\xff") {
+        return signature("image/jpeg");
+    }
+    if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        return signature("image/gif");
+    }
+    if at(0, b"RIFF") {
+        if at(8, b"WEBP") {
+            return signature("image/webp");
+        }
+        if at(8, b"WAVE") {
+            return signature("audio/wav");
+        }
+        if at(8, b"AVI ") {
+            return signature("video/x-msvideo");
+        }
+    }
+    if at(4, b"ftyp") {
+        let brand = data.get(8..12).unwrap_or_default();
+        return signature(match brand {
+            b"avif" | b"avis" => "image/avif",
+            b"heic" | b"heix" | b"hevc" | b"hevx" => "image/heic",
+            b"mif1" | b"msf1" => "image/heif",
+            b"qt  " => "video/quicktime",
+            b"M4A " | b"M4B " | b"M4P " => "audio/mp4",
+            b"crx " => "image/x-canon-cr3",
+            _ if brand.starts_with(b"3g2") => "video/3gpp2",
+            _ if brand.starts_with(b"3gp") => "video/3gpp",
+            _ => "video/mp4",
+        });
+    }
+    if at(0, b"\x00\x00\x00\x0cjP  \r\n\x87\n") {
+        return signature("image/jp2");
+    }
+    if at(0, b"\xff\x0a") || at(0, b"\x00\x00\x00\x0cJXL \r\n\x87\n") {
+        return signature("image/jxl");
+    }
+    if at(0, b"II*\x00") || at(0, b"MM\x00*") {
+        return signature("image/tiff");
+    }
+    // BMP 只有2字节魔数，同时检查保留字段和 DIB 头大小
+    if at(0, b"BM") && at(6, &[0, 0, 0, 0]) && matches!(data.get(14), Some(12 | 40 | 52 | 56 | 64 | 108 | 124)) {
+        return signature("image/bmp");
+    }
+    if at(0, &[0, 0, 1, 0]) && data.get(4..6).is_some_and(|count| count != [0, 0]) && data.get(9) == Some(&0) {
+        return signature("image/x-icon");
+    }
+    if at(0, b"8BPS") {
+        return signature("image/vnd.adobe.photoshop");
+    }
+    if at(0, b"icns") {
+        return signature("image/icns");
+    }
+    if at(0, b"qoif") {
+        return signature("image/qoi");
+    }
+    if data[..data.len().min(1024)].windows(5).any(|w| w == b"%PDF-") {
+        return signature("application/pdf");
+    }
+    if at(0, b"%!PS") {
+        return signature("application/postscript");
+    }
+    if at(0, b"{\\rtf") {
+        return signature("application/rtf");
+    }
+    if at(0, b"PK\x03\x04") || at(0, b"PK\x05\x06") || at(0, b"PK\x07\x08") {
+        // EPUB 和 OpenDocument 把未压缩的 mimetype 文件放在第一个条目
+        if at(30, b"mimetype") {
+            let mimetype = data.get(38..).unwrap_or_default();
+            for candidate in ["application/epub+zip", "application/vnd.oasis.opendocument.text", "application/vnd.oasis.opendocument.spreadsheet", "application/vnd.oasis.opendocument.presentation", "application/vnd.oasis.opendocument.graphics"] {
+                if mimetype.starts_with(candidate.as_bytes()) {
+                    return signature(candidate);
+                }
+            }
+        }
+        return signature("application/zip");
+    }
+    if at(0, b"\x1f\x8b") {
+        return signature("application/gzip");
+    }
+    if at(0, b"BZh") && data.get(3).is_some_and(|b| (b'1'..=b'9').contains(b)) {
+        return signature("application/x-bzip2");
+    }
+    if at(0, b"\xfd7zXZ\x00") {
+        return signature("application/x-xz");
+    }
+    if at(0, b"\x28\xb5\x2f\xfd") {
+        return signature("application/zstd");
+    }
+    if at(0, b"\x04\x22\x4d\x18") {
+        return signature("application/x-lz4");
+    }
+    if at(0, b"LZIP") {
+        return signature("application/x-lzip");
+    }
+    if at(0, b"7z\xbc\xaf\x27\x1c") {
+        return signature("application/x-7z-compressed");
+    }
+    if at(0, b"Rar!\x1a\x07") {
+        return signature("application/vnd.rar");
+    }
+    if at(0, b"MSCF") {
+        return signature("application/vnd.ms-cab-compressed");
+    }
+    if at(257, b"ustar") {
+        return signature("application/x-tar");
+    }
+    if at(0, b"!<arch>\n") {
+        return signature(if at(8, b"debian-binary") { "application/vnd.debian.binary-package" } else { "application/x-archive" });
+    }
+    if at(0, b"\xed\xab\xee\xdb") {
+        return signature("application/x-rpm");
+    }
+    if at(0, b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") {
+        return signature("application/x-ole-storage");
+    }
+    if at(0, b"\x00asm") {
+        return signature("application/wasm");
+    }
+    if at(0, b"\x7fELF") {
+        return signature("application/x-executable");
+    }
+    // PE：MZ 头的 e_lfanew 指向 "PE\0\0"
+    if at(0, b"MZ") {
+        let pe = data.get(0x3c..0x40).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+        match pe {
+            Some(offset) if at(offset, b"PE\x00\x00") => return signature("application/vnd.microsoft.portable-executable"),
+            _ if data.len() >= 0x40 => return signature("application/x-msdos-program"),
+            _ => {}
+        }
+    }
+    if at(0, b"\xfe\xed\xfa\xce") || at(0, b"\xfe\xed\xfa\xcf") || at(0, b"\xce\xfa\xed\xfe") || at(0, b"\xcf\xfa\xed\xfe") {
+        return signature("application/x-mach-binary");
+    }
+    // 0xCAFEBABE 同时是 Java 类文件和 Mach-O 通用二进制的魔数：类文件的主版本号不小于45，通用二进制的架构数很小
+    if at(0, b"\xca\xfe\xba\xbe") {
+        let value = data.get(4..8).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0);
+        return signature(if value < 45 { "application/x-mach-binary" } else { "application/java-vm" });
+    }
+    if at(0, b"SQLite format 3\x00") {
+        return signature("application/vnd.sqlite3");
+    }
+    if at(0, b"PAR1") {
+        return signature("application/vnd.apache.parquet");
+    }
+    if at(0, b"Obj\x01") {
+        return signature("application/avro");
+    }
+    if at(0, b"ARROW1") {
+        return signature("application/vnd.apache.arrow.file");
+    }
+    if at(0, b"\x89HDF\r\n\x1a\n") {
+        return signature("application/x-hdf5");
+    }
+    if at(0, b"\x93NUMPY") {
+        return signature("application/x-npy");
+    }
+    if at(0, b"glTF") {
+        return signature("model/gltf-binary");
+    }
+    if at(0, b"ID3") {
+        return signature("audio/mpeg");
+    }
+    if data.len() >= 2 && data[0] == 0xff && data[1] & 0xe0 == 0xe0 {
+        // MPEG 帧同步：layer 位为0的是 AAC ADTS
+        return signature(if data[1] & 0x06 == 0 { "audio/aac" } else { "audio/mpeg" });
+    }
+    if at(0, b"OggS") {
+        let page = &data[..data.len().min(64)];
+        let contains = |needle: &[u8]| page.windows(needle.len()).any(|w| w == needle);
+        return signature(if contains(b"\x80theora") {
+            "video/ogg"
+        } else if contains(b"OpusHead") {
+            "audio/opus"
+        } else if contains(b"\x01vorbis") || contains(b"\x7fFLAC") {
+            "audio/ogg"
+        } else {
+            "application/ogg"
+        });
+    }
+    if at(0, b"fLaC") {
+        return signature("audio/flac");
+    }
+    if at(0, b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+        return signature("audio/aiff");
+    }
+    if at(0, b"MThd") {
+        return signature("audio/midi");
+    }
+    if at(0, b"#!AMR") {
+        return signature("audio/amr");
+    }
+    if at(0, b"caff") {
+        return signature("audio/x-caf");
+    }
+    if at(0, b"\x1a\x45\xdf\xa3") {
+        let header = &data[..data.len().min(64)];
+        return signature(if header.windows(4).any(|w| w == b"webm") { "video/webm" } else { "video/x-matroska" });
+    }
+    if at(0, b"FLV\x01") {
+        return signature("video/x-flv");
+    }
+    if at(0, b"\x00\x00\x01\xba") || at(0, b"\x00\x00\x01\xb3") {
+        return signature("video/mpeg");
+    }
+    if data.first() == Some(&0x47) && data.get(188) == Some(&0x47) && data.len() > 376 && data[376] == 0x47 {
+        return signature("video/mp2t");
+    }
+    if at(0, b"\x30\x26\xb2\x75\x8e\x66\xcf\x11") {
+        return signature("video/x-ms-asf");
+    }
+    if at(0, b"wOFF") {
+        return signature("font/woff");
+    }
+    if at(0, b"wOF2") {
+        return signature("font/woff2");
+    }
+    if at(0, b"OTTO") {
+        return signature("font/otf");
+    }
+    if at(0, b"\x00\x01\x00\x00\x00") || at(0, b"true\x00") {
+        return signature("font/ttf");
+    }
+    if at(0, b"ttcf") {
+        return signature("font/collection");
+    }
+    if at(0, b"d8:announce") {
+        return signature("application/x-bittorrent");
+    }
+
+    sniff_text(data).map(|content_type| Detected { content_type, from_signature: false })
+}
+
+/// 文本内容：有效的 UTF-8（或带 BOM 的 UTF-16），且不含控制字符
+fn sniff_text(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\xfe\xff") || data.starts_with(b"\xff\xfe") {
+        return Some("text/plain");
+    }
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    if data.is_empty() {
+        return None;
+    }
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        // 前缀截断在多字节字符中间时只检查有效部分
+        Err(e) if e.error_len().is_none() && e.valid_up_to() + 4 > data.len() => std::str::from_utf8(&data[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    if text.chars().any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c' | '\x1b')) {
+        return None;
+    }
+
+    let start = text.trim_start().get(..text.trim_start().len().min(512)).unwrap_or_default().to_ascii_lowercase();
+    let content_type = if start.starts_with("<?xml") {
+        if start.contains("<svg") { "image/svg+xml" } else { "application/xml" }
+    } else if start.starts_with("<svg") {
+        "image/svg+xml"
+    } else if ["<!doctype html", "<html", "<head", "<body", "<!--"].iter().any(|tag| start.starts_with(tag)) {
+        "text/html"
+    } else {
+        "text/plain"
+    };
+    Some(content_type)
+}
+
+/// 结合文件内容和扩展名得到检测结果：ZIP、OLE 和纯文本这类通用容器用扩展名细化为具体类型
+pub fn detect(key: &str, data: &[u8]) -> Option<Detected> {
+    let detected = sniff(data)?;
+    let refined = from_extension(key)
+        .filter(|by_extension| *by_extension != detected.content_type && *by_extension != OCTET_STREAM)
+        .filter(|by_extension| match detected.content_type {
+            "application/zip" | "application/x-ole-storage" | "application/x-executable" | "video/mp4" | "application/ogg" => {
+                family(by_extension) == family(detected.content_type)
+            }
+            "text/plain" | "application/xml" => is_textual(by_extension),
+            _ => false,
+        });
+    Some(match refined {
+        Some(content_type) => Detected { content_type, ..detected },
+        None => detected,
+    })
+}
+
+/// 去掉参数并统一大小写
+fn essence(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase()
+}
+
+/// 声明的内容类型是否为空或通用的二进制类型（表示客户端不知道具体类型）
+pub fn is_unspecified(content_type: &str) -> bool {
+    let essence = essence(content_type);
+    essence.is_empty() || essence == OCTET_STREAM || essence == "binary/octet-stream"
+}
+
+/// 把别名和同一容器格式的类型归为同一族
+fn family(content_type: &str) -> String {
+    let essence = essence(content_type);
+    let canonical = match essence.as_str() {
+        "image/jpg" | "image/pjpeg" => "image/jpeg",
+        "image/x-png" => "image/png",
+        "image/x-ms-bmp" | "image/x-bmp" => "image/bmp",
+        "image/vnd.microsoft.icon" | "image/ico" | "image/x-win-bitmap" => "image/x-icon",
+        "image/x-tiff" => "image/tiff",
+        "image/heic" | "image/heif" | "image/avif" | "image/heic-sequence" | "image/heif-sequence" | "image/avif-sequence"
+        | "image/x-canon-cr3" | "video/mp4" | "audio/mp4" | "audio/x-m4a" | "video/x-m4v" | "video/quicktime"
+        | "video/3gpp" | "video/3gpp2" | "audio/3gpp" | "application/mp4" => "video/mp4",
+        "audio/x-wav" | "audio/wave" | "audio/vnd.wave" => "audio/wav",
+        "audio/mp3" | "audio/mpeg3" | "audio/x-mpeg" => "audio/mpeg",
+        "audio/x-aac" | "audio/aacp" => "audio/aac",
+        "audio/x-flac" => "audio/flac",
+        "audio/x-aiff" => "audio/aiff",
+        "audio/mid" | "audio/x-midi" => "audio/midi",
+        "application/ogg" | "audio/ogg" | "video/ogg" | "audio/opus" | "audio/vorbis" => "application/ogg",
+        "video/webm" | "audio/webm" | "video/x-matroska" | "audio/x-matroska" => "video/x-matroska",
+        "video/avi" | "video/msvideo" => "video/x-msvideo",
+        "video/x-ms-wmv" | "audio/x-ms-wma" | "video/x-ms-asf" => "video/x-ms-asf",
+        "application/x-gzip" => "application/gzip",
+        "application/x-zstd" => "application/zstd",
+        "application/x-rar-compressed" | "application/x-rar" => "application/vnd.rar",
+        "application/x-pdf" => "application/pdf",
+        "application/x-sqlite3" => "application/vnd.sqlite3",
+        "application/x-font-woff" | "application/font-woff" => "font/woff",
+        "application/font-woff2" => "font/woff2",
+        "font/ttf" | "font/otf" | "font/sfnt" | "application/x-font-ttf" | "application/x-font-otf" | "application/font-sfnt" => "font/sfnt",
+        "application/x-executable" | "application/x-elf" | "application/x-sharedlib" | "application/x-pie-executable" => "application/x-executable",
+        "application/vnd.microsoft.portable-executable" | "application/x-msdownload" | "application/x-dosexec"
+        | "application/x-msdos-program" | "application/exe" | "application/x-exe" => "application/vnd.microsoft.portable-executable",
+        "application/x-ole-storage" | "application/msword" | "application/vnd.ms-excel" | "application/vnd.ms-powerpoint"
+        | "application/x-msi" | "application/vnd.ms-outlook" | "application/vnd.visio" => "application/x-ole-storage",
+        "application/java-vm" | "application/x-java-applet" | "application/x-java-class" => "application/java-vm",
+        "application/x-mach-binary" | "application/x-mach-o-executable" => "application/x-mach-binary",
+        "application/postscript" | "application/eps" => "application/postscript",
+        "application/rtf" | "text/rtf" => "application/rtf",
+        zip if zip.starts_with("application/vnd.openxmlformats-officedocument.")
+            || zip.starts_with("application/vnd.oasis.opendocument.")
+            || zip.starts_with("application/vnd.ms-word.")
+            || zip.starts_with("application/vnd.ms-excel.")
+            || zip.starts_with("application/vnd.ms-powerpoint.")
+            || zip.ends_with("+zip")
+            || matches!(zip, "application/zip" | "application/x-zip-compressed" | "application/x-zip" | "application/java-archive"
+                | "application/vnd.android.package-archive" | "application/vnd.ms-xpsdocument" | "application/oxps"
+                | "application/vnd.google-earth.kmz" | "application/x-xpinstall" | "application/vsix" | "application/vnd.ms-appx"
+                | "application/vnd.apple.pages" | "application/vnd.apple.numbers" | "application/vnd.apple.keynote" | "application/x-ios-app") => "application/zip",
+        other => return other.to_string(),
+    };
+    canonical.to_string()
+}
+
+/// 基于文本的内容类型
+pub fn is_textual(content_type: &str) -> bool {
+    let essence = essence(content_type);
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(essence.as_str(),
+            "application/json" | "application/xml" | "application/javascript" | "application/ecmascript"
+            | "application/x-javascript" | "application/typescript" | "application/x-sh" | "application/x-csh"
+            | "application/x-bat" | "application/x-httpd-php" | "application/sql" | "application/yaml"
+            | "application/x-yaml" | "application/toml" | "application/graphql" | "application/x-ndjson"
+            | "application/x-subrip" | "application/x-tex" | "application/x-pem-file" | "application/pgp-signature"
+            | "application/mbox" | "message/rfc822" | "application/x-www-form-urlencoded" | "application/vnd.apple.mpegurl"
+            | "audio/x-mpegurl" | "audio/x-scpls")
+}
+
+/// 二进制签名可以识别的族：声明为这些类型的内容必须带有相应签名
+fn requires_signature(content_type: &str) -> bool {
+    const SIGNATURE_FAMILIES: &[&str] = &[
+        "image/png", "image/jpeg", "image/gif", "image/webp", "image/bmp", "image/tiff", "image/x-icon", "image/jxl",
+        "image/jp2", "image/vnd.adobe.photoshop", "video/mp4", "audio/wav", "audio/flac", "audio/aiff", "audio/midi",
+        "application/ogg", "video/x-matroska", "video/x-msvideo", "video/x-flv", "application/pdf", "application/zip",
+        "application/gzip", "application/x-bzip2", "application/x-xz", "application/zstd", "application/x-7z-compressed",
+        "application/vnd.rar", "application/x-tar", "application/wasm", "application/x-executable",
+        "application/vnd.microsoft.portable-executable", "application/x-ole-storage", "application/x-mach-binary",
+        "application/java-vm", "application/vnd.sqlite3", "font/woff", "font/woff2", "font/sfnt",
+    ];
+    SIGNATURE_FAMILIES.contains(&family(content_type).as_str())
+}
+
+/// 声明的类型与检测结果是否矛盾：签名识别出的格式与声明的族不同，
+/// 或声明的类型有固定签名但内容中没有该签名
+pub fn is_mismatch(declared: &str, detected: Option<Detected>) -> bool {
+    if is_unspecified(declared) {
+        return false;
+    }
+    let declared_family = family(declared);
+    match detected {
+        Some(detected) if family(detected.content_type) == declared_family => false,
+        Some(detected) if detected.from_signature => true,
+        Some(detected) if is_textual(declared) && is_textual(detected.content_type) => false,
+        _ => requires_signature(declared),
+    }
+}
+
+/// 按桶的检测模式确定存储的内容类型，返回 (内容类型, 检测到的类型)：
+/// trust 使用客户端声明的类型，sniff 使用检测到的类型（与声明的类型同族时保留更具体的声明），
+/// reject 在声明的类型与内容不符时拒绝上传；未声明类型时依次使用检测结果和扩展名
+pub fn resolve(
+    config: &ContentTypeConfiguration,
+    key: &str,
+    declared: &str,
+    data: &[u8],
+) -> Result<(String, Option<String>), ContentTypeMismatch> {
+    let detected = detect(key, data);
+    let detected_type = detected.map(|d| d.content_type.to_string());
+    let fallback = || {
+        detected_type.clone()
+            .or_else(|| from_extension(key).map(str::to_string))
+            .unwrap_or_else(|| OCTET_STREAM.to_string())
+    };
+    if is_unspecified(declared) {
+        // 没有声明类型时扩展名优先于文本嗅探（如 .csv 不应存为 text/plain），但文本内容不采用二进制类型的扩展名
+        let content_type = match detected {
+            Some(detected) if detected.from_signature => detected.content_type.to_string(),
+            _ => from_extension(key)
+                .filter(|t| *t != OCTET_STREAM && (detected.is_none() || is_textual(t)))
+                .map(str::to_string)
+                .unwrap_or_else(fallback),
+        };
+        return Ok((content_type, detected_type));
+    }
+
+    let mismatch = is_mismatch(declared, detected);
+    let content_type = match config.mode {
+        ContentTypeMode::Trust => declared.to_string(),
+        ContentTypeMode::Sniff if mismatch => fallback(),
+        ContentTypeMode::Sniff => declared.to_string(),
+        ContentTypeMode::Reject if mismatch => {
+            return Err(ContentTypeMismatch {
+                declared: declared.to_string(),
+                detected: detected_type.unwrap_or_else(|| "unknown".to_string()),
+            });
+        }
+        ContentTypeMode::Reject => declared.to_string(),
+    };
+    Ok((content_type, detected_type))
+}
+
+/// 修改或复制时按已记录的检测结果校验新的内容类型（只有 reject 模式会拒绝）
+pub fn check_declared(config: &ContentTypeConfiguration, declared: &str, detected: Option<&str>) -> Result<(), ContentTypeMismatch> {
+    if config.mode != ContentTypeMode::Reject || is_unspecified(declared) {
+        return Ok(());
+    }
+    let mismatch = match detected {
+        Some(detected) if family(detected) == family(declared) => false,
+        Some(detected) if is_textual(detected) => !is_textual(declared) && requires_signature(declared),
+        Some(_) => true,
+        None => requires_signature(declared),
+    };
+    if mismatch {
+        return Err(ContentTypeMismatch {
+            declared: declared.to_string(),
+            detected: detected.unwrap_or("unknown").to_string(),
+        });
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+    const ZIP: &[u8] = b"PK\x03\x04\x14\x00\x00\x00\x08\x00";
+
+    fn config(mode: ContentTypeMode) -> ContentTypeConfiguration {
+        ContentTypeConfiguration { mode }
+    }
+
+    fn epub() -> Vec<u8> {
+        let mut data = b"PK\x03\x04".to_vec();
+        data.resize(30, 0);
+        data.extend_from_slice(b"mimetypeapplication/epub+zip");
+        data
+    }
+
+    #[test]
+    fn from_extension_uses_the_mime_table() {
+        assert_eq!(from_extension("photos/cat.PNG"), Some("image/png"));
+        assert_eq!(from_extension("report.pdf"), Some("application/pdf"));
+        assert_eq!(from_extension("data.csv"), Some("text/csv"));
+        assert_eq!(from_extension("README"), None);
+    }
+
+    #[test]
+    fn sniff_recognizes_binary_signatures() {
+        let png = sniff(PNG).unwrap();
+        assert_eq!(png, Detected { content_type: "image/png", from_signature: true });
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0").unwrap().content_type, "image/jpeg");
+        assert_eq!(sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 ").unwrap().content_type, "image/webp");
+        assert_eq!(sniff(b"\x00\x00\x00\x18ftypheic").unwrap().content_type, "image/heic");
+        assert_eq!(sniff(b"%PDF-1.7\n").unwrap().content_type, "application/pdf");
+        assert_eq!(sniff(ZIP).unwrap().content_type, "application/zip");
+        assert_eq!(sniff(&epub()).unwrap().content_type, "application/epub+zip");
+        assert_eq!(sniff(b"\x1f\x8b\x08\x00").unwrap().content_type, "application/gzip");
+    }
+
+    #[test]
+    fn sniff_distinguishes_java_classes_from_mach_o() {
+        assert_eq!(sniff(b"\xca\xfe\xba\xbe\x00\x00\x00\x02").unwrap().content_type, "application/x-mach-binary");
+        assert_eq!(sniff(b"\xca\xfe\xba\xbe\x00\x00\x00\x34").unwrap().content_type, "application/java-vm");
+    }
+
+    #[test]
+    fn sniff_guesses_text_without_a_signature() {
+        let text = sniff(b"hello, world\n").unwrap();
+        assert_eq!(text, Detected { content_type: "text/plain", from_signature: false });
+        assert_eq!(sniff(b"  <!DOCTYPE html><html>").unwrap().content_type, "text/html");
+        assert_eq!(sniff(b"<?xml version=\"1.0\"?><svg>").unwrap().content_type, "image/svg+xml");
+        assert_eq!(sniff(b"<?xml version=\"1.0\"?><feed>").unwrap().content_type, "application/xml");
+        assert!(sniff(b"binary\x00\x01\x02").is_none());
+        assert!(sniff(b"").is_none());
+    }
+
+    #[test]
+    fn sniff_accepts_text_truncated_inside_a_character() {
+        let mut data = vec![b'a'; SNIFF_LEN - 1];
+        data.extend_from_slice("é".as_bytes());
+        assert_eq!(sniff(&data).unwrap().content_type, "text/plain");
+    }
+
+    #[test]
+    fn detect_refines_generic_containers_by_extension() {
+        assert_eq!(detect("report.docx", ZIP).unwrap().content_type, from_extension("report.docx").unwrap());
+        assert_eq!(detect("data.csv", b"a,b\n1,2\n").unwrap().content_type, "text/csv");
+        // 签名明确的格式不采用扩展名
+        assert_eq!(detect("image.jpg", PNG).unwrap().content_type, "image/png");
+        // 文本内容不采用二进制类型的扩展名
+        assert_eq!(detect("archive.zip", b"not a zip").unwrap().content_type, "text/plain");
+    }
+
+    #[test]
+    fn is_mismatch_compares_families() {
+        let png = sniff(PNG);
+        assert!(!is_mismatch("image/png", png));
+        assert!(!is_mismatch("image/x-png; charset=binary", png));
+        assert!(is_mismatch("image/jpeg", png));
+        assert!(!is_mismatch(OCTET_STREAM, png));
+        assert!(!is_mismatch("", png));
+    }
+
+    #[test]
+    fn is_mismatch_requires_signatures_only_for_binary_formats() {
+        let text = sniff(b"just some text");
+        assert!(is_mismatch("image/png", text));
+        assert!(is_mismatch("application/pdf", None));
+        assert!(!is_mismatch("application/json", text));
+        assert!(!is_mismatch("text/csv", text));
+        assert!(!is_mismatch("application/x-custom", None));
+    }
+
+    #[test]
+    fn resolve_trust_keeps_the_declared_type() {
+        let (content_type, detected) = resolve(&config(ContentTypeMode::Trust), "a.png", "image/jpeg", PNG).unwrap();
+        assert_eq!(content_type, "image/jpeg");
+        assert_eq!(detected.as_deref(), Some("image/png"));
+    }
+
+    #[test]
+    fn resolve_sniff_replaces_mismatched_types() {
+        let sniff_mode = config(ContentTypeMode::Sniff);
+        assert_eq!(resolve(&sniff_mode, "a.jpg", "image/jpeg", PNG).unwrap().0, "image/png");
+        // 同族时保留更具体的声明
+        assert_eq!(resolve(&sniff_mode, "a.png", "image/x-png", PNG).unwrap().0, "image/x-png");
+        assert_eq!(resolve(&sniff_mode, "a.txt", "text/markdown", b"# title").unwrap().0, "text/markdown");
+    }
+
+    #[test]
+    fn resolve_reject_refuses_mismatched_types() {
+        let reject = config(ContentTypeMode::Reject);
+        let error = resolve(&reject, "a.jpg", "image/jpeg", PNG).unwrap_err();
+        assert_eq!(error.declared, "image/jpeg");
+        assert_eq!(error.detected, "image/png");
+        let error = resolve(&reject, "a.pdf", "application/pdf", b"\x00\x01\x02").unwrap_err();
+        assert_eq!(error.detected, "unknown");
+        assert_eq!(resolve(&reject, "a.png", "image/png", PNG).unwrap().0, "image/png");
+    }
+
+    #[test]
+    fn resolve_fills_in_unspecified_types() {
+        for mode in [ContentTypeMode::Trust, ContentTypeMode::Sniff, ContentTypeMode::Reject] {
+            let config = config(mode);
+            assert_eq!(resolve(&config, "a.bin", OCTET_STREAM, PNG).unwrap().0, "image/png");
+            assert_eq!(resolve(&config, "data.csv", "", b"a,b\n").unwrap().0, "text/csv");
+            assert_eq!(resolve(&config, "notes", "", b"plain words").unwrap().0, "text/plain");
+            assert_eq!(resolve(&config, "blob", "", b"\x00\x01\x02").unwrap().0, OCTET_STREAM);
+            assert_eq!(resolve(&config, "photo.png", "", b"\x00\x01\x02").unwrap().0, "image/png");
+            assert_eq!(resolve(&config, "main.ts", "", b"let x = 1;").unwrap().0, "text/plain");
+        }
+    }
+
+    #[test]
+    fn check_declared_only_rejects_in_reject_mode() {
+        let reject = config(ContentTypeMode::Reject);
+        assert!(check_declared(&config(ContentTypeMode::Trust), "image/jpeg", Some("image/png")).is_ok());
+        assert!(check_declared(&config(ContentTypeMode::Sniff), "image/jpeg", Some("image/png")).is_ok());
+        assert!(check_declared(&reject, "image/jpeg", Some("image/png")).is_err());
+        assert!(check_declared(&reject, "image/x-png", Some("image/png")).is_ok());
+        assert!(check_declared(&reject, "application/json", Some("text/plain")).is_ok());
+        assert!(check_declared(&reject, "image/png", Some("text/plain")).is_err());
+        assert!(check_declared(&reject, "application/pdf", None).is_err());
+        assert!(check_declared(&reject, OCTET_STREAM, Some("image/png")).is_ok());
+    }
+}
@@ -0,0 +1,287 @@
+use crate::models::{DeleteJob, DeleteJobStatus, DeleteObjectEntry, DeleteObjectError};
+use crate::policy::{PolicyService, Requester};
+use crate::services::{ObjectService, StorageService, MAX_BATCH_DELETE_OBJECTS};
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 每个任务最多保留的失败对象数
+const MAX_JOB_ERRORS: usize = 100;
+/// 已结束的任务保留时长（小时）
+const FINISHED_JOB_RETENTION_HOURS: i64 = 24;
+
+/// 按前缀删除的后台任务服务（任务状态只保存在内存中，重启后丢失）
+#[derive(Clone)]
+pub struct DeleteJobService {
+    storage: StorageService,
+    object_service: ObjectService,
+    policy_service: PolicyService,
+    jobs: Arc<RwLock<HashMap<String, DeleteJob>>>,
+}
+
+impl DeleteJobService {
+    pub fn new(storage: StorageService, object_service: ObjectService, policy_service: PolicyService) -> Self {
+        Self { storage, object_service, policy_service, jobs: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    /// 创建按前缀删除的任务并在后台执行（按请求者的权限逐个检查对象）
+    pub async fn start(&self, bucket_name: &str, prefix: &str, requester: Requester, bypass_governance: bool) -> Result<DeleteJob> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        if prefix.is_empty() {
+            return Err(anyhow!("Prefix must not be empty; delete the bucket contents explicitly instead"));
+        }
+
+        let job = DeleteJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            bucket_name: bucket_name.to_string(),
+            prefix: prefix.to_string(),
+            status: DeleteJobStatus::Running,
+            total: 0,
+            deleted: 0,
+            failed: 0,
+            errors: Vec::new(),
+            message: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+
+        {
+            let mut jobs = self.jobs.write().await;
+            let cutoff = Utc::now() - Duration::hours(FINISHED_JOB_RETENTION_HOURS);
+            jobs.retain(|_, j| j.finished_at.map(|t| t > cutoff).unwrap_or(true));
+            jobs.insert(job.id.clone(), job.clone());
+        }
+
+        let service = self.clone();
+        let job_id = job.id.clone();
+        tokio::spawn(async move {
+            let outcome = service.run(&job_id, &requester, bypass_governance).await;
+            service.update(&job_id, |job| {
+                match outcome {
+                    Ok(()) => job.status = DeleteJobStatus::Completed,
+                    Err(e) => {
+                        job.status = DeleteJobStatus::Failed;
+                        job.message = Some(e.to_string());
+                    }
+                }
+                job.finished_at = Some(Utc::now());
+            }).await;
+        });
+
+        Ok(job)
+    }
+
+    /// 获取桶的删除任务
+    pub async fn get(&self, bucket_name: &str, job_id: &str) -> Option<DeleteJob> {
+        self.jobs.read().await.get(job_id).filter(|j| j.bucket_name == bucket_name).cloned()
+    }
+
+    /// 列出桶的删除任务（按创建时间倒序）
+    pub async fn list(&self, bucket_name: &str) -> Vec<DeleteJob> {
+        let mut jobs: Vec<DeleteJob> = self.jobs.read().await.values()
+            .filter(|j| j.bucket_name == bucket_name)
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.created_at));
+        jobs
+    }
+
+    async fn update(&self, job_id: &str, f: impl FnOnce(&mut DeleteJob)) {
+        if let Some(job) = self.jobs.write().await.get_mut(job_id) {
+            f(job);
+        }
+    }
+
+    /// 删除前缀下请求者有权删除的所有对象记录（包括历史版本），按批次执行并更新进度
+    async fn run(&self, job_id: &str, requester: &Requester, bypass_governance: bool) -> Result<()> {
+        let (bucket_name, prefix) = match self.jobs.read().await.get(job_id) {
+            Some(job) => (job.bucket_name.clone(), job.prefix.clone()),
+            None => return Ok(()),
+        };
+
+        let records = self.storage.list_object_metadata(&bucket_name).await?;
+        let mut entries = Vec::new();
+        for metadata in records.iter().filter(|m| m.key.starts_with(&prefix)) {
+            let record_id = StorageService::object_record_id(&bucket_name, metadata);
+            let current_id = self.storage.find_object_id_by_key(&bucket_name, &metadata.key).await?;
+            let version_id = if current_id.as_deref() == Some(record_id.as_str()) {
+                None
+            } else if metadata.version_id.is_some() {
+                metadata.version_id.clone()
+            } else {
+                continue;
+            };
+            entries.push((metadata.data_holder_id.is_some(), DeleteObjectEntry { key: metadata.key.clone(), version_id }));
+        }
+        // 引用对象先删除，数据持有者的引用计数才能降到 0
+        entries.sort_by_key(|(is_reference, _)| !*is_reference);
+
+        let total = entries.len() as u64;
+        self.update(job_id, |job| job.total = total).await;
+
+        let entries: Vec<DeleteObjectEntry> = entries.into_iter().map(|(_, entry)| entry).collect();
+        // 同一个键的多个版本只检查一次权限
+        let mut decisions: HashMap<String, Option<String>> = HashMap::new();
+        for batch in entries.chunks(MAX_BATCH_DELETE_OBJECTS) {
+            let mut allowed = Vec::with_capacity(batch.len());
+            let mut denied = Vec::new();
+            for entry in batch {
+                if !decisions.contains_key(&entry.key) {
+                    let decision = self.policy_service
+                        .authorize_key(requester, "sevino:DeleteObject", &bucket_name, &entry.key)
+                        .await?;
+                    decisions.insert(entry.key.clone(), decision);
+                }
+                match &decisions[&entry.key] {
+                    None => allowed.push(entry.clone()),
+                    Some(message) => denied.push(DeleteObjectError {
+                        key: entry.key.clone(),
+                        version_id: entry.version_id.clone(),
+                        message: message.clone(),
+                    }),
+                }
+            }
+
+            let mut result = self.object_service.delete_objects(&bucket_name, allowed, bypass_governance).await?;
+            result.errors.extend(denied);
+            self.update(job_id, |job| {
+                job.deleted += result.deleted.len() as u64;
+                job.failed += result.errors.len() as u64;
+                let room = MAX_JOB_ERRORS.saturating_sub(job.errors.len());
+                job.errors.extend(result.errors.into_iter().take(room));
+            }).await;
+        }
+
+        tracing::info!("Delete job {} removed objects under '{}/{}'", job_id, bucket_name, prefix);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BucketPolicy, CopyObjectRequest, MetadataDirective, PolicyConditions, PolicyEffect, PolicyStatement};
+    use crate::policy::Identity;
+    use crate::services::BucketService;
+
+    async fn setup() -> (tempfile::TempDir, StorageService, ObjectService, DeleteJobService) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = StorageService::new(dir.path().to_string_lossy().into_owned()).await.unwrap();
+        BucketService::new(storage.clone()).create_bucket("b".to_string()).await.unwrap();
+        let objects = ObjectService::new(storage.clone());
+        let policy_service = PolicyService::new(storage.clone(), &HashMap::new(), None);
+        let jobs = DeleteJobService::new(storage.clone(), objects.clone(), policy_service);
+        (dir, storage, objects, jobs)
+    }
+
+    fn requester(principal: &str, is_admin: bool) -> Requester {
+        Requester {
+            identity: Identity { principal: principal.to_string(), is_admin },
+            source_ip: None,
+            secure_transport: false,
+        }
+    }
+
+    async fn put(objects: &ObjectService, key: &str) {
+        objects.put_object("b", key, key.as_bytes().to_vec(), "text/plain", HashMap::new()).await.unwrap();
+    }
+
+    async fn finish(jobs: &DeleteJobService, job: DeleteJob) -> DeleteJob {
+        for _ in 0..200 {
+            let job = jobs.get("b", &job.id).await.unwrap();
+            if job.status != DeleteJobStatus::Running {
+                return job;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("delete job {} did not finish", job.id);
+    }
+
+    async fn keys(storage: &StorageService) -> Vec<String> {
+        let mut keys: Vec<String> = storage.list_object_metadata("b").await.unwrap().into_iter().map(|m| m.key).collect();
+        keys.sort();
+        keys
+    }
+
+    /// 允许所有人执行所有操作，但拒绝删除 logs/secret* 的策略
+    async fn deny_secret_deletes(storage: &StorageService) {
+        let statement = |effect, actions: &str, resource: &str| PolicyStatement {
+            sid: None,
+            effect,
+            principals: vec!["*".to_string()],
+            actions: vec![actions.to_string()],
+            resources: vec![resource.to_string()],
+            conditions: PolicyConditions::default(),
+        };
+        let policy = BucketPolicy {
+            version: None,
+            statements: vec![
+                statement(PolicyEffect::Allow, "sevino:*", "b/*"),
+                statement(PolicyEffect::Deny, "sevino:DeleteObject", "b/logs/secret*"),
+            ],
+        };
+        BucketService::new(storage.clone()).put_bucket_policy("b", policy).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn deletes_references_before_their_data_holders() {
+        let (_dir, storage, objects, jobs) = setup().await;
+        put(&objects, "logs/a").await;
+        put(&objects, "other").await;
+        let copy = CopyObjectRequest {
+            source_bucket: "b".to_string(),
+            source_key: "logs/a".to_string(),
+            source_version_id: None,
+            metadata_directive: MetadataDirective::Copy,
+            content_type: None,
+            user_metadata: HashMap::new(),
+            tags: HashMap::new(),
+        };
+        objects.copy_object("b", "logs/ref", copy, None, None).await.unwrap();
+
+        let job = finish(&jobs, jobs.start("b", "logs/", requester("alice", false), false).await.unwrap()).await;
+        assert_eq!(job.status, DeleteJobStatus::Completed);
+        assert_eq!((job.total, job.deleted, job.failed), (2, 2, 0));
+        assert_eq!(keys(&storage).await, ["other"]);
+    }
+
+    #[tokio::test]
+    async fn denied_keys_are_reported_and_kept() {
+        let (_dir, storage, objects, jobs) = setup().await;
+        for key in ["logs/a", "logs/b", "logs/secret"] {
+            put(&objects, key).await;
+        }
+        deny_secret_deletes(&storage).await;
+
+        let job = finish(&jobs, jobs.start("b", "logs/", requester("alice", false), false).await.unwrap()).await;
+        assert_eq!(job.status, DeleteJobStatus::Completed);
+        assert_eq!((job.total, job.deleted, job.failed), (3, 2, 1));
+        assert_eq!(job.errors.len(), 1);
+        assert_eq!(job.errors[0].key, "logs/secret");
+        assert!(job.errors[0].message.contains("Access denied"));
+        assert_eq!(keys(&storage).await, ["logs/secret"]);
+    }
+
+    #[tokio::test]
+    async fn admins_are_not_limited_by_the_policy() {
+        let (_dir, storage, objects, jobs) = setup().await;
+        put(&objects, "logs/secret").await;
+        deny_secret_deletes(&storage).await;
+
+        let job = finish(&jobs, jobs.start("b", "logs/", requester("admin", true), false).await.unwrap()).await;
+        assert_eq!((job.deleted, job.failed), (1, 0));
+        assert!(keys(&storage).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn jobs_require_a_bucket_and_a_prefix() {
+        let (_dir, _storage, _objects, jobs) = setup().await;
+        assert!(jobs.start("b", "", requester("alice", false), false).await.is_err());
+        assert!(jobs.start("missing", "logs/", requester("alice", false), false).await.is_err());
+        assert!(jobs.list("b").await.is_empty());
+    }
+}
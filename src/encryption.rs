@@ -0,0 +1,611 @@
+use crate::models::{CustomerEncryption, EncryptionConfiguration, KeyRotationReport, ObjectEncryption, ObjectMetadata};
+use crate::services::{RangeNotSatisfiable, StorageService};
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload, rand_core::RngCore};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Result, anyhow};
+use axum::http::HeaderMap;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 桶加密配置文件名（位于 .sevino.meta 目录下）
+pub const ENCRYPTION_CONFIG: &str = "encryption";
+/// 桶密钥环文件名（位于 .sevino.meta 目录下）
+const BUCKET_KEYS_CONFIG: &str = "bucket_keys";
+
+/// 加密算法标识
+pub const ALGORITHM: &str = "AES256-GCM";
+/// 明文分块大小
+pub const CHUNK_SIZE: u32 = 64 * 1024;
+/// GCM认证标签长度
+const TAG_SIZE: u64 = 16;
+/// 每个分块的nonce前缀长度（其余4字节为分块序号）
+const NONCE_PREFIX_SIZE: usize = 8;
+
+/// 客户提供密钥（SSE-C）请求头
+pub const CUSTOMER_ALGORITHM_HEADER: &str = "x-sevino-server-side-encryption-customer-algorithm";
+pub const CUSTOMER_KEY_HEADER: &str = "x-sevino-server-side-encryption-customer-key";
+pub const CUSTOMER_KEY_MD5_HEADER: &str = "x-sevino-server-side-encryption-customer-key-md5";
+/// SSE-C 支持的算法
+pub const CUSTOMER_ALGORITHM: &str = "AES256";
+
+/// 客户提供密钥错误
+#[derive(Debug, thiserror::Error)]
+pub enum CustomerKeyError {
+    #[error("Invalid customer key headers: {0}")]
+    Invalid(String),
+    #[error("Object is encrypted with a customer-provided key; the key must be supplied")]
+    Missing,
+    #[error("The customer-provided key does not match the key used to encrypt the object")]
+    Mismatch,
+    #[error("Object is not encrypted with a customer-provided key")]
+    NotEncrypted,
+}
+
+/// 被主密钥加密的桶密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WrappedBucketKey {
+    id: String,
+    /// base64(nonce || ciphertext)
+    wrapped_key: String,
+    created_at: DateTime<Utc>,
+}
+
+/// 桶密钥环（轮换后保留旧密钥）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BucketKeyRing {
+    active_key_id: Option<String>,
+    keys: Vec<WrappedBucketKey>,
+}
+
+impl BucketKeyRing {
+    fn find(&self, id: &str) -> Result<&WrappedBucketKey> {
+        self.keys
+            .iter()
+            .find(|key| key.id == id)
+            .ok_or_else(|| anyhow!("Bucket key '{}' not found", id))
+    }
+}
+
+/// 从环境变量或密钥文件加载主密钥（base64编码的32字节）
+pub fn load_master_key(key: Option<&str>, key_file: Option<&str>) -> Result<Option<[u8; 32]>> {
+    let encoded = match (key, key_file) {
+        (Some(key), _) => key.to_string(),
+        (None, Some(path)) => fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read master key file '{}': {}", path, e))?,
+        (None, None) => return Ok(None),
+    };
+
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| anyhow!("Master key must be base64 encoded: {}", e))?;
+    let key: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("Master key must be exactly 32 bytes"))?;
+    Ok(Some(key))
+}
+
+/// 服务端加密服务 - 信封加密：对象数据密钥由桶密钥包装，桶密钥由主密钥包装
+#[derive(Clone)]
+pub struct EncryptionService {
+    storage: StorageService,
+    master_key: Option<[u8; 32]>,
+    /// 桶名称 -> 密钥环锁，串行化同一个桶的密钥环读改写
+    keyring_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl EncryptionService {
+    pub fn new(storage: StorageService, master_key: Option<[u8; 32]>) -> Self {
+        Self { storage, master_key, keyring_locks: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    fn keyring_lock(&self, bucket_name: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.keyring_locks.lock().unwrap();
+        locks.entry(bucket_name.to_string()).or_default().clone()
+    }
+
+    async fn load_keyring(&self, bucket_name: &str) -> Result<BucketKeyRing> {
+        Ok(self.storage.load_bucket_config(bucket_name, BUCKET_KEYS_CONFIG).await?.unwrap_or_default())
+    }
+
+    /// 校验桶加密配置（启用加密需要配置主密钥）
+    pub fn validate_configuration(&self, config: &EncryptionConfiguration) -> Result<()> {
+        if config.enabled && self.master_key.is_none() {
+            return Err(anyhow!("Server-side encryption requires SEVINO_MASTER_KEY or SEVINO_MASTER_KEY_FILE"));
+        }
+        Ok(())
+    }
+
+    /// 桶是否启用了服务端加密
+    pub async fn is_enabled(&self, bucket_name: &str) -> Result<bool> {
+        let config: Option<EncryptionConfiguration> = self.storage.load_bucket_config(bucket_name, ENCRYPTION_CONFIG).await?;
+        Ok(config.map(|c| c.enabled).unwrap_or(false))
+    }
+
+    /// 加密对象数据，返回密文和加密信息
+    pub async fn encrypt(&self, bucket_name: &str, data: &[u8]) -> Result<(Vec<u8>, ObjectEncryption)> {
+        let (bucket_key_id, bucket_key) = self.active_bucket_key(bucket_name).await?;
+
+        let mut data_key = [0u8; 32];
+        OsRng.fill_bytes(&mut data_key);
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_SIZE];
+        OsRng.fill_bytes(&mut nonce_prefix);
+
+        let ciphertext = encrypt_chunks(&data_key, &nonce_prefix, CHUNK_SIZE, data)?;
+        let encryption = ObjectEncryption {
+            algorithm: ALGORITHM.to_string(),
+            bucket_key_id,
+            wrapped_data_key: wrap_key(&bucket_key, &data_key)?,
+            nonce_prefix: STANDARD.encode(nonce_prefix),
+            chunk_size: CHUNK_SIZE,
+        };
+
+        Ok((ciphertext, encryption))
+    }
+
+    /// 读取并解密对象数据的 [start, end] 区间（只读取涉及的分块）
+    pub async fn decrypt_range(&self, metadata: &ObjectMetadata, path: &Path, start: u64, end: u64) -> Result<Vec<u8>> {
+        let encryption = metadata.encryption.as_ref()
+            .ok_or_else(|| anyhow!("Object '{}' is not encrypted", metadata.key))?;
+        let data_key = self.unwrap_data_key(&metadata.bucket_name, encryption).await?;
+        read_chunks(&data_key, &encryption.nonce_prefix, encryption.chunk_size, metadata.payload_size(), path, start, end)
+    }
+
+    /// 轮换桶密钥：生成新的桶密钥并重新包装桶内所有数据密钥（不重写对象数据）
+    pub async fn rotate_bucket_key(&self, bucket_name: &str) -> Result<KeyRotationReport> {
+        let master_key = self.master_key()?;
+        // 整个轮换期间持有密钥环锁：并发的轮换和首次写入不会覆盖彼此保存的密钥环
+        let lock = self.keyring_lock(bucket_name);
+        let _guard = lock.lock().await;
+        let mut ring = self.load_keyring(bucket_name).await?;
+
+        let mut new_key = [0u8; 32];
+        OsRng.fill_bytes(&mut new_key);
+        let new_key_id = uuid::Uuid::new_v4().to_string();
+        ring.keys.push(WrappedBucketKey {
+            id: new_key_id.clone(),
+            wrapped_key: wrap_key(&master_key, &new_key)?,
+            created_at: Utc::now(),
+        });
+        ring.active_key_id = Some(new_key_id.clone());
+        self.storage.save_bucket_config(bucket_name, BUCKET_KEYS_CONFIG, &ring).await?;
+
+        let mut report = KeyRotationReport {
+            bucket_name: bucket_name.to_string(),
+            new_key_id: new_key_id.clone(),
+            ..Default::default()
+        };
+
+        for mut metadata in self.storage.list_object_metadata(bucket_name).await? {
+            let Some(encryption) = metadata.encryption.as_mut() else { continue };
+            if encryption.bucket_key_id == new_key_id {
+                continue;
+            }
+
+            let old_key = unwrap_key(&master_key, &ring.find(&encryption.bucket_key_id)?.wrapped_key)?;
+            let data_key = unwrap_key(&old_key, &encryption.wrapped_data_key)?;
+            encryption.wrapped_data_key = wrap_key(&new_key, &data_key)?;
+            encryption.bucket_key_id = new_key_id.clone();
+
+            let object_id = StorageService::object_record_id(bucket_name, &metadata);
+            self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+            report.rewrapped_objects += 1;
+        }
+
+        // 旧的桶密钥仍保留在密钥环中：轮换期间并发写入的对象可能仍使用旧密钥
+        Ok(report)
+    }
+
+    /// 使用新的主密钥重新包装所有桶密钥
+    pub async fn rewrap_bucket_keys(&self, bucket_names: &[String], new_master_key: &[u8; 32]) -> Result<usize> {
+        let master_key = self.master_key()?;
+        let mut count = 0;
+
+        for bucket_name in bucket_names {
+            let lock = self.keyring_lock(bucket_name);
+            let _guard = lock.lock().await;
+            let ring: Option<BucketKeyRing> = self.storage.load_bucket_config(bucket_name, BUCKET_KEYS_CONFIG).await?;
+            let Some(mut ring) = ring else { continue };
+
+            for key in ring.keys.iter_mut() {
+                let bucket_key = unwrap_key(&master_key, &key.wrapped_key)?;
+                key.wrapped_key = wrap_key(new_master_key, &bucket_key)?;
+                count += 1;
+            }
+            self.storage.save_bucket_config(bucket_name, BUCKET_KEYS_CONFIG, &ring).await?;
+        }
+
+        Ok(count)
+    }
+
+    /// 桶是否已有加密密钥（曾经写入过加密对象）
+    pub async fn has_bucket_keys(&self, bucket_name: &str) -> Result<bool> {
+        let ring: Option<BucketKeyRing> = self.storage.load_bucket_config(bucket_name, BUCKET_KEYS_CONFIG).await?;
+        Ok(ring.map(|r| !r.keys.is_empty()).unwrap_or(false))
+    }
+
+    fn master_key(&self) -> Result<[u8; 32]> {
+        self.master_key.ok_or_else(|| anyhow!("No master key configured"))
+    }
+
+    /// 获取当前使用的桶密钥（首次使用时生成）
+    async fn active_bucket_key(&self, bucket_name: &str) -> Result<(String, [u8; 32])> {
+        let master_key = self.master_key()?;
+        let ring = self.load_keyring(bucket_name).await?;
+        if let Some(active_id) = &ring.active_key_id {
+            let key = unwrap_key(&master_key, &ring.find(active_id)?.wrapped_key)?;
+            return Ok((active_id.clone(), key));
+        }
+
+        // 首次生成密钥时持锁并重新读取：并发的首次写入只会有一个生成密钥
+        let lock = self.keyring_lock(bucket_name);
+        let _guard = lock.lock().await;
+        let mut ring = self.load_keyring(bucket_name).await?;
+        if let Some(active_id) = &ring.active_key_id {
+            let key = unwrap_key(&master_key, &ring.find(active_id)?.wrapped_key)?;
+            return Ok((active_id.clone(), key));
+        }
+
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        let id = uuid::Uuid::new_v4().to_string();
+        ring.keys.push(WrappedBucketKey {
+            id: id.clone(),
+            wrapped_key: wrap_key(&master_key, &key)?,
+            created_at: Utc::now(),
+        });
+        ring.active_key_id = Some(id.clone());
+        self.storage.save_bucket_config(bucket_name, BUCKET_KEYS_CONFIG, &ring).await?;
+
+        Ok((id, key))
+    }
+
+    async fn unwrap_data_key(&self, bucket_name: &str, encryption: &ObjectEncryption) -> Result<[u8; 32]> {
+        let master_key = self.master_key()?;
+        let ring: BucketKeyRing = self.storage.load_bucket_config(bucket_name, BUCKET_KEYS_CONFIG).await?
+            .ok_or_else(|| anyhow!("Bucket '{}' has no encryption keys", bucket_name))?;
+        let bucket_key = unwrap_key(&master_key, &ring.find(&encryption.bucket_key_id)?.wrapped_key)?;
+        unwrap_key(&bucket_key, &encryption.wrapped_data_key)
+    }
+}
+
+/// 用包装密钥加密一个32字节密钥，返回 base64(nonce || ciphertext)
+fn wrap_key(wrapping_key: &[u8; 32], key: &[u8; 32]) -> Result<String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(wrapping_key));
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), key.as_slice())
+        .map_err(|_| anyhow!("Failed to wrap key"))?;
+
+    let mut wrapped = nonce.to_vec();
+    wrapped.extend_from_slice(&ciphertext);
+    Ok(STANDARD.encode(wrapped))
+}
+
+fn unwrap_key(wrapping_key: &[u8; 32], wrapped: &str) -> Result<[u8; 32]> {
+    let wrapped = STANDARD.decode(wrapped)?;
+    if wrapped.len() < 12 {
+        return Err(anyhow!("Wrapped key is truncated"));
+    }
+    let (nonce, ciphertext) = wrapped.split_at(12);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(wrapping_key));
+    let key = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Failed to unwrap key (wrong master or bucket key?)"))?;
+    key.try_into().map_err(|_| anyhow!("Unwrapped key has invalid length"))
+}
+
+/// 分块nonce：8字节随机前缀 + 4字节分块序号
+fn chunk_nonce(nonce_prefix: &[u8], index: u32) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_SIZE].copy_from_slice(nonce_prefix);
+    nonce[NONCE_PREFIX_SIZE..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+/// 分块附加认证数据：分块序号 + 是否为最后一块（防止分块被重排或截断）
+fn chunk_aad(index: u32, is_last: bool) -> [u8; 5] {
+    let mut aad = [0u8; 5];
+    aad[..4].copy_from_slice(&index.to_be_bytes());
+    aad[4] = is_last as u8;
+    aad
+}
+
+/// 分块加密（空数据也会生成一个只含认证标签的分块）
+pub fn encrypt_chunks(data_key: &[u8; 32], nonce_prefix: &[u8], chunk_size: u32, data: &[u8]) -> Result<Vec<u8>> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(data_key));
+    let chunk_count = data.len().div_ceil(chunk_size as usize).max(1);
+    let mut output = Vec::with_capacity(data.len() + chunk_count * TAG_SIZE as usize);
+
+    for index in 0..chunk_count {
+        let start = index * chunk_size as usize;
+        let end = (start + chunk_size as usize).min(data.len());
+        let nonce = chunk_nonce(nonce_prefix, index as u32);
+        let aad = chunk_aad(index as u32, index == chunk_count - 1);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &data[start..end], aad: &aad })
+            .map_err(|_| anyhow!("Failed to encrypt object data"))?;
+        output.extend_from_slice(&ciphertext);
+    }
+
+    Ok(output)
+}
+
+/// 读取并解密 [start, end] 区间涉及的分块
+pub fn read_chunks(data_key: &[u8; 32], nonce_prefix: &str, chunk_size: u32, size: u64, path: &Path, start: u64, end: u64) -> Result<Vec<u8>> {
+    if size == 0 {
+        return Ok(Vec::new());
+    }
+    if start > end || end >= size {
+        return Err(RangeNotSatisfiable { start, end, size }.into());
+    }
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(data_key));
+    let nonce_prefix = STANDARD.decode(nonce_prefix)?;
+    if nonce_prefix.len() != NONCE_PREFIX_SIZE {
+        return Err(anyhow!("Invalid nonce prefix"));
+    }
+
+    let chunk_size = chunk_size as u64;
+    let last_chunk = (size - 1) / chunk_size;
+    let first = start / chunk_size;
+    let last = end / chunk_size;
+
+    let mut file = File::open(path)?;
+    let mut output = Vec::with_capacity((end - start + 1) as usize);
+
+    for index in first..=last {
+        let plain_len = chunk_size.min(size - index * chunk_size);
+        let mut chunk = vec![0u8; (plain_len + TAG_SIZE) as usize];
+        file.seek(SeekFrom::Start(index * (chunk_size + TAG_SIZE)))?;
+        file.read_exact(&mut chunk)?;
+
+        let nonce = chunk_nonce(&nonce_prefix, index as u32);
+        let aad = chunk_aad(index as u32, index == last_chunk);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &chunk, aad: &aad })
+            .map_err(|_| anyhow!("Failed to decrypt object data (chunk {} is corrupted)", index))?;
+
+        let chunk_start = index * chunk_size;
+        let from = start.saturating_sub(chunk_start) as usize;
+        let to = ((end - chunk_start + 1).min(plain_len)) as usize;
+        output.extend_from_slice(&plaintext[from..to]);
+    }
+
+    Ok(output)
+}
+
+/// 从请求头解析客户提供的密钥（未携带时返回None）
+pub fn parse_customer_key(headers: &HeaderMap) -> Result<Option<[u8; 32]>, CustomerKeyError> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(|v| v.trim().to_string());
+
+    let (algorithm, key, key_md5) = match (header(CUSTOMER_ALGORITHM_HEADER), header(CUSTOMER_KEY_HEADER), header(CUSTOMER_KEY_MD5_HEADER)) {
+        (None, None, None) => return Ok(None),
+        (Some(algorithm), Some(key), Some(key_md5)) => (algorithm, key, key_md5),
+        _ => return Err(CustomerKeyError::Invalid("algorithm, key and key MD5 headers must all be provided".to_string())),
+    };
+
+    if algorithm != CUSTOMER_ALGORITHM {
+        return Err(CustomerKeyError::Invalid(format!("unsupported algorithm '{}', expected {}", algorithm, CUSTOMER_ALGORITHM)));
+    }
+    let key = STANDARD.decode(key).map_err(|_| CustomerKeyError::Invalid("key must be base64 encoded".to_string()))?;
+    let key: [u8; 32] = key.try_into().map_err(|_| CustomerKeyError::Invalid("key must be 256 bits".to_string()))?;
+    if STANDARD.encode(md5::compute(key).0) != key_md5 {
+        return Err(CustomerKeyError::Invalid("key MD5 does not match the key".to_string()));
+    }
+
+    Ok(Some(key))
+}
+
+/// 使用客户提供的密钥加密对象数据（只保存加盐的密钥指纹）
+pub fn encrypt_with_customer_key(customer_key: &[u8; 32], data: &[u8]) -> Result<(Vec<u8>, CustomerEncryption)> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce_prefix = [0u8; NONCE_PREFIX_SIZE];
+    OsRng.fill_bytes(&mut nonce_prefix);
+
+    let ciphertext = encrypt_chunks(customer_key, &nonce_prefix, CHUNK_SIZE, data)?;
+    let encryption = CustomerEncryption {
+        algorithm: CUSTOMER_ALGORITHM.to_string(),
+        key_salt: STANDARD.encode(salt),
+        key_fingerprint: customer_key_fingerprint(customer_key, &salt)?,
+        nonce_prefix: STANDARD.encode(nonce_prefix),
+        chunk_size: CHUNK_SIZE,
+    };
+
+    Ok((ciphertext, encryption))
+}
+
+/// 校验客户密钥指纹后解密 [start, end] 区间
+pub fn decrypt_with_customer_key(customer_key: &[u8; 32], metadata: &ObjectMetadata, path: &Path, start: u64, end: u64) -> Result<Vec<u8>> {
+    let encryption = metadata.customer_encryption.as_ref().ok_or(CustomerKeyError::NotEncrypted)?;
+    let salt = STANDARD.decode(&encryption.key_salt)?;
+    if customer_key_fingerprint(customer_key, &salt)? != encryption.key_fingerprint {
+        return Err(CustomerKeyError::Mismatch.into());
+    }
+
+    read_chunks(customer_key, &encryption.nonce_prefix, encryption.chunk_size, metadata.payload_size(), path, start, end)
+}
+
+/// 密钥指纹：HMAC-SHA256(salt, key)
+fn customer_key_fingerprint(customer_key: &[u8; 32], salt: &[u8]) -> Result<String> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(salt).map_err(|e| anyhow!("Invalid key salt: {}", e))?;
+    mac.update(customer_key);
+    Ok(format!("{:x}", mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Object;
+    use axum::http::HeaderValue;
+
+    const KEY: [u8; 32] = [7; 32];
+    const PREFIX: [u8; NONCE_PREFIX_SIZE] = [1, 2, 3, 4, 5, 6, 7, 8];
+    /// 小分块便于覆盖跨分块的区间
+    const SMALL_CHUNK: u32 = 16;
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    fn encrypted_file(data: &[u8], chunk_size: u32) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), encrypt_chunks(&KEY, &PREFIX, chunk_size, data).unwrap()).unwrap();
+        file
+    }
+
+    fn read(file: &tempfile::NamedTempFile, size: u64, start: u64, end: u64) -> Result<Vec<u8>> {
+        read_chunks(&KEY, &STANDARD.encode(PREFIX), SMALL_CHUNK, size, file.path(), start, end)
+    }
+
+    fn swap_chunks(file: &tempfile::NamedTempFile, a: usize, b: usize) {
+        let stride = SMALL_CHUNK as usize + TAG_SIZE as usize;
+        let mut ciphertext = fs::read(file.path()).unwrap();
+        let chunk_a = ciphertext[a * stride..(a + 1) * stride].to_vec();
+        let chunk_b = ciphertext[b * stride..(b + 1) * stride].to_vec();
+        ciphertext[a * stride..(a + 1) * stride].copy_from_slice(&chunk_b);
+        ciphertext[b * stride..(b + 1) * stride].copy_from_slice(&chunk_a);
+        fs::write(file.path(), ciphertext).unwrap();
+    }
+
+    #[test]
+    fn ciphertext_adds_one_tag_per_chunk() {
+        assert_eq!(encrypt_chunks(&KEY, &PREFIX, SMALL_CHUNK, &plaintext(50)).unwrap().len(), 50 + 4 * 16);
+        assert_eq!(encrypt_chunks(&KEY, &PREFIX, SMALL_CHUNK, &plaintext(48)).unwrap().len(), 48 + 3 * 16);
+        assert_eq!(encrypt_chunks(&KEY, &PREFIX, SMALL_CHUNK, &[]).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn every_range_decrypts_across_chunk_boundaries() {
+        // 50 字节：三个完整分块加一个2字节的最后分块；48 字节：最后分块正好是完整的
+        for size in [50usize, 48, 16, 1] {
+            let data = plaintext(size);
+            let file = encrypted_file(&data, SMALL_CHUNK);
+            for start in 0..size {
+                for end in start..size {
+                    let range = read(&file, size as u64, start as u64, end as u64).unwrap();
+                    assert_eq!(range, &data[start..=end], "size {} range {}-{}", size, start, end);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn default_chunk_size_round_trips() {
+        let data = plaintext(CHUNK_SIZE as usize * 2 + 100);
+        let file = encrypted_file(&data, CHUNK_SIZE);
+        let size = data.len() as u64;
+        let chunk = CHUNK_SIZE as u64;
+        let read = |start: u64, end: u64| read_chunks(&KEY, &STANDARD.encode(PREFIX), CHUNK_SIZE, size, file.path(), start, end).unwrap();
+        assert_eq!(read(0, size - 1), data);
+        assert_eq!(read(chunk - 1, chunk), &data[chunk as usize - 1..=chunk as usize]);
+        assert_eq!(read(2 * chunk, size - 1), &data[2 * chunk as usize..]);
+    }
+
+    #[test]
+    fn empty_objects_read_as_empty() {
+        let file = encrypted_file(&[], SMALL_CHUNK);
+        assert!(read(&file, 0, 0, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn out_of_bounds_ranges_are_not_satisfiable() {
+        let file = encrypted_file(&plaintext(50), SMALL_CHUNK);
+        for (start, end) in [(0, 50), (50, 60), (10, 5)] {
+            let error = read(&file, 50, start, end).unwrap_err();
+            let error = error.downcast_ref::<RangeNotSatisfiable>().unwrap();
+            assert_eq!((error.start, error.end, error.size), (start, end, 50));
+        }
+    }
+
+    #[test]
+    fn tampered_chunks_fail_authentication() {
+        let file = encrypted_file(&plaintext(50), SMALL_CHUNK);
+        let mut ciphertext = fs::read(file.path()).unwrap();
+        ciphertext[40] ^= 1;
+        fs::write(file.path(), ciphertext).unwrap();
+
+        assert_eq!(read(&file, 50, 0, 15).unwrap(), &plaintext(50)[..16]);
+        let error = read(&file, 50, 0, 49).unwrap_err();
+        assert!(error.to_string().contains("chunk 1"));
+    }
+
+    #[test]
+    fn reordered_chunks_fail_authentication() {
+        let file = encrypted_file(&plaintext(64), SMALL_CHUNK);
+        swap_chunks(&file, 0, 1);
+        assert!(read(&file, 64, 0, 0).is_err());
+        assert!(read(&file, 64, 16, 16).is_err());
+        assert_eq!(read(&file, 64, 32, 63).unwrap(), &plaintext(64)[32..]);
+    }
+
+    #[test]
+    fn truncated_objects_fail_authentication() {
+        // 去掉最后一个分块后，原来的倒数第二块没有“最后一块”的标记
+        let data = plaintext(64);
+        let file = encrypted_file(&data, SMALL_CHUNK);
+        let ciphertext = fs::read(file.path()).unwrap();
+        fs::write(file.path(), &ciphertext[..3 * (SMALL_CHUNK as usize + TAG_SIZE as usize)]).unwrap();
+        assert!(read(&file, 48, 32, 47).is_err());
+        assert_eq!(read(&file, 48, 0, 31).unwrap(), &data[..32]);
+    }
+
+    #[test]
+    fn wrong_key_or_prefix_is_rejected() {
+        let file = encrypted_file(&plaintext(20), SMALL_CHUNK);
+        assert!(read_chunks(&[8; 32], &STANDARD.encode(PREFIX), SMALL_CHUNK, 20, file.path(), 0, 19).is_err());
+        assert!(read_chunks(&KEY, &STANDARD.encode([0u8; NONCE_PREFIX_SIZE]), SMALL_CHUNK, 20, file.path(), 0, 19).is_err());
+        assert!(read_chunks(&KEY, &STANDARD.encode([0u8; 4]), SMALL_CHUNK, 20, file.path(), 0, 19).is_err());
+    }
+
+    #[test]
+    fn wrapped_keys_round_trip() {
+        let wrapped = wrap_key(&KEY, &[9; 32]).unwrap();
+        assert_eq!(unwrap_key(&KEY, &wrapped).unwrap(), [9; 32]);
+        assert!(unwrap_key(&[8; 32], &wrapped).is_err());
+        assert!(unwrap_key(&KEY, "AAAA").is_err());
+    }
+
+    fn customer_headers(key: &[u8], md5_of: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CUSTOMER_ALGORITHM_HEADER, HeaderValue::from_static(CUSTOMER_ALGORITHM));
+        headers.insert(CUSTOMER_KEY_HEADER, HeaderValue::from_str(&STANDARD.encode(key)).unwrap());
+        headers.insert(CUSTOMER_KEY_MD5_HEADER, HeaderValue::from_str(&STANDARD.encode(md5::compute(md5_of).0)).unwrap());
+        headers
+    }
+
+    #[test]
+    fn customer_key_headers_are_validated() {
+        assert!(parse_customer_key(&HeaderMap::new()).unwrap().is_none());
+        assert_eq!(parse_customer_key(&customer_headers(&KEY, &KEY)).unwrap(), Some(KEY));
+        assert!(matches!(parse_customer_key(&customer_headers(&KEY, &[8; 32])), Err(CustomerKeyError::Invalid(_))));
+        assert!(matches!(parse_customer_key(&customer_headers(&[7; 16], &[7; 16])), Err(CustomerKeyError::Invalid(_))));
+
+        let mut partial = customer_headers(&KEY, &KEY);
+        partial.remove(CUSTOMER_KEY_MD5_HEADER);
+        assert!(matches!(parse_customer_key(&partial), Err(CustomerKeyError::Invalid(_))));
+    }
+
+    #[test]
+    fn customer_key_ranges_require_the_same_key() {
+        let data = plaintext(100);
+        let (ciphertext, encryption) = encrypt_with_customer_key(&KEY, &data).unwrap();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), ciphertext).unwrap();
+        let mut metadata: ObjectMetadata = Object::new("k".to_string(), "b".to_string(), 100, "text/plain".to_string(), String::new(), HashMap::new()).into();
+        metadata.customer_encryption = Some(encryption);
+
+        assert_eq!(decrypt_with_customer_key(&KEY, &metadata, file.path(), 10, 20).unwrap(), &data[10..=20]);
+        let error = decrypt_with_customer_key(&[8; 32], &metadata, file.path(), 0, 99).unwrap_err();
+        assert!(matches!(error.downcast_ref::<CustomerKeyError>(), Some(CustomerKeyError::Mismatch)));
+    }
+}
@@ -0,0 +1,318 @@
+use crate::models::{CreateUploadPolicyRequest, Object, UploadPolicy, UploadPolicyDocument};
+use crate::services::{DeduplicationMode, ObjectService};
+use crate::share_links::hex_decode;
+use crate::utils::load_or_create_secret;
+use anyhow::anyhow;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use multipart::server::Multipart;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// 默认有效期（秒）
+const DEFAULT_EXPIRES_IN_SECS: u64 = 3600;
+/// 最长有效期（秒）
+const MAX_EXPIRES_IN_SECS: u64 = 7 * 24 * 3600;
+/// 映射为用户元数据的表单字段前缀
+const METADATA_FIELD_PREFIX: &str = "x-sevino-meta-";
+/// 单个文本字段的最大字节数
+const MAX_FIELD_BYTES: u64 = 64 * 1024;
+/// 允许的成功状态码
+const SUCCESS_STATUSES: [u16; 3] = [200, 201, 204];
+
+/// 表单上传失败原因
+#[derive(Debug, thiserror::Error)]
+pub enum FormUploadError {
+    #[error("Malformed form upload: {0}")]
+    Malformed(String),
+    #[error("Invalid upload policy signature")]
+    InvalidSignature,
+    #[error("Upload policy has expired")]
+    Expired,
+    #[error("Upload policy violation: {0}")]
+    PolicyViolation(String),
+    #[error("File exceeds the maximum allowed size of {0} bytes")]
+    TooLarge(u64),
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+/// 上传成功后的响应方式
+pub enum FormUploadOutcome {
+    /// 303 重定向到 success_action_redirect
+    Redirect(String),
+    /// 按 success_action_status 返回
+    Status(u16),
+}
+
+/// 已通过策略校验的表单上传
+pub struct FormUpload {
+    pub document: UploadPolicyDocument,
+    pub key: String,
+    pub content_type: String,
+    pub user_metadata: HashMap<String, String>,
+    pub data: Vec<u8>,
+    pub outcome: FormUploadOutcome,
+}
+
+/// 浏览器表单上传服务 - 签发带HMAC签名和过期时间的上传策略，并校验 multipart/form-data 上传
+#[derive(Clone)]
+pub struct FormUploadService {
+    object_service: ObjectService,
+    secret: Vec<u8>,
+}
+
+impl FormUploadService {
+    /// 未配置密钥时自动生成一个并保存到 {data_dir}/.sevino.uploads/secret
+    pub fn new(object_service: ObjectService, data_dir: &str, secret: &str) -> anyhow::Result<Self> {
+        let uploads_dir = PathBuf::from(data_dir).join(".sevino.uploads");
+        std::fs::create_dir_all(&uploads_dir)?;
+        let secret = load_or_create_secret(&uploads_dir, secret)?;
+
+        Ok(Self {
+            object_service,
+            secret: secret.into_bytes(),
+        })
+    }
+
+    /// 签发上传策略
+    pub fn create_policy(&self, bucket_name: &str, request: CreateUploadPolicyRequest, created_by: &str) -> anyhow::Result<UploadPolicy> {
+        let expires_in = request.expires_in_secs.unwrap_or(DEFAULT_EXPIRES_IN_SECS);
+        if expires_in == 0 || expires_in > MAX_EXPIRES_IN_SECS {
+            return Err(anyhow!("expires_in_secs must be between 1 and {}", MAX_EXPIRES_IN_SECS));
+        }
+        if request.max_content_length == Some(0) {
+            return Err(anyhow!("max_content_length must be greater than 0"));
+        }
+        if request.content_type.as_deref().is_some_and(|t| t.is_empty() || !t.contains('/')) {
+            return Err(anyhow!("content_type must be a MIME type such as 'image/png' or 'image/*'"));
+        }
+        if let Some(redirect) = &request.success_action_redirect {
+            validate_redirect(redirect)?;
+        }
+        if request.success_action_status.is_some_and(|status| !SUCCESS_STATUSES.contains(&status)) {
+            return Err(anyhow!("success_action_status must be 200, 201 or 204"));
+        }
+
+        let document = UploadPolicyDocument {
+            bucket_name: bucket_name.to_string(),
+            key_prefix: request.key_prefix,
+            max_content_length: request.max_content_length,
+            content_type: request.content_type,
+            success_action_redirect: request.success_action_redirect,
+            success_action_status: request.success_action_status,
+            expires_at: Utc::now() + Duration::seconds(expires_in as i64),
+            created_by: created_by.to_string(),
+        };
+        let policy = base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(&document)?);
+        let signature = format!("{:x}", self.mac(&policy)?.finalize().into_bytes());
+
+        Ok(UploadPolicy {
+            url: format!("/api/buckets/{}", bucket_name),
+            policy,
+            signature,
+            document,
+        })
+    }
+
+    /// 从请求体流式解析 multipart/form-data 表单并按策略校验；文件字段（file）之前必须出现 key、policy 和 signature，
+    /// 签名校验通过后才读取文件内容，文件之后的字段不再读取
+    pub fn parse<R: Read>(&self, bucket_name: &str, content_type: &str, body: R) -> Result<FormUpload, FormUploadError> {
+        let malformed = || FormUploadError::Malformed("Content-Type must be multipart/form-data with a boundary".to_string());
+        if !content_type.trim_start().to_ascii_lowercase().starts_with("multipart/form-data") {
+            return Err(malformed());
+        }
+        let boundary = content_type
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(name, _)| name.eq_ignore_ascii_case("boundary"))
+            .map(|(_, value)| value.trim_matches('"').to_string())
+            .ok_or_else(malformed)?;
+
+        let mut form = Multipart::with_body(body, boundary);
+        // 字段名不区分大小写
+        let mut fields: HashMap<String, String> = HashMap::new();
+        loop {
+            let mut entry = match form.read_entry() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => return Err(FormUploadError::Malformed("Form has no 'file' field".to_string())),
+                Err(e) => return Err(FormUploadError::Malformed(e.to_string())),
+            };
+            let name = entry.headers.name.to_ascii_lowercase();
+
+            if name != "file" {
+                let mut value = Vec::new();
+                entry.data.by_ref().take(MAX_FIELD_BYTES + 1).read_to_end(&mut value)
+                    .map_err(|e| FormUploadError::Malformed(e.to_string()))?;
+                if value.len() as u64 > MAX_FIELD_BYTES {
+                    return Err(FormUploadError::Malformed(format!("Field '{}' is too large", name)));
+                }
+                let value = String::from_utf8(value)
+                    .map_err(|_| FormUploadError::Malformed(format!("Field '{}' is not valid UTF-8", name)))?;
+                fields.insert(name, value);
+                continue;
+            }
+
+            let document = self.verify(bucket_name, &fields)?;
+
+            let filename = entry.headers.filename.clone().unwrap_or_default();
+            let key = fields.get("key")
+                .ok_or_else(|| FormUploadError::Malformed("Missing 'key' field".to_string()))?
+                .replace("${filename}", &filename);
+            if key.is_empty() {
+                return Err(FormUploadError::Malformed("Object key must not be empty".to_string()));
+            }
+            if !key.starts_with(&document.key_prefix) {
+                return Err(FormUploadError::PolicyViolation(format!("Key '{}' does not start with '{}'", key, document.key_prefix)));
+            }
+
+            // 表单的 Content-Type 字段优先于文件部分自带的类型
+            let content_type = fields.get("content-type").cloned()
+                .or_else(|| entry.headers.content_type.as_ref().map(|mime| mime.to_string()))
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            if let Some(allowed) = &document.content_type {
+                if !content_type_matches(allowed, &content_type) {
+                    return Err(FormUploadError::PolicyViolation(format!("Content type '{}' is not allowed, expected '{}'", content_type, allowed)));
+                }
+            }
+
+            let mut data = Vec::new();
+            match document.max_content_length {
+                Some(max) => {
+                    entry.data.by_ref().take(max + 1).read_to_end(&mut data)
+                        .map_err(|e| FormUploadError::Malformed(e.to_string()))?;
+                    if data.len() as u64 > max {
+                        return Err(FormUploadError::TooLarge(max));
+                    }
+                }
+                None => {
+                    entry.data.read_to_end(&mut data)
+                        .map_err(|e| FormUploadError::Malformed(e.to_string()))?;
+                }
+            }
+
+            let user_metadata = fields.iter()
+                .filter_map(|(name, value)| {
+                    name.strip_prefix(METADATA_FIELD_PREFIX)
+                        .filter(|key| !key.is_empty())
+                        .map(|key| (key.to_string(), value.clone()))
+                })
+                .collect();
+            let outcome = Self::outcome(&document, &fields)?;
+
+            return Ok(FormUpload { document, key, content_type, user_metadata, data, outcome });
+        }
+    }
+
+    /// 写入已校验的表单上传，返回对象和成功后的响应方式
+    pub async fn store(&self, bucket_name: &str, upload: FormUpload) -> Result<(Object, FormUploadOutcome), FormUploadError> {
+        let object = self.object_service.put_object_with_deduplication(
+            bucket_name,
+            &upload.key,
+            upload.data,
+            &upload.content_type,
+            upload.user_metadata,
+            DeduplicationMode::Allow,
+        ).await?;
+        Ok((object, upload.outcome))
+    }
+
+    /// 校验签名、所属桶和有效期，返回策略内容
+    fn verify(&self, bucket_name: &str, fields: &HashMap<String, String>) -> Result<UploadPolicyDocument, FormUploadError> {
+        let policy = fields.get("policy")
+            .ok_or_else(|| FormUploadError::Malformed("Missing 'policy' field before 'file'".to_string()))?;
+        let signature = fields.get("signature")
+            .ok_or_else(|| FormUploadError::Malformed("Missing 'signature' field before 'file'".to_string()))?;
+
+        let signature = hex_decode(signature.trim()).ok_or(FormUploadError::InvalidSignature)?;
+        if self.mac(policy)?.verify_slice(&signature).is_err() {
+            return Err(FormUploadError::InvalidSignature);
+        }
+
+        let document: UploadPolicyDocument = base64::engine::general_purpose::STANDARD.decode(policy)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| FormUploadError::Malformed("Policy is not valid base64-encoded JSON".to_string()))?;
+        if document.bucket_name != bucket_name {
+            return Err(FormUploadError::PolicyViolation(format!("Policy was issued for bucket '{}'", document.bucket_name)));
+        }
+        if Utc::now() >= document.expires_at {
+            return Err(FormUploadError::Expired);
+        }
+        Ok(document)
+    }
+
+    /// 只重定向到策略中签名固定的 success_action_redirect，表单字段必须与之一致；
+    /// success_action_status 可在策略未设置时由表单指定
+    fn outcome(document: &UploadPolicyDocument, fields: &HashMap<String, String>) -> Result<FormUploadOutcome, FormUploadError> {
+        let redirect = match (&document.success_action_redirect, fields.get("success_action_redirect")) {
+            (Some(allowed), Some(requested)) if allowed != requested => {
+                return Err(FormUploadError::PolicyViolation("success_action_redirect does not match the policy".to_string()));
+            }
+            (Some(redirect), _) => Some(redirect.clone()),
+            (None, Some(requested)) if !requested.is_empty() => {
+                return Err(FormUploadError::PolicyViolation("success_action_redirect is not allowed by the policy".to_string()));
+            }
+            (None, _) => None,
+        };
+        if let Some(redirect) = redirect {
+            return Ok(FormUploadOutcome::Redirect(redirect));
+        }
+
+        let requested = match fields.get("success_action_status") {
+            Some(status) => Some(status.trim().parse::<u16>().ok()
+                .filter(|status| SUCCESS_STATUSES.contains(status))
+                .ok_or_else(|| FormUploadError::Malformed("success_action_status must be 200, 201 or 204".to_string()))?),
+            None => None,
+        };
+        match (document.success_action_status, requested) {
+            (Some(allowed), Some(requested)) if allowed != requested => {
+                Err(FormUploadError::PolicyViolation("success_action_status does not match the policy".to_string()))
+            }
+            (allowed, requested) => Ok(FormUploadOutcome::Status(allowed.or(requested).unwrap_or(200))),
+        }
+    }
+
+    fn mac(&self, policy: &str) -> anyhow::Result<Hmac<Sha256>> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret)
+            .map_err(|e| anyhow!("Invalid upload policy secret: {}", e))?;
+        mac.update(policy.as_bytes());
+        Ok(mac)
+    }
+}
+
+/// 生成重定向地址：在 success_action_redirect 上附加 bucket、key 和 etag 查询参数
+pub fn redirect_location(redirect: &str, object: &Object) -> String {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("bucket", &object.bucket_name)
+        .append_pair("key", &object.key)
+        .append_pair("etag", &object.etag)
+        .finish();
+    let separator = if redirect.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", redirect, separator, query)
+}
+
+/// 重定向地址必须是 http(s) 绝对地址或以 / 开头的站内路径
+fn validate_redirect(redirect: &str) -> anyhow::Result<()> {
+    if redirect.starts_with('/') && !redirect.starts_with("//") {
+        return Ok(());
+    }
+    match url::Url::parse(redirect) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+        _ => Err(anyhow!("success_action_redirect must be an http(s) URL or a path starting with '/'")),
+    }
+}
+
+/// 内容类型匹配（忽略参数和大小写，`type/*` 匹配该主类型下的所有子类型）
+fn content_type_matches(allowed: &str, content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    let allowed = allowed.trim().to_ascii_lowercase();
+    match allowed.strip_suffix('*') {
+        Some(prefix) => essence.starts_with(prefix),
+        None => essence == allowed,
+    }
+}
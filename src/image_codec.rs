@@ -0,0 +1,141 @@
+use anyhow::{Result, anyhow};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{DynamicImage, ImageDecoder, ImageEncoder, ImageReader, Limits};
+use std::io::Cursor;
+
+/// RGBA8 图像（按行存储，每像素4字节）
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, pixels: vec![0; width as usize * height as usize * 4] }
+    }
+}
+
+/// 图片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "png" => Ok(Self::Png),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            "webp" => Ok(Self::WebP),
+            other => Err(anyhow!("Unsupported image format '{}', expected png, jpeg or webp", other)),
+        }
+    }
+
+    /// 按文件头识别图片格式
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]) {
+            Some(Self::Png)
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(Self::Jpeg)
+        } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(Self::WebP)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+            Self::WebP => "webp",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+        }
+    }
+
+    fn codec(&self) -> image::ImageFormat {
+        match self {
+            Self::Png => image::ImageFormat::Png,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::WebP => image::ImageFormat::WebP,
+        }
+    }
+}
+
+fn reader(data: &[u8]) -> Result<ImageReader<Cursor<&[u8]>>> {
+    let format = ImageFormat::detect(data).ok_or_else(|| anyhow!("Not a PNG, JPEG or WebP image"))?;
+    Ok(ImageReader::with_format(Cursor::new(data), format.codec()))
+}
+
+/// 只解析文件头得到图片尺寸，用于在分配像素内存之前检查像素上限
+pub fn dimensions(data: &[u8]) -> Result<(u32, u32)> {
+    reader(data)?.into_dimensions().map_err(|e| anyhow!("Invalid image: {}", e))
+}
+
+/// 解码 PNG、JPEG 或 WebP（像素数超过 max_pixels 时在分配内存前拒绝），按 EXIF 方向旋转
+pub fn decode(data: &[u8], max_pixels: u64) -> Result<RgbaImage> {
+    let mut decoder = reader(data)?.into_decoder().map_err(|e| anyhow!("Invalid image: {}", e))?;
+    let (width, height) = decoder.dimensions();
+    check_pixels(width, height, max_pixels)?;
+
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(max_pixels.saturating_mul(16));
+    decoder.set_limits(limits).map_err(|e| anyhow!("Image exceeds decoding limits: {}", e))?;
+    let orientation = decoder.orientation().map_err(|e| anyhow!("Invalid image: {}", e))?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| anyhow!("Invalid image: {}", e))?;
+    image.apply_orientation(orientation);
+
+    let image = image.into_rgba8();
+    Ok(RgbaImage { width: image.width(), height: image.height(), pixels: image.into_raw() })
+}
+
+/// 编码图片；quality 只对 JPEG 有效（PNG 和 WebP 均为无损）
+pub fn encode(image: &RgbaImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let (width, height) = (image.width, image.height);
+    let result = match format {
+        ImageFormat::Png => PngEncoder::new(&mut out)
+            .write_image(&image.pixels, width, height, image::ExtendedColorType::Rgba8),
+        // JPEG 不支持透明度，半透明像素合成到白色背景上
+        ImageFormat::Jpeg => JpegEncoder::new_with_quality(&mut out, quality.clamp(1, 100))
+            .write_image(&flatten_on_white(image), width, height, image::ExtendedColorType::Rgb8),
+        ImageFormat::WebP => WebPEncoder::new_lossless(&mut out)
+            .write_image(&image.pixels, width, height, image::ExtendedColorType::Rgba8),
+    };
+    result.map_err(|e| anyhow!("Failed to encode {} image: {}", format.name(), e))?;
+    Ok(out)
+}
+
+fn flatten_on_white(image: &RgbaImage) -> Vec<u8> {
+    image.pixels
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let alpha = p[3] as u32;
+            [0, 1, 2].map(|c| ((p[c] as u32 * alpha + 255 * (255 - alpha) + 127) / 255) as u8)
+        })
+        .collect()
+}
+
+fn check_pixels(width: u32, height: u32, max_pixels: u64) -> Result<()> {
+    if width == 0 || height == 0 {
+        return Err(anyhow!("Image has zero width or height"));
+    }
+    let pixels = width as u64 * height as u64;
+    if pixels > max_pixels {
+        return Err(anyhow!("Image has {} pixels, more than the limit of {}", pixels, max_pixels));
+    }
+    Ok(())
+}
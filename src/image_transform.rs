@@ -0,0 +1,290 @@
+use crate::image_codec::{self, ImageFormat, RgbaImage};
+use crate::services::{ObjectService, StorageService};
+use crate::utils::sha256_hash;
+use std::fs;
+use thiserror::Error;
+
+/// 变换结果的最大边长
+pub const MAX_OUTPUT_DIMENSION: u32 = 4096;
+/// 默认 JPEG 质量
+pub const DEFAULT_QUALITY: u8 = 80;
+
+/// 缩放方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    /// 等比缩放到目标框内（默认）
+    Contain,
+    /// 等比缩放填满目标框，居中裁掉多余部分
+    Cover,
+    /// 拉伸到目标尺寸，不保持宽高比
+    Fill,
+}
+
+impl FitMode {
+    fn parse(value: &str) -> Result<Self, ImageTransformError> {
+        match value.to_ascii_lowercase().as_str() {
+            "contain" => Ok(Self::Contain),
+            "cover" => Ok(Self::Cover),
+            "fill" => Ok(Self::Fill),
+            other => Err(ImageTransformError::InvalidParameter(format!("Unsupported fit '{}', expected contain, cover or fill", other))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Contain => "contain",
+            Self::Cover => "cover",
+            Self::Fill => "fill",
+        }
+    }
+}
+
+/// 图片变换参数
+#[derive(Debug, Clone)]
+pub struct TransformParams {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fit: FitMode,
+    /// 输出格式，未指定时与源图相同
+    pub format: Option<ImageFormat>,
+    pub quality: u8,
+}
+
+impl TransformParams {
+    /// 解析查询参数；没有任何变换参数时返回 None（按原样返回对象）
+    pub fn parse(
+        width: Option<u32>,
+        height: Option<u32>,
+        fit: Option<&str>,
+        format: Option<&str>,
+        quality: Option<u8>,
+    ) -> Result<Option<Self>, ImageTransformError> {
+        if width.is_none() && height.is_none() && fit.is_none() && format.is_none() && quality.is_none() {
+            return Ok(None);
+        }
+        for dimension in [width, height].into_iter().flatten() {
+            if dimension == 0 || dimension > MAX_OUTPUT_DIMENSION {
+                return Err(ImageTransformError::InvalidParameter(format!("width and height must be between 1 and {}", MAX_OUTPUT_DIMENSION)));
+            }
+        }
+        let quality = quality.unwrap_or(DEFAULT_QUALITY);
+        if !(1..=100).contains(&quality) {
+            return Err(ImageTransformError::InvalidParameter("quality must be between 1 and 100".to_string()));
+        }
+        Ok(Some(Self {
+            width,
+            height,
+            fit: fit.map(FitMode::parse).transpose()?.unwrap_or(FitMode::Contain),
+            format: format.map(ImageFormat::parse).transpose()
+                .map_err(|e| ImageTransformError::InvalidParameter(e.to_string()))?,
+            quality,
+        }))
+    }
+
+    /// 缓存键中使用的规范化参数串（质量只影响 JPEG）
+    fn canonical(&self, output: ImageFormat) -> String {
+        let dimension = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+        let quality = if output == ImageFormat::Jpeg { self.quality } else { 0 };
+        format!("w={};h={};fit={};format={};q={}", dimension(self.width), dimension(self.height), self.fit.name(), output.name(), quality)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ImageTransformError {
+    #[error("{0}")]
+    InvalidParameter(String),
+    #[error("{0}")]
+    UnsupportedImage(String),
+    #[error("{0}")]
+    TooLarge(String),
+    /// 读取源对象失败（对象不存在、SSE-C 密钥错误等）
+    #[error(transparent)]
+    Object(anyhow::Error),
+}
+
+/// 变换后的图片
+pub struct TransformedImage {
+    pub data: Vec<u8>,
+    pub content_type: &'static str,
+    /// 由源 ETag 和变换参数派生的 ETag
+    pub etag: String,
+}
+
+/// 图片变换服务：在 GET 时缩放和转换格式，结果按源 ETag 加参数缓存为派生对象
+#[derive(Clone)]
+pub struct ImageTransformService {
+    storage: StorageService,
+    object_service: ObjectService,
+    max_pixels: u64,
+}
+
+impl ImageTransformService {
+    /// `max_pixels` 为允许解码的源图最大像素数（防止解压炸弹）
+    pub fn new(storage: StorageService, object_service: ObjectService, max_pixels: u64) -> Self {
+        Self { storage, object_service, max_pixels }
+    }
+
+    pub async fn transform(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        params: &TransformParams,
+        customer_key: Option<&[u8; 32]>,
+    ) -> Result<TransformedImage, ImageTransformError> {
+        let (data, metadata) = self.object_service
+            .get_object_range_with_customer_key(bucket_name, key, None, customer_key).await
+            .map_err(ImageTransformError::Object)?;
+
+        let source = ImageFormat::detect(&data)
+            .ok_or_else(|| ImageTransformError::UnsupportedImage("Object is not a PNG, JPEG or WebP image".to_string()))?;
+        let output = params.format.unwrap_or(source);
+        let name = sha256_hash(format!("{}|{}", metadata.etag, params.canonical(output)).as_bytes());
+        let etag = format!("\"{}\"", name);
+
+        // 加密对象的变换结果不落盘缓存，避免以明文保存
+        let cache_path = (metadata.encryption.is_none() && metadata.customer_encryption.is_none())
+            .then(|| self.storage.derived_object_dir(bucket_name, key).join(format!("{}.{}", name, output.name())));
+        if let Some(data) = cache_path.as_ref().and_then(|path| fs::read(path).ok()) {
+            return Ok(TransformedImage { data, content_type: output.content_type(), etag });
+        }
+
+        let (width, height) = image_codec::dimensions(&data)
+            .map_err(|e| ImageTransformError::UnsupportedImage(e.to_string()))?;
+        if width as u64 * height as u64 > self.max_pixels {
+            return Err(ImageTransformError::TooLarge(format!(
+                "Image is {}x{} pixels, more than the limit of {} pixels", width, height, self.max_pixels
+            )));
+        }
+
+        let max_pixels = self.max_pixels;
+        let params = params.clone();
+        let encoded = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<u8>> {
+            let image = image_codec::decode(&data, max_pixels)?;
+            let image = resize_to_fit(&image, &params);
+            image_codec::encode(&image, output, params.quality)
+        }).await
+            .map_err(|e| ImageTransformError::Object(e.into()))?
+            .map_err(|e| ImageTransformError::UnsupportedImage(e.to_string()))?;
+
+        if let Some(path) = cache_path {
+            if let Err(e) = write_cache(&path, &encoded) {
+                tracing::warn!("Failed to cache transformed image {}/{}: {}", bucket_name, key, e);
+            }
+        }
+        Ok(TransformedImage { data: encoded, content_type: output.content_type(), etag })
+    }
+}
+
+/// 先写临时文件再重命名，并发请求不会读到半个文件
+fn write_cache(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    fs::write(&temp, data)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// 按参数计算目标尺寸并缩放（cover 模式先居中裁剪到目标宽高比）
+fn resize_to_fit(image: &RgbaImage, params: &TransformParams) -> RgbaImage {
+    let (source_w, source_h) = (image.width as f64, image.height as f64);
+    let scaled = |value: f64| (value.round() as u32).clamp(1, MAX_OUTPUT_DIMENSION);
+    let (width, height) = match (params.width, params.height) {
+        (None, None) => return image.clone(),
+        (Some(w), None) => (w, scaled(source_h * w as f64 / source_w)),
+        (None, Some(h)) => (scaled(source_w * h as f64 / source_h), h),
+        (Some(w), Some(h)) => match params.fit {
+            FitMode::Contain => {
+                let scale = (w as f64 / source_w).min(h as f64 / source_h);
+                (scaled(source_w * scale), scaled(source_h * scale))
+            }
+            FitMode::Cover | FitMode::Fill => (w, h),
+        },
+    };
+
+    let (mut x0, mut y0, mut crop_w, mut crop_h) = (0, 0, image.width, image.height);
+    if params.fit == FitMode::Cover && params.width.is_some() && params.height.is_some() {
+        let target_ratio = width as f64 / height as f64;
+        if source_w / source_h > target_ratio {
+            crop_w = ((source_h * target_ratio).round() as u32).clamp(1, image.width);
+            x0 = (image.width - crop_w) / 2;
+        } else {
+            crop_h = ((source_w / target_ratio).round() as u32).clamp(1, image.height);
+            y0 = (image.height - crop_h) / 2;
+        }
+    }
+    resize(image, (x0, y0, crop_w, crop_h), width, height)
+}
+
+/// 每个输出位置的源像素下标和权重
+fn filter_weights(source_start: u32, source_len: u32, target_len: u32) -> Vec<Vec<(usize, f32)>> {
+    let scale = source_len as f32 / target_len as f32;
+    // 缩小时放宽三角滤波的支撑范围，相当于对覆盖的源像素取加权平均
+    let support = scale.max(1.0);
+    (0..target_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale - 0.5;
+            let first = (center - support).floor().max(0.0) as u32;
+            let last = ((center + support).ceil() as u32).min(source_len - 1);
+            let mut weights: Vec<(usize, f32)> = (first..=last)
+                .map(|j| ((source_start + j) as usize, (1.0 - (j as f32 - center).abs() / support).max(0.0)))
+                .filter(|&(_, weight)| weight > 0.0)
+                .collect();
+            if weights.is_empty() {
+                weights.push(((source_start + (center.round().clamp(0.0, (source_len - 1) as f32)) as u32) as usize, 1.0));
+            }
+            let total: f32 = weights.iter().map(|(_, w)| w).sum();
+            weights.iter_mut().for_each(|(_, w)| *w /= total);
+            weights
+        })
+        .collect()
+}
+
+/// 可分离的三角滤波缩放，在预乘透明度的空间中插值以避免透明边缘发黑
+fn resize(image: &RgbaImage, (x0, y0, crop_w, crop_h): (u32, u32, u32, u32), width: u32, height: u32) -> RgbaImage {
+    let source_stride = image.width as usize;
+    let columns = filter_weights(x0, crop_w, width);
+    let rows = filter_weights(y0, crop_h, height);
+
+    let premultiplied = |offset: usize| {
+        let p = &image.pixels[offset * 4..offset * 4 + 4];
+        let alpha = p[3] as f32 / 255.0;
+        [p[0] as f32 * alpha, p[1] as f32 * alpha, p[2] as f32 * alpha, p[3] as f32]
+    };
+
+    // 水平方向：只处理裁剪区域内的行
+    let mut horizontal = vec![[0f32; 4]; width as usize * crop_h as usize];
+    for y in 0..crop_h as usize {
+        let row = (y0 as usize + y) * source_stride;
+        for (x, weights) in columns.iter().enumerate() {
+            let mut sum = [0f32; 4];
+            for &(source_x, weight) in weights {
+                let pixel = premultiplied(row + source_x);
+                for c in 0..4 {
+                    sum[c] += pixel[c] * weight;
+                }
+            }
+            horizontal[y * width as usize + x] = sum;
+        }
+    }
+
+    let mut out = RgbaImage::new(width, height);
+    for (y, weights) in rows.iter().enumerate() {
+        for x in 0..width as usize {
+            let mut sum = [0f32; 4];
+            for &(source_y, weight) in weights {
+                let pixel = horizontal[(source_y - y0 as usize) * width as usize + x];
+                for c in 0..4 {
+                    sum[c] += pixel[c] * weight;
+                }
+            }
+            let alpha = sum[3].clamp(0.0, 255.0);
+            let unpremultiply = |value: f32| if alpha > 0.0 { (value * 255.0 / alpha).round().clamp(0.0, 255.0) as u8 } else { 0 };
+            let offset = (y * width as usize + x) * 4;
+            out.pixels[offset..offset + 4].copy_from_slice(&[unpremultiply(sum[0]), unpremultiply(sum[1]), unpremultiply(sum[2]), alpha.round() as u8]);
+        }
+    }
+    out
+}
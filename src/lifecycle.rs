@@ -0,0 +1,278 @@
+use crate::models::{LifecycleConfiguration, LifecycleReport, LifecycleRule, ObjectMetadata};
+use crate::services::{BucketService, ObjectService, StorageService};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+
+/// 桶生命周期配置文件名（位于 .sevino.meta 目录下）
+pub const LIFECYCLE_CONFIG: &str = "lifecycle";
+
+/// 未完成的分片上传
+struct PendingUpload {
+    /// 已上传的分片key
+    part_keys: Vec<String>,
+    /// 总分片数
+    total_parts: u32,
+    /// 最早分片的创建时间
+    started_at: DateTime<Utc>,
+}
+
+/// 生命周期服务 - 定期按桶的生命周期规则清理过期对象、非当前版本和未完成的分片上传
+#[derive(Clone)]
+pub struct LifecycleService {
+    storage: StorageService,
+    bucket_service: BucketService,
+    object_service: ObjectService,
+}
+
+impl LifecycleService {
+    pub fn new(storage: StorageService, bucket_service: BucketService, object_service: ObjectService) -> Self {
+        Self { storage, bucket_service, object_service }
+    }
+
+    /// 校验生命周期配置，并为缺少ID的规则生成ID
+    pub fn validate_configuration(config: &mut LifecycleConfiguration) -> Result<()> {
+        let mut seen_ids = HashSet::new();
+
+        for rule in config.rules.iter_mut() {
+            if rule.id.is_empty() {
+                rule.id = uuid::Uuid::new_v4().to_string();
+            }
+            if !seen_ids.insert(rule.id.clone()) {
+                return Err(anyhow!("Duplicate lifecycle rule id: {}", rule.id));
+            }
+
+            let noncurrent = rule.noncurrent_version_expiration.as_ref();
+            let has_noncurrent_action = noncurrent
+                .map(|n| n.noncurrent_days.is_some() || n.newer_noncurrent_versions.is_some())
+                .unwrap_or(false);
+            if rule.expiration_days.is_none() && !has_noncurrent_action && rule.abort_incomplete_multipart_upload_days.is_none() {
+                return Err(anyhow!("Lifecycle rule '{}' must specify at least one action", rule.id));
+            }
+
+            if rule.abort_incomplete_multipart_upload_days.is_some() && !rule.filter.user_metadata.is_empty() {
+                return Err(anyhow!("Lifecycle rule '{}': user_metadata filters cannot be combined with abort_incomplete_multipart_upload_days", rule.id));
+            }
+            if rule.abort_incomplete_multipart_upload_days.is_some() && !rule.filter.tags.is_empty() {
+                return Err(anyhow!("Lifecycle rule '{}': tag filters cannot be combined with abort_incomplete_multipart_upload_days", rule.id));
+            }
+
+            let zero_days = rule.expiration_days == Some(0)
+                || rule.abort_incomplete_multipart_upload_days == Some(0)
+                || noncurrent.and_then(|n| n.noncurrent_days) == Some(0);
+            if zero_days {
+                return Err(anyhow!("Lifecycle rule '{}': days must be greater than 0", rule.id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 启动后台定时执行任务
+    pub fn start_scheduler(&self, interval_secs: u64) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                service.run_all().await;
+            }
+        });
+    }
+
+    /// 对所有配置了生命周期规则的桶执行一次
+    pub async fn run_all(&self) {
+        for bucket in self.bucket_service.list_buckets().await {
+            match self.run_bucket(&bucket.name).await {
+                Ok(Some(report)) => Self::log_report(&report),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Lifecycle evaluation failed for bucket '{}': {}", bucket.name, e),
+            }
+        }
+    }
+
+    /// 对单个桶执行生命周期规则（没有配置时返回None）
+    pub async fn run_bucket(&self, bucket_name: &str) -> Result<Option<LifecycleReport>> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+
+        let config: Option<LifecycleConfiguration> = self.storage.load_bucket_config(bucket_name, LIFECYCLE_CONFIG).await?;
+        match config {
+            Some(config) => Ok(Some(self.evaluate(bucket_name, &config, Utc::now()).await?)),
+            None => Ok(None),
+        }
+    }
+
+    fn log_report(report: &LifecycleReport) {
+        for key in &report.expired_objects {
+            tracing::info!("Lifecycle expired object '{}/{}'", report.bucket_name, key);
+        }
+        for version in &report.expired_versions {
+            tracing::info!("Lifecycle expired noncurrent version '{}/{}'", report.bucket_name, version);
+        }
+        for upload in &report.aborted_uploads {
+            tracing::info!("Lifecycle aborted incomplete multipart upload '{}/{}'", report.bucket_name, upload);
+        }
+        for error in &report.errors {
+            tracing::warn!("Lifecycle error in bucket '{}': {}", report.bucket_name, error);
+        }
+    }
+
+    async fn evaluate(&self, bucket_name: &str, config: &LifecycleConfiguration, now: DateTime<Utc>) -> Result<LifecycleReport> {
+        let mut report = LifecycleReport {
+            bucket_name: bucket_name.to_string(),
+            ..Default::default()
+        };
+
+        // 按key分组所有版本（最新的在前）
+        let mut versions_by_key: HashMap<String, Vec<ObjectMetadata>> = HashMap::new();
+        for metadata in self.storage.list_object_metadata(bucket_name).await? {
+            versions_by_key.entry(metadata.key.clone()).or_default().push(metadata);
+        }
+        for versions in versions_by_key.values_mut() {
+            versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+        }
+
+        // 记录本轮已删除的key和版本（key@version_id），避免多条规则重复删除
+        let mut removed_keys = HashSet::new();
+
+        for rule in config.rules.iter().filter(|rule| rule.enabled) {
+            self.abort_incomplete_uploads(bucket_name, rule, &versions_by_key, now, &mut removed_keys, &mut report).await;
+
+            if let Some(noncurrent) = &rule.noncurrent_version_expiration {
+                for (key, versions) in &versions_by_key {
+                    let current_id = self.storage.find_object_id_by_key(bucket_name, key).await?;
+                    let mut noncurrent_index = 0u32;
+
+                    for (i, version) in versions.iter().enumerate() {
+                        let Some(version_id) = &version.version_id else { continue };
+                        let object_id = format!("{}_{}", StorageService::generate_object_id(bucket_name, key), version_id);
+                        if current_id.as_deref() == Some(object_id.as_str()) || i == 0 {
+                            continue;
+                        }
+
+                        // 版本在其后继版本创建时成为非当前版本
+                        let noncurrent_since = versions[i - 1].created_at;
+                        let newer_noncurrent = noncurrent_index;
+                        noncurrent_index += 1;
+
+                        if !rule.filter.matches(key, &version.user_metadata, &version.tags) {
+                            continue;
+                        }
+
+                        // 同时配置两个条件时需要同时满足
+                        let days_ok = noncurrent.noncurrent_days
+                            .map(|days| now - noncurrent_since >= Duration::days(days as i64))
+                            .unwrap_or(true);
+                        let count_ok = noncurrent.newer_noncurrent_versions
+                            .map(|n| newer_noncurrent >= n)
+                            .unwrap_or(true);
+                        if !(days_ok && count_ok) {
+                            continue;
+                        }
+
+                        let version_key = format!("{}@{}", key, version_id);
+                        if removed_keys.contains(&version_key) {
+                            continue;
+                        }
+                        match self.object_service.delete_object_version(bucket_name, key, version_id).await {
+                            Ok(()) => {
+                                report.expired_versions.push(version_key.clone());
+                                removed_keys.insert(version_key);
+                            }
+                            Err(e) => report.errors.push(format!("{}: {}", version_key, e)),
+                        }
+                    }
+                }
+            }
+
+            if let Some(days) = rule.expiration_days {
+                for (key, versions) in &versions_by_key {
+                    if removed_keys.contains(key) {
+                        continue;
+                    }
+                    let Some(current_id) = self.storage.find_object_id_by_key(bucket_name, key).await? else { continue };
+                    let Some(current) = self.storage.load_object_metadata(bucket_name, &current_id).await? else { continue };
+                    if current.is_delete_marker || versions.is_empty() {
+                        continue;
+                    }
+
+                    if rule.filter.matches(key, &current.user_metadata, &current.tags) && now - current.created_at >= Duration::days(days as i64) {
+                        match self.object_service.delete_object(bucket_name, key).await {
+                            Ok(()) => {
+                                removed_keys.insert(key.clone());
+                                report.expired_objects.push(key.clone());
+                            }
+                            Err(e) => report.errors.push(format!("{}: {}", key, e)),
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 清理未完成的分片上传（分片数少于 total_parts 且最早的分片已超过规则的期限）
+    async fn abort_incomplete_uploads(
+        &self,
+        bucket_name: &str,
+        rule: &LifecycleRule,
+        versions_by_key: &HashMap<String, Vec<ObjectMetadata>>,
+        now: DateTime<Utc>,
+        removed_keys: &mut HashSet<String>,
+        report: &mut LifecycleReport,
+    ) {
+        let Some(days) = rule.abort_incomplete_multipart_upload_days else {
+            return;
+        };
+        // (原始key, upload_id) -> 已上传的分片
+        let mut uploads: HashMap<(String, String), PendingUpload> = HashMap::new();
+
+        for (key, versions) in versions_by_key {
+            let Some(part) = versions.first() else { continue };
+            let Some(upload_id) = part.user_metadata.get("multipart_upload_id") else { continue };
+            let Some(base_key) = key.rsplit_once(".part.").map(|(base, _)| base.to_string()) else { continue };
+            let total_parts = part.user_metadata.get("total_parts").and_then(|t| t.parse().ok()).unwrap_or(0);
+
+            let upload = uploads
+                .entry((base_key, upload_id.clone()))
+                .or_insert_with(|| PendingUpload {
+                    part_keys: Vec::new(),
+                    total_parts,
+                    started_at: part.created_at,
+                });
+            upload.part_keys.push(key.clone());
+            upload.started_at = upload.started_at.min(part.created_at);
+        }
+
+        for ((base_key, upload_id), PendingUpload { part_keys, total_parts, started_at }) in uploads {
+            let complete = part_keys.len() as u32 >= total_parts;
+            let prefix_ok = rule.filter.prefix.as_ref().map(|p| base_key.starts_with(p.as_str())).unwrap_or(true);
+            if complete || !prefix_ok || now - started_at < Duration::days(days as i64) {
+                continue;
+            }
+
+            let mut failed = false;
+            for part_key in part_keys {
+                if removed_keys.contains(&part_key) {
+                    continue;
+                }
+                match self.object_service.delete_object(bucket_name, &part_key).await {
+                    Ok(()) => {
+                        removed_keys.insert(part_key);
+                    }
+                    Err(e) => {
+                        failed = true;
+                        report.errors.push(format!("{}: {}", part_key, e));
+                    }
+                }
+            }
+
+            if !failed {
+                report.aborted_uploads.push(format!("{}@{}", base_key, upload_id));
+            }
+        }
+    }
+}
@@ -1,858 +1,3403 @@
-use axum::{
-    extract::{Path, State, Query},
-    http::StatusCode,
-    routing::{get, post, put, delete},
-    response::Json,
-    Router,
-};
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::collections::HashMap;
-use std::time::Instant;
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
-use anyhow::Result;
-use tower_http::cors::{CorsLayer, Any};
-use axum::http::{Method, HeaderName};
-
-mod models;
-mod services;
-mod utils;
-mod config;
-
-use crate::config::Settings;
-use crate::services::{StorageService, BucketService, ObjectService, DeduplicationMode};
-use crate::models::{Bucket, Object, ObjectMetadata};
-
-#[derive(OpenApi)]
-#[openapi(
-    paths(
-        root,
-        health_check,
-        list_buckets,
-        create_bucket,
-        get_bucket,
-        delete_bucket,
-        list_objects,
-        put_object,
-        put_object_multipart,
-        get_object,
-        delete_object,
-        get_object_metadata,
-        update_object_metadata,
-        list_object_versions,
-        test_duplicate_handling,
-        test_reference_mode_api
-    ),
-    components(
-        schemas(Bucket, Object, ObjectMetadata, ApiResponse<Bucket>, ApiResponse<Vec<Bucket>>, ApiResponse<Object>, ApiResponse<Vec<Object>>, ApiResponse<ObjectMetadata>, ApiResponse<()>, HealthResponse, CreateBucketRequest, PutObjectQuery, MultipartUploadQuery, UpdateObjectMetadataRequest, BucketListResponse, ObjectListResponse)
-    ),
-    tags(
-        (name = "buckets", description = "Bucket management endpoints"),
-        (name = "objects", description = "Object management endpoints"),
-        (name = "health", description = "Health check endpoints"),
-        (name = "test", description = "Test endpoints")
-    )
-)]
-struct ApiDoc;
-
-#[derive(Clone)]
-struct AppState {
-    bucket_service: BucketService,
-    object_service: ObjectService,
-}
-
-#[tokio::main]
-async fn main() {
-    tracing_subscriber::fmt::init();
-
-    let settings = Settings::from_env();
-    println!("Starting Sevino Object Storage Service with settings: {:?}", settings);
-
-    let storage_service = match StorageService::new(settings.data_dir.clone()).await {
-        Ok(service) => service,
-        Err(e) => {
-            eprintln!("Failed to initialize storage service: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    let bucket_service = BucketService::new(storage_service.clone());
-    let object_service = ObjectService::new(storage_service);
-
-    // 配置CORS
-    let cors_layer = if settings.enable_cors {
-        let mut cors = CorsLayer::new();
-        
-        // 配置允许的域名
-        if settings.cors_origins.contains(&"*".to_string()) {
-            cors = cors.allow_origin(Any);
-        } else {
-            let origins: Vec<_> = settings.cors_origins
-                .iter()
-                .filter_map(|origin| origin.parse().ok())
-                .collect();
-            if !origins.is_empty() {
-                cors = cors.allow_origin(origins);
-            }
-        }
-        
-        // 配置允许的方法
-        let methods: Vec<Method> = settings.cors_methods
-            .iter()
-            .filter_map(|method| method.parse().ok())
-            .collect();
-        if !methods.is_empty() {
-            cors = cors.allow_methods(methods);
-        }
-        
-        // 配置允许的头部
-        let headers: Vec<HeaderName> = settings.cors_headers
-            .iter()
-            .filter_map(|header| header.parse().ok())
-            .collect();
-        if !headers.is_empty() {
-            cors = cors.allow_headers(headers);
-        }
-        
-        // 配置凭据
-        if settings.cors_allow_credentials {
-            cors = cors.allow_credentials(true);
-        }
-        
-        // 设置预检请求缓存时间
-        cors = cors.max_age(std::time::Duration::from_secs(3600));
-        
-        cors
-    } else {
-        CorsLayer::new()
-    };
-
-    let app = Router::new()
-        .route("/", get(root))
-        .route("/health", get(health_check))
-        .route("/api/buckets", get(list_buckets))
-        .route("/api/buckets", post(create_bucket))
-        .route("/api/buckets/:name", get(get_bucket))
-        .route("/api/buckets/:name", delete(delete_bucket))
-        .route("/api/buckets/:bucket_name/objects", get(list_objects))
-        .route("/api/buckets/:bucket_name/objects/:key", put(put_object))
-        .route("/api/buckets/:bucket_name/objects/:key/multipart", put(put_object_multipart))
-        .route("/api/buckets/:bucket_name/objects/:key", get(get_object))
-        .route("/api/buckets/:bucket_name/objects/:key", delete(delete_object))
-        .route("/api/buckets/:bucket_name/objects/:key/metadata", get(get_object_metadata))
-        .route("/api/buckets/:bucket_name/objects/:key/metadata", put(update_object_metadata))
-        .route("/api/buckets/:bucket_name/objects/:key/versions", get(list_object_versions))
-        .route("/api/buckets/:bucket_name/objects/:key/duplicate-test", post(test_duplicate_handling))
-        .route("/api/test/reference-mode", get(test_reference_mode_api))
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .layer(cors_layer)
-        .with_state(Arc::new(AppState {
-            bucket_service,
-            object_service,
-        }));
-
-    let addr = format!("{}:{}", settings.host, settings.port);
-    println!("Server running on http://{}", addr);
-    println!("Swagger UI available at http://{}/swagger-ui/", addr);
-    println!("CORS enabled: {}", settings.enable_cors);
-    if settings.enable_cors {
-        println!("CORS origins: {:?}", settings.cors_origins);
-        println!("CORS methods: {:?}", settings.cors_methods);
-        println!("CORS allow credentials: {}", settings.cors_allow_credentials);
-    }
-
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
-}
-
-#[utoipa::path(
-    get,
-    path = "/",
-    tag = "health",
-    responses(
-        (status = 200, description = "Welcome message")
-    )
-)]
-async fn root() -> &'static str {
-    "Welcome to Sevino Object Storage Service!"
-}
-
-#[derive(Serialize, utoipa::ToSchema)]
-struct HealthResponse {
-    status: String,
-    timestamp: String,
-}
-
-#[utoipa::path(
-    get,
-    path = "/health",
-    tag = "health",
-    responses(
-        (status = 200, description = "Health check response", body = HealthResponse)
-    )
-)]
-async fn health_check() -> Json<HealthResponse> {
-    let response = HealthResponse {
-        status: "healthy".to_string(),
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    };
-    Json(response)
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/buckets",
-    tag = "buckets",
-    responses(
-        (status = 200, description = "List of buckets", body = ApiResponse<BucketListResponse>)
-    )
-)]
-async fn list_buckets(
-    State(state): State<Arc<AppState>>,
-) -> Json<ApiResponse<BucketListResponse>> {
-    let buckets = state.bucket_service.list_buckets().await;
-    let response = BucketListResponse { buckets };
-    Json(ApiResponse::success(response))
-}
-
-#[derive(Deserialize, utoipa::ToSchema)]
-struct CreateBucketRequest {
-    name: String,
-}
-
-#[utoipa::path(
-    post,
-    path = "/api/buckets",
-    tag = "buckets",
-    request_body(content = CreateBucketRequest, content_type = "application/json"),
-    responses(
-        (status = 200, description = "Bucket created successfully", body = ApiResponse<Bucket>),
-        (status = 400, description = "Invalid bucket name", body = ApiResponse<Bucket>)
-    )
-)]
-async fn create_bucket(
-    State(state): State<Arc<AppState>>,
-    Json(request): Json<CreateBucketRequest>,
-) -> Json<ApiResponse<Bucket>> {
-    match state.bucket_service.create_bucket(request.name).await {
-        Ok(bucket) => Json(ApiResponse::success(bucket)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/buckets/{name}",
-    tag = "buckets",
-    params(
-        ("name" = String, Path, description = "Bucket name")
-    ),
-    responses(
-        (status = 200, description = "Bucket details", body = ApiResponse<Bucket>),
-        (status = 404, description = "Bucket not found", body = ApiResponse<Bucket>)
-    )
-)]
-async fn get_bucket(
-    State(state): State<Arc<AppState>>,
-    Path(name): Path<String>,
-) -> Json<ApiResponse<Bucket>> {
-    match state.bucket_service.get_bucket(&name).await {
-        Some(bucket) => Json(ApiResponse::success(bucket)),
-        None => Json(ApiResponse::error("Bucket not found".to_string())),
-    }
-}
-
-#[utoipa::path(
-    delete,
-    path = "/api/buckets/{name}",
-    tag = "buckets",
-    params(
-        ("name" = String, Path, description = "Bucket name")
-    ),
-    responses(
-        (status = 200, description = "Bucket deleted successfully", body = ApiResponse<()>),
-        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
-    )
-)]
-async fn delete_bucket(
-    State(state): State<Arc<AppState>>,
-    Path(name): Path<String>,
-) -> Json<ApiResponse<()>> {
-    match state.bucket_service.delete_bucket(&name).await {
-        Ok(_) => Json(ApiResponse::success(())),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/buckets/{bucket_name}/objects",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("prefix" = Option<String>, Query, description = "Object key prefix filter"),
-        ("delimiter" = Option<String>, Query, description = "Delimiter for common prefixes"),
-        ("max_keys" = Option<u32>, Query, description = "Maximum number of keys to return"),
-        ("marker" = Option<String>, Query, description = "Pagination marker"),
-        ("etag_filter" = Option<String>, Query, description = "Filter objects by ETag (supports wildcards: *, ?)"),
-        ("custom_*" = Option<String>, Query, description = "Filter by custom metadata, e.g. custom_bizid=123")
-    ),
-    responses(
-        (status = 200, description = "List of objects", body = ApiResponse<ObjectListResponse>),
-        (status = 404, description = "Bucket not found", body = ApiResponse<ObjectListResponse>)
-    )
-)]
-async fn list_objects(
-    State(state): State<Arc<AppState>>,
-    Path(bucket_name): Path<String>,
-    Query(query): Query<ListObjectsQuery>,
-    axum::extract::RawQuery(raw_query): axum::extract::RawQuery,
-) -> Json<ApiResponse<ObjectListResponse>> {
-    // 解析 custom_xxx=yyy 过滤条件
-    let mut custom_filters = vec![];
-    if let Some(raw) = raw_query {
-        for (k, v) in url::form_urlencoded::parse(raw.as_bytes()) {
-            if let Some(stripped) = k.strip_prefix("custom_") {
-                custom_filters.push((stripped.to_string(), v.to_string()));
-            }
-        }
-    }
-    match state.object_service.list_objects_with_custom_filter(&bucket_name, query.prefix, query.delimiter, query.max_keys, query.marker, query.etag_filter, custom_filters).await {
-        Ok(objects) => {
-            let response = ObjectListResponse { objects };
-            Json(ApiResponse::success(response))
-        }
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[derive(Deserialize, utoipa::ToSchema)]
-struct PutObjectQuery {
-    #[serde(default = "default_deduplication_mode")]
-    deduplication_mode: Option<String>,
-    #[serde(default)]
-    content_type: Option<String>,
-    #[serde(default)]
-    custom: Option<String>,
-}
-
-#[derive(Deserialize, utoipa::ToSchema)]
-struct ListObjectsQuery {
-    #[serde(default)]
-    prefix: Option<String>,
-    #[serde(default)]
-    delimiter: Option<String>,
-    #[serde(default)]
-    max_keys: Option<u32>,
-    #[serde(default)]
-    marker: Option<String>,
-    #[serde(default)]
-    etag_filter: Option<String>,
-}
-
-fn default_deduplication_mode() -> Option<String> {
-    Some("allow".to_string())
-}
-
-#[derive(Deserialize, utoipa::ToSchema)]
-struct MultipartUploadQuery {
-    part_number: u32,
-    total_parts: u32,
-    upload_id: String,
-    #[serde(default)]
-    content_type: Option<String>,
-}
-
-#[derive(Deserialize, utoipa::ToSchema)]
-struct UpdateObjectMetadataRequest {
-    #[serde(default)]
-    content_type: Option<String>,
-    #[serde(default)]
-    user_metadata: Option<HashMap<String, String>>,
-    #[serde(default)]
-    custom_etag: Option<String>,
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/buckets/{bucket_name}/objects/{key}/metadata",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key")
-    ),
-    responses(
-        (status = 200, description = "Object metadata", body = ApiResponse<ObjectMetadata>),
-        (status = 404, description = "Object not found", body = ApiResponse<ObjectMetadata>)
-    )
-)]
-async fn get_object_metadata(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-) -> Json<ApiResponse<ObjectMetadata>> {
-    match state.object_service.get_object_metadata(&bucket_name, &key).await {
-        Ok(metadata) => Json(ApiResponse::success(metadata)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    put,
-    path = "/api/buckets/{bucket_name}/objects/{key}",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key"),
-        ("deduplication_mode" = Option<String>, Query, description = "Deduplication mode: reject, allow, reference"),
-        ("content_type" = Option<String>, Query, description = "Content type"),
-        ("custom_etag" = Option<String>, Query, description = "Custom ETag (e.g., \"md5-hash\", \"sha256-hash\", \"W/weak-etag\")")
-    ),
-    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
-    responses(
-        (status = 200, description = "Object uploaded successfully", body = ApiResponse<Object>),
-        (status = 400, description = "Invalid deduplication mode or ETag format", body = ApiResponse<Object>),
-        (status = 404, description = "Bucket not found", body = ApiResponse<Object>)
-    )
-)]
-async fn put_object(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-    Query(query): Query<PutObjectQuery>,
-    body: axum::body::Bytes,
-) -> Json<ApiResponse<Object>> {
-    let data = body.to_vec();
-    let content_type = query.content_type.unwrap_or_else(|| "application/octet-stream".to_string());
-    let mut user_metadata = std::collections::HashMap::new();
-
-    // 解析 custom 参数（json字符串）
-    if let Some(custom_str) = &query.custom {
-        match serde_json::from_str::<HashMap<String, String>>(custom_str) {
-            Ok(map) => user_metadata.extend(map),
-            Err(e) => return Json(ApiResponse::error(format!("Invalid custom metadata: {}", e))),
-        }
-    }
-
-    // 如果指定了去重模式，使用去重上传
-    if let Some(dedup_mode) = query.deduplication_mode {
-        let deduplication_mode = match dedup_mode.to_lowercase().as_str() {
-            "reject" => DeduplicationMode::Reject,
-            "allow" => DeduplicationMode::Allow,
-            "reference" => DeduplicationMode::Reference,
-            _ => {
-                return Json(ApiResponse::error(format!(
-                    "Invalid deduplication mode: {}. Valid modes are: reject, allow, reference",
-                    dedup_mode
-                )));
-            }
-        };
-
-        match state.object_service.put_object_with_deduplication(
-            &bucket_name, 
-            &key, 
-            data, 
-            &content_type, 
-            user_metadata,
-            deduplication_mode
-        ).await {
-            Ok(object) => Json(ApiResponse::success(object)),
-            Err(e) => Json(ApiResponse::error(e.to_string())),
-        }
-    } else {
-        // 默认上传模式 - 使用 Allow 模式允许重复内容
-        match state.object_service.put_object_with_deduplication(
-            &bucket_name, 
-            &key, 
-            data, 
-            &content_type, 
-            user_metadata,
-            DeduplicationMode::Allow
-        ).await {
-            Ok(object) => Json(ApiResponse::success(object)),
-            Err(e) => Json(ApiResponse::error(e.to_string())),
-        }
-    }
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/buckets/{bucket_name}/objects/{key}",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key")
-    ),
-    responses(
-        (status = 200, description = "Object data", body = Vec<u8>),
-        (status = 404, description = "Object not found")
-    )
-)]
-async fn get_object(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-) -> Result<axum::response::Response, StatusCode> {
-    match state.object_service.get_object(&bucket_name, &key).await {
-        Ok((data, metadata)) => {
-            let response = axum::response::Response::builder()
-                .status(StatusCode::OK)
-                .header("Content-Type", metadata.content_type)
-                .header("ETag", metadata.etag)
-                .header("Content-Length", metadata.size.to_string())
-                .body(axum::body::Body::from(data))
-                .unwrap();
-            Ok(response)
-        }
-        Err(_) => Err(StatusCode::NOT_FOUND),
-    }
-}
-
-#[utoipa::path(
-    delete,
-    path = "/api/buckets/{bucket_name}/objects/{key}",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key")
-    ),
-    responses(
-        (status = 200, description = "Object deleted successfully", body = ApiResponse<()>),
-        (status = 404, description = "Object not found", body = ApiResponse<()>)
-    )
-)]
-async fn delete_object(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-) -> Json<ApiResponse<()>> {
-    match state.object_service.delete_object(&bucket_name, &key).await {
-        Ok(_) => Json(ApiResponse::success(())),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/buckets/{bucket_name}/objects/{key}/versions",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key")
-    ),
-    responses(
-        (status = 200, description = "List of object versions", body = ApiResponse<Vec<ObjectMetadata>>),
-        (status = 404, description = "Object not found", body = ApiResponse<Vec<ObjectMetadata>>)
-    )
-)]
-async fn list_object_versions(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-) -> Json<ApiResponse<Vec<ObjectMetadata>>> {
-    match state.object_service.list_object_versions(&bucket_name, &key).await {
-        Ok(versions) => Json(ApiResponse::success(versions)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    post,
-    path = "/api/buckets/{bucket_name}/objects/{key}/duplicate-test",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key")
-    ),
-    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
-    responses(
-        (status = 200, description = "Duplicate handling test result", body = ApiResponse<String>),
-        (status = 404, description = "Object not found", body = ApiResponse<String>)
-    )
-)]
-async fn test_duplicate_handling(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-    body: axum::body::Bytes,
-) -> Json<ApiResponse<String>> {
-    let data = body.to_vec();
-    let content_type = "application/octet-stream";
-    let user_metadata = std::collections::HashMap::new();
-
-    match state.object_service.test_duplicate_handling(&bucket_name, &key, data, content_type, user_metadata).await {
-        Ok(result) => Json(ApiResponse::success(result)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[derive(Serialize, utoipa::ToSchema)]
-struct BucketListResponse {
-    buckets: Vec<Bucket>,
-}
-
-#[derive(Serialize, utoipa::ToSchema)]
-struct ObjectListResponse {
-    objects: Vec<Object>,
-}
-
-#[derive(Serialize, utoipa::ToSchema)]
-struct ApiResponse<T> {
-    success: bool,
-    data: Option<T>,
-    error: Option<String>,
-}
-
-impl<T> ApiResponse<T> {
-    fn success(data: T) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            error: None,
-        }
-    }
-
-    fn error(message: String) -> Self {
-        Self {
-            success: false,
-            data: None,
-            error: Some(message),
-        }
-    }
-}
-
-/// 测试Reference模式的工作原理
-async fn test_reference_mode() -> Result<String> {
-    let storage = StorageService::new("./data".to_string()).await?;
-    let object_service = ObjectService::new(storage.clone());
-    let bucket_service = BucketService::new(storage);
-    
-    let bucket_name = "test-reference-bucket-v2";
-    let test_data = b"Hello, this is test content for decentralized reference mode!".to_vec();
-    let content_type = "text/plain";
-    let mut user_metadata = HashMap::new();
-    user_metadata.insert("test".to_string(), "reference".to_string());
-    
-    let mut result = String::new();
-    result.push_str("=== 去中心化Reference模式测试 ===\n\n");
-    
-    // 1. 创建桶
-    result.push_str("1. 创建测试桶\n");
-    match bucket_service.create_bucket(bucket_name.to_string()).await {
-        Ok(_) => result.push_str("   ✓ 桶创建成功\n\n"),
-        Err(e) => result.push_str(&format!("   ✗ 桶创建失败: {}\n\n", e)),
-    }
-    
-    // 2. 上传第一个文件
-    result.push_str("2. 上传第一个文件 (key: file1.txt)\n");
-    match object_service.put_object(bucket_name, "file1.txt", test_data.clone(), content_type, user_metadata.clone()).await {
-        Ok(obj) => {
-            result.push_str(&format!("   ✓ 文件上传成功\n"));
-            result.push_str(&format!("   - ETag: {}\n", obj.etag));
-            result.push_str(&format!("   - 大小: {} bytes\n", obj.size));
-            result.push_str(&format!("   - 对象ID: {}\n\n", StorageService::generate_object_id(bucket_name, "file1.txt")));
-        },
-        Err(e) => result.push_str(&format!("   ✗ 文件上传失败: {}\n\n", e)),
-    }
-    
-    // 3. 使用Reference模式上传相同内容的不同key
-    result.push_str("3. 使用Reference模式上传相同内容 (key: file2.txt)\n");
-    match object_service.put_object_with_deduplication(
-        bucket_name, 
-        "file2.txt", 
-        test_data.clone(), 
-        content_type, 
-        user_metadata.clone(),
-        DeduplicationMode::Reference
-    ).await {
-        Ok(obj) => {
-            result.push_str(&format!("   ✓ 引用创建成功\n"));
-            result.push_str(&format!("   - ETag: {}\n", obj.etag));
-            result.push_str(&format!("   - 大小: {} bytes\n", obj.size));
-            result.push_str(&format!("   - 对象ID: {}\n", StorageService::generate_object_id(bucket_name, "file2.txt")));
-            
-            // 检查元数据
-            if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file2.txt").await {
-                result.push_str(&format!("   - 数据持有者ID: {:?}\n", metadata.data_holder_id));
-                result.push_str(&format!("   - 引用计数: {}\n", metadata.reference_count));
-            }
-            result.push_str("\n");
-        },
-        Err(e) => result.push_str(&format!("   ✗ 引用创建失败: {}\n\n", e)),
-    }
-    
-    // 4. 检查数据持有者的引用计数
-    result.push_str("4. 检查数据持有者的引用计数\n");
-    if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file1.txt").await {
-        result.push_str(&format!("   file1.txt 引用计数: {}\n", metadata.reference_count));
-        result.push_str(&format!("   file1.txt 数据持有者ID: {:?}\n", metadata.data_holder_id));
-    }
-    result.push_str("\n");
-    
-    // 5. 读取两个文件并比较
-    result.push_str("5. 读取并比较两个文件\n");
-    match object_service.get_object(bucket_name, "file1.txt").await {
-        Ok((data1, metadata1)) => {
-            result.push_str(&format!("   file1.txt 读取成功，大小: {} bytes\n", data1.len()));
-            
-            match object_service.get_object(bucket_name, "file2.txt").await {
-                Ok((data2, metadata2)) => {
-                    result.push_str(&format!("   file2.txt 读取成功，大小: {} bytes\n", data2.len()));
-                    result.push_str(&format!("   数据相同: {}\n", data1 == data2));
-                    result.push_str(&format!("   ETag相同: {}\n", metadata1.etag == metadata2.etag));
-                    result.push_str(&format!("   file1数据持有者ID: {:?}\n", metadata1.data_holder_id));
-                    result.push_str(&format!("   file2数据持有者ID: {:?}\n", metadata2.data_holder_id));
-                },
-                Err(e) => result.push_str(&format!("   file2.txt 读取失败: {}\n", e)),
-            }
-        },
-        Err(e) => result.push_str(&format!("   file1.txt 读取失败: {}\n", e)),
-    }
-    result.push_str("\n");
-    
-    // 6. 测试删除引用对象
-    result.push_str("6. 测试删除引用对象\n");
-    match object_service.delete_object(bucket_name, "file2.txt").await {
-        Ok(_) => {
-            result.push_str("   ✓ 引用对象删除成功\n");
-            
-            // 检查数据持有者的引用计数是否减少
-            if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file1.txt").await {
-                result.push_str(&format!("   file1.txt 引用计数: {}\n", metadata.reference_count));
-            }
-        },
-        Err(e) => result.push_str(&format!("   ✗ 引用对象删除失败: {}\n", e)),
-    }
-    result.push_str("\n");
-    
-    // 7. 测试删除数据持有者（应该成功，因为没有引用了）
-    result.push_str("7. 测试删除数据持有者（应该成功）\n");
-    match object_service.delete_object(bucket_name, "file1.txt").await {
-        Ok(_) => result.push_str("   ✓ 数据持有者删除成功\n"),
-        Err(e) => result.push_str(&format!("   ✗ 数据持有者删除失败: {}\n", e)),
-    }
-    result.push_str("\n");
-    
-    // 8. 测试多个对象的引用关系
-    result.push_str("8. 测试多个对象的引用关系\n");
-    match object_service.put_object(bucket_name, "file3.txt", test_data.clone(), content_type, user_metadata.clone()).await {
-        Ok(_) => {
-            result.push_str("   ✓ file3.txt 上传成功\n");
-            
-            // 创建多个引用
-            for i in 4..=6 {
-                let key = format!("file{}.txt", i);
-                match object_service.put_object_with_deduplication(
-                    bucket_name, 
-                    &key, 
-                    test_data.clone(), 
-                    content_type, 
-                    user_metadata.clone(),
-                    DeduplicationMode::Reference
-                ).await {
-                    Ok(_) => result.push_str(&format!("   ✓ {} 引用创建成功\n", key)),
-                    Err(e) => result.push_str(&format!("   ✗ {} 引用创建失败: {}\n", key, e)),
-                }
-            }
-            
-            // 检查引用计数
-            if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file3.txt").await {
-                result.push_str(&format!("   file3.txt 引用计数: {}\n", metadata.reference_count));
-            }
-        },
-        Err(e) => result.push_str(&format!("   ✗ file3.txt 上传失败: {}\n", e)),
-    }
-    result.push_str("\n");
-    
-    // 9. 验证所有对象都可以正常读取
-    result.push_str("9. 验证所有对象都可以正常读取\n");
-    for i in 3..=6 {
-        let key = format!("file{}.txt", i);
-        match object_service.get_object(bucket_name, &key).await {
-            Ok((data, _)) => result.push_str(&format!("   ✓ {} 读取成功，大小: {} bytes\n", key, data.len())),
-            Err(e) => result.push_str(&format!("   ✗ {} 读取失败: {}\n", key, e)),
-        }
-    }
-    
-    Ok(result)
-}
-
-#[utoipa::path(
-    get,
-    path = "/api/test/reference-mode",
-    tag = "test",
-    responses(
-        (status = 200, description = "Reference mode test results", body = ApiResponse<String>)
-    )
-)]
-async fn test_reference_mode_api() -> Json<ApiResponse<String>> {
-    match test_reference_mode().await {
-        Ok(result) => Json(ApiResponse::success(result)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    put,
-    path = "/api/buckets/{bucket_name}/objects/{key}/multipart",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key"),
-        ("part_number" = u32, Query, description = "分片编号，从1开始"),
-        ("total_parts" = u32, Query, description = "总分片数"),
-        ("upload_id" = String, Query, description = "上传ID"),
-        ("content_type" = Option<String>, Query, description = "内容类型")
-    ),
-    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
-    responses(
-        (status = 200, description = "Multipart upload part uploaded successfully", body = ApiResponse<Object>),
-        (status = 400, description = "Invalid multipart upload request", body = ApiResponse<Object>),
-        (status = 404, description = "Bucket not found", body = ApiResponse<Object>)
-    )
-)]
-async fn put_object_multipart(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-    Query(query): Query<MultipartUploadQuery>,
-    body: axum::body::Bytes,
-) -> Json<ApiResponse<Object>> {
-    let data = body.to_vec();
-    let content_type = query.content_type.clone().unwrap_or_else(|| "application/octet-stream".to_string());
-    let mut user_metadata = std::collections::HashMap::new();
-    user_metadata.insert("multipart_upload_id".to_string(), query.upload_id.clone());
-    user_metadata.insert("part_number".to_string(), query.part_number.to_string());
-    user_metadata.insert("total_parts".to_string(), query.total_parts.to_string());
-    let part_key = format!("{}.part.{}", key, query.part_number);
-    match state.object_service.put_object(&bucket_name, &part_key, data, &content_type, user_metadata).await {
-        Ok(object) => Json(ApiResponse::success(object)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
-
-#[utoipa::path(
-    put,
-    path = "/api/buckets/{bucket_name}/objects/{key}/metadata",
-    tag = "objects",
-    params(
-        ("bucket_name" = String, Path, description = "Bucket name"),
-        ("key" = String, Path, description = "Object key")
-    ),
-    request_body(content = UpdateObjectMetadataRequest, content_type = "application/json"),
-    responses(
-        (status = 200, description = "Object metadata updated successfully", body = ApiResponse<Object>),
-        (status = 400, description = "Invalid ETag format", body = ApiResponse<Object>),
-        (status = 404, description = "Object not found", body = ApiResponse<Object>)
-    )
-)]
-async fn update_object_metadata(
-    State(state): State<Arc<AppState>>,
-    Path((bucket_name, key)): Path<(String, String)>,
-    Json(request): Json<UpdateObjectMetadataRequest>,
-) -> Json<ApiResponse<Object>> {
-    match state.object_service.update_object_metadata(
-        &bucket_name,
-        &key,
-        request.content_type,
-        request.user_metadata,
-        request.custom_etag,
-    ).await {
-        Ok(object) => Json(ApiResponse::success(object)),
-        Err(e) => Json(ApiResponse::error(e.to_string())),
-    }
-}
\ No newline at end of file
+
+use axum::{
+    extract::{Path, State, Query, Request, ConnectInfo},
+    Extension,
+    http::StatusCode,
+    middleware::{self, Next},
+    routing::{get, post, put, delete},
+    response::{Json, IntoResponse, Response},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::net::SocketAddr;
+use std::collections::HashMap;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use tower_http::cors::{CorsLayer, Any};
+use axum::http::{Method, HeaderName, HeaderMap};
+use axum::body::HttpBody;
+use futures_util::TryStreamExt;
+use tokio_util::io::{StreamReader, SyncIoBridge};
+
+mod models;
+mod services;
+mod utils;
+mod config;
+mod notifications;
+mod lifecycle;
+mod object_lock;
+mod policy;
+mod share_links;
+mod form_upload;
+mod archive;
+mod image_codec;
+mod image_transform;
+mod content_type;
+mod encryption;
+mod compression;
+mod quota;
+mod metrics;
+mod audit;
+mod access_log;
+mod rate_limit;
+mod response_compression;
+mod delete_jobs;
+mod tagging;
+mod query;
+mod metadata_index;
+mod website;
+
+use crate::config::Settings;
+use crate::services::{StorageService, BucketService, ObjectService, DeduplicationMode, PreconditionFailed, RangeNotSatisfiable, MAX_BATCH_DELETE_OBJECTS};
+use crate::notifications::NotificationService;
+use crate::lifecycle::LifecycleService;
+use crate::policy::{PolicyService, AuthorizationRequest, Identity, Requester, classify_request, is_unclassified_bucket_request, ADMIN_PRINCIPAL, ANONYMOUS_PRINCIPAL};
+use crate::share_links::{ShareLinkService, ShareLinkError};
+use crate::form_upload::{FormUploadService, FormUploadError, FormUploadOutcome};
+use crate::archive::{ArchiveService, ArchiveFormat};
+use crate::image_transform::{ImageTransformService, ImageTransformError, TransformParams};
+use crate::encryption::{EncryptionService, CustomerKeyError};
+use crate::metrics::{Metrics, MetricsLayer};
+use crate::audit::{AuditLog, RequestContext};
+use crate::access_log::{AccessLog, AccessLogEntry, RequestId};
+use crate::rate_limit::RateLimiter;
+use crate::response_compression::{ObjectBody, ResponseCompression};
+use crate::delete_jobs::DeleteJobService;
+use crate::query::QuerySyntaxError;
+use crate::website::{WebsiteService, WebsiteRequest, WebsiteResponse};
+use crate::models::{Bucket, Object, ObjectMetadata, EventType, NotificationRule, NotificationConfiguration, ObjectEvent, LifecycleFilter, NoncurrentVersionExpiration, LifecycleRule, LifecycleConfiguration, LifecycleReport, ObjectLockMode, DefaultRetention, ObjectLockConfiguration, ObjectRetention, ObjectLegalHold, PolicyEffect, PolicyConditions, PolicyStatement, BucketPolicy, PolicySimulationRequest, PolicyDecision, PolicyEvaluation, CreateShareLinkRequest, ShareLink, CreateUploadPolicyRequest, UploadPolicyDocument, UploadPolicy, EncryptionConfiguration, ObjectEncryption, CustomerEncryption, CompressionAlgorithm, CompressionConfiguration, ObjectCompression, ContentTypeMode, ContentTypeConfiguration, QuotaSizeBasis, QuotaConfiguration, BucketUsage, SizeHistogramBucket, ContentTypeUsage, BucketQuotaStatus, MetadataDirective, CopyObjectRequest, RenameObjectRequest, RenamePrefixRequest, RenamedObject, RenameReport, DeleteObjectEntry, BatchDeleteRequest, DeleteObjectError, BatchDeleteResult, DeletePrefixRequest, DeleteJobStatus, DeleteJob, ArchiveEntryError, ArchiveExtractReport, ObjectTagging, ObjectSearchResult, MetadataIndexConfiguration, MetadataIndexStats, MetadataIndexStatus, WebsiteRedirectRule, WebsiteConfiguration};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        root,
+        health_check,
+        get_metrics,
+        list_buckets,
+        create_bucket,
+        get_bucket,
+        delete_bucket,
+        get_bucket_stats,
+        get_bucket_notification,
+        put_bucket_notification,
+        delete_bucket_notification,
+        get_bucket_lifecycle,
+        put_bucket_lifecycle,
+        delete_bucket_lifecycle,
+        run_bucket_lifecycle,
+        get_bucket_object_lock,
+        put_bucket_object_lock,
+        get_bucket_policy,
+        put_bucket_policy,
+        delete_bucket_policy,
+        simulate_bucket_policy,
+        get_bucket_encryption,
+        put_bucket_encryption,
+        get_bucket_compression,
+        put_bucket_compression,
+        delete_bucket_compression,
+        get_bucket_content_type,
+        put_bucket_content_type,
+        delete_bucket_content_type,
+        get_bucket_quota,
+        put_bucket_quota,
+        delete_bucket_quota,
+        get_bucket_metadata_index,
+        put_bucket_metadata_index,
+        delete_bucket_metadata_index,
+        rebuild_bucket_metadata_index,
+        get_bucket_website,
+        put_bucket_website,
+        delete_bucket_website,
+        rename_prefix,
+        delete_objects,
+        start_delete_job,
+        list_delete_jobs,
+        get_delete_job,
+        extract_archive,
+        download_archive,
+        list_objects,
+        search_objects,
+        put_object,
+        put_object_multipart,
+        copy_object,
+        rename_object,
+        get_object,
+        delete_object,
+        get_object_metadata,
+        update_object_metadata,
+        put_object_retention,
+        put_object_legal_hold,
+        get_object_tagging,
+        put_object_tagging,
+        delete_object_tagging,
+        list_object_versions,
+        create_share_link,
+        get_share_link,
+        revoke_share_link,
+        download_share_link,
+        create_upload_policy,
+        post_object,
+        test_duplicate_handling,
+        test_reference_mode_api
+    ),
+    components(
+        schemas(Bucket, Object, ObjectMetadata, ApiResponse<Bucket>, ApiResponse<Vec<Bucket>>, ApiResponse<Object>, ApiResponse<Vec<Object>>, ApiResponse<ObjectMetadata>, ApiResponse<()>, HealthResponse, CreateBucketRequest, PutObjectQuery, MultipartUploadQuery, UpdateObjectMetadataRequest, BucketListResponse, ObjectListResponse, EventType, NotificationRule, NotificationConfiguration, ApiResponse<NotificationConfiguration>, ObjectEvent, LifecycleFilter, NoncurrentVersionExpiration, LifecycleRule, LifecycleConfiguration, LifecycleReport, ApiResponse<LifecycleConfiguration>, ApiResponse<LifecycleReport>, ObjectLockMode, DefaultRetention, ObjectLockConfiguration, ObjectRetention, ObjectLegalHold, ApiResponse<ObjectLockConfiguration>, DeleteObjectQuery, PolicyEffect, PolicyConditions, PolicyStatement, BucketPolicy, PolicySimulationRequest, PolicyDecision, PolicyEvaluation, ApiResponse<BucketPolicy>, ApiResponse<PolicyEvaluation>, CreateShareLinkRequest, ShareLink, ApiResponse<ShareLink>, ShareDownloadQuery, CreateUploadPolicyRequest, UploadPolicyDocument, UploadPolicy, ApiResponse<UploadPolicy>, EncryptionConfiguration, ObjectEncryption, CustomerEncryption, ApiResponse<EncryptionConfiguration>, CompressionAlgorithm, CompressionConfiguration, ObjectCompression, ApiResponse<CompressionConfiguration>, ContentTypeMode, ContentTypeConfiguration, ApiResponse<ContentTypeConfiguration>, QuotaSizeBasis, QuotaConfiguration, BucketUsage, SizeHistogramBucket, ContentTypeUsage, BucketQuotaStatus, ApiResponse<QuotaConfiguration>, ApiResponse<BucketQuotaStatus>, ApiResponse<BucketUsage>, MetadataDirective, CopyObjectRequest, RenameObjectRequest, RenamePrefixRequest, RenamedObject, RenameReport, ApiResponse<RenameReport>, DeleteObjectEntry, BatchDeleteRequest, DeleteObjectError, BatchDeleteResult, DeletePrefixRequest, DeleteJobStatus, DeleteJob, ApiResponse<BatchDeleteResult>, ApiResponse<DeleteJob>, ApiResponse<Vec<DeleteJob>>, ArchiveQuery, ArchiveEntryError, ArchiveExtractReport, ApiResponse<ArchiveExtractReport>, ObjectTagging, ApiResponse<ObjectTagging>, ObjectTaggingQuery, ObjectSearchResult, ApiResponse<ObjectSearchResult>, SearchObjectsQuery, MetadataIndexConfiguration, MetadataIndexStats, MetadataIndexStatus, ApiResponse<MetadataIndexStatus>, WebsiteRedirectRule, WebsiteConfiguration, ApiResponse<WebsiteConfiguration>)
+    ),
+    tags(
+        (name = "buckets", description = "Bucket management endpoints"),
+        (name = "objects", description = "Object management endpoints"),
+        (name = "health", description = "Health check endpoints"),
+        (name = "test", description = "Test endpoints")
+    )
+)]
+struct ApiDoc;
+
+/// 命令行参数
+#[derive(Parser)]
+#[command(name = "sevino", about = "Sevino Object Storage Service")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 启动对象存储服务（默认）
+    Serve,
+    /// 轮换服务端加密密钥（只重新包装密钥，不重写对象数据）
+    RotateKeys {
+        /// 只轮换指定桶的桶密钥（默认所有已有加密密钥的桶）
+        #[arg(long)]
+        bucket: Option<String>,
+        /// 使用新的主密钥文件重新包装所有桶密钥；完成后需将 SEVINO_MASTER_KEY_FILE 指向该文件
+        #[arg(long)]
+        new_master_key_file: Option<String>,
+    },
+    /// 校验审计日志的哈希链是否完整
+    VerifyAudit,
+}
+
+#[derive(Clone)]
+struct AppState {
+    bucket_service: BucketService,
+    object_service: ObjectService,
+    lifecycle_service: LifecycleService,
+    delete_job_service: DeleteJobService,
+    archive_service: ArchiveService,
+    image_transform_service: ImageTransformService,
+    website_service: WebsiteService,
+    policy_service: PolicyService,
+    share_link_service: ShareLinkService,
+    form_upload_service: FormUploadService,
+    storage_service: StorageService,
+    metrics: Metrics,
+    access_log: AccessLog,
+    rate_limiter: RateLimiter,
+    response_compression: ResponseCompression,
+    admin_token: Option<String>,
+    website_domain: Option<String>,
+    trusted_proxies: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let cli = Cli::parse();
+    let settings = Settings::from_env();
+
+    match cli.command {
+        Some(Command::RotateKeys { bucket, new_master_key_file }) => {
+            if let Err(e) = rotate_keys(&settings, bucket, new_master_key_file).await {
+                eprintln!("Key rotation failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::VerifyAudit) => {
+            match audit::verify(&settings.data_dir) {
+                Ok(verification) => {
+                    println!("Checked {} entries in {} audit log file(s)", verification.entries, verification.files);
+                    if let Some(error) = verification.error {
+                        eprintln!("Audit log verification failed at {}", error);
+                        std::process::exit(1);
+                    }
+                    println!("Audit log hash chain is intact");
+                }
+                Err(e) => {
+                    eprintln!("Audit log verification failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Serve) | None => {}
+    }
+
+    println!("Starting Sevino Object Storage Service with settings: {:?}", settings.redacted());
+
+    let any_ip = std::net::IpAddr::from([0, 0, 0, 0]);
+    if let Some(Err(e)) = settings.trusted_proxies.iter().map(|cidr| utils::ip_in_cidr(&any_ip, cidr)).find(|result| result.is_err()) {
+        eprintln!("Invalid SEVINO_TRUSTED_PROXIES: {}", e);
+        std::process::exit(1);
+    }
+
+    // Prometheus 指标
+    let metrics = match Metrics::new() {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            eprintln!("Failed to initialize metrics: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let storage_service = match StorageService::new(settings.data_dir.clone()).await {
+        Ok(service) => service.with_metrics(metrics.clone()),
+        Err(e) => {
+            eprintln!("Failed to initialize storage service: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 事件通知（磁盘发件箱 + 后台投递）
+    let notification_service = match NotificationService::new(
+        storage_service.clone(),
+        &settings.data_dir,
+        settings.webhook_secret.clone(),
+        settings.webhook_max_attempts,
+        settings.webhook_timeout_secs,
+        settings.webhook_concurrency,
+        settings.webhook_allowed_networks.clone(),
+    ) {
+        Ok(service) => service,
+        Err(e) => {
+            eprintln!("Failed to initialize notification service: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // 没有签名密钥时接收端无法验证负载，已有通知规则则拒绝启动
+    if !notification_service.enabled() {
+        match notification_service.configured_buckets().await {
+            Ok(buckets) if buckets.is_empty() => {}
+            Ok(buckets) => {
+                eprintln!("SEVINO_WEBHOOK_SECRET must be set, buckets with notification rules: {}", buckets.join(", "));
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Failed to load notification configurations: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    notification_service.start_dispatcher();
+
+    // 访问日志和哈希链审计日志
+    let access_log = match AccessLog::open(&settings.access_log) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Failed to open access log: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let audit_log = match AuditLog::open(&settings.data_dir, settings.audit_log_max_bytes) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Failed to open audit log: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 服务端加密（未配置主密钥时不可用）
+    let master_key = match encryption::load_master_key(settings.master_key.as_deref(), settings.master_key_file.as_deref()) {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("Failed to load master key: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let encryption_service = EncryptionService::new(storage_service.clone(), master_key);
+
+    let bucket_service = BucketService::new(storage_service.clone())
+        .with_notifications(notification_service.clone())
+        .with_encryption(encryption_service.clone())
+        .with_audit(audit_log.clone());
+    let object_service = ObjectService::new(storage_service.clone())
+        .with_notifications(notification_service)
+        .with_encryption(encryption_service)
+        .with_metrics(metrics.clone())
+        .with_audit(audit_log);
+
+    // 生命周期规则定时执行
+    let lifecycle_service = LifecycleService::new(storage_service.clone(), bucket_service.clone(), object_service.clone());
+    lifecycle_service.start_scheduler(settings.lifecycle_interval_secs);
+
+    // GET 时的图片变换
+    let image_transform_service = ImageTransformService::new(storage_service.clone(), object_service.clone(), settings.image_max_pixels);
+
+    // 静态网站托管
+    let website_service = WebsiteService::new(storage_service.clone(), object_service.clone());
+
+    // 分享链接
+    let share_link_service = match ShareLinkService::new(object_service.clone(), &settings.data_dir, &settings.share_link_secret) {
+        Ok(service) => service,
+        Err(e) => {
+            eprintln!("Failed to initialize share link service: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 浏览器表单上传
+    let form_upload_service = match FormUploadService::new(object_service.clone(), &settings.data_dir, &settings.upload_policy_secret) {
+        Ok(service) => service,
+        Err(e) => {
+            eprintln!("Failed to initialize form upload service: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 桶策略授权
+    let policy_service = PolicyService::new(storage_service.clone(), &settings.access_keys, settings.admin_token.clone());
+
+    // 按前缀删除的后台任务
+    let delete_job_service = DeleteJobService::new(storage_service.clone(), object_service.clone(), policy_service.clone());
+
+    // 归档解包和按前缀打包下载
+    let archive_service = ArchiveService::new(storage_service.clone(), object_service.clone(), policy_service.clone(), settings.max_file_size);
+
+    let state = Arc::new(AppState {
+        bucket_service,
+        object_service,
+        lifecycle_service,
+        delete_job_service,
+        archive_service,
+        image_transform_service,
+        website_service,
+        policy_service,
+        share_link_service,
+        form_upload_service,
+        storage_service,
+        metrics: metrics.clone(),
+        access_log,
+        rate_limiter: RateLimiter::new(&settings),
+        response_compression: ResponseCompression::new(&settings),
+        admin_token: settings.admin_token.clone(),
+        website_domain: settings.website_domain.clone(),
+        trusted_proxies: settings.trusted_proxies.clone(),
+    });
+
+    // 配置CORS
+    let cors_layer = if settings.enable_cors {
+        let mut cors = CorsLayer::new();
+        
+        // 配置允许的域名
+        if settings.cors_origins.contains(&"*".to_string()) {
+            cors = cors.allow_origin(Any);
+        } else {
+            let origins: Vec<_> = settings.cors_origins
+                .iter()
+                .filter_map(|origin| origin.parse().ok())
+                .collect();
+            if !origins.is_empty() {
+                cors = cors.allow_origin(origins);
+            }
+        }
+        
+        // 配置允许的方法
+        let methods: Vec<Method> = settings.cors_methods
+            .iter()
+            .filter_map(|method| method.parse().ok())
+            .collect();
+        if !methods.is_empty() {
+            cors = cors.allow_methods(methods);
+        }
+        
+        // 配置允许的头部
+        let headers: Vec<HeaderName> = settings.cors_headers
+            .iter()
+            .filter_map(|header| header.parse().ok())
+            .collect();
+        if !headers.is_empty() {
+            cors = cors.allow_headers(headers);
+        }
+        
+        // 配置凭据
+        if settings.cors_allow_credentials {
+            cors = cors.allow_credentials(true);
+        }
+        
+        // 设置预检请求缓存时间
+        cors = cors.max_age(std::time::Duration::from_secs(3600));
+        
+        cors
+    } else {
+        CorsLayer::new()
+    };
+
+    let app = Router::new()
+        .route("/", get(root))
+        .route("/health", get(health_check))
+        .route("/metrics", get(get_metrics))
+        .route("/api/buckets", get(list_buckets))
+        .route("/api/buckets", post(create_bucket))
+        .route("/api/buckets/:name", get(get_bucket))
+        .route("/api/buckets/:name", delete(delete_bucket))
+        .route("/api/buckets/:name", post(post_object))
+        .route("/api/buckets/:name/upload-policy", post(create_upload_policy))
+        .route("/api/buckets/:name/stats", get(get_bucket_stats))
+        .route("/api/buckets/:name/notification", get(get_bucket_notification))
+        .route("/api/buckets/:name/notification", put(put_bucket_notification))
+        .route("/api/buckets/:name/notification", delete(delete_bucket_notification))
+        .route("/api/buckets/:name/lifecycle", get(get_bucket_lifecycle))
+        .route("/api/buckets/:name/lifecycle", put(put_bucket_lifecycle))
+        .route("/api/buckets/:name/lifecycle", delete(delete_bucket_lifecycle))
+        .route("/api/buckets/:name/lifecycle/run", post(run_bucket_lifecycle))
+        .route("/api/buckets/:name/object-lock", get(get_bucket_object_lock))
+        .route("/api/buckets/:name/object-lock", put(put_bucket_object_lock))
+        .route("/api/buckets/:name/policy", get(get_bucket_policy))
+        .route("/api/buckets/:name/policy", put(put_bucket_policy))
+        .route("/api/buckets/:name/policy", delete(delete_bucket_policy))
+        .route("/api/buckets/:name/policy/simulate", post(simulate_bucket_policy))
+        .route("/api/buckets/:name/encryption", get(get_bucket_encryption))
+        .route("/api/buckets/:name/encryption", put(put_bucket_encryption))
+        .route("/api/buckets/:name/compression", get(get_bucket_compression))
+        .route("/api/buckets/:name/compression", put(put_bucket_compression))
+        .route("/api/buckets/:name/compression", delete(delete_bucket_compression))
+        .route("/api/buckets/:name/content-type", get(get_bucket_content_type))
+        .route("/api/buckets/:name/content-type", put(put_bucket_content_type))
+        .route("/api/buckets/:name/content-type", delete(delete_bucket_content_type))
+        .route("/api/buckets/:name/quota", get(get_bucket_quota))
+        .route("/api/buckets/:name/quota", put(put_bucket_quota))
+        .route("/api/buckets/:name/quota", delete(delete_bucket_quota))
+        .route("/api/buckets/:name/metadata-index", get(get_bucket_metadata_index))
+        .route("/api/buckets/:name/metadata-index", put(put_bucket_metadata_index))
+        .route("/api/buckets/:name/metadata-index", delete(delete_bucket_metadata_index))
+        .route("/api/buckets/:name/metadata-index/rebuild", post(rebuild_bucket_metadata_index))
+        .route("/api/buckets/:name/website", get(get_bucket_website))
+        .route("/api/buckets/:name/website", put(put_bucket_website))
+        .route("/api/buckets/:name/website", delete(delete_bucket_website))
+        .route("/api/buckets/:name/rename", post(rename_prefix))
+        .route("/api/buckets/:name/delete", post(delete_objects))
+        .route("/api/buckets/:name/delete-jobs", post(start_delete_job))
+        .route("/api/buckets/:name/delete-jobs", get(list_delete_jobs))
+        .route("/api/buckets/:name/delete-jobs/:job_id", get(get_delete_job))
+        .route("/api/buckets/:name/archive", post(extract_archive))
+        .route("/api/buckets/:name/archive", get(download_archive))
+        .route("/api/buckets/:bucket_name/objects", get(list_objects))
+        .route("/api/buckets/:name/search", get(search_objects))
+        .route("/api/buckets/:bucket_name/objects/:key", put(put_object))
+        .route("/api/buckets/:bucket_name/objects/:key/multipart", put(put_object_multipart))
+        .route("/api/buckets/:bucket_name/objects/:key/copy", put(copy_object))
+        .route("/api/buckets/:bucket_name/objects/:key/rename", post(rename_object))
+        .route("/api/buckets/:bucket_name/objects/:key", get(get_object))
+        .route("/api/buckets/:bucket_name/objects/:key", delete(delete_object))
+        .route("/api/buckets/:bucket_name/objects/:key/metadata", get(get_object_metadata))
+        .route("/api/buckets/:bucket_name/objects/:key/metadata", put(update_object_metadata))
+        .route("/api/buckets/:bucket_name/objects/:key/retention", put(put_object_retention))
+        .route("/api/buckets/:bucket_name/objects/:key/legal-hold", put(put_object_legal_hold))
+        .route("/api/buckets/:bucket_name/objects/:key/tagging", get(get_object_tagging))
+        .route("/api/buckets/:bucket_name/objects/:key/tagging", put(put_object_tagging))
+        .route("/api/buckets/:bucket_name/objects/:key/tagging", delete(delete_object_tagging))
+        .route("/api/buckets/:bucket_name/objects/:key/versions", get(list_object_versions))
+        .route("/api/buckets/:bucket_name/objects/:key/duplicate-test", post(test_duplicate_handling))
+        .route("/api/buckets/:bucket_name/objects/:key/share", post(create_share_link))
+        .route("/api/share-links/:id", get(get_share_link))
+        .route("/api/share-links/:id", delete(revoke_share_link))
+        .route("/share/:id", get(download_share_link))
+        .route("/website/:bucket", get(serve_website_root))
+        .route("/website/:bucket/", get(serve_website_root))
+        .route("/website/:bucket/*path", get(serve_website_path))
+        .route("/api/test/reference-mode", get(test_reference_mode_api))
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .layer(middleware::from_fn_with_state(state.clone(), website_host))
+        .layer(middleware::from_fn_with_state(state.clone(), compress_response))
+        .layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+        .layer(middleware::from_fn_with_state(state.clone(), authorize_request))
+        .layer(MetricsLayer::new(metrics))
+        .layer(middleware::from_fn_with_state(state.clone(), log_access))
+        .layer(cors_layer)
+        .with_state(state);
+
+    let addr = format!("{}:{}", settings.host, settings.port);
+    println!("Server running on http://{}", addr);
+    println!("Swagger UI available at http://{}/swagger-ui/", addr);
+    println!("CORS enabled: {}", settings.enable_cors);
+    if settings.enable_cors {
+        println!("CORS origins: {:?}", settings.cors_origins);
+        println!("CORS methods: {:?}", settings.cors_methods);
+        println!("CORS allow credentials: {}", settings.cors_allow_credentials);
+    }
+
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
+}
+
+/// 轮换桶密钥，并在指定新主密钥时重新包装所有桶密钥
+async fn rotate_keys(settings: &Settings, bucket: Option<String>, new_master_key_file: Option<String>) -> Result<()> {
+    let storage_service = StorageService::new(settings.data_dir.clone()).await?;
+    let master_key = encryption::load_master_key(settings.master_key.as_deref(), settings.master_key_file.as_deref())?;
+    let encryption_service = EncryptionService::new(storage_service.clone(), master_key);
+
+    let all_buckets: Vec<String> = BucketService::new(storage_service).list_buckets().await.into_iter().map(|b| b.name).collect();
+    let bucket_names = match bucket {
+        Some(bucket) => vec![bucket],
+        None => all_buckets.clone(),
+    };
+
+    for bucket_name in &bucket_names {
+        if !encryption_service.has_bucket_keys(bucket_name).await? {
+            continue;
+        }
+        let report = encryption_service.rotate_bucket_key(bucket_name).await?;
+        println!(
+            "Rotated bucket key for '{}': new key {}, {} data key(s) re-wrapped",
+            report.bucket_name, report.new_key_id, report.rewrapped_objects
+        );
+    }
+
+    if let Some(path) = new_master_key_file {
+        let new_master_key = encryption::load_master_key(None, Some(&path))?
+            .ok_or_else(|| anyhow::anyhow!("New master key file is empty"))?;
+        // 主密钥轮换必须覆盖所有桶，否则切换主密钥后未处理的桶将无法解密
+        let count = encryption_service.rewrap_bucket_keys(&all_buckets, &new_master_key).await?;
+        println!("Re-wrapped {} bucket key(s) with the new master key; point SEVINO_MASTER_KEY_FILE at {} before restarting", count, path);
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "health",
+    responses(
+        (status = 200, description = "Welcome message")
+    )
+)]
+async fn root() -> &'static str {
+    "Welcome to Sevino Object Storage Service!"
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+struct HealthResponse {
+    status: String,
+    timestamp: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Health check response", body = HealthResponse)
+    )
+)]
+async fn health_check() -> Json<HealthResponse> {
+    let response = HealthResponse {
+        status: "healthy".to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    Json(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses(
+        (status = 200, description = "Metrics in Prometheus text format", body = String, content_type = "text/plain")
+    )
+)]
+async fn get_metrics(State(state): State<Arc<AppState>>) -> Response {
+    match state.metrics.render(&state.storage_service).await {
+        Ok(body) => (
+            [("Content-Type", "text/plain; version=0.0.4")],
+            body,
+        ).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets",
+    tag = "buckets",
+    responses(
+        (status = 200, description = "List of buckets", body = ApiResponse<BucketListResponse>)
+    )
+)]
+async fn list_buckets(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<BucketListResponse>> {
+    let buckets = state.bucket_service.list_buckets().await;
+    let response = BucketListResponse { buckets };
+    Json(ApiResponse::success(response))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct CreateBucketRequest {
+    name: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets",
+    tag = "buckets",
+    request_body(content = CreateBucketRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket created successfully", body = ApiResponse<Bucket>),
+        (status = 400, description = "Invalid bucket name", body = ApiResponse<Bucket>)
+    )
+)]
+async fn create_bucket(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateBucketRequest>,
+) -> Json<ApiResponse<Bucket>> {
+    match state.bucket_service.create_bucket(request.name).await {
+        Ok(bucket) => Json(ApiResponse::success(bucket)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket details", body = ApiResponse<Bucket>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<Bucket>)
+    )
+)]
+async fn get_bucket(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<Bucket>> {
+    match state.bucket_service.get_bucket(&name).await {
+        Some(bucket) => Json(ApiResponse::success(bucket)),
+        None => Json(ApiResponse::error("Bucket not found".to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket deleted successfully", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_bucket(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/rename",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = RenamePrefixRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Objects under the prefix renamed", body = ApiResponse<RenameReport>),
+        (status = 403, description = "Not allowed to rename a source object or write a destination key", body = ApiResponse<RenameReport>),
+        (status = 404, description = "Bucket not found or prefix is empty", body = ApiResponse<RenameReport>)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+async fn rename_prefix(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    Json(request): Json<RenamePrefixRequest>,
+) -> (StatusCode, Json<ApiResponse<RenameReport>>) {
+    if request.source_prefix.is_empty() {
+        return (StatusCode::OK, Json(ApiResponse::error("Source prefix must not be empty".to_string())));
+    }
+    let keys = match state.object_service.prefix_keys(&name, &request.source_prefix).await {
+        Ok(keys) => keys,
+        Err(e) => return (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    };
+
+    // 授权中间件只检查了桶级别的权限，这里逐个检查源对象的重命名权限和目标键的写权限
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    for key in &keys {
+        let destination = format!("{}{}", request.destination_prefix, &key[request.source_prefix.len()..]);
+        for (action, target) in [("sevino:RenameObject", key), ("sevino:PutObject", &destination)] {
+            if let Err((status, message)) = authorize_additional(&state, &identity, action, &name, target, addr, secure_transport).await {
+                return (status, Json(ApiResponse::error(message)));
+            }
+        }
+    }
+
+    let keys = keys.into_iter().collect();
+    match state.object_service.rename_prefix(&name, &request.source_prefix, &request.destination_prefix, &keys).await {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::success(report))),
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/stats",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket usage and deduplication statistics", body = ApiResponse<BucketUsage>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<BucketUsage>)
+    )
+)]
+async fn get_bucket_stats(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<BucketUsage>> {
+    match state.bucket_service.get_bucket_stats(&name).await {
+        Ok(stats) => Json(ApiResponse::success(stats)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/notification",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket notification configuration", body = ApiResponse<NotificationConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<NotificationConfiguration>)
+    )
+)]
+async fn get_bucket_notification(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<NotificationConfiguration>> {
+    match state.bucket_service.get_notification_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/notification",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = NotificationConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket notification configuration updated", body = ApiResponse<NotificationConfiguration>),
+        (status = 400, description = "Invalid notification configuration", body = ApiResponse<NotificationConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<NotificationConfiguration>)
+    )
+)]
+async fn put_bucket_notification(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<NotificationConfiguration>,
+) -> Json<ApiResponse<NotificationConfiguration>> {
+    match state.bucket_service.put_notification_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/notification",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket notification configuration removed", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_notification(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_notification_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/lifecycle",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket lifecycle configuration", body = ApiResponse<LifecycleConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<LifecycleConfiguration>)
+    )
+)]
+async fn get_bucket_lifecycle(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<LifecycleConfiguration>> {
+    match state.bucket_service.get_lifecycle_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/lifecycle",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = LifecycleConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket lifecycle configuration updated", body = ApiResponse<LifecycleConfiguration>),
+        (status = 400, description = "Invalid lifecycle configuration", body = ApiResponse<LifecycleConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<LifecycleConfiguration>)
+    )
+)]
+async fn put_bucket_lifecycle(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<LifecycleConfiguration>,
+) -> Json<ApiResponse<LifecycleConfiguration>> {
+    match state.bucket_service.put_lifecycle_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/lifecycle",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket lifecycle configuration removed", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_lifecycle(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_lifecycle_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/lifecycle/run",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Lifecycle rules applied immediately", body = ApiResponse<LifecycleReport>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<LifecycleReport>)
+    )
+)]
+async fn run_bucket_lifecycle(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<LifecycleReport>> {
+    match state.lifecycle_service.run_bucket(&name).await {
+        Ok(report) => Json(ApiResponse::success(report.unwrap_or_else(|| LifecycleReport {
+            bucket_name: name,
+            ..Default::default()
+        }))),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/object-lock",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket object lock configuration", body = ApiResponse<ObjectLockConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<ObjectLockConfiguration>)
+    )
+)]
+async fn get_bucket_object_lock(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<ObjectLockConfiguration>> {
+    match state.bucket_service.get_object_lock_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/object-lock",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = ObjectLockConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket object lock configuration updated", body = ApiResponse<ObjectLockConfiguration>),
+        (status = 400, description = "Invalid object lock configuration", body = ApiResponse<ObjectLockConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<ObjectLockConfiguration>)
+    )
+)]
+async fn put_bucket_object_lock(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<ObjectLockConfiguration>,
+) -> Json<ApiResponse<ObjectLockConfiguration>> {
+    match state.bucket_service.put_object_lock_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/policy",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket policy", body = ApiResponse<BucketPolicy>),
+        (status = 404, description = "Bucket or policy not found", body = ApiResponse<BucketPolicy>)
+    )
+)]
+async fn get_bucket_policy(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<BucketPolicy>> {
+    match state.bucket_service.get_bucket_policy(&name).await {
+        Ok(policy) => Json(ApiResponse::success(policy)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/policy",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = BucketPolicy, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket policy updated", body = ApiResponse<BucketPolicy>),
+        (status = 400, description = "Invalid bucket policy", body = ApiResponse<BucketPolicy>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<BucketPolicy>)
+    )
+)]
+async fn put_bucket_policy(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(policy): Json<BucketPolicy>,
+) -> Json<ApiResponse<BucketPolicy>> {
+    match state.bucket_service.put_bucket_policy(&name, policy).await {
+        Ok(policy) => Json(ApiResponse::success(policy)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/policy",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket policy deleted", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_policy(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_bucket_policy(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/policy/simulate",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = PolicySimulationRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Policy evaluation result", body = ApiResponse<PolicyEvaluation>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<PolicyEvaluation>)
+    )
+)]
+async fn simulate_bucket_policy(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(request): Json<PolicySimulationRequest>,
+) -> Json<ApiResponse<PolicyEvaluation>> {
+    match state.policy_service.simulate(&name, request).await {
+        Ok(evaluation) => Json(ApiResponse::success(evaluation)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/encryption",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket encryption configuration", body = ApiResponse<EncryptionConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<EncryptionConfiguration>)
+    )
+)]
+async fn get_bucket_encryption(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<EncryptionConfiguration>> {
+    match state.bucket_service.get_encryption_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/encryption",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = EncryptionConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket encryption configuration updated", body = ApiResponse<EncryptionConfiguration>),
+        (status = 400, description = "No master key configured", body = ApiResponse<EncryptionConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<EncryptionConfiguration>)
+    )
+)]
+async fn put_bucket_encryption(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<EncryptionConfiguration>,
+) -> Json<ApiResponse<EncryptionConfiguration>> {
+    match state.bucket_service.put_encryption_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/compression",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket compression configuration", body = ApiResponse<CompressionConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<CompressionConfiguration>)
+    )
+)]
+async fn get_bucket_compression(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<CompressionConfiguration>> {
+    match state.bucket_service.get_compression_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/compression",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = CompressionConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket compression configuration updated", body = ApiResponse<CompressionConfiguration>),
+        (status = 400, description = "Invalid compression configuration", body = ApiResponse<CompressionConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<CompressionConfiguration>)
+    )
+)]
+async fn put_bucket_compression(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<CompressionConfiguration>,
+) -> Json<ApiResponse<CompressionConfiguration>> {
+    match state.bucket_service.put_compression_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/compression",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket compression configuration deleted", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_compression(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_compression_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/content-type",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket content type detection configuration", body = ApiResponse<ContentTypeConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<ContentTypeConfiguration>)
+    )
+)]
+async fn get_bucket_content_type(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<ContentTypeConfiguration>> {
+    match state.bucket_service.get_content_type_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/content-type",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = ContentTypeConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket content type detection configuration updated", body = ApiResponse<ContentTypeConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<ContentTypeConfiguration>)
+    )
+)]
+async fn put_bucket_content_type(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<ContentTypeConfiguration>,
+) -> Json<ApiResponse<ContentTypeConfiguration>> {
+    match state.bucket_service.put_content_type_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/content-type",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket content type detection configuration deleted", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_content_type(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_content_type_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/quota",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket quota configuration and current usage", body = ApiResponse<BucketQuotaStatus>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<BucketQuotaStatus>)
+    )
+)]
+async fn get_bucket_quota(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<BucketQuotaStatus>> {
+    match state.bucket_service.get_quota(&name).await {
+        Ok(status) => Json(ApiResponse::success(status)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/quota",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = QuotaConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket quota configuration updated", body = ApiResponse<QuotaConfiguration>),
+        (status = 400, description = "Invalid quota configuration", body = ApiResponse<QuotaConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<QuotaConfiguration>)
+    )
+)]
+async fn put_bucket_quota(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<QuotaConfiguration>,
+) -> Json<ApiResponse<QuotaConfiguration>> {
+    match state.bucket_service.put_quota_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/quota",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket quota configuration deleted", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_quota(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_quota_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/metadata-index",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Metadata index configuration and index statistics", body = ApiResponse<MetadataIndexStatus>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<MetadataIndexStatus>)
+    )
+)]
+async fn get_bucket_metadata_index(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<MetadataIndexStatus>> {
+    match state.bucket_service.get_metadata_index(&name).await {
+        Ok(status) => Json(ApiResponse::success(status)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/metadata-index",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = MetadataIndexConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Metadata index configuration updated and indexes built", body = ApiResponse<MetadataIndexStatus>),
+        (status = 400, description = "Invalid metadata index configuration", body = ApiResponse<MetadataIndexStatus>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<MetadataIndexStatus>)
+    )
+)]
+async fn put_bucket_metadata_index(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<MetadataIndexConfiguration>,
+) -> Json<ApiResponse<MetadataIndexStatus>> {
+    match state.bucket_service.put_metadata_index_configuration(&name, config).await {
+        Ok(status) => Json(ApiResponse::success(status)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/metadata-index",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Metadata index configuration and indexes deleted", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_metadata_index(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_metadata_index_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/metadata-index/rebuild",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Metadata indexes rebuilt from object metadata on disk", body = ApiResponse<MetadataIndexStatus>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<MetadataIndexStatus>)
+    )
+)]
+async fn rebuild_bucket_metadata_index(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<MetadataIndexStatus>> {
+    match state.bucket_service.rebuild_metadata_index(&name).await {
+        Ok(status) => Json(ApiResponse::success(status)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/website",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket website configuration", body = ApiResponse<WebsiteConfiguration>),
+        (status = 404, description = "Bucket not found or not configured as a website", body = ApiResponse<WebsiteConfiguration>)
+    )
+)]
+async fn get_bucket_website(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<WebsiteConfiguration>> {
+    match state.bucket_service.get_website_configuration(&name).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{name}/website",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = WebsiteConfiguration, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Bucket website configuration updated", body = ApiResponse<WebsiteConfiguration>),
+        (status = 400, description = "Invalid website configuration", body = ApiResponse<WebsiteConfiguration>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<WebsiteConfiguration>)
+    )
+)]
+async fn put_bucket_website(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(config): Json<WebsiteConfiguration>,
+) -> Json<ApiResponse<WebsiteConfiguration>> {
+    match state.bucket_service.put_website_configuration(&name, config).await {
+        Ok(config) => Json(ApiResponse::success(config)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{name}/website",
+    tag = "buckets",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Bucket website configuration deleted", body = ApiResponse<()>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_bucket_website(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match state.bucket_service.delete_website_configuration(&name).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+/// 基于路径访问静态网站的根目录（/website/{bucket}）
+async fn serve_website_root(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    Path(bucket_name): Path<String>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+) -> Response {
+    let base = format!("/website/{}", bucket_name);
+    let request = website_request(&state, addr, "", &base, &uri, &headers);
+    serve_website(&state, &identity, addr, &bucket_name, &request, &headers).await
+}
+
+/// 基于路径访问静态网站（/website/{bucket}/{path}）
+async fn serve_website_path(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    Path((bucket_name, path)): Path<(String, String)>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+) -> Response {
+    let base = format!("/website/{}", bucket_name);
+    let request = website_request(&state, addr, &path, &base, &uri, &headers);
+    serve_website(&state, &identity, addr, &bucket_name, &request, &headers).await
+}
+
+/// 基于主机名访问静态网站：Host 为 {桶名}.{SEVINO_WEBSITE_DOMAIN} 的 GET/HEAD 请求不进入 API 路由
+async fn website_host(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let bucket_name = state.website_domain.as_deref().and_then(|domain| {
+        let host = request.headers().get("host")?.to_str().ok()?;
+        let host = host.split(':').next()?.to_lowercase();
+        host.strip_suffix(domain)?.strip_suffix('.').map(str::to_string)
+    });
+    let Some(bucket_name) = bucket_name else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return (StatusCode::METHOD_NOT_ALLOWED, "Method not allowed").into_response();
+    }
+
+    let identity = match request.extensions().get::<Identity>() {
+        Some(identity) => identity.clone(),
+        None => return (StatusCode::INTERNAL_SERVER_ERROR, "Missing request identity").into_response(),
+    };
+    let path = percent_encoding::percent_decode_str(request.uri().path().trim_start_matches('/')).decode_utf8_lossy().into_owned();
+    let website_request = website_request(&state, addr, &path, "", request.uri(), request.headers());
+    serve_website(&state, &identity, addr, &bucket_name, &website_request, request.headers()).await
+}
+
+fn website_request<'a>(
+    state: &AppState,
+    addr: SocketAddr,
+    path: &'a str,
+    base: &'a str,
+    uri: &axum::http::Uri,
+    headers: &'a HeaderMap,
+) -> WebsiteRequest<'a> {
+    WebsiteRequest {
+        path,
+        base,
+        host: headers.get("host").and_then(|v| v.to_str().ok()),
+        secure: is_secure_transport(state, addr, uri, headers),
+    }
+}
+
+/// 按桶的网站配置处理请求，并按桶策略检查对返回对象的 sevino:GetObject 权限
+async fn serve_website(
+    state: &AppState,
+    identity: &Identity,
+    addr: SocketAddr,
+    bucket_name: &str,
+    request: &WebsiteRequest<'_>,
+    headers: &HeaderMap,
+) -> Response {
+    match state.website_service.serve(bucket_name, request).await {
+        Ok(WebsiteResponse::Object { status, data, metadata, content_type, cache_control }) => {
+            if let Err((status, message)) = authorize_additional(state, identity, "sevino:GetObject", bucket_name, &metadata.key, addr, request.secure).await {
+                return (status, message).into_response();
+            }
+
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+            let not_modified = status == StatusCode::OK && headers.get("if-none-match")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|value| utils::etag_condition_matches(value, &metadata.etag));
+            let mut response = axum::response::Response::builder()
+                .header("Content-Type", content_type)
+                .header("ETag", metadata.etag.as_str())
+                .header("Last-Modified", metadata.last_modified.to_rfc2822());
+            if let Some(cache_control) = cache_control {
+                response = response.header("Cache-Control", cache_control);
+            }
+            if not_modified {
+                return response.status(StatusCode::NOT_MODIFIED).body(axum::body::Body::empty()).unwrap();
+            }
+            response
+                .status(status)
+                .header("Content-Length", data.len().to_string())
+                .extension(ObjectBody { stored_compressed: metadata.compression.is_some() })
+                .body(axum::body::Body::from(data))
+                .unwrap()
+        }
+        Ok(WebsiteResponse::Redirect { status, location }) => axum::response::Response::builder()
+            .status(StatusCode::from_u16(status).unwrap_or(StatusCode::MOVED_PERMANENTLY))
+            .header("Location", location)
+            .body(axum::body::Body::empty())
+            .unwrap(),
+        Ok(WebsiteResponse::NotFound) => (StatusCode::NOT_FOUND, "Not Found").into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+    }
+}
+
+/// 授权中间件：解析请求身份，并按目标桶的策略评估请求
+async fn authorize_request(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let identity = match state.policy_service.resolve_identity(request.headers()) {
+        Ok(identity) => identity,
+        Err(e) => {
+            return (StatusCode::UNAUTHORIZED, Json(ApiResponse::<()>::error(e.to_string()))).into_response();
+        }
+    };
+
+    // 管理员不受桶策略限制，避免策略配置错误导致无法恢复
+    if !identity.is_admin {
+        if is_unclassified_bucket_request(request.method(), request.uri().path()) {
+            let message = format!("Access denied: {} {} is not covered by any bucket policy action", request.method(), request.uri().path());
+            let mut response = (StatusCode::FORBIDDEN, Json(ApiResponse::<()>::error(message))).into_response();
+            response.extensions_mut().insert(identity);
+            return response;
+        }
+        if let Some(target) = classify_request(request.method(), request.uri().path()) {
+            let prefix = request.uri().query().and_then(|query| {
+                url::form_urlencoded::parse(query.as_bytes())
+                    .find(|(name, _)| name == "prefix")
+                    .map(|(_, value)| value.into_owned())
+            });
+
+            let authorization = AuthorizationRequest {
+                principal: identity.principal.clone(),
+                action: target.action.to_string(),
+                bucket_name: target.bucket_name,
+                key: target.key,
+                prefix,
+                source_ip: Some(addr.ip()),
+                secure_transport: is_secure_transport(&state, addr, request.uri(), request.headers()),
+                object_tags: None,
+            };
+
+            match state.policy_service.authorize(&authorization).await {
+                Ok(evaluation) if evaluation.allowed => {}
+                Ok(evaluation) => {
+                    let message = format!(
+                        "Access denied: '{}' is not allowed to perform {} on '{}'",
+                        evaluation.principal, authorization.action, evaluation.resource
+                    );
+                    let mut response = (StatusCode::FORBIDDEN, Json(ApiResponse::<()>::error(message))).into_response();
+                    response.extensions_mut().insert(identity);
+                    return response;
+                }
+                Err(e) => {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(e.to_string()))).into_response();
+                }
+            }
+        }
+    }
+
+    // 审计日志通过请求上下文获取操作者
+    let context = RequestContext {
+        request_id: request.extensions().get::<RequestId>().map(|id| id.0.clone()).unwrap_or_default(),
+        principal: identity.principal.clone(),
+    };
+    request.extensions_mut().insert(identity.clone());
+    let mut response = audit::with_request_context(context, next.run(request)).await;
+    response.extensions_mut().insert(identity);
+    response
+}
+
+/// 请求是否通过HTTPS到达（包括反向代理转发的HTTPS请求）
+fn is_secure_transport(state: &AppState, addr: SocketAddr, uri: &axum::http::Uri, headers: &HeaderMap) -> bool {
+    uri.scheme_str() == Some("https") || forwarded_https(state, addr, headers)
+}
+
+/// X-Forwarded-Proto 是否声明了 HTTPS：只采信来自受信任反向代理的请求头，客户端直连时忽略
+fn forwarded_https(state: &AppState, addr: SocketAddr, headers: &HeaderMap) -> bool {
+    let trusted = state.trusted_proxies.iter().any(|cidr| utils::ip_in_cidr(&addr.ip(), cidr).unwrap_or(false));
+    trusted && headers
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("https"))
+        .unwrap_or(false)
+}
+
+/// 限速中间件：超过请求数限制时返回 429，带宽限制通过节流请求和响应消息体实现
+async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // 管理员不受限速约束；匿名请求按来源IP计数，其他请求按访问密钥计数
+    let identity = request.extensions().get::<Identity>().cloned();
+    if identity.as_ref().map(|identity| identity.is_admin).unwrap_or(false) {
+        return next.run(request).await;
+    }
+    let client = match identity.filter(|identity| identity.principal != ANONYMOUS_PRINCIPAL) {
+        Some(identity) => format!("key:{}", identity.principal),
+        None => format!("ip:{}", addr.ip()),
+    };
+    let bucket_name = classify_request(request.method(), request.uri().path()).map(|target| target.bucket_name);
+
+    if let Err(retry_after) = state.rate_limiter.check_request(&client, bucket_name.as_deref()) {
+        let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+        let message = format!("Rate limit exceeded, retry after {} seconds", secs);
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(ApiResponse::<()>::error(message))).into_response();
+        response.headers_mut().insert("retry-after", secs.into());
+        return response;
+    }
+
+    let Some(throttle) = state.rate_limiter.bandwidth(&client, bucket_name.as_deref()) else {
+        return next.run(request).await;
+    };
+    let request = request.map(|body| rate_limit::throttle_body(body, throttle.clone()));
+    let response = next.run(request).await;
+
+    // 节流后的消息体长度未知，保留原始的 Content-Length
+    let length = response.body().size_hint().exact().filter(|length| *length > 0);
+    let (mut parts, body) = response.into_parts();
+    if let Some(length) = length {
+        parts.headers.entry("content-length").or_insert(length.into());
+    }
+    Response::from_parts(parts, rate_limit::throttle_body(body, throttle))
+}
+
+/// 响应压缩中间件：按 Accept-Encoding 压缩响应（位于限速之内，带宽按压缩后的字节计算）
+async fn compress_response(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.response_compression.enabled() {
+        return next.run(request).await;
+    }
+    let negotiation = state.response_compression.negotiation(request.method(), request.headers());
+    let response = next.run(request).await;
+    state.response_compression.apply(negotiation, response).await
+}
+
+/// 访问日志中间件：分配请求ID，请求结束后输出一行JSON访问日志
+async fn log_access(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let started_at = std::time::Instant::now();
+    let request_id = RequestId::from_headers(request.headers());
+    request.extensions_mut().insert(request_id.clone());
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let target = classify_request(&method, &path);
+    let bytes_in = utils::body_length(request.headers(), request.body().size_hint().exact());
+
+    let mut response = next.run(request).await;
+
+    let principal = response
+        .extensions()
+        .get::<Identity>()
+        .map(|identity| identity.principal.clone())
+        .unwrap_or_else(|| ANONYMOUS_PRINCIPAL.to_string());
+    state.access_log.record(&AccessLogEntry {
+        time: chrono::Utc::now(),
+        request_id: request_id.0.clone(),
+        principal,
+        remote_addr: addr.to_string(),
+        method: method.to_string(),
+        path,
+        bucket_name: target.as_ref().map(|t| t.bucket_name.clone()),
+        key: target.and_then(|t| t.key),
+        status: response.status().as_u16(),
+        bytes_in,
+        bytes_out: utils::body_length(response.headers(), response.body().size_hint().exact()),
+        latency_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+    });
+
+    if let Ok(value) = request_id.0.parse() {
+        response.headers_mut().insert("x-request-id", value);
+    }
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{bucket_name}/objects",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("prefix" = Option<String>, Query, description = "Object key prefix filter"),
+        ("delimiter" = Option<String>, Query, description = "Delimiter for common prefixes"),
+        ("max_keys" = Option<u32>, Query, description = "Maximum number of keys to return"),
+        ("marker" = Option<String>, Query, description = "Pagination marker"),
+        ("etag_filter" = Option<String>, Query, description = "Filter objects by ETag (supports wildcards: *, ?)"),
+        ("custom_*" = Option<String>, Query, description = "Filter by custom metadata, e.g. custom_bizid=123"),
+        ("tag_*" = Option<String>, Query, description = "Filter by object tag, e.g. tag_project=alpha")
+    ),
+    responses(
+        (status = 200, description = "List of objects", body = ApiResponse<ObjectListResponse>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<ObjectListResponse>)
+    )
+)]
+async fn list_objects(
+    State(state): State<Arc<AppState>>,
+    Path(bucket_name): Path<String>,
+    Query(query): Query<ListObjectsQuery>,
+    axum::extract::RawQuery(raw_query): axum::extract::RawQuery,
+) -> Json<ApiResponse<ObjectListResponse>> {
+    // 解析 custom_xxx=yyy 和 tag_xxx=yyy 过滤条件
+    let mut filters = vec![];
+    if let Some(raw) = raw_query {
+        for (k, v) in url::form_urlencoded::parse(raw.as_bytes()) {
+            if let Some(stripped) = k.strip_prefix("custom_") {
+                filters.push((query::Field::Metadata(stripped.to_string()), v.to_string()));
+            } else if let Some(stripped) = k.strip_prefix("tag_") {
+                filters.push((query::Field::Tag(stripped.to_string()), v.to_string()));
+            }
+        }
+    }
+    match state.object_service.list_objects_with_custom_filter(&bucket_name, query.prefix, query.delimiter, query.max_keys, query.marker, query.etag_filter, filters).await {
+        Ok(objects) => {
+            let response = ObjectListResponse { objects };
+            Json(ApiResponse::success(response))
+        }
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/search",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name"),
+        ("q" = Option<String>, Query, description = "Query, e.g. meta.project = 'alpha' AND size > 1024"),
+        ("sort" = Option<String>, Query, description = "Sort field (key, size, content_type, last_modified, etag, meta.<name>, tag.<name>), defaults to key"),
+        ("order" = Option<String>, Query, description = "Sort direction: asc or desc"),
+        ("limit" = Option<u32>, Query, description = "Maximum number of objects per page (1-1000, default 100)"),
+        ("cursor" = Option<String>, Query, description = "next_cursor from the previous page")
+    ),
+    responses(
+        (status = 200, description = "One page of matching objects", body = ApiResponse<ObjectSearchResult>),
+        (status = 400, description = "Query syntax error", body = ApiResponse<ObjectSearchResult>)
+    )
+)]
+async fn search_objects(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<SearchObjectsQuery>,
+) -> (StatusCode, Json<ApiResponse<ObjectSearchResult>>) {
+    match state.object_service.search_objects(
+        &name,
+        query.q.as_deref().unwrap_or(""),
+        query.sort.as_deref(),
+        query.order.as_deref(),
+        query.limit,
+        query.cursor.as_deref(),
+    ).await {
+        Ok(result) => (StatusCode::OK, Json(ApiResponse::success(result))),
+        Err(e) if e.downcast_ref::<QuerySyntaxError>().is_some() => {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e.to_string())))
+        }
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct SearchObjectsQuery {
+    #[serde(default)]
+    q: Option<String>,
+    #[serde(default)]
+    sort: Option<String>,
+    #[serde(default)]
+    order: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct PutObjectQuery {
+    #[serde(default = "default_deduplication_mode")]
+    deduplication_mode: Option<String>,
+    #[serde(default)]
+    content_type: Option<String>,
+    #[serde(default)]
+    custom: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct ListObjectsQuery {
+    #[serde(default)]
+    prefix: Option<String>,
+    #[serde(default)]
+    delimiter: Option<String>,
+    #[serde(default)]
+    max_keys: Option<u32>,
+    #[serde(default)]
+    marker: Option<String>,
+    #[serde(default)]
+    etag_filter: Option<String>,
+}
+
+fn default_deduplication_mode() -> Option<String> {
+    Some("allow".to_string())
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct MultipartUploadQuery {
+    part_number: u32,
+    total_parts: u32,
+    upload_id: String,
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct UpdateObjectMetadataRequest {
+    #[serde(default)]
+    content_type: Option<String>,
+    #[serde(default)]
+    user_metadata: Option<HashMap<String, String>>,
+    #[serde(default)]
+    custom_etag: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{bucket_name}/objects/{key}/metadata",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key")
+    ),
+    responses(
+        (status = 200, description = "Object metadata", body = ApiResponse<ObjectMetadata>),
+        (status = 404, description = "Object not found", body = ApiResponse<ObjectMetadata>)
+    )
+)]
+async fn get_object_metadata(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+) -> Json<ApiResponse<ObjectMetadata>> {
+    match state.object_service.get_object_metadata(&bucket_name, &key).await {
+        Ok(metadata) => Json(ApiResponse::success(metadata)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("deduplication_mode" = Option<String>, Query, description = "Deduplication mode: reject, allow, reference"),
+        ("content_type" = Option<String>, Query, description = "Content type"),
+        ("custom_etag" = Option<String>, Query, description = "Custom ETag (e.g., \"md5-hash\", \"sha256-hash\", \"W/weak-etag\")"),
+        ("x-sevino-server-side-encryption-customer-algorithm" = Option<String>, Header, description = "SSE-C algorithm (AES256)"),
+        ("x-sevino-server-side-encryption-customer-key" = Option<String>, Header, description = "SSE-C key (base64, 256 bits)"),
+        ("x-sevino-server-side-encryption-customer-key-md5" = Option<String>, Header, description = "SSE-C key MD5 (base64)")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Object uploaded successfully", body = ApiResponse<Object>),
+        (status = 400, description = "Invalid deduplication mode or ETag format", body = ApiResponse<Object>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<Object>)
+    )
+)]
+async fn put_object(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<PutObjectQuery>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Json<ApiResponse<Object>> {
+    let data = body.to_vec();
+    let content_type = query.content_type.unwrap_or_else(|| "application/octet-stream".to_string());
+    let mut user_metadata = std::collections::HashMap::new();
+
+    // 解析 custom 参数（json字符串）
+    if let Some(custom_str) = &query.custom {
+        match serde_json::from_str::<HashMap<String, String>>(custom_str) {
+            Ok(map) => user_metadata.extend(map),
+            Err(e) => return Json(ApiResponse::error(format!("Invalid custom metadata: {}", e))),
+        }
+    }
+
+    // 客户提供密钥（SSE-C）的对象不参与去重
+    match encryption::parse_customer_key(&headers) {
+        Ok(Some(customer_key)) => {
+            return match state.object_service.put_object_with_customer_key(
+                &bucket_name,
+                &key,
+                data,
+                &content_type,
+                user_metadata,
+                &customer_key
+            ).await {
+                Ok(object) => Json(ApiResponse::success(object)),
+                Err(e) => Json(ApiResponse::error(e.to_string())),
+            };
+        }
+        Ok(None) => {}
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
+    }
+
+    // 如果指定了去重模式，使用去重上传
+    if let Some(dedup_mode) = query.deduplication_mode {
+        let deduplication_mode = match dedup_mode.to_lowercase().as_str() {
+            "reject" => DeduplicationMode::Reject,
+            "allow" => DeduplicationMode::Allow,
+            "reference" => DeduplicationMode::Reference,
+            _ => {
+                return Json(ApiResponse::error(format!(
+                    "Invalid deduplication mode: {}. Valid modes are: reject, allow, reference",
+                    dedup_mode
+                )));
+            }
+        };
+
+        match state.object_service.put_object_with_deduplication(
+            &bucket_name, 
+            &key, 
+            data, 
+            &content_type, 
+            user_metadata,
+            deduplication_mode
+        ).await {
+            Ok(object) => Json(ApiResponse::success(object)),
+            Err(e) => Json(ApiResponse::error(e.to_string())),
+        }
+    } else {
+        // 默认上传模式 - 使用 Allow 模式允许重复内容
+        match state.object_service.put_object_with_deduplication(
+            &bucket_name, 
+            &key, 
+            data, 
+            &content_type, 
+            user_metadata,
+            DeduplicationMode::Allow
+        ).await {
+            Ok(object) => Json(ApiResponse::success(object)),
+            Err(e) => Json(ApiResponse::error(e.to_string())),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{bucket_name}/objects/{key}",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("range" = Option<String>, Header, description = "Single byte range, e.g. bytes=0-1023"),
+        ("x-sevino-server-side-encryption-customer-algorithm" = Option<String>, Header, description = "SSE-C algorithm (AES256)"),
+        ("x-sevino-server-side-encryption-customer-key" = Option<String>, Header, description = "SSE-C key (base64, 256 bits)"),
+        ("x-sevino-server-side-encryption-customer-key-md5" = Option<String>, Header, description = "SSE-C key MD5 (base64)"),
+        ("width" = Option<u32>, Query, description = "Resize images to this width (max 4096)"),
+        ("height" = Option<u32>, Query, description = "Resize images to this height (max 4096)"),
+        ("fit" = Option<String>, Query, description = "contain (default), cover or fill when both width and height are given"),
+        ("format" = Option<String>, Query, description = "Output image format: png, jpeg or webp (default: same as source)"),
+        ("quality" = Option<u8>, Query, description = "JPEG quality 1-100 (default 80)")
+    ),
+    responses(
+        (status = 200, description = "Object data", body = Vec<u8>),
+        (status = 206, description = "Partial object data", body = Vec<u8>),
+        (status = 304, description = "Transformed image not modified (If-None-Match)"),
+        (status = 400, description = "Missing or invalid customer key, invalid transform parameters or image too large"),
+        (status = 403, description = "Customer key does not match"),
+        (status = 404, description = "Object not found"),
+        (status = 415, description = "Object is not a supported image"),
+        (status = 416, description = "Range not satisfiable")
+    )
+)]
+async fn get_object(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<ImageTransformQuery>,
+    headers: HeaderMap,
+) -> Result<axum::response::Response, StatusCode> {
+    let customer_key = match encryption::parse_customer_key(&headers) {
+        Ok(key) => key,
+        Err(e) => return Ok(customer_key_error_response(&e)),
+    };
+
+    let transform = match TransformParams::parse(query.width, query.height, query.fit.as_deref(), query.format.as_deref(), query.quality) {
+        Ok(transform) => transform,
+        Err(e) => return Ok(image_transform_error_response(e)),
+    };
+    if let Some(params) = transform {
+        return match state.image_transform_service.transform(&bucket_name, &key, &params, customer_key.as_ref()).await {
+            Ok(image) => {
+                let not_modified = headers.get("if-none-match").and_then(|v| v.to_str().ok())
+                    .is_some_and(|value| value.split(',').any(|tag| tag.trim() == image.etag || tag.trim() == "*"));
+                let response = axum::response::Response::builder()
+                    .header("ETag", &image.etag)
+                    .header("Content-Type", image.content_type);
+                if not_modified {
+                    return Ok(response.status(StatusCode::NOT_MODIFIED).body(axum::body::Body::empty()).unwrap());
+                }
+                Ok(response
+                    .header("Content-Length", image.data.len().to_string())
+                    .status(StatusCode::OK)
+                    .body(axum::body::Body::from(image.data))
+                    .unwrap())
+            }
+            Err(e) => Ok(image_transform_error_response(e)),
+        };
+    }
+
+    let range = match headers.get("range").and_then(|v| v.to_str().ok()) {
+        Some(range) => {
+            let metadata = state.object_service.get_object_metadata(&bucket_name, &key).await
+                .map_err(|_| StatusCode::NOT_FOUND)?;
+            match utils::parse_range_header(range, metadata.size) {
+                Ok(range) => range,
+                Err(_) => {
+                    let response = axum::response::Response::builder()
+                        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                        .header("Content-Range", format!("bytes */{}", metadata.size))
+                        .body(axum::body::Body::empty())
+                        .unwrap();
+                    return Ok(response);
+                }
+            }
+        }
+        None => None,
+    };
+
+    match state.object_service.get_object_range_with_customer_key(&bucket_name, &key, range, customer_key.as_ref()).await {
+        Ok((data, metadata)) => {
+            let mut response = axum::response::Response::builder()
+                .header("Content-Type", metadata.content_type)
+                .header("ETag", metadata.etag)
+                .header("Accept-Ranges", "bytes")
+                .header("Content-Length", data.len().to_string());
+            if let Some(customer_encryption) = &metadata.customer_encryption {
+                response = response.header(encryption::CUSTOMER_ALGORITHM_HEADER, customer_encryption.algorithm.as_str());
+            }
+            response = match range {
+                Some((start, end)) => response
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header("Content-Range", format!("bytes {}-{}/{}", start, end, metadata.size)),
+                None => response.status(StatusCode::OK),
+            };
+            Ok(response
+                .extension(ObjectBody { stored_compressed: metadata.compression.is_some() })
+                .body(axum::body::Body::from(data))
+                .unwrap())
+        }
+        Err(e) => {
+            if let Some(e) = e.downcast_ref::<RangeNotSatisfiable>() {
+                return Ok(axum::response::Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header("Content-Range", format!("bytes */{}", e.size))
+                    .body(axum::body::Body::empty())
+                    .unwrap());
+            }
+            match e.downcast_ref::<CustomerKeyError>() {
+                Some(e) => Ok(customer_key_error_response(e)),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        }
+    }
+}
+
+/// 图片变换错误响应：参数错误或超过像素上限返回400，不支持的图片返回415，读取对象失败按普通 GET 处理
+fn image_transform_error_response(error: ImageTransformError) -> Response {
+    let status = match &error {
+        ImageTransformError::InvalidParameter(_) | ImageTransformError::TooLarge(_) => StatusCode::BAD_REQUEST,
+        ImageTransformError::UnsupportedImage(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ImageTransformError::Object(e) => match e.downcast_ref::<CustomerKeyError>() {
+            Some(e) => return customer_key_error_response(e),
+            None => return StatusCode::NOT_FOUND.into_response(),
+        },
+    };
+    (status, Json(ApiResponse::<()>::error(error.to_string()))).into_response()
+}
+
+/// SSE-C 错误响应：密钥缺失或无效返回400，密钥不匹配返回403
+fn customer_key_error_response(error: &CustomerKeyError) -> Response {
+    let status = match error {
+        CustomerKeyError::Mismatch => StatusCode::FORBIDDEN,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(ApiResponse::<()>::error(error.to_string()))).into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{bucket_name}/objects/{key}",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("force" = Option<bool>, Query, description = "Also delete all reference objects pointing to this object"),
+        ("x-sevino-bypass-governance-retention" = Option<String>, Header, description = "Set to true to bypass governance retention (requires x-sevino-admin-token)")
+    ),
+    responses(
+        (status = 200, description = "Object deleted successfully", body = ApiResponse<()>),
+        (status = 404, description = "Object not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_object(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<DeleteObjectQuery>,
+    headers: HeaderMap,
+) -> Json<ApiResponse<()>> {
+    let bypass_governance = match governance_bypass(&state, &headers) {
+        Ok(bypass) => bypass,
+        Err(e) => return Json(ApiResponse::error(e)),
+    };
+
+    let result = if query.force {
+        state.object_service.force_delete_object_with_references(&bucket_name, &key, bypass_governance).await
+    } else {
+        state.object_service.delete_object_with_governance_bypass(&bucket_name, &key, bypass_governance).await
+    };
+
+    match result {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/delete",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name"),
+        ("x-sevino-bypass-governance-retention" = Option<String>, Header, description = "Set to true to bypass governance retention (requires x-sevino-admin-token)")
+    ),
+    request_body(content = BatchDeleteRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Per-object delete results", body = ApiResponse<BatchDeleteResult>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<BatchDeleteResult>)
+    )
+)]
+async fn delete_objects(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    Json(request): Json<BatchDeleteRequest>,
+) -> Json<ApiResponse<BatchDeleteResult>> {
+    let bypass_governance = match governance_bypass(&state, &headers) {
+        Ok(bypass) => bypass,
+        Err(e) => return Json(ApiResponse::error(e)),
+    };
+    if request.objects.len() > MAX_BATCH_DELETE_OBJECTS {
+        return Json(ApiResponse::error(format!("At most {} objects can be deleted in one request", MAX_BATCH_DELETE_OBJECTS)));
+    }
+
+    // 授权中间件只检查了桶级别的权限，这里逐个检查对象的删除权限
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    let mut allowed = Vec::with_capacity(request.objects.len());
+    let mut denied = Vec::new();
+    for entry in request.objects {
+        match authorize_additional(&state, &identity, "sevino:DeleteObject", &name, &entry.key, addr, secure_transport).await {
+            Ok(()) => allowed.push(entry),
+            Err((_, message)) => denied.push(DeleteObjectError { key: entry.key, version_id: entry.version_id, message }),
+        }
+    }
+
+    match state.object_service.delete_objects(&name, allowed, bypass_governance).await {
+        Ok(mut result) => {
+            result.errors.extend(denied);
+            if request.quiet {
+                result.deleted.clear();
+            }
+            Json(ApiResponse::success(result))
+        }
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/delete-jobs",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name"),
+        ("x-sevino-bypass-governance-retention" = Option<String>, Header, description = "Set to true to bypass governance retention (requires x-sevino-admin-token)")
+    ),
+    request_body(content = DeletePrefixRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Background delete job started", body = ApiResponse<DeleteJob>),
+        (status = 403, description = "Not allowed to delete objects under the prefix", body = ApiResponse<DeleteJob>)
+    )
+)]
+async fn start_delete_job(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    Json(request): Json<DeletePrefixRequest>,
+) -> (StatusCode, Json<ApiResponse<DeleteJob>>) {
+    let bypass_governance = match governance_bypass(&state, &headers) {
+        Ok(bypass) => bypass,
+        Err(e) => return (StatusCode::OK, Json(ApiResponse::error(e))),
+    };
+
+    // 前缀本身作为资源检查删除权限，任务执行时再逐个检查对象的删除权限
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &identity, "sevino:DeleteObject", &name, &request.prefix, addr, secure_transport).await {
+        return (status, Json(ApiResponse::error(message)));
+    }
+
+    let requester = Requester { identity, source_ip: Some(addr.ip()), secure_transport };
+    match state.delete_job_service.start(&name, &request.prefix, requester, bypass_governance).await {
+        Ok(job) => (StatusCode::OK, Json(ApiResponse::success(job))),
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/delete-jobs",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    responses(
+        (status = 200, description = "Delete jobs of the bucket, newest first", body = ApiResponse<Vec<DeleteJob>>)
+    )
+)]
+async fn list_delete_jobs(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<Vec<DeleteJob>>> {
+    Json(ApiResponse::success(state.delete_job_service.list(&name).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/delete-jobs/{job_id}",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name"),
+        ("job_id" = String, Path, description = "Delete job ID")
+    ),
+    responses(
+        (status = 200, description = "Delete job progress", body = ApiResponse<DeleteJob>),
+        (status = 404, description = "Delete job not found", body = ApiResponse<DeleteJob>)
+    )
+)]
+async fn get_delete_job(
+    State(state): State<Arc<AppState>>,
+    Path((name, job_id)): Path<(String, String)>,
+) -> Json<ApiResponse<DeleteJob>> {
+    match state.delete_job_service.get(&name, &job_id).await {
+        Some(job) => Json(ApiResponse::success(job)),
+        None => Json(ApiResponse::error(format!("Delete job '{}' not found", job_id))),
+    }
+}
+
+#[derive(Deserialize)]
+struct ImageTransformQuery {
+    /// 目标宽度（像素，最大4096）
+    #[serde(default)]
+    width: Option<u32>,
+    /// 目标高度（像素，最大4096）
+    #[serde(default)]
+    height: Option<u32>,
+    /// 同时指定宽高时的缩放方式：contain（默认）、cover 或 fill
+    #[serde(default)]
+    fit: Option<String>,
+    /// 输出格式：png、jpeg 或 webp（默认与源图相同）
+    #[serde(default)]
+    format: Option<String>,
+    /// JPEG 质量 1-100（默认80）
+    #[serde(default)]
+    quality: Option<u8>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct ArchiveQuery {
+    /// 解包的目标前缀或打包的对象前缀
+    #[serde(default)]
+    prefix: Option<String>,
+    /// zip、tar 或 tar.gz（解包时默认按文件头识别，下载时默认 zip）
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/archive",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name"),
+        ("prefix" = Option<String>, Query, description = "Prefix the archive entries are written under"),
+        ("format" = Option<String>, Query, description = "zip, tar or tar.gz; detected from the content when omitted")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Archive extracted into objects", body = ApiResponse<ArchiveExtractReport>),
+        (status = 403, description = "Not allowed to write objects under the prefix", body = ApiResponse<ArchiveExtractReport>)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+async fn extract_archive(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<ArchiveQuery>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> (StatusCode, Json<ApiResponse<ArchiveExtractReport>>) {
+    let prefix = query.prefix.unwrap_or_default();
+    let format = match query.format.as_deref().map(ArchiveFormat::parse).transpose() {
+        Ok(format) => format,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e.to_string()))),
+    };
+
+    // 前缀本身作为资源检查写权限，解包时再逐个检查条目的写权限
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &identity, "sevino:PutObject", &name, &prefix, addr, secure_transport).await {
+        return (status, Json(ApiResponse::error(message)));
+    }
+
+    let requester = Requester { identity, source_ip: Some(addr.ip()), secure_transport };
+    match state.archive_service.extract(&name, &prefix, format, body, &requester).await {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::success(report))),
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{name}/archive",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name"),
+        ("prefix" = Option<String>, Query, description = "Prefix of the objects to archive; entry names are relative to it"),
+        ("format" = Option<String>, Query, description = "zip (default), tar or tar.gz")
+    ),
+    responses(
+        (status = 200, description = "Archive stream", body = Vec<u8>),
+        (status = 400, description = "Invalid format or prefix too large"),
+        (status = 403, description = "Not allowed to read objects under the prefix")
+    )
+)]
+async fn download_archive(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<ArchiveQuery>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+) -> Response {
+    let prefix = query.prefix.unwrap_or_default();
+    let format = match ArchiveFormat::parse(query.format.as_deref().unwrap_or("zip")) {
+        Ok(format) => format,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    };
+
+    // 授权中间件检查了列举权限，这里再以前缀为资源检查读权限；无权读取的对象不包含在归档中
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &identity, "sevino:GetObject", &name, &prefix, addr, secure_transport).await {
+        return (status, Json(ApiResponse::<()>::error(message))).into_response();
+    }
+
+    let requester = Requester { identity, source_ip: Some(addr.ip()), secure_transport };
+    let records = match state.archive_service.list_prefix(&name, &prefix, format, &requester).await {
+        Ok(records) => records,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    };
+
+    let base = prefix.trim_matches('/').replace('/', "-");
+    let filename = if base.is_empty() { format!("{}.{}", name, format.name()) } else { format!("{}-{}.{}", name, base, format.name()) };
+    axum::response::Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", format.content_type())
+        .header("Content-Disposition", ShareLinkService::content_disposition(&filename))
+        .body(axum::body::Body::from_stream(state.archive_service.stream(&name, &prefix, format, records)))
+        .unwrap()
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct DeleteObjectQuery {
+    /// 同时删除所有引用该对象的引用对象
+    #[serde(default)]
+    force: bool,
+}
+
+/// 解析治理模式绕过请求：需要同时携带绕过请求头和有效的管理员令牌
+fn governance_bypass(state: &AppState, headers: &HeaderMap) -> std::result::Result<bool, String> {
+    let requested = headers
+        .get("x-sevino-bypass-governance-retention")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if !requested {
+        return Ok(false);
+    }
+
+    let token = headers.get("x-sevino-admin-token").and_then(|v| v.to_str().ok());
+    match (&state.admin_token, token) {
+        (Some(expected), Some(token)) if utils::constant_time_eq(expected, token) => Ok(true),
+        _ => Err("Governance bypass requires a valid admin token".to_string()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{bucket_name}/objects/{key}/versions",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key")
+    ),
+    responses(
+        (status = 200, description = "List of object versions", body = ApiResponse<Vec<ObjectMetadata>>),
+        (status = 404, description = "Object not found", body = ApiResponse<Vec<ObjectMetadata>>)
+    )
+)]
+async fn list_object_versions(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+) -> Json<ApiResponse<Vec<ObjectMetadata>>> {
+    match state.object_service.list_object_versions(&bucket_name, &key).await {
+        Ok(versions) => Json(ApiResponse::success(versions)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{bucket_name}/objects/{key}/share",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key")
+    ),
+    request_body(content = CreateShareLinkRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Share link created", body = ApiResponse<ShareLink>),
+        (status = 403, description = "Anonymous requests cannot create share links", body = ApiResponse<ShareLink>),
+        (status = 404, description = "Object not found", body = ApiResponse<ShareLink>)
+    )
+)]
+async fn create_share_link(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Extension(identity): Extension<Identity>,
+    headers: HeaderMap,
+    Json(request): Json<CreateShareLinkRequest>,
+) -> (StatusCode, Json<ApiResponse<ShareLink>>) {
+    // 链接只能由创建者查询和撤销，匿名请求之间无法区分创建者
+    if identity.principal == ANONYMOUS_PRINCIPAL {
+        return (StatusCode::FORBIDDEN, Json(ApiResponse::error("Share links must be created with an access key".to_string())));
+    }
+
+    match state.share_link_service.create(&bucket_name, &key, request, &identity.principal).await {
+        Ok(link) => (StatusCode::OK, Json(ApiResponse::success(with_absolute_url(link, forwarded_https(&state, addr, &headers), &headers)))),
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+/// 查询和撤销分享链接：必须是创建者或管理员，并且按链接所属桶的策略授权
+async fn authorize_share_link(
+    state: &AppState,
+    identity: &Identity,
+    action: &str,
+    id: &str,
+    addr: SocketAddr,
+    uri: &axum::http::Uri,
+    headers: &HeaderMap,
+) -> std::result::Result<ShareLink, (StatusCode, String)> {
+    let link = state.share_link_service.get(id).map_err(|e| (share_link_status(&e), e.to_string()))?;
+    if !identity.is_admin && (identity.principal == ANONYMOUS_PRINCIPAL || identity.principal != link.created_by) {
+        return Err((StatusCode::FORBIDDEN, "Only the creator can manage this share link".to_string()));
+    }
+    let secure_transport = is_secure_transport(state, addr, uri, headers);
+    authorize_additional(state, identity, action, &link.bucket_name, &link.key, addr, secure_transport).await?;
+    Ok(link)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/share-links/{id}",
+    tag = "objects",
+    params(
+        ("id" = String, Path, description = "Share link ID")
+    ),
+    responses(
+        (status = 200, description = "Share link", body = ApiResponse<ShareLink>),
+        (status = 403, description = "Not the creator of the share link or denied by the bucket policy", body = ApiResponse<ShareLink>),
+        (status = 404, description = "Share link not found", body = ApiResponse<ShareLink>)
+    )
+)]
+async fn get_share_link(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<ShareLink>>) {
+    let link = match authorize_share_link(&state, &identity, "sevino:GetShareLink", &id, addr, &uri, &headers).await {
+        Ok(link) => link,
+        Err((status, message)) => return (status, Json(ApiResponse::error(message))),
+    };
+
+    (StatusCode::OK, Json(ApiResponse::success(with_absolute_url(link, forwarded_https(&state, addr, &headers), &headers))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/share-links/{id}",
+    tag = "objects",
+    params(
+        ("id" = String, Path, description = "Share link ID")
+    ),
+    responses(
+        (status = 200, description = "Share link revoked", body = ApiResponse<ShareLink>),
+        (status = 403, description = "Not the creator of the share link or denied by the bucket policy", body = ApiResponse<ShareLink>),
+        (status = 404, description = "Share link not found", body = ApiResponse<ShareLink>)
+    )
+)]
+async fn revoke_share_link(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<ShareLink>>) {
+    if let Err((status, message)) = authorize_share_link(&state, &identity, "sevino:RevokeShareLink", &id, addr, &uri, &headers).await {
+        return (status, Json(ApiResponse::error(message)));
+    }
+
+    match state.share_link_service.revoke(&id).await {
+        Ok(link) => (StatusCode::OK, Json(ApiResponse::success(with_absolute_url(link, forwarded_https(&state, addr, &headers), &headers)))),
+        Err(e) => (share_link_status(&e), Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct ShareDownloadQuery {
+    /// 过期时间（Unix时间戳）
+    expires: i64,
+    /// HMAC-SHA256签名（十六进制）
+    signature: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/share/{id}",
+    tag = "objects",
+    params(
+        ("id" = String, Path, description = "Share link ID"),
+        ("expires" = i64, Query, description = "Expiry timestamp"),
+        ("signature" = String, Query, description = "Link signature")
+    ),
+    responses(
+        (status = 200, description = "Object content", content_type = "application/octet-stream"),
+        (status = 403, description = "Invalid signature"),
+        (status = 404, description = "Share link or object not found"),
+        (status = 410, description = "Share link expired, revoked or exhausted")
+    )
+)]
+async fn download_share_link(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ShareDownloadQuery>,
+) -> Response {
+    let link = match state.share_link_service.redeem(&id, query.expires, &query.signature).await {
+        Ok(link) => link,
+        Err(e) => return (share_link_status(&e), Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    };
+
+    // 读取成功后才计入下载次数
+    let (data, metadata) = match state.share_link_service.fetch(&link).await {
+        Ok(object) => object,
+        Err(e) => return (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    };
+    if let Err(e) = state.share_link_service.record_download(&link.id).await {
+        return (share_link_status(&e), Json(ApiResponse::<()>::error(e.to_string()))).into_response();
+    }
+
+    let mut response = axum::response::Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", metadata.content_type)
+        .header("ETag", metadata.etag)
+        .header("Content-Length", metadata.size.to_string());
+    if let Some(filename) = &link.filename {
+        response = response.header("Content-Disposition", ShareLinkService::content_disposition(filename));
+    }
+    response
+        .extension(ObjectBody { stored_compressed: metadata.compression.is_some() })
+        .body(axum::body::Body::from(data))
+        .unwrap()
+}
+
+fn share_link_status(error: &ShareLinkError) -> StatusCode {
+    match error {
+        ShareLinkError::NotFound => StatusCode::NOT_FOUND,
+        ShareLinkError::InvalidSignature => StatusCode::FORBIDDEN,
+        ShareLinkError::Expired | ShareLinkError::Revoked | ShareLinkError::DownloadLimitReached => StatusCode::GONE,
+        ShareLinkError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}/upload-policy",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = CreateUploadPolicyRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Signed upload policy", body = ApiResponse<UploadPolicy>),
+        (status = 403, description = "Not allowed to upload under the key prefix", body = ApiResponse<UploadPolicy>)
+    )
+)]
+async fn create_upload_policy(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    Json(request): Json<CreateUploadPolicyRequest>,
+) -> (StatusCode, Json<ApiResponse<UploadPolicy>>) {
+    if !state.storage_service.bucket_exists(&name).await {
+        return (StatusCode::NOT_FOUND, Json(ApiResponse::error(format!("Bucket '{}' not found", name))));
+    }
+
+    // 签发者必须能写入策略允许的前缀
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &identity, "sevino:PutObject", &name, &request.key_prefix, addr, secure_transport).await {
+        return (status, Json(ApiResponse::error(message)));
+    }
+
+    match state.form_upload_service.create_policy(&name, request, &identity.principal) {
+        Ok(policy) => (StatusCode::OK, Json(ApiResponse::success(policy))),
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{name}",
+    tag = "objects",
+    params(
+        ("name" = String, Path, description = "Bucket name")
+    ),
+    request_body(content = String, content_type = "multipart/form-data", description = "Fields key, policy, signature, optional Content-Type, success_action_redirect, success_action_status and x-sevino-meta-*, followed by the file field"),
+    responses(
+        (status = 200, description = "Object uploaded", body = ApiResponse<Object>),
+        (status = 201, description = "Object uploaded (success_action_status=201)", body = ApiResponse<Object>),
+        (status = 204, description = "Object uploaded (success_action_status=204)"),
+        (status = 303, description = "Redirect to success_action_redirect"),
+        (status = 400, description = "Malformed form or file too large", body = ApiResponse<Object>),
+        (status = 403, description = "Invalid signature, expired policy or policy violation", body = ApiResponse<Object>)
+    )
+)]
+async fn post_object(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    body: axum::body::Body,
+) -> Response {
+    // multipart 解析是同步的，在阻塞线程中边接收边解析请求体
+    let content_type = headers.get("content-type").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+    let reader = SyncIoBridge::new(StreamReader::new(body.into_data_stream().map_err(std::io::Error::other)));
+    let service = state.form_upload_service.clone();
+    let bucket_name = name.clone();
+    let parsed = tokio::task::spawn_blocking(move || service.parse(&bucket_name, &content_type, reader)).await
+        .unwrap_or_else(|e| Err(FormUploadError::Internal(anyhow::anyhow!("Form upload parser failed: {}", e))));
+    let upload = match parsed {
+        Ok(upload) => upload,
+        Err(e) => return (form_upload_status(&e), Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    };
+
+    // 上传以签发者的身份授权，签发后被收回的权限同样生效
+    let issuer = Identity {
+        principal: upload.document.created_by.clone(),
+        is_admin: upload.document.created_by == ADMIN_PRINCIPAL,
+    };
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &issuer, "sevino:PutObject", &name, &upload.key, addr, secure_transport).await {
+        return (status, Json(ApiResponse::<()>::error(message))).into_response();
+    }
+
+    match state.form_upload_service.store(&name, upload).await {
+        Ok((object, FormUploadOutcome::Redirect(redirect))) => {
+            (StatusCode::SEE_OTHER, [("Location", form_upload::redirect_location(&redirect, &object))]).into_response()
+        }
+        Ok((_, FormUploadOutcome::Status(204))) => StatusCode::NO_CONTENT.into_response(),
+        Ok((object, FormUploadOutcome::Status(status))) => {
+            (StatusCode::from_u16(status).unwrap_or(StatusCode::OK), Json(ApiResponse::success(object))).into_response()
+        }
+        Err(e) => (form_upload_status(&e), Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    }
+}
+
+fn form_upload_status(error: &FormUploadError) -> StatusCode {
+    match error {
+        FormUploadError::Malformed(_) | FormUploadError::TooLarge(_) => StatusCode::BAD_REQUEST,
+        FormUploadError::InvalidSignature | FormUploadError::Expired | FormUploadError::PolicyViolation(_) => StatusCode::FORBIDDEN,
+        // 写入失败沿用其他上传接口的约定
+        FormUploadError::Internal(_) => StatusCode::OK,
+    }
+}
+
+/// 根据请求的 Host 头把分享链接转换为完整URL（受信任代理声明 HTTPS 时使用 https）
+fn with_absolute_url(mut link: ShareLink, secure: bool, headers: &HeaderMap) -> ShareLink {
+    if let Some(host) = headers.get("host").and_then(|v| v.to_str().ok()) {
+        let scheme = if secure { "https" } else { "http" };
+        link.url = format!("{}://{}{}", scheme, host, link.url);
+    }
+    link
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{bucket_name}/objects/{key}/duplicate-test",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Duplicate handling test result", body = ApiResponse<String>),
+        (status = 404, description = "Object not found", body = ApiResponse<String>)
+    )
+)]
+async fn test_duplicate_handling(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    body: axum::body::Bytes,
+) -> Json<ApiResponse<String>> {
+    let data = body.to_vec();
+    let content_type = "application/octet-stream";
+    let user_metadata = std::collections::HashMap::new();
+
+    match state.object_service.test_duplicate_handling(&bucket_name, &key, data, content_type, user_metadata).await {
+        Ok(result) => Json(ApiResponse::success(result)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+struct BucketListResponse {
+    buckets: Vec<Bucket>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+struct ObjectListResponse {
+    objects: Vec<Object>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+struct ApiResponse<T> {
+    success: bool,
+    data: Option<T>,
+    error: Option<String>,
+}
+
+impl<T> ApiResponse<T> {
+    fn success(data: T) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(message),
+        }
+    }
+}
+
+/// 测试Reference模式的工作原理
+async fn test_reference_mode() -> Result<String> {
+    let storage = StorageService::new("./data".to_string()).await?;
+    let object_service = ObjectService::new(storage.clone());
+    let bucket_service = BucketService::new(storage);
+    
+    let bucket_name = "test-reference-bucket-v2";
+    let test_data = b"Hello, this is test content for decentralized reference mode!".to_vec();
+    let content_type = "text/plain";
+    let mut user_metadata = HashMap::new();
+    user_metadata.insert("test".to_string(), "reference".to_string());
+    
+    let mut result = String::new();
+    result.push_str("=== 去中心化Reference模式测试 ===\n\n");
+    
+    // 1. 创建桶
+    result.push_str("1. 创建测试桶\n");
+    match bucket_service.create_bucket(bucket_name.to_string()).await {
+        Ok(_) => result.push_str("   ✓ 桶创建成功\n\n"),
+        Err(e) => result.push_str(&format!("   ✗ 桶创建失败: {}\n\n", e)),
+    }
+    
+    // 2. 上传第一个文件
+    result.push_str("2. 上传第一个文件 (key: file1.txt)\n");
+    match object_service.put_object(bucket_name, "file1.txt", test_data.clone(), content_type, user_metadata.clone()).await {
+        Ok(obj) => {
+            result.push_str("   ✓ 文件上传成功\n");
+            result.push_str(&format!("   - ETag: {}\n", obj.etag));
+            result.push_str(&format!("   - 大小: {} bytes\n", obj.size));
+            result.push_str(&format!("   - 对象ID: {}\n\n", StorageService::generate_object_id(bucket_name, "file1.txt")));
+        },
+        Err(e) => result.push_str(&format!("   ✗ 文件上传失败: {}\n\n", e)),
+    }
+    
+    // 3. 使用Reference模式上传相同内容的不同key
+    result.push_str("3. 使用Reference模式上传相同内容 (key: file2.txt)\n");
+    match object_service.put_object_with_deduplication(
+        bucket_name, 
+        "file2.txt", 
+        test_data.clone(), 
+        content_type, 
+        user_metadata.clone(),
+        DeduplicationMode::Reference
+    ).await {
+        Ok(obj) => {
+            result.push_str("   ✓ 引用创建成功\n");
+            result.push_str(&format!("   - ETag: {}\n", obj.etag));
+            result.push_str(&format!("   - 大小: {} bytes\n", obj.size));
+            result.push_str(&format!("   - 对象ID: {}\n", StorageService::generate_object_id(bucket_name, "file2.txt")));
+            
+            // 检查元数据
+            if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file2.txt").await {
+                result.push_str(&format!("   - 数据持有者ID: {:?}\n", metadata.data_holder_id));
+                result.push_str(&format!("   - 引用计数: {}\n", metadata.reference_count));
+            }
+            result.push('\n');
+        },
+        Err(e) => result.push_str(&format!("   ✗ 引用创建失败: {}\n\n", e)),
+    }
+    
+    // 4. 检查数据持有者的引用计数
+    result.push_str("4. 检查数据持有者的引用计数\n");
+    if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file1.txt").await {
+        result.push_str(&format!("   file1.txt 引用计数: {}\n", metadata.reference_count));
+        result.push_str(&format!("   file1.txt 数据持有者ID: {:?}\n", metadata.data_holder_id));
+    }
+    result.push('\n');
+    
+    // 5. 读取两个文件并比较
+    result.push_str("5. 读取并比较两个文件\n");
+    match object_service.get_object(bucket_name, "file1.txt").await {
+        Ok((data1, metadata1)) => {
+            result.push_str(&format!("   file1.txt 读取成功，大小: {} bytes\n", data1.len()));
+            
+            match object_service.get_object(bucket_name, "file2.txt").await {
+                Ok((data2, metadata2)) => {
+                    result.push_str(&format!("   file2.txt 读取成功，大小: {} bytes\n", data2.len()));
+                    result.push_str(&format!("   数据相同: {}\n", data1 == data2));
+                    result.push_str(&format!("   ETag相同: {}\n", metadata1.etag == metadata2.etag));
+                    result.push_str(&format!("   file1数据持有者ID: {:?}\n", metadata1.data_holder_id));
+                    result.push_str(&format!("   file2数据持有者ID: {:?}\n", metadata2.data_holder_id));
+                },
+                Err(e) => result.push_str(&format!("   file2.txt 读取失败: {}\n", e)),
+            }
+        },
+        Err(e) => result.push_str(&format!("   file1.txt 读取失败: {}\n", e)),
+    }
+    result.push('\n');
+    
+    // 6. 测试删除引用对象
+    result.push_str("6. 测试删除引用对象\n");
+    match object_service.delete_object(bucket_name, "file2.txt").await {
+        Ok(_) => {
+            result.push_str("   ✓ 引用对象删除成功\n");
+            
+            // 检查数据持有者的引用计数是否减少
+            if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file1.txt").await {
+                result.push_str(&format!("   file1.txt 引用计数: {}\n", metadata.reference_count));
+            }
+        },
+        Err(e) => result.push_str(&format!("   ✗ 引用对象删除失败: {}\n", e)),
+    }
+    result.push('\n');
+    
+    // 7. 测试删除数据持有者（应该成功，因为没有引用了）
+    result.push_str("7. 测试删除数据持有者（应该成功）\n");
+    match object_service.delete_object(bucket_name, "file1.txt").await {
+        Ok(_) => result.push_str("   ✓ 数据持有者删除成功\n"),
+        Err(e) => result.push_str(&format!("   ✗ 数据持有者删除失败: {}\n", e)),
+    }
+    result.push('\n');
+    
+    // 8. 测试多个对象的引用关系
+    result.push_str("8. 测试多个对象的引用关系\n");
+    match object_service.put_object(bucket_name, "file3.txt", test_data.clone(), content_type, user_metadata.clone()).await {
+        Ok(_) => {
+            result.push_str("   ✓ file3.txt 上传成功\n");
+            
+            // 创建多个引用
+            for i in 4..=6 {
+                let key = format!("file{}.txt", i);
+                match object_service.put_object_with_deduplication(
+                    bucket_name, 
+                    &key, 
+                    test_data.clone(), 
+                    content_type, 
+                    user_metadata.clone(),
+                    DeduplicationMode::Reference
+                ).await {
+                    Ok(_) => result.push_str(&format!("   ✓ {} 引用创建成功\n", key)),
+                    Err(e) => result.push_str(&format!("   ✗ {} 引用创建失败: {}\n", key, e)),
+                }
+            }
+            
+            // 检查引用计数
+            if let Ok(metadata) = object_service.get_object_metadata(bucket_name, "file3.txt").await {
+                result.push_str(&format!("   file3.txt 引用计数: {}\n", metadata.reference_count));
+            }
+        },
+        Err(e) => result.push_str(&format!("   ✗ file3.txt 上传失败: {}\n", e)),
+    }
+    result.push('\n');
+    
+    // 9. 验证所有对象都可以正常读取
+    result.push_str("9. 验证所有对象都可以正常读取\n");
+    for i in 3..=6 {
+        let key = format!("file{}.txt", i);
+        match object_service.get_object(bucket_name, &key).await {
+            Ok((data, _)) => result.push_str(&format!("   ✓ {} 读取成功，大小: {} bytes\n", key, data.len())),
+            Err(e) => result.push_str(&format!("   ✗ {} 读取失败: {}\n", key, e)),
+        }
+    }
+    
+    Ok(result)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/test/reference-mode",
+    tag = "test",
+    responses(
+        (status = 200, description = "Reference mode test results", body = ApiResponse<String>)
+    )
+)]
+async fn test_reference_mode_api() -> Json<ApiResponse<String>> {
+    match test_reference_mode().await {
+        Ok(result) => Json(ApiResponse::success(result)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+/// 检查请求路径之外的对象的权限（授权中间件只检查路径中的资源），管理员总是允许
+async fn authorize_additional(
+    state: &AppState,
+    identity: &Identity,
+    action: &str,
+    bucket_name: &str,
+    key: &str,
+    addr: SocketAddr,
+    secure_transport: bool,
+) -> std::result::Result<(), (StatusCode, String)> {
+    let requester = Requester { identity: identity.clone(), source_ip: Some(addr.ip()), secure_transport };
+    match state.policy_service.authorize_key(&requester, action, bucket_name, key).await {
+        Ok(None) => Ok(()),
+        Ok(Some(message)) => Err((StatusCode::FORBIDDEN, message)),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}/copy",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Destination bucket name"),
+        ("key" = String, Path, description = "Destination object key"),
+        ("x-sevino-copy-source-if-match" = Option<String>, Header, description = "Copy only if the source ETag matches"),
+        ("x-sevino-copy-source-if-none-match" = Option<String>, Header, description = "Copy only if the source ETag does not match")
+    ),
+    request_body(content = CopyObjectRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Object copied", body = ApiResponse<Object>),
+        (status = 403, description = "Not allowed to read the source object", body = ApiResponse<Object>),
+        (status = 404, description = "Source object or bucket not found", body = ApiResponse<Object>),
+        (status = 412, description = "Source ETag condition not met", body = ApiResponse<Object>)
+    )
+)]
+async fn copy_object(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    Json(request): Json<CopyObjectRequest>,
+) -> (StatusCode, Json<ApiResponse<Object>>) {
+    // 授权中间件只检查了目标对象的写权限，这里再按源桶的策略检查读权限
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &identity, "sevino:GetObject", &request.source_bucket, &request.source_key, addr, secure_transport).await {
+        return (status, Json(ApiResponse::error(message)));
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let if_match = header("x-sevino-copy-source-if-match");
+    let if_none_match = header("x-sevino-copy-source-if-none-match");
+    match state.object_service.copy_object(&bucket_name, &key, request, if_match, if_none_match).await {
+        Ok(object) => (StatusCode::OK, Json(ApiResponse::success(object))),
+        Err(e) if e.downcast_ref::<PreconditionFailed>().is_some() => {
+            (StatusCode::PRECONDITION_FAILED, Json(ApiResponse::error(e.to_string())))
+        }
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/buckets/{bucket_name}/objects/{key}/rename",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key to rename")
+    ),
+    request_body(content = RenameObjectRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Object and its versions renamed", body = ApiResponse<RenameReport>),
+        (status = 403, description = "Not allowed to write the destination key", body = ApiResponse<RenameReport>),
+        (status = 404, description = "Object or bucket not found", body = ApiResponse<RenameReport>)
+    )
+)]
+async fn rename_object(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(identity): Extension<Identity>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    Json(request): Json<RenameObjectRequest>,
+) -> (StatusCode, Json<ApiResponse<RenameReport>>) {
+    // 授权中间件只检查了源对象键，这里再检查目标对象键
+    let secure_transport = is_secure_transport(&state, addr, &uri, &headers);
+    if let Err((status, message)) = authorize_additional(&state, &identity, "sevino:RenameObject", &bucket_name, &request.destination_key, addr, secure_transport).await {
+        return (status, Json(ApiResponse::error(message)));
+    }
+
+    match state.object_service.rename_object(&bucket_name, &key, &request.destination_key).await {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::success(report))),
+        Err(e) => (StatusCode::OK, Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}/multipart",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("part_number" = u32, Query, description = "分片编号，从1开始"),
+        ("total_parts" = u32, Query, description = "总分片数"),
+        ("upload_id" = String, Query, description = "上传ID"),
+        ("content_type" = Option<String>, Query, description = "内容类型")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Multipart upload part uploaded successfully", body = ApiResponse<Object>),
+        (status = 400, description = "Invalid multipart upload request", body = ApiResponse<Object>),
+        (status = 404, description = "Bucket not found", body = ApiResponse<Object>)
+    )
+)]
+async fn put_object_multipart(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<MultipartUploadQuery>,
+    body: axum::body::Bytes,
+) -> Json<ApiResponse<Object>> {
+    let data = body.to_vec();
+    let content_type = query.content_type.clone().unwrap_or_else(|| "application/octet-stream".to_string());
+    let mut user_metadata = std::collections::HashMap::new();
+    user_metadata.insert("multipart_upload_id".to_string(), query.upload_id.clone());
+    user_metadata.insert("part_number".to_string(), query.part_number.to_string());
+    user_metadata.insert("total_parts".to_string(), query.total_parts.to_string());
+    let part_key = format!("{}.part.{}", key, query.part_number);
+    match state.object_service.put_object(&bucket_name, &part_key, data, &content_type, user_metadata).await {
+        Ok(object) => Json(ApiResponse::success(object)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}/metadata",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key")
+    ),
+    request_body(content = UpdateObjectMetadataRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Object metadata updated successfully", body = ApiResponse<Object>),
+        (status = 400, description = "Invalid ETag format", body = ApiResponse<Object>),
+        (status = 404, description = "Object not found", body = ApiResponse<Object>)
+    )
+)]
+async fn update_object_metadata(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateObjectMetadataRequest>,
+) -> Json<ApiResponse<Object>> {
+    let bypass_governance = match governance_bypass(&state, &headers) {
+        Ok(bypass) => bypass,
+        Err(e) => return Json(ApiResponse::error(e)),
+    };
+
+    match state.object_service.update_object_metadata_with_governance_bypass(
+        &bucket_name,
+        &key,
+        request.content_type,
+        request.user_metadata,
+        request.custom_etag,
+        bypass_governance,
+    ).await {
+        Ok(object) => Json(ApiResponse::success(object)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}/retention",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("x-sevino-bypass-governance-retention" = Option<String>, Header, description = "Set to true to shorten governance retention (requires x-sevino-admin-token)")
+    ),
+    request_body(content = ObjectRetention, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Object retention updated", body = ApiResponse<ObjectMetadata>),
+        (status = 400, description = "Object lock not enabled or retention cannot be shortened", body = ApiResponse<ObjectMetadata>),
+        (status = 404, description = "Object not found", body = ApiResponse<ObjectMetadata>)
+    )
+)]
+async fn put_object_retention(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(retention): Json<ObjectRetention>,
+) -> Json<ApiResponse<ObjectMetadata>> {
+    let bypass_governance = match governance_bypass(&state, &headers) {
+        Ok(bypass) => bypass,
+        Err(e) => return Json(ApiResponse::error(e)),
+    };
+
+    match state.object_service.put_object_retention(&bucket_name, &key, retention, bypass_governance).await {
+        Ok(metadata) => Json(ApiResponse::success(metadata)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+struct ObjectTaggingQuery {
+    /// 版本ID（为空时使用当前版本）
+    #[serde(default)]
+    version_id: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/buckets/{bucket_name}/objects/{key}/tagging",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("version_id" = Option<String>, Query, description = "Version ID (defaults to the current version)")
+    ),
+    responses(
+        (status = 200, description = "Object tag set", body = ApiResponse<ObjectTagging>),
+        (status = 404, description = "Object not found", body = ApiResponse<ObjectTagging>)
+    )
+)]
+async fn get_object_tagging(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<ObjectTaggingQuery>,
+) -> Json<ApiResponse<ObjectTagging>> {
+    match state.object_service.get_object_tagging(&bucket_name, &key, query.version_id.as_deref()).await {
+        Ok(tags) => Json(ApiResponse::success(ObjectTagging { tags })),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}/tagging",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("version_id" = Option<String>, Query, description = "Version ID (defaults to the current version)")
+    ),
+    request_body(content = ObjectTagging, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Object tag set replaced", body = ApiResponse<ObjectTagging>),
+        (status = 400, description = "Invalid tags", body = ApiResponse<ObjectTagging>),
+        (status = 404, description = "Object not found", body = ApiResponse<ObjectTagging>)
+    )
+)]
+async fn put_object_tagging(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<ObjectTaggingQuery>,
+    Json(tagging): Json<ObjectTagging>,
+) -> Json<ApiResponse<ObjectTagging>> {
+    match state.object_service.put_object_tagging(&bucket_name, &key, query.version_id.as_deref(), tagging.tags).await {
+        Ok(tags) => Json(ApiResponse::success(ObjectTagging { tags })),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/buckets/{bucket_name}/objects/{key}/tagging",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key"),
+        ("version_id" = Option<String>, Query, description = "Version ID (defaults to the current version)")
+    ),
+    responses(
+        (status = 200, description = "Object tags removed", body = ApiResponse<()>),
+        (status = 404, description = "Object not found", body = ApiResponse<()>)
+    )
+)]
+async fn delete_object_tagging(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(query): Query<ObjectTaggingQuery>,
+) -> Json<ApiResponse<()>> {
+    match state.object_service.delete_object_tagging(&bucket_name, &key, query.version_id.as_deref()).await {
+        Ok(_) => Json(ApiResponse::success(())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/buckets/{bucket_name}/objects/{key}/legal-hold",
+    tag = "objects",
+    params(
+        ("bucket_name" = String, Path, description = "Bucket name"),
+        ("key" = String, Path, description = "Object key")
+    ),
+    request_body(content = ObjectLegalHold, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Object legal hold updated", body = ApiResponse<ObjectMetadata>),
+        (status = 400, description = "Object lock not enabled", body = ApiResponse<ObjectMetadata>),
+        (status = 404, description = "Object not found", body = ApiResponse<ObjectMetadata>)
+    )
+)]
+async fn put_object_legal_hold(
+    State(state): State<Arc<AppState>>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Json(legal_hold): Json<ObjectLegalHold>,
+) -> Json<ApiResponse<ObjectMetadata>> {
+    match state.object_service.put_object_legal_hold(&bucket_name, &key, legal_hold.enabled).await {
+        Ok(metadata) => Json(ApiResponse::success(metadata)),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
@@ -0,0 +1,209 @@
+use crate::models::{MetadataIndexConfiguration, MetadataIndexStats, ObjectMetadata};
+use crate::query::{CompareOp, Condition, Expr, Field, Value};
+use anyhow::{Result, anyhow};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound;
+
+/// 元数据索引配置文件名（位于 .sevino.meta 目录下）
+pub const METADATA_INDEX_CONFIG: &str = "metadata_index";
+/// 每个桶最多建立索引的元数据键数
+pub const MAX_INDEXED_KEYS: usize = 16;
+/// 元数据键的最大长度
+const MAX_INDEXED_KEY_LENGTH: usize = 128;
+
+/// 校验元数据索引配置
+pub fn validate_configuration(config: &MetadataIndexConfiguration) -> Result<()> {
+    if config.keys.len() > MAX_INDEXED_KEYS {
+        return Err(anyhow!("A bucket can index at most {} metadata keys, got {}", MAX_INDEXED_KEYS, config.keys.len()));
+    }
+
+    let mut seen = HashSet::new();
+    for key in &config.keys {
+        if key.is_empty() || key.chars().count() > MAX_INDEXED_KEY_LENGTH {
+            return Err(anyhow!("Indexed metadata key '{}' must be 1 to {} characters long", key, MAX_INDEXED_KEY_LENGTH));
+        }
+        if !seen.insert(key) {
+            return Err(anyhow!("Metadata key '{}' is listed more than once", key));
+        }
+    }
+    Ok(())
+}
+
+/// 按数字排序的索引键
+#[derive(Debug, Clone, Copy)]
+struct NumberKey(f64);
+
+impl PartialEq for NumberKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NumberKey {}
+
+impl PartialOrd for NumberKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NumberKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// 单个元数据键的索引
+#[derive(Debug, Default)]
+struct FieldIndex {
+    /// 记录ID -> 元数据值（更新和删除时用于找到旧的索引项）
+    values: HashMap<String, String>,
+    /// 值 -> 记录ID，按字符串排序
+    text: BTreeMap<String, BTreeSet<String>>,
+    /// 能解析为数字的值 -> 记录ID，按数字排序
+    numbers: BTreeMap<NumberKey, BTreeSet<String>>,
+}
+
+impl FieldIndex {
+    fn insert(&mut self, record_id: &str, value: &str) {
+        self.values.insert(record_id.to_string(), value.to_string());
+        self.text.entry(value.to_string()).or_default().insert(record_id.to_string());
+        if let Ok(number) = value.trim().parse::<f64>() {
+            self.numbers.entry(NumberKey(number)).or_default().insert(record_id.to_string());
+        }
+    }
+
+    fn remove(&mut self, record_id: &str) {
+        let Some(value) = self.values.remove(record_id) else {
+            return;
+        };
+        if let Some(ids) = self.text.get_mut(&value) {
+            ids.remove(record_id);
+            if ids.is_empty() {
+                self.text.remove(&value);
+            }
+        }
+        if let Ok(number) = value.trim().parse::<f64>() {
+            if let Some(ids) = self.numbers.get_mut(&NumberKey(number)) {
+                ids.remove(record_id);
+                if ids.is_empty() {
+                    self.numbers.remove(&NumberKey(number));
+                }
+            }
+        }
+    }
+
+    /// 满足比较条件的记录ID（字面量为数字时查数字索引，否则查字符串索引）
+    fn compare(&self, op: CompareOp, literal: &Value) -> Option<BTreeSet<String>> {
+        let (lower, upper) = match op {
+            CompareOp::Eq => (Bound::Included(()), Bound::Included(())),
+            CompareOp::Lt => (Bound::Unbounded, Bound::Excluded(())),
+            CompareOp::Le => (Bound::Unbounded, Bound::Included(())),
+            CompareOp::Gt => (Bound::Excluded(()), Bound::Unbounded),
+            CompareOp::Ge => (Bound::Included(()), Bound::Unbounded),
+            // 不等于几乎匹配所有记录，索引没有帮助
+            CompareOp::Ne => return None,
+        };
+        match literal {
+            Value::Text(text) => {
+                let range = (lower.map(|_| text.clone()), upper.map(|_| text.clone()));
+                Some(self.text.range(range).flat_map(|(_, ids)| ids.iter().cloned()).collect())
+            }
+            Value::Number(number) => {
+                let range = (lower.map(|_| NumberKey(*number)), upper.map(|_| NumberKey(*number)));
+                Some(self.numbers.range(range).flat_map(|(_, ids)| ids.iter().cloned()).collect())
+            }
+            Value::Time(_) => None,
+        }
+    }
+
+    fn prefix(&self, prefix: &str) -> BTreeSet<String> {
+        self.text.range(prefix.to_string()..)
+            .take_while(|(value, _)| value.starts_with(prefix))
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect()
+    }
+}
+
+/// 桶的元数据二级索引，覆盖所有对象记录（包括历史版本），随元数据的写入和删除增量维护
+#[derive(Debug, Default)]
+pub struct BucketMetadataIndex {
+    fields: HashMap<String, FieldIndex>,
+}
+
+impl BucketMetadataIndex {
+    pub fn new(keys: &[String]) -> Self {
+        Self { fields: keys.iter().map(|key| (key.clone(), FieldIndex::default())).collect() }
+    }
+
+    /// 写入（或替换）一条对象记录的索引项
+    pub fn insert(&mut self, record_id: &str, metadata: &ObjectMetadata) {
+        for (key, field) in self.fields.iter_mut() {
+            field.remove(record_id);
+            if let Some(value) = metadata.user_metadata.get(key) {
+                field.insert(record_id, value);
+            }
+        }
+    }
+
+    /// 删除一条对象记录的索引项
+    pub fn remove(&mut self, record_id: &str) {
+        for field in self.fields.values_mut() {
+            field.remove(record_id);
+        }
+    }
+
+    /// 各索引的统计（按元数据键排序）
+    pub fn stats(&self) -> Vec<MetadataIndexStats> {
+        let mut stats: Vec<MetadataIndexStats> = self.fields.iter()
+            .map(|(key, field)| MetadataIndexStats {
+                key: key.clone(),
+                records: field.values.len() as u64,
+                distinct_values: field.text.len() as u64,
+            })
+            .collect();
+        stats.sort_by(|a, b| a.key.cmp(&b.key));
+        stats
+    }
+
+    /// 用索引求可能满足表达式的记录ID（结果是匹配记录的超集，仍需逐条求值）；
+    /// 表达式中没有可用索引的条件时返回 None，调用方需要扫描整个桶
+    pub fn candidates(&self, expr: &Expr) -> Option<BTreeSet<String>> {
+        match expr {
+            Expr::Condition(condition) => self.condition_candidates(condition),
+            Expr::And(left, right) => match (self.candidates(left), self.candidates(right)) {
+                (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+                (Some(a), None) | (None, Some(a)) => Some(a),
+                (None, None) => None,
+            },
+            Expr::Or(left, right) => {
+                let mut a = self.candidates(left)?;
+                a.extend(self.candidates(right)?);
+                Some(a)
+            }
+            Expr::Not(_) => None,
+        }
+    }
+
+    fn condition_candidates(&self, condition: &Condition) -> Option<BTreeSet<String>> {
+        let field = |field: &Field| match field {
+            Field::Metadata(key) => self.fields.get(key),
+            _ => None,
+        };
+        match condition {
+            Condition::Compare(f, op, literal) => field(f)?.compare(*op, literal),
+            Condition::In(f, literals) => {
+                let index = field(f)?;
+                let mut ids = BTreeSet::new();
+                for literal in literals {
+                    ids.extend(index.compare(CompareOp::Eq, literal)?);
+                }
+                Some(ids)
+            }
+            Condition::Prefix(f, prefix) => Some(field(f)?.prefix(prefix)),
+            Condition::Exists(f) => Some(field(f)?.values.keys().cloned().collect()),
+            Condition::Matches(_, _) => None,
+        }
+    }
+}
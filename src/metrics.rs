@@ -0,0 +1,297 @@
+use crate::services::StorageService;
+use crate::utils::body_length;
+use anyhow::Result;
+use axum::body::HttpBody;
+use axum::extract::{MatchedPath, Request};
+use axum::response::Response;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower::{Layer, Service};
+
+/// Prometheus 指标
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    http_request_bytes: IntCounterVec,
+    http_response_bytes: IntCounterVec,
+    object_operations: IntCounterVec,
+    object_bytes: IntCounterVec,
+    dedup_reference_attempts: IntCounter,
+    dedup_reference_hits: IntCounter,
+    index_lock_hold: HistogramVec,
+    buckets: IntGauge,
+    objects: IntGaugeVec,
+    bucket_disk_usage: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("sevino_http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new("sevino_http_request_duration_seconds", "HTTP request latency by route and status"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_bytes = IntCounterVec::new(
+            Opts::new("sevino_http_request_bytes_total", "HTTP request body bytes received"),
+            &["method", "route"],
+        )?;
+        let http_response_bytes = IntCounterVec::new(
+            Opts::new("sevino_http_response_bytes_total", "HTTP response body bytes sent (responses with a known length)"),
+            &["method", "route"],
+        )?;
+        let object_operations = IntCounterVec::new(
+            Opts::new("sevino_object_operations_total", "Object operations handled by the object service"),
+            &["operation"],
+        )?;
+        let object_bytes = IntCounterVec::new(
+            Opts::new("sevino_object_bytes_total", "Logical object bytes written and read by the object service"),
+            &["operation"],
+        )?;
+        let dedup_reference_attempts = IntCounter::new(
+            "sevino_dedup_reference_attempts_total",
+            "Uploads using the reference deduplication mode",
+        )?;
+        let dedup_reference_hits = IntCounter::new(
+            "sevino_dedup_reference_hits_total",
+            "Reference mode uploads stored as a reference to existing data",
+        )?;
+        let index_lock_hold = HistogramVec::new(
+            HistogramOpts::new("sevino_index_lock_hold_seconds", "Time spent holding the in-memory index locks")
+                .buckets(vec![0.000_001, 0.000_01, 0.000_1, 0.001, 0.01, 0.1, 1.0]),
+            &["index", "mode"],
+        )?;
+        let buckets = IntGauge::new("sevino_buckets", "Number of buckets")?;
+        let objects = IntGaugeVec::new(
+            Opts::new("sevino_objects", "Number of objects in the object index per bucket"),
+            &["bucket"],
+        )?;
+        let bucket_disk_usage = IntGaugeVec::new(
+            Opts::new("sevino_bucket_disk_usage_bytes", "Disk space used by each bucket directory"),
+            &["bucket"],
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(http_request_bytes.clone()))?;
+        registry.register(Box::new(http_response_bytes.clone()))?;
+        registry.register(Box::new(object_operations.clone()))?;
+        registry.register(Box::new(object_bytes.clone()))?;
+        registry.register(Box::new(dedup_reference_attempts.clone()))?;
+        registry.register(Box::new(dedup_reference_hits.clone()))?;
+        registry.register(Box::new(index_lock_hold.clone()))?;
+        registry.register(Box::new(buckets.clone()))?;
+        registry.register(Box::new(objects.clone()))?;
+        registry.register(Box::new(bucket_disk_usage.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            http_request_bytes,
+            http_response_bytes,
+            object_operations,
+            object_bytes,
+            dedup_reference_attempts,
+            dedup_reference_hits,
+            index_lock_hold,
+            buckets,
+            objects,
+            bucket_disk_usage,
+        })
+    }
+
+    /// 记录一次HTTP请求
+    fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64, bytes_in: u64, bytes_out: u64) {
+        let status = status.to_string();
+        self.http_requests.with_label_values(&[method, route, &status]).inc();
+        self.http_request_duration.with_label_values(&[method, route, &status]).observe(seconds);
+        self.http_request_bytes.with_label_values(&[method, route]).inc_by(bytes_in);
+        self.http_response_bytes.with_label_values(&[method, route]).inc_by(bytes_out);
+    }
+
+    /// 记录一次对象操作（put/get/delete）及其逻辑字节数
+    pub fn record_object_operation(&self, operation: &str, bytes: u64) {
+        self.object_operations.with_label_values(&[operation]).inc();
+        self.object_bytes.with_label_values(&[operation]).inc_by(bytes);
+    }
+
+    /// 记录一次 Reference 模式上传
+    pub fn record_dedup_reference_attempt(&self) {
+        self.dedup_reference_attempts.inc();
+    }
+
+    /// 记录一次复用了已有数据的 Reference 模式上传
+    pub fn record_dedup_reference_hit(&self) {
+        self.dedup_reference_hits.inc();
+    }
+
+    /// 为索引锁的守卫计时，守卫释放时记录持有时间
+    pub fn time_lock<G>(metrics: Option<&Metrics>, index: &'static str, mode: &'static str, guard: G) -> TimedGuard<G> {
+        TimedGuard {
+            guard,
+            started_at: Instant::now(),
+            histogram: metrics.map(|m| m.index_lock_hold.with_label_values(&[index, mode])),
+        }
+    }
+
+    /// 刷新桶、对象数量和磁盘占用后输出 Prometheus 文本格式
+    pub async fn render(&self, storage: &StorageService) -> Result<String> {
+        let object_counts = storage.object_counts().await;
+        let bucket_names = storage.bucket_names().await;
+        let data_dir = storage.data_dir().to_path_buf();
+        let disk_usage = {
+            let bucket_names = bucket_names.clone();
+            tokio::task::spawn_blocking(move || {
+                bucket_names
+                    .into_iter()
+                    .map(|name| {
+                        let size = directory_size(&data_dir.join(&name));
+                        (name, size)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await?
+        };
+
+        self.buckets.set(bucket_names.len() as i64);
+        self.objects.reset();
+        for name in &bucket_names {
+            let count = object_counts.get(name).copied().unwrap_or(0);
+            self.objects.with_label_values(&[name]).set(count as i64);
+        }
+        self.bucket_disk_usage.reset();
+        for (name, size) in disk_usage {
+            self.bucket_disk_usage.with_label_values(&[&name]).set(size as i64);
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// 记录持有时间的锁守卫
+pub struct TimedGuard<G> {
+    guard: G,
+    started_at: Instant,
+    histogram: Option<prometheus::Histogram>,
+}
+
+impl<G> Deref for TimedGuard<G> {
+    type Target = G;
+
+    fn deref(&self) -> &G {
+        &self.guard
+    }
+}
+
+impl<G> DerefMut for TimedGuard<G> {
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.guard
+    }
+}
+
+impl<G> Drop for TimedGuard<G> {
+    fn drop(&mut self) {
+        if let Some(histogram) = &self.histogram {
+            histogram.observe(self.started_at.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// 递归统计目录占用的字节数
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// 收集HTTP请求指标的 tower 中间件（路由标签使用匹配的路由模板）
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Metrics,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: Metrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService { inner, metrics: self.metrics.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Metrics,
+}
+
+impl<S> Service<Request> for MetricsService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let started_at = Instant::now();
+        let method = request.method().to_string();
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+        let bytes_in = body_length(request.headers(), request.body().size_hint().exact());
+
+        // 使用已就绪的服务处理请求，克隆体留给下一次 poll_ready
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let metrics = self.metrics.clone();
+
+        Box::pin(async move {
+            let response = inner.call(request).await?;
+            let bytes_out = body_length(response.headers(), response.body().size_hint().exact());
+            metrics.observe_request(
+                &method,
+                &route,
+                response.status().as_u16(),
+                started_at.elapsed().as_secs_f64(),
+                bytes_in,
+                bytes_out,
+            );
+            Ok(response)
+        })
+    }
+}
@@ -1,116 +1,1100 @@
-use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
-
-/// 存储桶模型
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct Bucket {
-    /// 桶名称
-    pub name: String,
-    /// 创建时间
-    pub created_at: DateTime<Utc>,
-    /// 桶的元数据
-    pub metadata: HashMap<String, String>,
-}
-
-/// 对象模型
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct Object {
-    /// 对象键（文件名）
-    pub key: String,
-    /// 所属桶名称
-    pub bucket_name: String,
-    /// 对象大小（字节）
-    pub size: u64,
-    /// 内容类型
-    pub content_type: String,
-    /// ETag（用于缓存验证）
-    pub etag: String,
-    /// 创建时间
-    pub created_at: DateTime<Utc>,
-    /// 最后修改时间
-    pub last_modified: DateTime<Utc>,
-    /// 用户自定义元数据
-    pub user_metadata: HashMap<String, String>,
-}
-
-/// 对象元数据
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct ObjectMetadata {
-    /// 对象键（文件名）
-    pub key: String,
-    /// 所属桶名称
-    pub bucket_name: String,
-    /// 对象大小（字节）
-    pub size: u64,
-    /// 内容类型
-    pub content_type: String,
-    /// ETag（用于缓存验证）
-    pub etag: String,
-    /// 创建时间
-    pub created_at: DateTime<Utc>,
-    /// 最后修改时间
-    pub last_modified: DateTime<Utc>,
-    /// 用户自定义元数据
-    pub user_metadata: HashMap<String, String>,
-    /// 版本ID（用于版本控制）
-    pub version_id: Option<String>,
-    /// 是否为删除标记（用于版本控制）
-    pub is_delete_marker: bool,
-    /// 引用计数（用于去重）
-    pub reference_count: u32,
-    /// 数据持有者对象ID（如果为None，则自己是数据持有者）
-    pub data_holder_id: Option<String>,
-}
-
-impl Bucket {
-    pub fn new(name: String) -> Self {
-        Self {
-            name,
-            created_at: Utc::now(),
-            metadata: HashMap::new(),
-        }
-    }
-}
-
-impl Object {
-    pub fn new(
-        key: String,
-        bucket_name: String,
-        size: u64,
-        content_type: String,
-        etag: String,
-        user_metadata: HashMap<String, String>,
-    ) -> Self {
-        let now = Utc::now();
-        Self {
-            key,
-            bucket_name,
-            size,
-            content_type,
-            etag,
-            created_at: now,
-            last_modified: now,
-            user_metadata,
-        }
-    }
-}
-
-impl From<Object> for ObjectMetadata {
-    fn from(obj: Object) -> Self {
-        Self {
-            key: obj.key,
-            bucket_name: obj.bucket_name,
-            size: obj.size,
-            content_type: obj.content_type,
-            etag: obj.etag,
-            created_at: obj.created_at,
-            last_modified: obj.last_modified,
-            user_metadata: obj.user_metadata,
-            version_id: None,
-            is_delete_marker: false,
-            reference_count: 0,
-            data_holder_id: None,
-        }
-    }
-} 
\ No newline at end of file
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// 存储桶模型
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Bucket {
+    /// 桶名称
+    pub name: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 桶的元数据
+    pub metadata: HashMap<String, String>,
+}
+
+/// 对象模型
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Object {
+    /// 对象键（文件名）
+    pub key: String,
+    /// 所属桶名称
+    pub bucket_name: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    /// 内容类型
+    pub content_type: String,
+    /// ETag（用于缓存验证）
+    pub etag: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 最后修改时间
+    pub last_modified: DateTime<Utc>,
+    /// 用户自定义元数据
+    pub user_metadata: HashMap<String, String>,
+    /// 对象标签
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+/// 对象元数据
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectMetadata {
+    /// 对象键（文件名）
+    pub key: String,
+    /// 所属桶名称
+    pub bucket_name: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    /// 内容类型
+    pub content_type: String,
+    /// ETag（用于缓存验证）
+    pub etag: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 最后修改时间
+    pub last_modified: DateTime<Utc>,
+    /// 用户自定义元数据
+    pub user_metadata: HashMap<String, String>,
+    /// 版本ID（用于版本控制）
+    pub version_id: Option<String>,
+    /// 是否为删除标记（用于版本控制）
+    pub is_delete_marker: bool,
+    /// 引用计数（用于去重）
+    pub reference_count: u32,
+    /// 数据持有者对象ID（如果为None，则自己是数据持有者）
+    pub data_holder_id: Option<String>,
+    /// 对象锁定保留模式
+    #[serde(default)]
+    pub retention_mode: Option<ObjectLockMode>,
+    /// 对象锁定保留截止时间
+    #[serde(default)]
+    pub retain_until: Option<DateTime<Utc>>,
+    /// 是否处于合规保留（Legal Hold）状态
+    #[serde(default)]
+    pub legal_hold: bool,
+    /// 服务端加密信息（未加密时为空）
+    #[serde(default)]
+    pub encryption: Option<ObjectEncryption>,
+    /// 客户提供密钥加密信息（SSE-C，未使用时为空）
+    #[serde(default)]
+    pub customer_encryption: Option<CustomerEncryption>,
+    /// 压缩信息（未压缩时为空）
+    #[serde(default)]
+    pub compression: Option<ObjectCompression>,
+    /// 实际占用的存储空间（字节，包含压缩和加密的影响；引用对象为0）
+    #[serde(default)]
+    pub stored_size: Option<u64>,
+    /// 对象标签（修改标签不改变 last_modified 和 ETag）
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// 按文件内容（魔数）检测到的内容类型（无法识别时为空）
+    #[serde(default)]
+    pub detected_content_type: Option<String>,
+}
+
+impl Bucket {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            created_at: Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+}
+
+impl Object {
+    pub fn new(
+        key: String,
+        bucket_name: String,
+        size: u64,
+        content_type: String,
+        etag: String,
+        user_metadata: HashMap<String, String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            key,
+            bucket_name,
+            size,
+            content_type,
+            etag,
+            created_at: now,
+            last_modified: now,
+            user_metadata,
+            tags: HashMap::new(),
+        }
+    }
+}
+
+impl From<ObjectMetadata> for Object {
+    fn from(metadata: ObjectMetadata) -> Self {
+        Self {
+            key: metadata.key,
+            bucket_name: metadata.bucket_name,
+            size: metadata.size,
+            content_type: metadata.content_type,
+            etag: metadata.etag,
+            created_at: metadata.created_at,
+            last_modified: metadata.last_modified,
+            user_metadata: metadata.user_metadata,
+            tags: metadata.tags,
+        }
+    }
+}
+
+impl From<Object> for ObjectMetadata {
+    fn from(obj: Object) -> Self {
+        Self {
+            key: obj.key,
+            bucket_name: obj.bucket_name,
+            size: obj.size,
+            content_type: obj.content_type,
+            etag: obj.etag,
+            created_at: obj.created_at,
+            last_modified: obj.last_modified,
+            user_metadata: obj.user_metadata,
+            version_id: None,
+            is_delete_marker: false,
+            reference_count: 0,
+            data_holder_id: None,
+            retention_mode: None,
+            retain_until: None,
+            legal_hold: false,
+            encryption: None,
+            customer_encryption: None,
+            compression: None,
+            stored_size: None,
+            tags: obj.tags,
+            detected_content_type: None,
+        }
+    }
+} 
+
+/// 对象事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum EventType {
+    /// 对象创建（包括覆盖写入）
+    ObjectCreated,
+    /// 对象删除
+    ObjectRemoved,
+    /// 对象元数据更新
+    MetadataUpdated,
+    /// 桶用量超过软配额
+    QuotaSoftLimitExceeded,
+}
+
+/// 桶事件通知规则
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NotificationRule {
+    /// 规则ID（为空时自动生成）
+    #[serde(default)]
+    pub id: String,
+    /// 订阅的事件类型
+    pub events: Vec<EventType>,
+    /// 对象键前缀过滤
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 对象键后缀过滤
+    #[serde(default)]
+    pub suffix: Option<String>,
+    /// Webhook地址
+    pub webhook_url: String,
+}
+
+/// 桶事件通知配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NotificationConfiguration {
+    /// 通知规则列表
+    pub rules: Vec<NotificationRule>,
+}
+
+/// 对象事件（Webhook投递的负载）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectEvent {
+    /// 事件ID
+    pub event_id: String,
+    /// 事件类型
+    pub event_type: EventType,
+    /// 事件发生时间
+    pub event_time: DateTime<Utc>,
+    /// 所属桶名称
+    pub bucket_name: String,
+    /// 对象键
+    pub key: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    /// ETag
+    pub etag: String,
+    /// 版本ID
+    pub version_id: Option<String>,
+}
+
+impl NotificationRule {
+    /// 检查规则是否匹配事件类型和对象键
+    pub fn matches(&self, event_type: EventType, key: &str) -> bool {
+        self.events.contains(&event_type)
+            && self.prefix.as_ref().map(|p| key.starts_with(p.as_str())).unwrap_or(true)
+            && self.suffix.as_ref().map(|s| key.ends_with(s.as_str())).unwrap_or(true)
+    }
+}
+
+/// 生命周期规则过滤条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LifecycleFilter {
+    /// 对象键前缀
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 用户元数据匹配条件（所有键值都必须相等）
+    #[serde(default)]
+    pub user_metadata: HashMap<String, String>,
+    /// 对象标签匹配条件（所有标签都必须相等）
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+/// 非当前版本过期策略
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NoncurrentVersionExpiration {
+    /// 版本成为非当前版本N天后删除
+    #[serde(default)]
+    pub noncurrent_days: Option<u32>,
+    /// 保留最新的N个非当前版本，更早的版本删除
+    #[serde(default)]
+    pub newer_noncurrent_versions: Option<u32>,
+}
+
+/// 生命周期规则
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LifecycleRule {
+    /// 规则ID（为空时自动生成）
+    #[serde(default)]
+    pub id: String,
+    /// 是否启用
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 过滤条件
+    #[serde(default)]
+    pub filter: LifecycleFilter,
+    /// 对象创建N天后过期删除
+    #[serde(default)]
+    pub expiration_days: Option<u32>,
+    /// 非当前版本过期策略
+    #[serde(default)]
+    pub noncurrent_version_expiration: Option<NoncurrentVersionExpiration>,
+    /// 未完成的分片上传在N天后清理
+    #[serde(default)]
+    pub abort_incomplete_multipart_upload_days: Option<u32>,
+}
+
+/// 桶生命周期配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LifecycleConfiguration {
+    /// 生命周期规则列表
+    pub rules: Vec<LifecycleRule>,
+}
+
+/// 生命周期执行报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LifecycleReport {
+    /// 桶名称
+    pub bucket_name: String,
+    /// 过期删除的对象键
+    pub expired_objects: Vec<String>,
+    /// 删除的非当前版本（key@version_id）
+    pub expired_versions: Vec<String>,
+    /// 清理的未完成分片上传（key@upload_id）
+    pub aborted_uploads: Vec<String>,
+    /// 执行过程中的错误
+    pub errors: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl LifecycleFilter {
+    /// 检查对象是否匹配过滤条件
+    pub fn matches(&self, key: &str, user_metadata: &HashMap<String, String>, tags: &HashMap<String, String>) -> bool {
+        self.prefix.as_ref().map(|p| key.starts_with(p.as_str())).unwrap_or(true)
+            && self.user_metadata.iter().all(|(k, v)| user_metadata.get(k) == Some(v))
+            && self.tags.iter().all(|(k, v)| tags.get(k) == Some(v))
+    }
+}
+
+
+/// 对象锁定模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum ObjectLockMode {
+    /// 治理模式：持有管理员令牌时可以绕过
+    Governance,
+    /// 合规模式：保留期内任何人都无法删除或修改
+    Compliance,
+}
+
+/// 默认保留策略
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DefaultRetention {
+    /// 保留模式
+    pub mode: ObjectLockMode,
+    /// 保留天数
+    pub days: u32,
+}
+
+/// 桶对象锁定配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectLockConfiguration {
+    /// 是否启用对象锁定（启用后不能关闭）
+    pub enabled: bool,
+    /// 新对象的默认保留策略
+    #[serde(default)]
+    pub default_retention: Option<DefaultRetention>,
+}
+
+/// 对象保留设置
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectRetention {
+    /// 保留模式
+    pub mode: ObjectLockMode,
+    /// 保留截止时间
+    pub retain_until: DateTime<Utc>,
+}
+
+/// 对象合规保留设置
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectLegalHold {
+    /// 是否开启合规保留
+    pub enabled: bool,
+}
+
+/// 策略语句效果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+}
+
+/// 策略语句条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PolicyConditions {
+    /// 来源IP（支持CIDR，如 10.0.0.0/8），匹配任意一个即可
+    #[serde(default)]
+    pub source_ip: Vec<String>,
+    /// 对象键前缀（列举请求使用 prefix 查询参数），匹配任意一个即可
+    #[serde(default)]
+    pub prefix: Vec<String>,
+    /// 是否要求HTTPS传输
+    #[serde(default)]
+    pub secure_transport: Option<bool>,
+    /// 对象标签（对象级操作，目标对象必须带有所有这些标签）
+    #[serde(default)]
+    pub object_tags: HashMap<String, String>,
+}
+
+/// 策略语句
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PolicyStatement {
+    /// 语句ID
+    #[serde(default)]
+    pub sid: Option<String>,
+    /// 效果
+    pub effect: PolicyEffect,
+    /// 主体（访问密钥对应的名称，* 表示任何人，包括匿名用户）
+    pub principals: Vec<String>,
+    /// 操作（如 sevino:GetObject，支持通配符）
+    pub actions: Vec<String>,
+    /// 资源（bucket 或 bucket/key，支持通配符）
+    pub resources: Vec<String>,
+    /// 条件
+    #[serde(default)]
+    pub conditions: PolicyConditions,
+}
+
+/// 桶策略文档
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BucketPolicy {
+    /// 策略版本
+    #[serde(default)]
+    pub version: Option<String>,
+    /// 策略语句
+    pub statements: Vec<PolicyStatement>,
+}
+
+/// 策略模拟请求
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PolicySimulationRequest {
+    /// 主体名称（为空表示匿名用户）
+    #[serde(default)]
+    pub principal: Option<String>,
+    /// 操作（如 sevino:GetObject）
+    pub action: String,
+    /// 对象键（对象级操作）
+    #[serde(default)]
+    pub key: Option<String>,
+    /// 列举前缀（sevino:ListBucket）
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 来源IP
+    #[serde(default)]
+    pub source_ip: Option<String>,
+    /// 是否为HTTPS请求
+    #[serde(default)]
+    pub secure_transport: bool,
+    /// 目标对象的标签（为空时使用对象当前的标签）
+    #[serde(default)]
+    pub object_tags: Option<HashMap<String, String>>,
+}
+
+/// 策略评估结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum PolicyDecision {
+    /// 桶未配置策略，默认允许
+    NoPolicy,
+    /// 被 Allow 语句允许
+    Allow,
+    /// 被 Deny 语句显式拒绝
+    ExplicitDeny,
+    /// 没有匹配的 Allow 语句
+    ImplicitDeny,
+}
+
+/// 策略评估说明
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PolicyEvaluation {
+    /// 评估结果
+    pub decision: PolicyDecision,
+    /// 是否允许
+    pub allowed: bool,
+    /// 评估使用的主体
+    pub principal: String,
+    /// 评估使用的资源
+    pub resource: String,
+    /// 每条语句的匹配说明
+    pub explanations: Vec<String>,
+}
+
+/// 创建分享链接请求
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateShareLinkRequest {
+    /// 有效期（秒），默认3600
+    #[serde(default)]
+    pub expires_in_secs: Option<u64>,
+    /// 固定分享的对象版本（为空时始终下载当前版本）
+    #[serde(default)]
+    pub version_id: Option<String>,
+    /// 最大下载次数（为空表示不限制）
+    #[serde(default)]
+    pub max_downloads: Option<u32>,
+    /// 下载时使用的文件名（Content-Disposition）
+    #[serde(default)]
+    pub filename: Option<String>,
+}
+
+/// 分享链接
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ShareLink {
+    /// 链接ID（用于查询和撤销）
+    pub id: String,
+    /// 下载地址（包含过期时间和签名）
+    pub url: String,
+    pub bucket_name: String,
+    pub key: String,
+    pub version_id: Option<String>,
+    pub filename: Option<String>,
+    pub max_downloads: Option<u32>,
+    /// 已下载次数
+    pub downloads: u32,
+    /// 创建者
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// 是否已撤销
+    pub revoked: bool,
+}
+
+/// 创建表单上传策略请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateUploadPolicyRequest {
+    /// 允许上传的key前缀（为空表示整个桶）
+    #[serde(default)]
+    pub key_prefix: String,
+    /// 文件最大字节数（为空时只受服务器上传大小限制）
+    #[serde(default)]
+    pub max_content_length: Option<u64>,
+    /// 允许的内容类型，支持 `image/*` 形式的前缀匹配
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// 有效期（秒），默认3600
+    #[serde(default)]
+    pub expires_in_secs: Option<u64>,
+    /// 上传成功后重定向的地址（附加 bucket、key 和 etag 查询参数）
+    #[serde(default)]
+    pub success_action_redirect: Option<String>,
+    /// 上传成功后的响应状态码（200、201 或 204），未设置重定向时生效
+    #[serde(default)]
+    pub success_action_status: Option<u16>,
+}
+
+/// 表单上传策略文档（base64编码后作为表单的 policy 字段）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UploadPolicyDocument {
+    pub bucket_name: String,
+    pub key_prefix: String,
+    pub max_content_length: Option<u64>,
+    pub content_type: Option<String>,
+    pub success_action_redirect: Option<String>,
+    pub success_action_status: Option<u16>,
+    pub expires_at: DateTime<Utc>,
+    /// 签发者（上传时按签发者的桶策略授权）
+    pub created_by: String,
+}
+
+/// 已签名的表单上传策略
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UploadPolicy {
+    /// 表单提交地址
+    pub url: String,
+    /// 表单 policy 字段
+    pub policy: String,
+    /// 表单 signature 字段（policy 的 HMAC-SHA256，十六进制）
+    pub signature: String,
+    /// 策略内容
+    pub document: UploadPolicyDocument,
+}
+
+
+/// 桶服务端加密配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EncryptionConfiguration {
+    /// 是否对新写入的对象启用加密
+    pub enabled: bool,
+}
+
+/// 对象加密信息（信封加密）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectEncryption {
+    /// 加密算法（AES256-GCM）
+    pub algorithm: String,
+    /// 包装数据密钥的桶密钥ID
+    pub bucket_key_id: String,
+    /// 被桶密钥包装的数据密钥（base64）
+    pub wrapped_data_key: String,
+    /// 分块nonce前缀（base64）
+    pub nonce_prefix: String,
+    /// 明文分块大小（字节）
+    pub chunk_size: u32,
+}
+
+/// 客户提供密钥加密信息（SSE-C，服务端不保存密钥）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CustomerEncryption {
+    /// 加密算法（AES256）
+    pub algorithm: String,
+    /// 密钥指纹的盐（base64）
+    pub key_salt: String,
+    /// 加盐的密钥指纹（HMAC-SHA256，十六进制）
+    pub key_fingerprint: String,
+    /// 分块nonce前缀（base64）
+    pub nonce_prefix: String,
+    /// 明文分块大小（字节）
+    pub chunk_size: u32,
+}
+
+/// 桶密钥轮换结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct KeyRotationReport {
+    pub bucket_name: String,
+    /// 新的桶密钥ID
+    pub new_key_id: String,
+    /// 重新包装的数据密钥数量
+    pub rewrapped_objects: usize,
+}
+
+/// 压缩算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    /// 不压缩
+    #[default]
+    Off,
+    Gzip,
+    Zstd,
+}
+
+/// 桶压缩配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CompressionConfiguration {
+    /// 压缩算法
+    #[serde(default)]
+    pub algorithm: CompressionAlgorithm,
+    /// 压缩级别（gzip 0-9，zstd 1-22，为空时使用算法默认级别）
+    #[serde(default)]
+    pub level: Option<i32>,
+    /// 只压缩这些内容类型（支持 * 通配符，为空表示所有类型）
+    #[serde(default)]
+    pub include_content_types: Vec<String>,
+    /// 不压缩这些内容类型（支持 * 通配符，优先于 include_content_types）
+    #[serde(default)]
+    pub exclude_content_types: Vec<String>,
+}
+
+/// 内容类型检测模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentTypeMode {
+    /// 使用客户端声明的类型（默认）
+    #[default]
+    Trust,
+    /// 声明的类型与内容不符时改用检测到的类型
+    Sniff,
+    /// 声明的类型与内容不符时拒绝上传
+    Reject,
+}
+
+/// 桶内容类型检测配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ContentTypeConfiguration {
+    /// 检测模式
+    #[serde(default)]
+    pub mode: ContentTypeMode,
+}
+
+/// 对象压缩信息
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectCompression {
+    /// 压缩算法
+    pub algorithm: CompressionAlgorithm,
+    /// 压缩后的大小（字节，加密前）
+    pub compressed_size: u64,
+}
+
+impl ObjectMetadata {
+    /// 加密层的明文大小（压缩对象为压缩后的大小）
+    pub fn payload_size(&self) -> u64 {
+        self.compression.as_ref().map(|c| c.compressed_size).unwrap_or(self.size)
+    }
+
+    /// 对象数据占用的存储空间（引用对象为0；未记录时按对象大小估算）
+    pub fn physical_size(&self) -> u64 {
+        if self.data_holder_id.is_some() {
+            return 0;
+        }
+        self.stored_size.unwrap_or(self.size)
+    }
+}
+
+/// 配额字节数的计量方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaSizeBasis {
+    /// 按对象大小计量（引用对象按原始大小计算）
+    #[default]
+    Logical,
+    /// 按实际占用的存储空间计量（引用对象不占用空间）
+    Physical,
+}
+
+/// 桶配额配置（超过硬配额的写入会被拒绝，首次超过软配额时发出警告事件）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct QuotaConfiguration {
+    /// 字节数的计量方式
+    #[serde(default)]
+    pub size_basis: QuotaSizeBasis,
+    /// 字节数硬配额
+    #[serde(default)]
+    pub hard_limit_bytes: Option<u64>,
+    /// 字节数软配额
+    #[serde(default)]
+    pub soft_limit_bytes: Option<u64>,
+    /// 对象数硬配额
+    #[serde(default)]
+    pub hard_limit_objects: Option<u64>,
+    /// 对象数软配额
+    #[serde(default)]
+    pub soft_limit_objects: Option<u64>,
+}
+
+/// 对象大小分布的区间上限（字节），最后一个区间没有上限
+pub const SIZE_HISTOGRAM_BOUNDS: [u64; 6] = [
+    1024,
+    64 * 1024,
+    1024 * 1024,
+    16 * 1024 * 1024,
+    128 * 1024 * 1024,
+    1024 * 1024 * 1024,
+];
+
+/// 桶用量（随对象写入和删除增量维护）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BucketUsage {
+    /// 对象记录数（包括历史版本和引用对象）
+    pub object_count: u64,
+    /// 逻辑字节数（所有对象大小之和）
+    pub logical_bytes: u64,
+    /// 物理字节数（实际写入磁盘的对象数据）
+    pub physical_bytes: u64,
+    /// 引用对象数
+    pub reference_objects: u64,
+    /// 去重节省的字节数（引用对象的逻辑大小之和）
+    pub dedup_saved_bytes: u64,
+    /// 带版本ID的对象记录数
+    pub version_count: u64,
+    /// 删除标记数
+    pub delete_markers: u64,
+    /// 对象大小分布
+    pub size_histogram: Vec<SizeHistogramBucket>,
+    /// 按内容类型统计
+    pub content_types: BTreeMap<String, ContentTypeUsage>,
+}
+
+/// 对象大小分布区间
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SizeHistogramBucket {
+    /// 区间上限（字节，不含），为空表示没有上限
+    pub max_size: Option<u64>,
+    /// 对象数量
+    pub count: u64,
+}
+
+/// 单个内容类型的用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ContentTypeUsage {
+    pub object_count: u64,
+    pub logical_bytes: u64,
+}
+
+/// 桶配额及当前用量
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BucketQuotaStatus {
+    pub configuration: QuotaConfiguration,
+    pub usage: BucketUsage,
+}
+
+impl BucketUsage {
+    /// 计入一条对象记录
+    pub fn add(&mut self, metadata: &ObjectMetadata) {
+        self.apply(metadata, true);
+    }
+
+    /// 移除一条对象记录
+    pub fn remove(&mut self, metadata: &ObjectMetadata) {
+        self.apply(metadata, false);
+    }
+
+    fn apply(&mut self, metadata: &ObjectMetadata, adding: bool) {
+        let update = |value: &mut u64, amount: u64| {
+            *value = if adding { *value + amount } else { value.saturating_sub(amount) };
+        };
+        let is_reference = metadata.data_holder_id.is_some();
+
+        update(&mut self.object_count, 1);
+        update(&mut self.logical_bytes, metadata.size);
+        update(&mut self.physical_bytes, metadata.physical_size());
+        update(&mut self.reference_objects, is_reference as u64);
+        update(&mut self.dedup_saved_bytes, if is_reference { metadata.size } else { 0 });
+        update(&mut self.version_count, metadata.version_id.is_some() as u64);
+        update(&mut self.delete_markers, metadata.is_delete_marker as u64);
+
+        if self.size_histogram.is_empty() {
+            self.size_histogram = SIZE_HISTOGRAM_BOUNDS
+                .iter()
+                .map(|bound| Some(*bound))
+                .chain(std::iter::once(None))
+                .map(|max_size| SizeHistogramBucket { max_size, count: 0 })
+                .collect();
+        }
+        let index = SIZE_HISTOGRAM_BOUNDS
+            .iter()
+            .position(|bound| metadata.size < *bound)
+            .unwrap_or(SIZE_HISTOGRAM_BOUNDS.len());
+        update(&mut self.size_histogram[index].count, 1);
+
+        // 忽略 charset 等参数
+        let content_type = metadata.content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        let entry = self.content_types.entry(content_type.clone()).or_default();
+        update(&mut entry.object_count, 1);
+        update(&mut entry.logical_bytes, metadata.size);
+        if entry.object_count == 0 {
+            self.content_types.remove(&content_type);
+        }
+    }
+}
+
+/// 复制对象时元数据的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataDirective {
+    /// 沿用源对象的内容类型和用户元数据
+    #[default]
+    Copy,
+    /// 使用请求中提供的内容类型和用户元数据
+    Replace,
+}
+
+/// 复制对象请求（目标桶和对象键由请求路径指定）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CopyObjectRequest {
+    /// 源桶名称
+    pub source_bucket: String,
+    /// 源对象键
+    pub source_key: String,
+    /// 源对象版本ID（为空时复制当前版本）
+    #[serde(default)]
+    pub source_version_id: Option<String>,
+    /// 元数据处理方式
+    #[serde(default)]
+    pub metadata_directive: MetadataDirective,
+    /// 新的内容类型（仅 replace 时使用，为空时沿用源对象的内容类型）
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// 新的用户元数据（仅 replace 时使用）
+    #[serde(default)]
+    pub user_metadata: HashMap<String, String>,
+    /// 新的对象标签（仅 replace 时使用）
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+/// 重命名单个对象请求（源桶和对象键由请求路径指定）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RenameObjectRequest {
+    /// 新的对象键
+    pub destination_key: String,
+}
+
+/// 按前缀重命名对象请求
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RenamePrefixRequest {
+    /// 源前缀（不能为空）
+    pub source_prefix: String,
+    /// 目标前缀（为空时移动到桶的根部）
+    #[serde(default)]
+    pub destination_prefix: String,
+}
+
+/// 被重命名的对象键
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RenamedObject {
+    /// 原对象键
+    pub source_key: String,
+    /// 新对象键
+    pub destination_key: String,
+}
+
+/// 重命名结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RenameReport {
+    /// 被重命名的对象键
+    pub renamed: Vec<RenamedObject>,
+    /// 被改写的对象记录数（包括历史版本）
+    pub records: u64,
+}
+
+/// 批量删除中的一个对象（可指定版本）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeleteObjectEntry {
+    /// 对象键
+    pub key: String,
+    /// 版本ID（为空时删除当前版本）
+    #[serde(default)]
+    pub version_id: Option<String>,
+}
+
+/// 批量删除请求
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BatchDeleteRequest {
+    /// 要删除的对象
+    pub objects: Vec<DeleteObjectEntry>,
+    /// 安静模式：响应中只列出删除失败的对象
+    #[serde(default)]
+    pub quiet: bool,
+}
+
+/// 删除失败的对象
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeleteObjectError {
+    /// 对象键
+    pub key: String,
+    /// 版本ID
+    pub version_id: Option<String>,
+    /// 失败原因
+    pub message: String,
+}
+
+/// 批量删除结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BatchDeleteResult {
+    /// 删除成功的对象（安静模式下为空）
+    pub deleted: Vec<DeleteObjectEntry>,
+    /// 删除失败的对象
+    pub errors: Vec<DeleteObjectError>,
+}
+
+/// 归档中无法解包的条目
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ArchiveEntryError {
+    /// 条目在归档中的路径
+    pub path: String,
+    pub message: String,
+}
+
+/// 归档解包结果
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ArchiveExtractReport {
+    /// 条目写入的前缀
+    pub prefix: String,
+    /// 归档格式（zip、tar 或 tar.gz）
+    pub format: String,
+    /// 写入的对象
+    pub extracted: Vec<Object>,
+    /// 被拒绝或写入失败的条目
+    pub errors: Vec<ArchiveEntryError>,
+    /// 写入的总字节数
+    pub total_bytes: u64,
+}
+
+/// 按前缀删除请求
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeletePrefixRequest {
+    /// 要删除的前缀（不能为空）
+    pub prefix: String,
+}
+
+/// 后台删除任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteJobStatus {
+    /// 执行中
+    Running,
+    /// 已完成（可能有部分对象删除失败）
+    Completed,
+    /// 执行中止
+    Failed,
+}
+
+/// 按前缀删除的后台任务
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeleteJob {
+    /// 任务ID
+    pub id: String,
+    /// 桶名称
+    pub bucket_name: String,
+    /// 删除的前缀
+    pub prefix: String,
+    /// 任务状态
+    pub status: DeleteJobStatus,
+    /// 需要删除的对象记录数（包括历史版本），扫描完成前为 0
+    pub total: u64,
+    /// 已删除的记录数
+    pub deleted: u64,
+    /// 删除失败的记录数
+    pub failed: u64,
+    /// 删除失败的对象（最多保留前 100 个）
+    pub errors: Vec<DeleteObjectError>,
+    /// 任务中止的原因
+    pub message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 结束时间
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// 对象标签集
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ObjectTagging {
+    /// 标签（键 -> 值）
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+/// 对象搜索结果（一页）
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ObjectSearchResult {
+    /// 本页匹配的对象
+    pub objects: Vec<Object>,
+    /// 下一页的游标（没有更多结果时为空）
+    pub next_cursor: Option<String>,
+}
+
+/// 桶的元数据二级索引配置（为指定的用户元数据键建立索引，加速等值和范围查询）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetadataIndexConfiguration {
+    /// 建立索引的用户元数据键
+    #[serde(default)]
+    pub keys: Vec<String>,
+}
+
+/// 单个元数据索引的统计
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetadataIndexStats {
+    /// 用户元数据键
+    pub key: String,
+    /// 有该元数据的对象记录数（包括历史版本）
+    pub records: u64,
+    /// 不同值的个数
+    pub distinct_values: u64,
+}
+
+/// 桶的元数据索引配置及索引统计
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetadataIndexStatus {
+    pub configuration: MetadataIndexConfiguration,
+    pub indexes: Vec<MetadataIndexStats>,
+}
+
+/// 静态网站托管的重定向规则（按顺序匹配第一条）
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct WebsiteRedirectRule {
+    /// 条件：请求路径（对象键）的前缀，为空时匹配所有请求
+    #[serde(default)]
+    pub key_prefix: String,
+    /// 条件：只在对象不存在（404）时重定向；为空时在查找对象之前重定向
+    #[serde(default)]
+    pub http_error_code: Option<u16>,
+    /// 重定向到的主机名（为空时重定向到本站）
+    #[serde(default)]
+    pub host_name: Option<String>,
+    /// 重定向使用的协议（http 或 https，为空时与请求相同）
+    #[serde(default)]
+    pub protocol: Option<String>,
+    /// 用该值替换匹配的前缀（不能与 replace_key_with 同时设置）
+    #[serde(default)]
+    pub replace_key_prefix_with: Option<String>,
+    /// 用该值替换整个对象键
+    #[serde(default)]
+    pub replace_key_with: Option<String>,
+    /// 重定向状态码（301、302、303、307 或 308，默认 301）
+    #[serde(default = "default_redirect_code")]
+    pub http_redirect_code: u16,
+}
+
+fn default_redirect_code() -> u16 {
+    301
+}
+
+/// 桶的静态网站托管配置
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct WebsiteConfiguration {
+    /// 请求目录（空路径或以 / 结尾）时返回的索引文档
+    #[serde(default = "default_index_document")]
+    pub index_document: String,
+    /// 对象不存在时返回的错误文档（状态码 404）
+    #[serde(default)]
+    pub error_document: Option<String>,
+    /// 单页应用回退：不存在且最后一段没有扩展名的路径返回根索引文档（状态码 200）
+    #[serde(default)]
+    pub spa_fallback: bool,
+    /// 重定向规则
+    #[serde(default)]
+    pub redirect_rules: Vec<WebsiteRedirectRule>,
+    /// 非 HTML 对象的 Cache-Control 响应头
+    #[serde(default)]
+    pub cache_control: Option<String>,
+    /// HTML 页面（包括索引、错误和回退页面）的 Cache-Control 响应头
+    #[serde(default)]
+    pub html_cache_control: Option<String>,
+}
+
+fn default_index_document() -> String {
+    "index.html".to_string()
+}
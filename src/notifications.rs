@@ -0,0 +1,695 @@
+use crate::models::{EventType, NotificationConfiguration, ObjectEvent, ObjectMetadata};
+use crate::services::StorageService;
+use crate::utils::ip_in_cidr;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// 桶通知配置文件名（位于 .sevino.meta 目录下）
+pub const NOTIFICATION_CONFIG: &str = "notification";
+
+/// 首次重试的退避时间（秒）
+const BASE_BACKOFF_SECS: i64 = 2;
+/// 最大退避时间（秒）
+const MAX_BACKOFF_SECS: i64 = 3600;
+/// 发件箱轮询间隔（秒）
+const POLL_INTERVAL_SECS: u64 = 5;
+/// 同时投递的 Webhook 地址数
+const MAX_CONCURRENT_TARGETS: usize = 16;
+/// 默认不允许投递的地址：本机、回环、私有、链路本地（含云元数据服务）、共享地址、组播和保留地址
+const BLOCKED_NETWORKS: &[&str] = &[
+    "0.0.0.0/8", "10.0.0.0/8", "100.64.0.0/10", "127.0.0.0/8", "169.254.0.0/16", "172.16.0.0/12",
+    "192.0.0.0/24", "192.168.0.0/16", "198.18.0.0/15", "224.0.0.0/4", "240.0.0.0/4",
+    "::/128", "::1/128", "fc00::/7", "fe80::/10", "ff00::/8",
+];
+
+/// 发件箱中的待投递事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OutboxEntry {
+    /// 投递ID（每个事件 × 规则一条）
+    delivery_id: String,
+    /// 匹配的规则ID
+    rule_id: String,
+    /// Webhook地址
+    webhook_url: String,
+    /// 事件负载
+    event: ObjectEvent,
+    /// 已尝试次数
+    attempts: u32,
+    /// 下次尝试时间
+    next_attempt_at: DateTime<Utc>,
+    /// 最近一次失败原因
+    last_error: Option<String>,
+}
+
+/// 事件通知服务 - 将对象事件写入磁盘发件箱，并由后台任务投递到Webhook
+#[derive(Clone)]
+pub struct NotificationService {
+    storage: StorageService,
+    outbox_dir: PathBuf,
+    dead_letter_dir: PathBuf,
+    secret: String,
+    max_attempts: u32,
+    /// 每个 Webhook 地址同时进行的最大投递数
+    concurrency: usize,
+    /// 运维允许投递的内网地址（IP 或 CIDR）
+    allowed_networks: Arc<Vec<String>>,
+    client: reqwest::Client,
+    wakeup: Arc<Notify>,
+}
+
+impl NotificationService {
+    pub fn new(
+        storage: StorageService,
+        data_dir: &str,
+        secret: String,
+        max_attempts: u32,
+        timeout_secs: u64,
+        concurrency: usize,
+        allowed_networks: Vec<String>,
+    ) -> Result<Self> {
+        let events_dir = PathBuf::from(data_dir).join(".sevino.events");
+        let outbox_dir = events_dir.join("outbox");
+        let dead_letter_dir = events_dir.join("dead");
+        fs::create_dir_all(&outbox_dir)?;
+        fs::create_dir_all(&dead_letter_dir)?;
+
+        let any_ip = IpAddr::from([0, 0, 0, 0]);
+        for network in &allowed_networks {
+            ip_in_cidr(&any_ip, network).map_err(|e| anyhow!("Invalid SEVINO_WEBHOOK_ALLOWED_NETWORKS entry: {}", e))?;
+        }
+        let allowed_networks = Arc::new(allowed_networks);
+
+        // 连接前检查解析出的地址（防止 DNS 重绑定），不跟随重定向（重定向目标不经过地址检查）
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(WebhookResolver { allowed_networks: allowed_networks.clone() }))
+            .build()?;
+
+        Ok(Self {
+            storage,
+            outbox_dir,
+            dead_letter_dir,
+            secret,
+            max_attempts,
+            concurrency: concurrency.max(1),
+            allowed_networks,
+            client,
+            wakeup: Arc::new(Notify::new()),
+        })
+    }
+
+    /// 未设置签名密钥时不投递事件（接收端无法验证未签名的负载）
+    pub fn enabled(&self) -> bool {
+        !self.secret.is_empty()
+    }
+
+    /// 配置了通知规则的桶
+    pub async fn configured_buckets(&self) -> Result<Vec<String>> {
+        let mut buckets = Vec::new();
+        for name in self.storage.bucket_names().await {
+            let config: Option<NotificationConfiguration> = self.storage.load_bucket_config(&name, NOTIFICATION_CONFIG).await?;
+            if config.is_some_and(|config| !config.rules.is_empty()) {
+                buckets.push(name);
+            }
+        }
+        buckets.sort();
+        Ok(buckets)
+    }
+
+    /// 校验通知配置，并为缺少ID的规则生成ID
+    pub fn validate_configuration(&self, config: &mut NotificationConfiguration) -> Result<()> {
+        let mut seen_ids = std::collections::HashSet::new();
+
+        for rule in config.rules.iter_mut() {
+            if rule.id.is_empty() {
+                rule.id = uuid::Uuid::new_v4().to_string();
+            }
+            if !seen_ids.insert(rule.id.clone()) {
+                return Err(anyhow!("Duplicate notification rule id: {}", rule.id));
+            }
+            if rule.events.is_empty() {
+                return Err(anyhow!("Notification rule '{}' must subscribe to at least one event", rule.id));
+            }
+
+            let url = url::Url::parse(&rule.webhook_url)
+                .map_err(|e| anyhow!("Invalid webhook URL '{}': {}", rule.webhook_url, e))?;
+            if url.scheme() != "http" && url.scheme() != "https" {
+                return Err(anyhow!("Webhook URL must use http or https: {}", rule.webhook_url));
+            }
+            // 域名在投递时解析后再检查，这里只能拒绝字面IP和 localhost
+            match url.host() {
+                Some(url::Host::Ipv4(ip)) => check_webhook_address(IpAddr::V4(ip), &self.allowed_networks)?,
+                Some(url::Host::Ipv6(ip)) => check_webhook_address(IpAddr::V6(ip), &self.allowed_networks)?,
+                Some(url::Host::Domain(domain)) => {
+                    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+                    if domain == "localhost" || domain.ends_with(".localhost") {
+                        return Err(anyhow!("Webhook URL must not point to localhost: {}", rule.webhook_url));
+                    }
+                }
+                None => return Err(anyhow!("Webhook URL must have a host: {}", rule.webhook_url)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 发布对象事件：为每条匹配的规则写入一条发件箱记录
+    pub async fn publish(&self, event_type: EventType, metadata: &ObjectMetadata) -> Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        let config: Option<NotificationConfiguration> = self.storage
+            .load_bucket_config(&metadata.bucket_name, NOTIFICATION_CONFIG)
+            .await?;
+        let Some(config) = config else {
+            return Ok(());
+        };
+
+        let event = ObjectEvent {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            event_type,
+            event_time: Utc::now(),
+            bucket_name: metadata.bucket_name.clone(),
+            key: metadata.key.clone(),
+            size: metadata.size,
+            etag: metadata.etag.clone(),
+            version_id: metadata.version_id.clone(),
+        };
+
+        let mut queued = false;
+        for rule in config.rules.iter().filter(|rule| rule.matches(event_type, &metadata.key)) {
+            let entry = OutboxEntry {
+                delivery_id: format!("{}-{}", event.event_id, rule.id),
+                rule_id: rule.id.clone(),
+                webhook_url: rule.webhook_url.clone(),
+                event: event.clone(),
+                attempts: 0,
+                next_attempt_at: event.event_time,
+                last_error: None,
+            };
+            self.write_entry(&self.outbox_dir, &entry)?;
+            queued = true;
+        }
+
+        if queued {
+            self.wakeup.notify_one();
+        }
+
+        Ok(())
+    }
+
+    /// 启动后台投递任务（启动时会先处理重启前遗留的事件）
+    pub fn start_dispatcher(&self) {
+        if !self.enabled() {
+            return;
+        }
+        let service = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = service.process_outbox().await {
+                    tracing::warn!("Failed to process notification outbox: {}", e);
+                }
+
+                tokio::select! {
+                    _ = service.wakeup.notified() => {}
+                    _ = tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)) => {}
+                }
+            }
+        });
+    }
+
+    /// 处理发件箱中所有到期的事件：不同地址并行投递，同一地址最多同时进行 concurrency 个请求
+    async fn process_outbox(&self) -> Result<()> {
+        let mut entries: Vec<_> = fs::read_dir(&self.outbox_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .collect();
+
+        // 按文件名排序，尽量保持投递顺序
+        entries.sort();
+
+        let now = Utc::now();
+        // Webhook地址 -> 到期的事件（保持文件名顺序）
+        let mut targets: Vec<(String, Vec<(PathBuf, OutboxEntry)>)> = Vec::new();
+        for path in entries {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            let entry: OutboxEntry = match serde_json::from_str(&content) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("Discarding malformed outbox entry {:?}: {}", path, e);
+                    let _ = fs::rename(&path, self.dead_letter_dir.join(path.file_name().unwrap_or_default()));
+                    continue;
+                }
+            };
+
+            if entry.next_attempt_at > now {
+                continue;
+            }
+
+            match targets.iter_mut().find(|(url, _)| *url == entry.webhook_url) {
+                Some((_, due)) => due.push((path, entry)),
+                None => targets.push((entry.webhook_url.clone(), vec![(path, entry)])),
+            }
+        }
+
+        futures_util::stream::iter(targets)
+            .for_each_concurrent(MAX_CONCURRENT_TARGETS, |(_, due)| async move {
+                futures_util::stream::iter(due)
+                    .for_each_concurrent(self.concurrency, |(path, entry)| async move {
+                        if let Err(e) = self.process_entry(&path, entry).await {
+                            tracing::warn!("Failed to update notification outbox entry {:?}: {}", path, e);
+                        }
+                    })
+                    .await;
+            })
+            .await;
+
+        Ok(())
+    }
+
+    /// 投递一条到期的事件：成功后删除记录，失败时按指数退避重新排期或移入死信目录
+    async fn process_entry(&self, path: &std::path::Path, mut entry: OutboxEntry) -> Result<()> {
+        match self.deliver(&entry).await {
+            Ok(()) => {
+                fs::remove_file(path)?;
+            }
+            Err(e) => {
+                entry.attempts += 1;
+                entry.last_error = Some(e.to_string());
+
+                if entry.attempts >= self.max_attempts {
+                    tracing::warn!(
+                        "Giving up on webhook delivery {} to {} after {} attempts: {}",
+                        entry.delivery_id, entry.webhook_url, entry.attempts, e
+                    );
+                    self.write_entry(&self.dead_letter_dir, &entry)?;
+                    fs::remove_file(path)?;
+                } else {
+                    let backoff = (BASE_BACKOFF_SECS << (entry.attempts - 1).min(20)).min(MAX_BACKOFF_SECS);
+                    entry.next_attempt_at = Utc::now() + Duration::seconds(backoff);
+                    self.write_entry(&self.outbox_dir, &entry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 投递单个事件（HMAC-SHA256签名）
+    async fn deliver(&self, entry: &OutboxEntry) -> Result<()> {
+        // 字面IP不经过解析器，在这里检查（配置保存后允许的地址范围可能已经改变）
+        let url = url::Url::parse(&entry.webhook_url)?;
+        match url.host() {
+            Some(url::Host::Ipv4(ip)) => check_webhook_address(IpAddr::V4(ip), &self.allowed_networks)?,
+            Some(url::Host::Ipv6(ip)) => check_webhook_address(IpAddr::V6(ip), &self.allowed_networks)?,
+            _ => {}
+        }
+
+        let body = serde_json::to_vec(&entry.event)?;
+        // 签名覆盖投递时间戳，接收端拒绝时间戳过旧的请求即可防止重放
+        let timestamp = Utc::now().timestamp();
+        let signature = self.sign(timestamp, &body)?;
+
+        let response = self.client
+            .post(&entry.webhook_url)
+            .header("Content-Type", "application/json")
+            .header("X-Sevino-Event", format!("{:?}", entry.event.event_type))
+            .header("X-Sevino-Delivery", &entry.delivery_id)
+            .header("X-Sevino-Timestamp", timestamp.to_string())
+            .header("X-Sevino-Signature", format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Webhook responded with status {}", response.status()));
+        }
+
+        Ok(())
+    }
+
+    /// 计算 `{timestamp}.{body}` 的HMAC-SHA256签名（十六进制）
+    fn sign(&self, timestamp: i64, body: &[u8]) -> Result<String> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .map_err(|e| anyhow!("Invalid webhook secret: {}", e))?;
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        Ok(format!("{:x}", mac.finalize().into_bytes()))
+    }
+
+    /// 原子写入发件箱记录（先写临时文件再重命名）
+    fn write_entry(&self, dir: &std::path::Path, entry: &OutboxEntry) -> Result<()> {
+        let file_name = format!("{}-{}.json", entry.event.event_time.format("%Y%m%d%H%M%S%f"), entry.delivery_id);
+        let tmp_path = dir.join(format!(".{}.tmp", file_name));
+        fs::write(&tmp_path, serde_json::to_string_pretty(entry)?)?;
+        fs::rename(&tmp_path, dir.join(file_name))?;
+        Ok(())
+    }
+}
+
+/// Webhook 地址是否允许投递：运维允许的网络优先，否则拒绝内网和保留地址
+fn check_webhook_address(ip: IpAddr, allowed_networks: &[String]) -> Result<()> {
+    let allowed = |networks: &[String]| networks.iter().any(|network| ip_in_cidr(&ip, network).unwrap_or(false));
+    if allowed(allowed_networks) {
+        return Ok(());
+    }
+    if BLOCKED_NETWORKS.iter().any(|network| ip_in_cidr(&ip, network).unwrap_or(false)) {
+        return Err(anyhow!(
+            "Webhook address {} is a loopback, private or reserved address; allow it with SEVINO_WEBHOOK_ALLOWED_NETWORKS",
+            ip
+        ));
+    }
+    Ok(())
+}
+
+/// Webhook 请求的 DNS 解析器：解析结果中不允许投递的地址被丢弃，全部不允许时解析失败
+struct WebhookResolver {
+    allowed_networks: Arc<Vec<String>>,
+}
+
+impl reqwest::dns::Resolve for WebhookResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        let allowed_networks = self.allowed_networks.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let mut addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let resolved = addrs.len();
+            addrs.retain(|addr| check_webhook_address(addr.ip(), &allowed_networks).is_ok());
+            if addrs.is_empty() {
+                let message = if resolved == 0 {
+                    format!("Webhook host '{}' did not resolve to any address", host)
+                } else {
+                    format!("Webhook host '{}' resolves only to loopback, private or reserved addresses", host)
+                };
+                return Err(message.into());
+            }
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NotificationRule;
+    use std::str::FromStr;
+
+    async fn service(dir: &tempfile::TempDir, allowed_networks: &[&str]) -> NotificationService {
+        let data_dir = dir.path().to_string_lossy().into_owned();
+        let storage = StorageService::new(data_dir.clone()).await.unwrap();
+        let allowed_networks = allowed_networks.iter().map(|s| s.to_string()).collect();
+        NotificationService::new(storage, &data_dir, "secret".to_string(), 3, 5, 4, allowed_networks).unwrap()
+    }
+
+    /// 创建桶并保存通知配置
+    async fn configure(service: &NotificationService, rules: Vec<NotificationRule>) {
+        crate::services::BucketService::new(service.storage.clone()).create_bucket("photos".to_string()).await.unwrap();
+        let config = NotificationConfiguration { rules };
+        service.storage.save_bucket_config("photos", NOTIFICATION_CONFIG, &config).await.unwrap();
+    }
+
+    fn rule(id: &str, suffix: Option<&str>, webhook_url: &str) -> NotificationRule {
+        NotificationRule {
+            id: id.to_string(),
+            events: vec![EventType::ObjectCreated],
+            prefix: None,
+            suffix: suffix.map(|s| s.to_string()),
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+
+    fn object(key: &str) -> ObjectMetadata {
+        crate::models::Object::new(key.to_string(), "photos".to_string(), 5, "image/png".to_string(), "etag".to_string(), Default::default()).into()
+    }
+
+    fn entries(dir: &std::path::Path) -> Vec<OutboxEntry> {
+        let mut entries: Vec<OutboxEntry> = fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .map(|path| serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+        entries.sort_by(|a, b| a.rule_id.cmp(&b.rule_id));
+        entries
+    }
+
+    /// 把发件箱中的记录改为已到期
+    fn make_due(service: &NotificationService) {
+        for mut entry in entries(&service.outbox_dir) {
+            entry.next_attempt_at = Utc::now() - Duration::seconds(1);
+            service.write_entry(&service.outbox_dir, &entry).unwrap();
+        }
+    }
+
+    /// 只处理一个连接的 HTTP 接收端，返回地址和收到的请求头
+    async fn receiver(status: u16) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head.lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if body.len() >= length || n == 0 {
+                        break;
+                    }
+                }
+            }
+            let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_ascii_lowercase()
+        });
+        (url, handle)
+    }
+
+    fn config(webhook_url: &str) -> NotificationConfiguration {
+        NotificationConfiguration {
+            rules: vec![NotificationRule {
+                id: String::new(),
+                events: vec![EventType::ObjectCreated],
+                prefix: None,
+                suffix: None,
+                webhook_url: webhook_url.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn blocks_internal_addresses_by_default() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1"] {
+            assert!(check_webhook_address(ip.parse().unwrap(), &[]).is_err(), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700::1111"] {
+            assert!(check_webhook_address(ip.parse().unwrap(), &[]).is_ok(), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn allowed_networks_override_the_block_list() {
+        let allowed = vec!["10.20.0.0/16".to_string(), "127.0.0.1".to_string()];
+        assert!(check_webhook_address("10.20.3.4".parse().unwrap(), &allowed).is_ok());
+        assert!(check_webhook_address("127.0.0.1".parse().unwrap(), &allowed).is_ok());
+        assert!(check_webhook_address("10.21.0.1".parse().unwrap(), &allowed).is_err());
+        assert!(check_webhook_address("169.254.169.254".parse().unwrap(), &allowed).is_err());
+    }
+
+    #[tokio::test]
+    async fn validate_configuration_rejects_internal_hosts() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &[]).await;
+
+        for url in ["http://127.0.0.1:9000/hook", "http://169.254.169.254/latest/meta-data", "http://[::1]/hook", "http://localhost/hook", "http://api.localhost./hook", "ftp://example.com/hook"] {
+            assert!(service.validate_configuration(&mut config(url)).is_err(), "{}", url);
+        }
+
+        let mut valid = config("https://hooks.example.com/sevino");
+        service.validate_configuration(&mut valid).unwrap();
+        assert!(!valid.rules[0].id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn validate_configuration_accepts_allowed_networks() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &["127.0.0.0/8"]).await;
+        service.validate_configuration(&mut config("http://127.0.0.1:9000/hook")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalid_allowed_network_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().to_string_lossy().into_owned();
+        let storage = StorageService::new(data_dir.clone()).await.unwrap();
+        assert!(NotificationService::new(storage, &data_dir, "secret".to_string(), 3, 5, 4, vec!["10.0.0.0/99".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn publish_queues_one_entry_per_matching_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &[]).await;
+        configure(&service, vec![
+            rule("a", Some(".png"), "https://a.example.com/hook"),
+            rule("b", Some(".jpg"), "https://b.example.com/hook"),
+            rule("c", None, "https://c.example.com/hook"),
+        ]).await;
+
+        service.publish(EventType::ObjectCreated, &object("x.png")).await.unwrap();
+        service.publish(EventType::ObjectRemoved, &object("x.png")).await.unwrap();
+
+        let queued = entries(&service.outbox_dir);
+        assert_eq!(queued.iter().map(|e| e.rule_id.as_str()).collect::<Vec<_>>(), ["a", "c"]);
+        assert!(queued.iter().all(|e| e.attempts == 0 && e.event.key == "x.png"));
+        assert_eq!(queued[0].event.event_id, queued[1].event.event_id);
+        assert_ne!(queued[0].delivery_id, queued[1].delivery_id);
+    }
+
+    #[tokio::test]
+    async fn publish_is_disabled_without_a_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().to_string_lossy().into_owned();
+        let storage = StorageService::new(data_dir.clone()).await.unwrap();
+        let service = NotificationService::new(storage, &data_dir, String::new(), 3, 5, 4, Vec::new()).unwrap();
+        configure(&service, vec![rule("a", None, "https://a.example.com/hook")]).await;
+
+        service.publish(EventType::ObjectCreated, &object("x.png")).await.unwrap();
+        assert!(entries(&service.outbox_dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn successful_delivery_removes_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &["127.0.0.1"]).await;
+        let (url, received) = receiver(204).await;
+        configure(&service, vec![rule("a", None, &url)]).await;
+
+        service.publish(EventType::ObjectCreated, &object("x.png")).await.unwrap();
+        service.process_outbox().await.unwrap();
+
+        assert!(entries(&service.outbox_dir).is_empty());
+        assert!(entries(&service.dead_letter_dir).is_empty());
+        let request = received.await.unwrap();
+        assert!(request.contains("x-sevino-signature: sha256="));
+        assert!(request.contains("x-sevino-timestamp: "));
+        assert!(request.contains("\"key\":\"x.png\""));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_with_exponential_backoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &["127.0.0.1"]).await;
+        let (url, received) = receiver(500).await;
+        configure(&service, vec![rule("a", None, &url)]).await;
+
+        service.publish(EventType::ObjectCreated, &object("x.png")).await.unwrap();
+        let before = Utc::now();
+        service.process_outbox().await.unwrap();
+        received.await.unwrap();
+
+        let entry = entries(&service.outbox_dir).pop().unwrap();
+        assert_eq!(entry.attempts, 1);
+        assert!(entry.last_error.as_deref().unwrap().contains("500"));
+        let delay = entry.next_attempt_at - before;
+        assert!(delay >= Duration::milliseconds(1900) && delay <= Duration::seconds(3), "{:?}", delay);
+
+        // 未到期的记录不会被投递
+        service.process_outbox().await.unwrap();
+        assert_eq!(entries(&service.outbox_dir).pop().unwrap().attempts, 1);
+
+        // 第二次失败后退避时间加倍
+        let (url, received) = receiver(503).await;
+        let mut entry = entries(&service.outbox_dir).pop().unwrap();
+        entry.webhook_url = url;
+        entry.next_attempt_at = Utc::now() - Duration::seconds(1);
+        service.write_entry(&service.outbox_dir, &entry).unwrap();
+        let before = Utc::now();
+        service.process_outbox().await.unwrap();
+        received.await.unwrap();
+
+        let entry = entries(&service.outbox_dir).pop().unwrap();
+        assert_eq!(entry.attempts, 2);
+        let delay = entry.next_attempt_at - before;
+        assert!(delay >= Duration::milliseconds(3900) && delay <= Duration::seconds(5), "{:?}", delay);
+    }
+
+    #[tokio::test]
+    async fn delivery_moves_to_dead_letters_after_max_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        // 回环地址未放行，每次投递都会失败
+        let service = service(&dir, &[]).await;
+        configure(&service, vec![rule("a", None, "http://127.0.0.1:9/hook")]).await;
+        service.publish(EventType::ObjectCreated, &object("x.png")).await.unwrap();
+
+        for attempt in 1..=2 {
+            service.process_outbox().await.unwrap();
+            assert_eq!(entries(&service.outbox_dir).pop().unwrap().attempts, attempt);
+            make_due(&service);
+        }
+        service.process_outbox().await.unwrap();
+
+        assert!(entries(&service.outbox_dir).is_empty());
+        let dead = entries(&service.dead_letter_dir).pop().unwrap();
+        assert_eq!(dead.attempts, 3);
+        assert!(dead.last_error.unwrap().contains("loopback"));
+    }
+
+    #[tokio::test]
+    async fn malformed_entries_are_moved_to_dead_letters() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &[]).await;
+        fs::write(service.outbox_dir.join("broken.json"), "{not json").unwrap();
+
+        service.process_outbox().await.unwrap();
+        assert!(!service.outbox_dir.join("broken.json").exists());
+        assert!(service.dead_letter_dir.join("broken.json").exists());
+    }
+
+    #[tokio::test]
+    async fn signature_covers_timestamp_and_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service(&dir, &[]).await;
+        let body = br#"{"key":"a.txt"}"#;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(br#"1760000000.{"key":"a.txt"}"#);
+        let expected = format!("{:x}", mac.finalize().into_bytes());
+
+        assert_eq!(service.sign(1760000000, body).unwrap(), expected);
+        assert_ne!(service.sign(1760000001, body).unwrap(), expected);
+        assert_ne!(service.sign(1760000000, br#"{"key":"b.txt"}"#).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn resolver_drops_blocked_addresses() {
+        let resolver = WebhookResolver { allowed_networks: Arc::new(Vec::new()) };
+        let name = hyper::client::connect::dns::Name::from_str("localhost").unwrap();
+        let err = reqwest::dns::Resolve::resolve(&resolver, name).await.err().unwrap();
+        assert!(err.to_string().contains("localhost"));
+
+        let resolver = WebhookResolver { allowed_networks: Arc::new(vec!["127.0.0.0/8".to_string(), "::1".to_string()]) };
+        let name = hyper::client::connect::dns::Name::from_str("localhost").unwrap();
+        let addrs: Vec<SocketAddr> = reqwest::dns::Resolve::resolve(&resolver, name).await.unwrap().collect();
+        assert!(!addrs.is_empty());
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback()));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::models::{ObjectLockConfiguration, ObjectLockMode, ObjectMetadata};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+
+/// 桶对象锁定配置文件名（位于 .sevino.meta 目录下）
+pub const OBJECT_LOCK_CONFIG: &str = "object_lock";
+
+/// 校验对象锁定配置（启用后不能关闭）
+pub fn validate_configuration(existing: Option<&ObjectLockConfiguration>, config: &ObjectLockConfiguration) -> Result<()> {
+    if existing.map(|c| c.enabled).unwrap_or(false) && !config.enabled {
+        return Err(anyhow!("Object lock cannot be disabled once it has been enabled"));
+    }
+
+    if let Some(retention) = &config.default_retention {
+        if !config.enabled {
+            return Err(anyhow!("Default retention requires object lock to be enabled"));
+        }
+        if retention.days == 0 {
+            return Err(anyhow!("Default retention days must be greater than 0"));
+        }
+    }
+
+    Ok(())
+}
+
+/// 检查对象是否允许删除或修改
+///
+/// 返回 `Ok(true)` 表示治理模式保护被绕过，调用方需要写入审计日志。
+pub fn check_object_mutable(metadata: &ObjectMetadata, bypass_governance: bool) -> Result<bool> {
+    if metadata.legal_hold {
+        return Err(anyhow!("Object '{}' is under legal hold", metadata.key));
+    }
+
+    if let Some(until) = metadata.retain_until {
+        if until > Utc::now() {
+            return match metadata.retention_mode {
+                Some(ObjectLockMode::Governance) if bypass_governance => Ok(true),
+                Some(ObjectLockMode::Governance) => Err(anyhow!(
+                    "Object '{}' is locked in governance mode until {}",
+                    metadata.key, until.to_rfc3339()
+                )),
+                _ => Err(anyhow!(
+                    "Object '{}' is locked in compliance mode until {}",
+                    metadata.key, until.to_rfc3339()
+                )),
+            };
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DefaultRetention, Object};
+    use chrono::Duration;
+
+    fn metadata() -> ObjectMetadata {
+        Object::new("a.txt".to_string(), "b".to_string(), 1, "text/plain".to_string(), "etag".to_string(), Default::default()).into()
+    }
+
+    fn locked(mode: ObjectLockMode, until: Duration) -> ObjectMetadata {
+        let mut metadata = metadata();
+        metadata.retention_mode = Some(mode);
+        metadata.retain_until = Some(Utc::now() + until);
+        metadata
+    }
+
+    #[test]
+    fn unlocked_and_expired_objects_are_mutable() {
+        assert!(!check_object_mutable(&metadata(), false).unwrap());
+        assert!(!check_object_mutable(&locked(ObjectLockMode::Compliance, Duration::seconds(-1)), false).unwrap());
+    }
+
+    #[test]
+    fn governance_retention_can_only_be_bypassed_explicitly() {
+        let metadata = locked(ObjectLockMode::Governance, Duration::days(1));
+        assert!(check_object_mutable(&metadata, false).unwrap_err().to_string().contains("governance"));
+        assert!(check_object_mutable(&metadata, true).unwrap());
+    }
+
+    #[test]
+    fn compliance_retention_cannot_be_bypassed() {
+        let metadata = locked(ObjectLockMode::Compliance, Duration::days(1));
+        assert!(check_object_mutable(&metadata, false).unwrap_err().to_string().contains("compliance"));
+        assert!(check_object_mutable(&metadata, true).is_err());
+    }
+
+    #[test]
+    fn legal_hold_blocks_even_with_bypass() {
+        let mut metadata = metadata();
+        metadata.legal_hold = true;
+        assert!(check_object_mutable(&metadata, true).unwrap_err().to_string().contains("legal hold"));
+
+        let mut metadata = locked(ObjectLockMode::Governance, Duration::seconds(-1));
+        metadata.legal_hold = true;
+        assert!(check_object_mutable(&metadata, true).is_err());
+    }
+
+    #[test]
+    fn configuration_cannot_be_disabled_once_enabled() {
+        let enabled = ObjectLockConfiguration { enabled: true, default_retention: None };
+        let disabled = ObjectLockConfiguration::default();
+
+        assert!(validate_configuration(None, &enabled).is_ok());
+        assert!(validate_configuration(Some(&disabled), &enabled).is_ok());
+        assert!(validate_configuration(Some(&enabled), &enabled).is_ok());
+        assert!(validate_configuration(Some(&enabled), &disabled).is_err());
+    }
+
+    #[test]
+    fn default_retention_requires_lock_and_positive_days() {
+        let retention = |days| Some(DefaultRetention { mode: ObjectLockMode::Governance, days });
+
+        assert!(validate_configuration(None, &ObjectLockConfiguration { enabled: true, default_retention: retention(30) }).is_ok());
+        assert!(validate_configuration(None, &ObjectLockConfiguration { enabled: true, default_retention: retention(0) }).is_err());
+        assert!(validate_configuration(None, &ObjectLockConfiguration { enabled: false, default_retention: retention(30) }).is_err());
+    }
+}
@@ -0,0 +1,655 @@
+use crate::models::{BucketPolicy, PolicyDecision, PolicyEffect, PolicyEvaluation, PolicySimulationRequest, PolicyStatement};
+use crate::services::StorageService;
+use crate::utils::{constant_time_eq, ip_in_cidr, wildcard_match};
+use anyhow::{Result, anyhow};
+use axum::http::{HeaderMap, Method};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// 桶策略配置文件名（位于 .sevino.meta 目录下）
+pub const POLICY_CONFIG: &str = "policy";
+
+/// 未携带访问密钥的请求主体
+pub const ANONYMOUS_PRINCIPAL: &str = "anonymous";
+
+/// 持有管理员令牌的请求主体（不受桶策略限制）
+pub const ADMIN_PRINCIPAL: &str = "admin";
+
+/// 请求身份
+#[derive(Debug, Clone)]
+pub struct Identity {
+    /// 主体名称
+    pub principal: String,
+    /// 是否为管理员
+    pub is_admin: bool,
+}
+
+/// 授权请求
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub principal: String,
+    pub action: String,
+    pub bucket_name: String,
+    pub key: Option<String>,
+    pub prefix: Option<String>,
+    pub source_ip: Option<IpAddr>,
+    pub secure_transport: bool,
+    /// 目标对象的标签（为空时按需从对象元数据加载）
+    pub object_tags: Option<HashMap<String, String>>,
+}
+
+/// 请求者信息，用于在请求路径之外（包括后台任务中）逐个检查对象权限
+#[derive(Debug, Clone)]
+pub struct Requester {
+    pub identity: Identity,
+    pub source_ip: Option<IpAddr>,
+    pub secure_transport: bool,
+}
+
+/// 请求对应的操作和资源
+#[derive(Debug, Clone)]
+pub struct RequestTarget {
+    pub action: &'static str,
+    pub bucket_name: String,
+    pub key: Option<String>,
+}
+
+/// 桶策略服务 - 解析请求身份并按桶策略评估授权
+#[derive(Clone)]
+pub struct PolicyService {
+    storage: StorageService,
+    /// 访问密钥 -> 主体名称
+    access_keys: HashMap<String, String>,
+    admin_token: Option<String>,
+}
+
+impl PolicyService {
+    /// `access_keys` 为 主体名称 -> 访问密钥
+    pub fn new(storage: StorageService, access_keys: &HashMap<String, String>, admin_token: Option<String>) -> Self {
+        let access_keys = access_keys
+            .iter()
+            .map(|(principal, key)| (key.clone(), principal.clone()))
+            .collect();
+        Self { storage, access_keys, admin_token }
+    }
+
+    /// 校验桶策略文档
+    pub fn validate_policy(bucket_name: &str, policy: &BucketPolicy) -> Result<()> {
+        if policy.statements.is_empty() {
+            return Err(anyhow!("Bucket policy must contain at least one statement"));
+        }
+
+        for (i, statement) in policy.statements.iter().enumerate() {
+            let name = statement.sid.clone().unwrap_or_else(|| format!("#{}", i + 1));
+
+            if statement.principals.is_empty() || statement.actions.is_empty() || statement.resources.is_empty() {
+                return Err(anyhow!("Statement '{}' must specify principals, actions and resources", name));
+            }
+            if let Some(action) = statement.actions.iter().find(|a| !a.starts_with("sevino:")) {
+                return Err(anyhow!("Statement '{}': action '{}' must start with 'sevino:'", name, action));
+            }
+            if let Some(resource) = statement.resources.iter().find(|r| {
+                r.as_str() != "*" && r.as_str() != bucket_name && !r.starts_with(&format!("{}/", bucket_name))
+            }) {
+                return Err(anyhow!("Statement '{}': resource '{}' does not belong to bucket '{}'", name, resource, bucket_name));
+            }
+
+            let any_ip: IpAddr = "0.0.0.0".parse()?;
+            for cidr in &statement.conditions.source_ip {
+                ip_in_cidr(&any_ip, cidr).map_err(|e| anyhow!("Statement '{}': {}", name, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 根据请求头解析身份（Authorization: Bearer <key> 或 X-Sevino-Access-Key）
+    pub fn resolve_identity(&self, headers: &HeaderMap) -> Result<Identity> {
+        if let Some(token) = headers.get("x-sevino-admin-token").and_then(|v| v.to_str().ok()) {
+            return match &self.admin_token {
+                Some(expected) if constant_time_eq(expected, token) => Ok(Identity {
+                    principal: ADMIN_PRINCIPAL.to_string(),
+                    is_admin: true,
+                }),
+                _ => Err(anyhow!("Invalid admin token")),
+            };
+        }
+
+        let access_key = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| headers.get("x-sevino-access-key").and_then(|v| v.to_str().ok()));
+
+        match access_key {
+            Some(key) => self.access_keys
+                .get(key.trim())
+                .map(|principal| Identity {
+                    principal: principal.clone(),
+                    is_admin: false,
+                })
+                .ok_or_else(|| anyhow!("Invalid access key")),
+            None => Ok(Identity {
+                principal: ANONYMOUS_PRINCIPAL.to_string(),
+                is_admin: false,
+            }),
+        }
+    }
+
+    /// 按桶策略评估请求
+    pub async fn authorize(&self, request: &AuthorizationRequest) -> Result<PolicyEvaluation> {
+        let policy: Option<BucketPolicy> = self.storage.load_bucket_config(&request.bucket_name, POLICY_CONFIG).await?;
+
+        // 只有策略使用了标签条件时才加载对象的标签
+        let uses_tags = policy.as_ref()
+            .is_some_and(|p| p.statements.iter().any(|s| !s.conditions.object_tags.is_empty()));
+        if let (true, None, Some(key)) = (uses_tags, &request.object_tags, &request.key) {
+            let mut request = request.clone();
+            request.object_tags = Some(self.load_object_tags(&request.bucket_name, key).await?);
+            return Ok(evaluate_policy(policy.as_ref(), &request));
+        }
+
+        Ok(evaluate_policy(policy.as_ref(), request))
+    }
+
+    /// 检查请求者对某个对象的权限，拒绝时返回原因；管理员总是允许
+    pub async fn authorize_key(&self, requester: &Requester, action: &str, bucket_name: &str, key: &str) -> Result<Option<String>> {
+        if requester.identity.is_admin {
+            return Ok(None);
+        }
+        let request = AuthorizationRequest {
+            principal: requester.identity.principal.clone(),
+            action: action.to_string(),
+            bucket_name: bucket_name.to_string(),
+            key: Some(key.to_string()),
+            prefix: None,
+            source_ip: requester.source_ip,
+            secure_transport: requester.secure_transport,
+            object_tags: None,
+        };
+        let evaluation = self.authorize(&request).await?;
+        Ok((!evaluation.allowed).then(|| {
+            format!("Access denied: '{}' is not allowed to perform {} on '{}'", evaluation.principal, action, evaluation.resource)
+        }))
+    }
+
+    /// 对象当前版本的标签（对象不存在时为空）
+    async fn load_object_tags(&self, bucket_name: &str, key: &str) -> Result<HashMap<String, String>> {
+        let Some(object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? else {
+            return Ok(HashMap::new());
+        };
+        Ok(self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .map(|metadata| metadata.tags)
+            .unwrap_or_default())
+    }
+
+    /// 模拟评估（dry-run），返回每条语句的匹配说明
+    pub async fn simulate(&self, bucket_name: &str, simulation: PolicySimulationRequest) -> Result<PolicyEvaluation> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+
+        let source_ip = match &simulation.source_ip {
+            Some(ip) => Some(ip.parse().map_err(|_| anyhow!("Invalid source IP: {}", ip))?),
+            None => None,
+        };
+
+        let request = AuthorizationRequest {
+            principal: simulation.principal.unwrap_or_else(|| ANONYMOUS_PRINCIPAL.to_string()),
+            action: simulation.action,
+            bucket_name: bucket_name.to_string(),
+            key: simulation.key,
+            prefix: simulation.prefix,
+            source_ip,
+            secure_transport: simulation.secure_transport,
+            object_tags: simulation.object_tags,
+        };
+
+        self.authorize(&request).await
+    }
+}
+
+/// 评估策略：显式 Deny 优先，其次任意 Allow，否则隐式拒绝；未配置策略时允许
+pub fn evaluate_policy(policy: Option<&BucketPolicy>, request: &AuthorizationRequest) -> PolicyEvaluation {
+    let resource = match &request.key {
+        Some(key) => format!("{}/{}", request.bucket_name, key),
+        None => request.bucket_name.clone(),
+    };
+
+    let Some(policy) = policy else {
+        return PolicyEvaluation {
+            decision: PolicyDecision::NoPolicy,
+            allowed: true,
+            principal: request.principal.clone(),
+            resource,
+            explanations: vec!["Bucket has no policy; request is allowed by default".to_string()],
+        };
+    };
+
+    let mut explanations = Vec::new();
+    let mut allowed = false;
+    let mut denied = false;
+
+    for (i, statement) in policy.statements.iter().enumerate() {
+        let name = statement.sid.clone().unwrap_or_else(|| format!("#{}", i + 1));
+        match statement_mismatch(statement, request, &resource) {
+            None => {
+                explanations.push(format!("Statement '{}' matched with effect {:?}", name, statement.effect));
+                match statement.effect {
+                    PolicyEffect::Allow => allowed = true,
+                    PolicyEffect::Deny => denied = true,
+                }
+            }
+            Some(reason) => explanations.push(format!("Statement '{}' did not match: {}", name, reason)),
+        }
+    }
+
+    let decision = if denied {
+        PolicyDecision::ExplicitDeny
+    } else if allowed {
+        PolicyDecision::Allow
+    } else {
+        explanations.push("No Allow statement matched; request is implicitly denied".to_string());
+        PolicyDecision::ImplicitDeny
+    };
+
+    PolicyEvaluation {
+        decision,
+        allowed: decision == PolicyDecision::Allow,
+        principal: request.principal.clone(),
+        resource,
+        explanations,
+    }
+}
+
+/// 检查语句是否匹配请求，不匹配时返回原因
+fn statement_mismatch(statement: &PolicyStatement, request: &AuthorizationRequest, resource: &str) -> Option<String> {
+    if !statement.principals.iter().any(|p| wildcard_match(p, &request.principal)) {
+        return Some(format!("principal '{}' is not listed", request.principal));
+    }
+    if !statement.actions.iter().any(|a| wildcard_match(a, &request.action)) {
+        return Some(format!("action '{}' is not listed", request.action));
+    }
+    if !statement.resources.iter().any(|r| wildcard_match(r, resource)) {
+        return Some(format!("resource '{}' is not listed", resource));
+    }
+
+    let conditions = &statement.conditions;
+    if !conditions.source_ip.is_empty() {
+        let ip_matches = request.source_ip
+            .map(|ip| conditions.source_ip.iter().any(|cidr| ip_in_cidr(&ip, cidr).unwrap_or(false)))
+            .unwrap_or(false);
+        if !ip_matches {
+            let ip = request.source_ip.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".to_string());
+            return Some(format!("source IP {} is not in {:?}", ip, conditions.source_ip));
+        }
+    }
+    if !conditions.prefix.is_empty() {
+        let target = request.key.as_ref().or(request.prefix.as_ref());
+        let prefix_matches = target
+            .map(|t| conditions.prefix.iter().any(|p| t.starts_with(p.as_str())))
+            .unwrap_or(false);
+        if !prefix_matches {
+            return Some(format!("key/prefix {:?} does not start with any of {:?}", target, conditions.prefix));
+        }
+    }
+    if let Some(secure) = conditions.secure_transport {
+        if secure != request.secure_transport {
+            return Some(format!("secure transport is {} but {} is required", request.secure_transport, secure));
+        }
+    }
+    if !conditions.object_tags.is_empty() {
+        let empty = HashMap::new();
+        let tags = request.object_tags.as_ref().unwrap_or(&empty);
+        if let Some((key, value)) = conditions.object_tags.iter().find(|(k, v)| tags.get(*k) != Some(*v)) {
+            return Some(format!("object tag '{}={}' is not present", key, value));
+        }
+    }
+
+    None
+}
+
+/// 根据请求方法和路径确定操作和资源（非桶级接口返回None）
+pub fn classify_request(method: &Method, path: &str) -> Option<RequestTarget> {
+    let rest = path.strip_prefix("/api/buckets/")?;
+    let segments: Vec<String> = rest
+        .split('/')
+        .map(|s| percent_encoding::percent_decode_str(s).decode_utf8_lossy().into_owned())
+        .collect();
+
+    let bucket_name = segments.first()?.clone();
+    let key = segments.get(2).cloned();
+    let sub: Vec<&str> = segments.iter().skip(1).map(|s| s.as_str()).collect();
+    // axum 的 GET 路由同样响应 HEAD，HEAD 请求按对应的 GET 操作授权
+    let method = if method == Method::HEAD { "GET" } else { method.as_str() };
+
+    let (action, object_level) = match (method, sub.as_slice()) {
+        ("GET", []) => ("sevino:GetBucket", false),
+        ("DELETE", []) => ("sevino:DeleteBucket", false),
+        ("GET", ["stats"]) => ("sevino:GetBucketStats", false),
+        ("GET", ["notification"]) => ("sevino:GetBucketNotification", false),
+        ("PUT", ["notification"]) => ("sevino:PutBucketNotification", false),
+        ("DELETE", ["notification"]) => ("sevino:DeleteBucketNotification", false),
+        ("GET", ["lifecycle"]) => ("sevino:GetLifecycleConfiguration", false),
+        ("PUT", ["lifecycle"]) => ("sevino:PutLifecycleConfiguration", false),
+        ("DELETE", ["lifecycle"]) => ("sevino:DeleteLifecycleConfiguration", false),
+        ("POST", ["lifecycle", "run"]) => ("sevino:RunLifecycle", false),
+        ("GET", ["object-lock"]) => ("sevino:GetBucketObjectLockConfiguration", false),
+        ("PUT", ["object-lock"]) => ("sevino:PutBucketObjectLockConfiguration", false),
+        ("GET", ["policy"]) => ("sevino:GetBucketPolicy", false),
+        ("PUT", ["policy"]) => ("sevino:PutBucketPolicy", false),
+        ("DELETE", ["policy"]) => ("sevino:DeleteBucketPolicy", false),
+        ("POST", ["policy", "simulate"]) => ("sevino:GetBucketPolicy", false),
+        ("GET", ["encryption"]) => ("sevino:GetBucketEncryption", false),
+        ("PUT", ["encryption"]) => ("sevino:PutBucketEncryption", false),
+        ("GET", ["compression"]) => ("sevino:GetBucketCompression", false),
+        ("PUT", ["compression"]) => ("sevino:PutBucketCompression", false),
+        ("DELETE", ["compression"]) => ("sevino:DeleteBucketCompression", false),
+        ("GET", ["content-type"]) => ("sevino:GetBucketContentType", false),
+        ("PUT", ["content-type"]) => ("sevino:PutBucketContentType", false),
+        ("DELETE", ["content-type"]) => ("sevino:DeleteBucketContentType", false),
+        ("GET", ["quota"]) => ("sevino:GetBucketQuota", false),
+        ("PUT", ["quota"]) => ("sevino:PutBucketQuota", false),
+        ("DELETE", ["quota"]) => ("sevino:DeleteBucketQuota", false),
+        ("GET", ["metadata-index"]) => ("sevino:GetBucketMetadataIndex", false),
+        ("PUT", ["metadata-index"]) => ("sevino:PutBucketMetadataIndex", false),
+        ("DELETE", ["metadata-index"]) => ("sevino:DeleteBucketMetadataIndex", false),
+        ("POST", ["metadata-index", "rebuild"]) => ("sevino:RebuildBucketMetadataIndex", false),
+        ("GET", ["website"]) => ("sevino:GetBucketWebsite", false),
+        ("PUT", ["website"]) => ("sevino:PutBucketWebsite", false),
+        ("DELETE", ["website"]) => ("sevino:DeleteBucketWebsite", false),
+        ("POST", ["rename"]) => ("sevino:RenameObject", false),
+        ("POST", ["delete"]) => ("sevino:DeleteObjects", false),
+        ("POST", ["delete-jobs"]) => ("sevino:DeleteObjects", false),
+        ("GET", ["delete-jobs"]) => ("sevino:GetDeleteJob", false),
+        ("GET", ["delete-jobs", _]) => ("sevino:GetDeleteJob", false),
+        ("POST", ["archive"]) => ("sevino:ExtractArchive", false),
+        ("GET", ["archive"]) => ("sevino:ListBucket", false),
+        ("GET", ["objects"]) => ("sevino:ListBucket", false),
+        ("GET", ["search"]) => ("sevino:ListBucket", false),
+        ("GET", ["objects", _]) => ("sevino:GetObject", true),
+        ("PUT", ["objects", _]) => ("sevino:PutObject", true),
+        ("DELETE", ["objects", _]) => ("sevino:DeleteObject", true),
+        ("PUT", ["objects", _, "multipart"]) => ("sevino:PutObject", true),
+        ("PUT", ["objects", _, "copy"]) => ("sevino:PutObject", true),
+        ("POST", ["objects", _, "rename"]) => ("sevino:RenameObject", true),
+        ("GET", ["objects", _, "metadata"]) => ("sevino:GetObject", true),
+        ("PUT", ["objects", _, "metadata"]) => ("sevino:UpdateObjectMetadata", true),
+        ("PUT", ["objects", _, "retention"]) => ("sevino:PutObjectRetention", true),
+        ("PUT", ["objects", _, "legal-hold"]) => ("sevino:PutObjectLegalHold", true),
+        ("GET", ["objects", _, "versions"]) => ("sevino:ListObjectVersions", true),
+        ("GET", ["objects", _, "tagging"]) => ("sevino:GetObjectTagging", true),
+        ("PUT", ["objects", _, "tagging"]) => ("sevino:PutObjectTagging", true),
+        ("DELETE", ["objects", _, "tagging"]) => ("sevino:DeleteObjectTagging", true),
+        ("POST", ["objects", _, "duplicate-test"]) => ("sevino:PutObject", true),
+        ("POST", ["objects", _, "share"]) => ("sevino:CreateShareLink", true),
+        // 表单上传（POST 到桶本身）由签名的上传策略授权，不在这里分类；
+        // /api/share-links/{id} 的路径中没有桶名，由处理函数按链接所属的桶授权
+        ("POST", ["upload-policy"]) => ("sevino:CreateUploadPolicy", false),
+        _ => return None,
+    };
+
+    Some(RequestTarget {
+        action,
+        bucket_name,
+        key: if object_level { key } else { None },
+    })
+}
+
+/// 桶级接口下无法归类为策略操作的请求（表单上传除外），授权中间件默认拒绝
+pub fn is_unclassified_bucket_request(method: &Method, path: &str) -> bool {
+    let Some(rest) = path.strip_prefix("/api/buckets/") else {
+        return false;
+    };
+    let form_upload = method == Method::POST && !rest.is_empty() && !rest.contains('/');
+    !form_upload && classify_request(method, path).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PolicyConditions;
+
+    fn statement(effect: PolicyEffect, principals: &[&str], actions: &[&str], resources: &[&str]) -> PolicyStatement {
+        PolicyStatement {
+            sid: None,
+            effect,
+            principals: principals.iter().map(|s| s.to_string()).collect(),
+            actions: actions.iter().map(|s| s.to_string()).collect(),
+            resources: resources.iter().map(|s| s.to_string()).collect(),
+            conditions: PolicyConditions::default(),
+        }
+    }
+
+    fn policy(statements: Vec<PolicyStatement>) -> BucketPolicy {
+        BucketPolicy { version: None, statements }
+    }
+
+    fn request(principal: &str, action: &str, key: Option<&str>) -> AuthorizationRequest {
+        AuthorizationRequest {
+            principal: principal.to_string(),
+            action: action.to_string(),
+            bucket_name: "photos".to_string(),
+            key: key.map(|k| k.to_string()),
+            prefix: None,
+            source_ip: None,
+            secure_transport: false,
+            object_tags: None,
+        }
+    }
+
+    fn decision(policy: &BucketPolicy, request: &AuthorizationRequest) -> PolicyDecision {
+        evaluate_policy(Some(policy), request).decision
+    }
+
+    #[test]
+    fn no_policy_allows_by_default() {
+        let evaluation = evaluate_policy(None, &request("alice", "sevino:GetObject", Some("a.jpg")));
+        assert_eq!(evaluation.decision, PolicyDecision::NoPolicy);
+        assert!(evaluation.allowed);
+        assert_eq!(evaluation.resource, "photos/a.jpg");
+    }
+
+    #[test]
+    fn matching_allow_statement_allows() {
+        let policy = policy(vec![statement(PolicyEffect::Allow, &["alice"], &["sevino:GetObject"], &["photos/*"])]);
+        let evaluation = evaluate_policy(Some(&policy), &request("alice", "sevino:GetObject", Some("a.jpg")));
+        assert_eq!(evaluation.decision, PolicyDecision::Allow);
+        assert!(evaluation.allowed);
+    }
+
+    #[test]
+    fn unmatched_request_is_implicitly_denied() {
+        let policy = policy(vec![statement(PolicyEffect::Allow, &["alice"], &["sevino:GetObject"], &["photos/*"])]);
+
+        let evaluation = evaluate_policy(Some(&policy), &request("bob", "sevino:GetObject", Some("a.jpg")));
+        assert_eq!(evaluation.decision, PolicyDecision::ImplicitDeny);
+        assert!(!evaluation.allowed);
+        assert!(evaluation.explanations[0].contains("principal 'bob'"));
+
+        assert_eq!(decision(&policy, &request("alice", "sevino:PutObject", Some("a.jpg"))), PolicyDecision::ImplicitDeny);
+        // 桶级资源不匹配 photos/*
+        assert_eq!(decision(&policy, &request("alice", "sevino:GetObject", None)), PolicyDecision::ImplicitDeny);
+    }
+
+    #[test]
+    fn explicit_deny_overrides_allow() {
+        let policy = policy(vec![
+            statement(PolicyEffect::Allow, &["*"], &["sevino:*"], &["*"]),
+            statement(PolicyEffect::Deny, &["bob"], &["sevino:DeleteObject"], &["photos/private/*"]),
+        ]);
+
+        assert_eq!(decision(&policy, &request("bob", "sevino:DeleteObject", Some("private/a.jpg"))), PolicyDecision::ExplicitDeny);
+        assert_eq!(decision(&policy, &request("bob", "sevino:DeleteObject", Some("public/a.jpg"))), PolicyDecision::Allow);
+        assert_eq!(decision(&policy, &request("alice", "sevino:DeleteObject", Some("private/a.jpg"))), PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn wildcards_match_principals_actions_and_resources() {
+        let policy = policy(vec![statement(PolicyEffect::Allow, &["team-*"], &["sevino:Get*"], &["photos/2024/*.jpg"])]);
+
+        assert_eq!(decision(&policy, &request("team-a", "sevino:GetObjectTagging", Some("2024/x.jpg"))), PolicyDecision::Allow);
+        assert_eq!(decision(&policy, &request("team-a", "sevino:GetObject", Some("2024/x.png"))), PolicyDecision::ImplicitDeny);
+        assert_eq!(decision(&policy, &request("other", "sevino:GetObject", Some("2024/x.jpg"))), PolicyDecision::ImplicitDeny);
+    }
+
+    #[test]
+    fn source_ip_condition_matches_cidrs() {
+        let mut allow = statement(PolicyEffect::Allow, &["*"], &["sevino:GetObject"], &["photos/*"]);
+        allow.conditions.source_ip = vec!["10.0.0.0/8".to_string(), "192.168.1.5".to_string()];
+        let policy = policy(vec![allow]);
+
+        let mut req = request("alice", "sevino:GetObject", Some("a.jpg"));
+        assert_eq!(decision(&policy, &req), PolicyDecision::ImplicitDeny);
+
+        req.source_ip = Some("10.20.30.40".parse().unwrap());
+        assert_eq!(decision(&policy, &req), PolicyDecision::Allow);
+        req.source_ip = Some("192.168.1.5".parse().unwrap());
+        assert_eq!(decision(&policy, &req), PolicyDecision::Allow);
+        req.source_ip = Some("192.168.1.6".parse().unwrap());
+        assert_eq!(decision(&policy, &req), PolicyDecision::ImplicitDeny);
+    }
+
+    #[test]
+    fn prefix_condition_uses_key_or_list_prefix() {
+        let mut allow = statement(PolicyEffect::Allow, &["*"], &["sevino:GetObject", "sevino:ListBucket"], &["*"]);
+        allow.conditions.prefix = vec!["public/".to_string()];
+        let policy = policy(vec![allow]);
+
+        assert_eq!(decision(&policy, &request("alice", "sevino:GetObject", Some("public/a.jpg"))), PolicyDecision::Allow);
+        assert_eq!(decision(&policy, &request("alice", "sevino:GetObject", Some("private/a.jpg"))), PolicyDecision::ImplicitDeny);
+
+        let mut list = request("alice", "sevino:ListBucket", None);
+        assert_eq!(decision(&policy, &list), PolicyDecision::ImplicitDeny);
+        list.prefix = Some("public/2024/".to_string());
+        assert_eq!(decision(&policy, &list), PolicyDecision::Allow);
+        list.prefix = Some("pub".to_string());
+        assert_eq!(decision(&policy, &list), PolicyDecision::ImplicitDeny);
+    }
+
+    #[test]
+    fn secure_transport_condition() {
+        let mut deny = statement(PolicyEffect::Deny, &["*"], &["sevino:*"], &["*"]);
+        deny.conditions.secure_transport = Some(false);
+        let policy = policy(vec![statement(PolicyEffect::Allow, &["*"], &["sevino:*"], &["*"]), deny]);
+
+        let mut req = request("alice", "sevino:GetObject", Some("a.jpg"));
+        assert_eq!(decision(&policy, &req), PolicyDecision::ExplicitDeny);
+        req.secure_transport = true;
+        assert_eq!(decision(&policy, &req), PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn object_tags_condition_requires_all_tags() {
+        let mut allow = statement(PolicyEffect::Allow, &["*"], &["sevino:GetObject"], &["photos/*"]);
+        allow.conditions.object_tags = HashMap::from([
+            ("visibility".to_string(), "public".to_string()),
+            ("team".to_string(), "web".to_string()),
+        ]);
+        let policy = policy(vec![allow]);
+
+        let mut req = request("alice", "sevino:GetObject", Some("a.jpg"));
+        assert_eq!(decision(&policy, &req), PolicyDecision::ImplicitDeny);
+
+        req.object_tags = Some(HashMap::from([("visibility".to_string(), "public".to_string())]));
+        assert_eq!(decision(&policy, &req), PolicyDecision::ImplicitDeny);
+
+        req.object_tags = Some(HashMap::from([
+            ("visibility".to_string(), "public".to_string()),
+            ("team".to_string(), "web".to_string()),
+            ("extra".to_string(), "1".to_string()),
+        ]));
+        assert_eq!(decision(&policy, &req), PolicyDecision::Allow);
+
+        req.object_tags.as_mut().unwrap().insert("team".to_string(), "ops".to_string());
+        assert_eq!(decision(&policy, &req), PolicyDecision::ImplicitDeny);
+    }
+
+    #[test]
+    fn validate_policy_accepts_valid_documents() {
+        let mut allow = statement(PolicyEffect::Allow, &["*"], &["sevino:GetObject"], &["photos", "photos/*", "*"]);
+        allow.conditions.source_ip = vec!["10.0.0.0/8".to_string(), "::1".to_string()];
+        assert!(PolicyService::validate_policy("photos", &policy(vec![allow])).is_ok());
+    }
+
+    #[test]
+    fn validate_policy_rejects_invalid_documents() {
+        let valid = || statement(PolicyEffect::Allow, &["*"], &["sevino:GetObject"], &["photos/*"]);
+
+        assert!(PolicyService::validate_policy("photos", &policy(vec![])).is_err());
+
+        let mut empty_actions = valid();
+        empty_actions.actions.clear();
+        assert!(PolicyService::validate_policy("photos", &policy(vec![empty_actions])).is_err());
+
+        let foreign_action = statement(PolicyEffect::Allow, &["*"], &["s3:GetObject"], &["photos/*"]);
+        let err = PolicyService::validate_policy("photos", &policy(vec![foreign_action])).unwrap_err();
+        assert!(err.to_string().contains("s3:GetObject"));
+
+        for resource in ["videos/*", "photosx", "photosx/a"] {
+            let other_bucket = statement(PolicyEffect::Allow, &["*"], &["sevino:GetObject"], &[resource]);
+            assert!(PolicyService::validate_policy("photos", &policy(vec![other_bucket])).is_err(), "{}", resource);
+        }
+
+        let mut bad_cidr = valid();
+        bad_cidr.sid = Some("ip".to_string());
+        bad_cidr.conditions.source_ip = vec!["10.0.0.0/40".to_string()];
+        let err = PolicyService::validate_policy("photos", &policy(vec![bad_cidr])).unwrap_err();
+        assert!(err.to_string().contains("Statement 'ip'"));
+    }
+
+    #[test]
+    fn classify_request_maps_routes_to_actions() {
+        let target = classify_request(&Method::GET, "/api/buckets/photos/objects/a%2Fb.jpg").unwrap();
+        assert_eq!(target.action, "sevino:GetObject");
+        assert_eq!(target.bucket_name, "photos");
+        assert_eq!(target.key.as_deref(), Some("a/b.jpg"));
+
+        let target = classify_request(&Method::PUT, "/api/buckets/photos/objects/a.jpg/tagging").unwrap();
+        assert_eq!(target.action, "sevino:PutObjectTagging");
+        assert_eq!(target.key.as_deref(), Some("a.jpg"));
+
+        let target = classify_request(&Method::GET, "/api/buckets/photos/objects").unwrap();
+        assert_eq!(target.action, "sevino:ListBucket");
+        assert_eq!(target.key, None);
+
+        // 桶级操作不带对象键
+        let target = classify_request(&Method::POST, "/api/buckets/photos/delete-jobs").unwrap();
+        assert_eq!(target.action, "sevino:DeleteObjects");
+        assert_eq!(target.key, None);
+
+        assert_eq!(classify_request(&Method::DELETE, "/api/buckets/photos").unwrap().action, "sevino:DeleteBucket");
+    }
+
+    #[test]
+    fn classify_request_ignores_unscoped_routes() {
+        assert!(classify_request(&Method::GET, "/api/buckets").is_none());
+        assert!(classify_request(&Method::GET, "/api/share-links/abc").is_none());
+        assert!(classify_request(&Method::POST, "/api/buckets/photos").is_none());
+        assert!(classify_request(&Method::PATCH, "/api/buckets/photos/objects/a.jpg").is_none());
+        assert!(classify_request(&Method::GET, "/health").is_none());
+    }
+
+    #[test]
+    fn classify_request_treats_head_as_get() {
+        let target = classify_request(&Method::HEAD, "/api/buckets/photos/objects/a.jpg").unwrap();
+        assert_eq!(target.action, "sevino:GetObject");
+        assert_eq!(target.key.as_deref(), Some("a.jpg"));
+
+        assert_eq!(classify_request(&Method::HEAD, "/api/buckets/photos/objects/a.jpg/metadata").unwrap().action, "sevino:GetObject");
+        assert_eq!(classify_request(&Method::HEAD, "/api/buckets/photos/objects/a.jpg/tagging").unwrap().action, "sevino:GetObjectTagging");
+        assert_eq!(classify_request(&Method::HEAD, "/api/buckets/photos/objects").unwrap().action, "sevino:ListBucket");
+    }
+
+    #[test]
+    fn unclassified_bucket_requests_are_denied_except_form_uploads() {
+        assert!(is_unclassified_bucket_request(&Method::PATCH, "/api/buckets/photos/objects/a.jpg"));
+        assert!(is_unclassified_bucket_request(&Method::GET, "/api/buckets/photos/unknown"));
+        assert!(is_unclassified_bucket_request(&Method::POST, "/api/buckets/"));
+
+        assert!(!is_unclassified_bucket_request(&Method::POST, "/api/buckets/photos"));
+        assert!(!is_unclassified_bucket_request(&Method::HEAD, "/api/buckets/photos/objects/a.jpg"));
+        assert!(!is_unclassified_bucket_request(&Method::GET, "/api/buckets"));
+        assert!(!is_unclassified_bucket_request(&Method::GET, "/api/share-links/abc"));
+    }
+}
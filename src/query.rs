@@ -0,0 +1,774 @@
+use crate::models::ObjectMetadata;
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// 查询语法错误（对应 HTTP 400），位置从 1 开始按字符计算
+#[derive(Debug, thiserror::Error)]
+#[error("Query syntax error at position {position}: {message}")]
+pub struct QuerySyntaxError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// 可查询的字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    Key,
+    Size,
+    ContentType,
+    LastModified,
+    Etag,
+    /// 用户元数据（meta.<name>）
+    Metadata(String),
+    /// 对象标签（tag.<name>）
+    Tag(String),
+}
+
+impl Field {
+    /// 解析字段名：key、size、content_type、last_modified、etag、meta.<name>、tag.<name>
+    pub fn parse(name: &str) -> Option<Field> {
+        let field = match name.to_ascii_lowercase().as_str() {
+            "key" => Field::Key,
+            "size" => Field::Size,
+            "content_type" => Field::ContentType,
+            "last_modified" => Field::LastModified,
+            "etag" => Field::Etag,
+            _ => {
+                let (namespace, rest) = name.split_once('.')?;
+                if rest.is_empty() {
+                    return None;
+                }
+                match namespace.to_ascii_lowercase().as_str() {
+                    "meta" | "user_metadata" => Field::Metadata(rest.to_string()),
+                    "tag" | "tags" => Field::Tag(rest.to_string()),
+                    _ => return None,
+                }
+            }
+        };
+        Some(field)
+    }
+
+    fn kind(&self) -> FieldKind {
+        match self {
+            Field::Size => FieldKind::Number,
+            Field::LastModified => FieldKind::Time,
+            Field::Key | Field::ContentType | Field::Etag => FieldKind::Text,
+            Field::Metadata(_) | Field::Tag(_) => FieldKind::Dynamic,
+        }
+    }
+
+    /// 对象在该字段上的值（没有该元数据或标签时为 None）
+    fn value(&self, metadata: &ObjectMetadata) -> Option<Value> {
+        match self {
+            Field::Key => Some(Value::Text(metadata.key.clone())),
+            Field::Size => Some(Value::Number(metadata.size as f64)),
+            Field::ContentType => Some(Value::Text(metadata.content_type.clone())),
+            Field::LastModified => Some(Value::Time(metadata.last_modified)),
+            Field::Etag => Some(Value::Text(normalize_etag(&metadata.etag).to_string())),
+            Field::Metadata(name) => metadata.user_metadata.get(name).map(|v| Value::Text(v.clone())),
+            Field::Tag(name) => metadata.tags.get(name).map(|v| Value::Text(v.clone())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Text,
+    Number,
+    Time,
+    /// 元数据和标签：与数字比较时按数字解析，否则按字符串比较
+    Dynamic,
+}
+
+/// 字段值或字面量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "t", content = "v", rename_all = "lowercase")]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+impl Value {
+    /// 排序时的全序：不同类型之间数字在前，其次字符串，最后时间
+    fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::Text(a), Value::Text(b)) => a.cmp(b),
+            (Value::Time(a), Value::Time(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Number(_) => 0,
+            Value::Text(_) => 1,
+            Value::Time(_) => 2,
+        }
+    }
+
+    /// 条件比较：字面量为数字时把字符串值按数字解析，无法比较时返回 None
+    fn compare_to(&self, literal: &Value) -> Option<Ordering> {
+        match (self, literal) {
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::Text(a), Value::Number(b)) => a.trim().parse::<f64>().ok()?.partial_cmp(b),
+            (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+            (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn matches(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// 单个条件
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Compare(Field, CompareOp, Value),
+    In(Field, Vec<Value>),
+    Prefix(Field, String),
+    Matches(Field, Regex),
+    Exists(Field),
+}
+
+impl Condition {
+    fn evaluate(&self, metadata: &ObjectMetadata) -> bool {
+        match self {
+            Condition::Exists(field) => field.value(metadata).is_some(),
+            Condition::Compare(field, op, literal) => field.value(metadata)
+                .and_then(|value| value.compare_to(literal))
+                .map(|ordering| op.matches(ordering))
+                .unwrap_or(false),
+            Condition::In(field, literals) => field.value(metadata)
+                .map(|value| literals.iter().any(|l| value.compare_to(l) == Some(Ordering::Equal)))
+                .unwrap_or(false),
+            Condition::Prefix(field, prefix) => matches!(field.value(metadata), Some(Value::Text(text)) if text.starts_with(prefix.as_str())),
+            Condition::Matches(field, regex) => matches!(field.value(metadata), Some(Value::Text(text)) if regex.is_match(&text)),
+        }
+    }
+}
+
+/// 查询表达式
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Condition(Condition),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    /// 判断对象是否满足表达式
+    pub fn evaluate(&self, metadata: &ObjectMetadata) -> bool {
+        match self {
+            Expr::Condition(condition) => condition.evaluate(metadata),
+            Expr::And(left, right) => left.evaluate(metadata) && right.evaluate(metadata),
+            Expr::Or(left, right) => left.evaluate(metadata) || right.evaluate(metadata),
+            Expr::Not(inner) => !inner.evaluate(metadata),
+        }
+    }
+}
+
+/// 解析查询语句；空语句返回 None（匹配所有对象）
+pub fn parse(query: &str) -> std::result::Result<Option<Expr>, QuerySyntaxError> {
+    let tokens = tokenize(query)?;
+    if tokens.len() == 1 {
+        return Ok(None);
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    let token = parser.peek();
+    if token.kind != TokenKind::End {
+        return Err(syntax_error(token.position, format!("unexpected {}", token.kind.describe())));
+    }
+    Ok(Some(expr))
+}
+
+fn syntax_error(position: usize, message: impl Into<String>) -> QuerySyntaxError {
+    QuerySyntaxError { position, message: message.into() }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Text(String),
+    Number(f64),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    Comma,
+    End,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Ident(name) => format!("'{}'", name),
+            TokenKind::Text(text) => format!("string '{}'", text),
+            TokenKind::Number(n) => format!("number {}", n),
+            TokenKind::Op(_) => "operator".to_string(),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::Comma => "','".to_string(),
+            TokenKind::End => "end of query".to_string(),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, TokenKind::Ident(name) if name.eq_ignore_ascii_case(keyword))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn tokenize(query: &str) -> std::result::Result<Vec<Token>, QuerySyntaxError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = match c {
+            '(' => { i += 1; TokenKind::LParen }
+            ')' => { i += 1; TokenKind::RParen }
+            ',' => { i += 1; TokenKind::Comma }
+            '=' => { i += 1; TokenKind::Op(CompareOp::Eq) }
+            '!' if chars.get(i + 1) == Some(&'=') => { i += 2; TokenKind::Op(CompareOp::Ne) }
+            '<' | '>' => {
+                let or_equal = chars.get(i + 1) == Some(&'=');
+                i += if or_equal { 2 } else { 1 };
+                TokenKind::Op(match (c, or_equal) {
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    _ => CompareOp::Ge,
+                })
+            }
+            '\'' | '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error(position, "unterminated string")),
+                        Some('\\') => {
+                            let escaped = chars.get(i + 1).ok_or_else(|| syntax_error(position, "unterminated string"))?;
+                            text.push(*escaped);
+                            i += 2;
+                        }
+                        Some(q) if *q == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(other) => {
+                            text.push(*other);
+                            i += 1;
+                        }
+                    }
+                }
+                TokenKind::Text(text)
+            }
+            c if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let number = literal.parse::<f64>().map_err(|_| syntax_error(position, format!("invalid number '{}'", literal)))?;
+                TokenKind::Number(number)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '-' | '.' | ':')) {
+                    i += 1;
+                }
+                TokenKind::Ident(chars[start..i].iter().collect())
+            }
+            other => return Err(syntax_error(position, format!("unexpected character '{}'", other))),
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    tokens.push(Token { kind: TokenKind::End, position: chars.len() + 1 });
+    Ok(tokens)
+}
+
+/// 递归下降解析器，优先级：NOT > AND > OR
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn parse_or(&mut self) -> std::result::Result<Expr, QuerySyntaxError> {
+        let mut expr = self.parse_and()?;
+        while self.peek().kind.is_keyword("OR") {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> std::result::Result<Expr, QuerySyntaxError> {
+        let mut expr = self.parse_not()?;
+        while self.peek().kind.is_keyword("AND") {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> std::result::Result<Expr, QuerySyntaxError> {
+        if self.peek().kind.is_keyword("NOT") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> std::result::Result<Expr, QuerySyntaxError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::LParen => {
+                let expr = self.parse_or()?;
+                let closing = self.next();
+                if closing.kind != TokenKind::RParen {
+                    return Err(syntax_error(closing.position, format!("expected ')' but found {}", closing.kind.describe())));
+                }
+                Ok(expr)
+            }
+            TokenKind::Ident(name) => {
+                let field = Field::parse(&name).ok_or_else(|| syntax_error(
+                    token.position,
+                    format!("unknown field '{}' (expected key, size, content_type, last_modified, etag, meta.<name> or tag.<name>)", name),
+                ))?;
+                self.parse_condition(field, token.position).map(Expr::Condition)
+            }
+            other => Err(syntax_error(token.position, format!("expected a field name or '(' but found {}", other.describe()))),
+        }
+    }
+
+    fn parse_condition(&mut self, field: Field, field_position: usize) -> std::result::Result<Condition, QuerySyntaxError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Op(op) => {
+                let value = self.parse_value(&field)?;
+                Ok(Condition::Compare(field, op, value))
+            }
+            ref kind if kind.is_keyword("IN") => {
+                let open = self.next();
+                if open.kind != TokenKind::LParen {
+                    return Err(syntax_error(open.position, format!("expected '(' after IN but found {}", open.kind.describe())));
+                }
+                let mut values = vec![self.parse_value(&field)?];
+                loop {
+                    let separator = self.next();
+                    match separator.kind {
+                        TokenKind::Comma => values.push(self.parse_value(&field)?),
+                        TokenKind::RParen => break,
+                        other => return Err(syntax_error(separator.position, format!("expected ',' or ')' but found {}", other.describe()))),
+                    }
+                }
+                Ok(Condition::In(field, values))
+            }
+            ref kind if kind.is_keyword("PREFIX") => {
+                self.require_text_field(&field, field_position, "PREFIX")?;
+                let (prefix, _) = self.parse_string("PREFIX")?;
+                Ok(Condition::Prefix(field, prefix))
+            }
+            ref kind if kind.is_keyword("MATCHES") => {
+                self.require_text_field(&field, field_position, "MATCHES")?;
+                let (pattern, position) = self.parse_string("MATCHES")?;
+                let regex = Regex::new(&pattern).map_err(|e| syntax_error(position, format!("invalid regular expression: {}", e)))?;
+                Ok(Condition::Matches(field, regex))
+            }
+            ref kind if kind.is_keyword("EXISTS") => Ok(Condition::Exists(field)),
+            other => Err(syntax_error(
+                token.position,
+                format!("expected an operator (=, !=, <, <=, >, >=, IN, PREFIX, MATCHES, EXISTS) but found {}", other.describe()),
+            )),
+        }
+    }
+
+    fn require_text_field(&self, field: &Field, position: usize, operator: &str) -> std::result::Result<(), QuerySyntaxError> {
+        match field.kind() {
+            FieldKind::Number | FieldKind::Time => Err(syntax_error(position, format!("{} can only be used with text fields", operator))),
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_string(&mut self, operator: &str) -> std::result::Result<(String, usize), QuerySyntaxError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Text(text) => Ok((text, token.position)),
+            other => Err(syntax_error(token.position, format!("{} expects a quoted string but found {}", operator, other.describe()))),
+        }
+    }
+
+    /// 解析字面量并按字段类型转换
+    fn parse_value(&mut self, field: &Field) -> std::result::Result<Value, QuerySyntaxError> {
+        let token = self.next();
+        let position = token.position;
+        match (field.kind(), token.kind) {
+            (FieldKind::Number, TokenKind::Number(n)) => Ok(Value::Number(n)),
+            (FieldKind::Number, other) => Err(syntax_error(position, format!("size must be compared with a number, found {}", other.describe()))),
+            (FieldKind::Time, TokenKind::Text(text)) => parse_time(&text)
+                .map(Value::Time)
+                .ok_or_else(|| syntax_error(position, format!("invalid timestamp '{}' (expected RFC 3339 or YYYY-MM-DD)", text))),
+            (FieldKind::Time, other) => Err(syntax_error(position, format!("last_modified must be compared with a quoted timestamp, found {}", other.describe()))),
+            (FieldKind::Text, TokenKind::Text(text)) if *field == Field::Etag => Ok(Value::Text(normalize_etag(&text).to_string())),
+            (FieldKind::Text, TokenKind::Text(text)) => Ok(Value::Text(text)),
+            (FieldKind::Text, other) => Err(syntax_error(position, format!("expected a quoted string but found {}", other.describe()))),
+            (FieldKind::Dynamic, TokenKind::Text(text)) => Ok(Value::Text(text)),
+            (FieldKind::Dynamic, TokenKind::Number(n)) => Ok(Value::Number(n)),
+            (FieldKind::Dynamic, other) => Err(syntax_error(position, format!("expected a string or number but found {}", other.describe()))),
+        }
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// 去掉ETag的引号和弱ETag前缀
+fn normalize_etag(etag: &str) -> &str {
+    etag.trim().trim_start_matches("W/").trim_matches('"')
+}
+
+/// 搜索默认每页返回的对象数
+pub const DEFAULT_SEARCH_LIMIT: usize = 100;
+/// 搜索每页最多返回的对象数
+pub const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// 排序方式
+#[derive(Debug, Clone)]
+pub struct SortOrder {
+    name: String,
+    field: Field,
+    descending: bool,
+}
+
+impl SortOrder {
+    /// 解析排序字段（默认 key）和方向（asc 或 desc，默认 asc）
+    pub fn parse(sort: Option<&str>, order: Option<&str>) -> Result<SortOrder> {
+        let name = sort.unwrap_or("key");
+        let field = Field::parse(name).ok_or_else(|| anyhow!("Unknown sort field '{}'", name))?;
+        let descending = match order.unwrap_or("asc").to_ascii_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            other => return Err(anyhow!("Invalid sort order '{}', expected 'asc' or 'desc'", other)),
+        };
+        Ok(SortOrder { name: name.to_string(), field, descending })
+    }
+
+    fn id(&self) -> String {
+        format!("{}:{}", self.name, if self.descending { "desc" } else { "asc" })
+    }
+
+    /// 对象的排序键：字段值（缺失时排在最后）和对象键（相同值时按对象键升序）
+    fn sort_key(&self, metadata: &ObjectMetadata) -> (Option<Value>, String) {
+        let value = self.field.value(metadata).map(|value| match value {
+            // 元数据值能解析为数字时按数字排序
+            Value::Text(text) if matches!(self.field, Field::Metadata(_) | Field::Tag(_)) => text.trim().parse::<f64>()
+                .map(Value::Number)
+                .unwrap_or(Value::Text(text)),
+            other => other,
+        });
+        (value, metadata.key.clone())
+    }
+
+    fn compare(&self, a: &(Option<Value>, String), b: &(Option<Value>, String)) -> Ordering {
+        let by_value = match (&a.0, &b.0) {
+            (Some(x), Some(y)) if self.descending => y.total_cmp(x),
+            (Some(x), Some(y)) => x.total_cmp(y),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        by_value.then_with(|| a.1.cmp(&b.1))
+    }
+}
+
+/// 分页游标：上一页最后一个对象的排序键
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    /// 排序方式（字段名和方向），游标只能用于相同排序的请求
+    sort: String,
+    value: Option<Value>,
+    key: String,
+}
+
+/// 过滤、排序并分页；返回本页的对象和下一页的游标
+pub fn paginate(
+    records: Vec<ObjectMetadata>,
+    expr: Option<&Expr>,
+    order: &SortOrder,
+    limit: usize,
+    cursor: Option<&str>,
+) -> Result<(Vec<ObjectMetadata>, Option<String>)> {
+    let sort_id = order.id();
+    let after = match cursor {
+        Some(cursor) => {
+            let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(cursor)
+                .map_err(|_| anyhow!("Invalid cursor"))?;
+            let cursor: Cursor = serde_json::from_slice(&bytes).map_err(|_| anyhow!("Invalid cursor"))?;
+            if cursor.sort != sort_id {
+                return Err(anyhow!("Cursor was created for sort order '{}' and cannot be used with '{}'", cursor.sort, sort_id));
+            }
+            Some((cursor.value, cursor.key))
+        }
+        None => None,
+    };
+
+    let mut matched: Vec<((Option<Value>, String), ObjectMetadata)> = records.into_iter()
+        .filter(|m| expr.map(|e| e.evaluate(m)).unwrap_or(true))
+        .map(|m| (order.sort_key(&m), m))
+        .filter(|(sort_key, _)| after.as_ref().map(|a| order.compare(sort_key, a) == Ordering::Greater).unwrap_or(true))
+        .collect();
+    matched.sort_by(|a, b| order.compare(&a.0, &b.0));
+
+    let has_more = matched.len() > limit;
+    matched.truncate(limit);
+    let next_cursor = match (has_more, matched.last()) {
+        (true, Some(((value, key), _))) => {
+            let cursor = Cursor { sort: sort_id, value: value.clone(), key: key.clone() };
+            Some(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(&cursor)?))
+        }
+        _ => None,
+    };
+
+    Ok((matched.into_iter().map(|(_, m)| m).collect(), next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Object;
+    use std::collections::HashMap;
+
+    fn object(key: &str, size: u64, content_type: &str) -> ObjectMetadata {
+        let object = Object::new(key.to_string(), "b".to_string(), size, content_type.to_string(), format!("\"etag-{}\"", key), HashMap::new());
+        let mut metadata: ObjectMetadata = object.into();
+        metadata.last_modified = "2026-10-01T12:00:00Z".parse().unwrap();
+        metadata
+    }
+
+    fn with_meta(mut metadata: ObjectMetadata, name: &str, value: &str) -> ObjectMetadata {
+        metadata.user_metadata.insert(name.to_string(), value.to_string());
+        metadata
+    }
+
+    fn matches(query: &str, metadata: &ObjectMetadata) -> bool {
+        parse(query).unwrap().unwrap().evaluate(metadata)
+    }
+
+    fn error(query: &str) -> QuerySyntaxError {
+        parse(query).unwrap_err()
+    }
+
+    fn keys(records: &[ObjectMetadata]) -> Vec<&str> {
+        records.iter().map(|m| m.key.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_empty_query_matches_everything() {
+        assert!(parse("").unwrap().is_none());
+        assert!(parse("   ").unwrap().is_none());
+    }
+
+    #[test]
+    fn field_names_are_case_insensitive_with_namespaces() {
+        assert_eq!(Field::parse("SIZE"), Some(Field::Size));
+        assert_eq!(Field::parse("meta.Owner"), Some(Field::Metadata("Owner".to_string())));
+        assert_eq!(Field::parse("tags.env"), Some(Field::Tag("env".to_string())));
+        assert_eq!(Field::parse("meta."), None);
+        assert_eq!(Field::parse("other.x"), None);
+    }
+
+    #[test]
+    fn compares_builtin_fields() {
+        let report = object("reports/2026.pdf", 2048, "application/pdf");
+        assert!(matches("size > 1024", &report));
+        assert!(matches("size >= 2048 AND size <= 2048", &report));
+        assert!(!matches("size < 2048", &report));
+        assert!(matches("content_type = 'application/pdf'", &report));
+        assert!(matches("key != \"other\"", &report));
+        assert!(matches("last_modified >= '2026-10-01'", &report));
+        assert!(!matches("last_modified > '2026-10-01T12:00:00Z'", &report));
+        assert!(matches("etag = 'W/\"etag-reports/2026.pdf\"'", &report));
+    }
+
+    #[test]
+    fn prefix_matches_in_and_exists() {
+        let report = with_meta(object("reports/2026.pdf", 10, "application/pdf"), "owner", "alice");
+        assert!(matches("key PREFIX 'reports/'", &report));
+        assert!(matches("key MATCHES '^reports/\\\\d+\\\\.pdf$'", &report));
+        assert!(matches("meta.owner IN ('bob', 'alice')", &report));
+        assert!(matches("meta.owner EXISTS", &report));
+        assert!(!matches("meta.team EXISTS", &report));
+        assert!(!matches("meta.team = 'x'", &report));
+        assert!(matches("NOT meta.team = 'x'", &report));
+    }
+
+    #[test]
+    fn metadata_compares_as_numbers_against_numeric_literals() {
+        let record = with_meta(object("a", 1, "text/plain"), "priority", " 10 ");
+        assert!(matches("meta.priority > 9", &record));
+        assert!(!matches("meta.priority > '9'", &record));
+        assert!(!matches("meta.priority > 'x' OR meta.priority = 3", &record));
+        let text = with_meta(object("b", 1, "text/plain"), "priority", "high");
+        assert!(!matches("meta.priority > 1", &text));
+        assert!(!matches("meta.priority != 1", &text));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_which_binds_tighter_than_or() {
+        let record = object("a.txt", 5, "text/plain");
+        // size = 1 OR (size = 5 AND key = 'a.txt')
+        assert!(matches("size = 1 OR size = 5 AND key = 'a.txt'", &record));
+        // (NOT size = 5) AND key = 'a.txt'
+        assert!(!matches("NOT size = 5 AND key = 'a.txt'", &record));
+        assert!(matches("NOT (size = 5 AND key = 'b.txt')", &record));
+        assert!(matches("(size = 1 OR size = 5) and key = 'a.txt'", &record));
+    }
+
+    #[test]
+    fn string_escapes_are_unescaped() {
+        let record = object("it's \"quoted\"", 1, "text/plain");
+        assert!(matches(r#"key = 'it\'s "quoted"'"#, &record));
+    }
+
+    #[test]
+    fn syntax_errors_report_positions() {
+        let e = error("size >");
+        assert_eq!(e.position, 7);
+        assert!(e.message.contains("end of query"));
+        assert_eq!(error("colour = 'red'").position, 1);
+        assert_eq!(error("key = 'open").position, 7);
+        assert_eq!(error("size = 'big'").position, 8);
+        assert_eq!(error("size PREFIX 'a'").position, 1);
+        assert_eq!(error("last_modified > 'yesterday'").position, 17);
+        assert_eq!(error("key MATCHES '('").position, 13);
+        assert_eq!(error("(size = 1").position, 10);
+        assert_eq!(error("size = 1 size = 2").position, 10);
+        assert_eq!(error("key IN ('a' 'b')").position, 13);
+        assert_eq!(error("key = 'a' # comment").position, 11);
+    }
+
+    #[test]
+    fn positions_count_characters_not_bytes() {
+        assert_eq!(error("key = 'é' AND ?").position, 15);
+    }
+
+    #[test]
+    fn sort_order_validates_field_and_direction() {
+        assert!(SortOrder::parse(None, None).is_ok());
+        assert!(SortOrder::parse(Some("meta.rank"), Some("DESC")).is_ok());
+        assert!(SortOrder::parse(Some("colour"), None).is_err());
+        assert!(SortOrder::parse(None, Some("sideways")).is_err());
+    }
+
+    #[test]
+    fn paginate_sorts_filters_and_pages_with_cursors() {
+        let records = vec![
+            object("c", 30, "text/plain"),
+            object("a", 10, "text/plain"),
+            object("d", 20, "image/png"),
+            object("b", 20, "text/plain"),
+            object("e", 5, "text/plain"),
+        ];
+        let expr = parse("content_type = 'text/plain'").unwrap();
+        let order = SortOrder::parse(Some("size"), Some("desc")).unwrap();
+
+        let (page, cursor) = paginate(records.clone(), expr.as_ref(), &order, 2, None).unwrap();
+        assert_eq!(keys(&page), ["c", "b"]);
+        let (page, cursor) = paginate(records.clone(), expr.as_ref(), &order, 2, cursor.as_deref()).unwrap();
+        assert_eq!(keys(&page), ["a", "e"]);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn paginate_breaks_ties_by_key_and_puts_missing_values_last() {
+        let records = vec![
+            object("z", 1, "text/plain"),
+            with_meta(object("y", 1, "text/plain"), "rank", "10"),
+            with_meta(object("x", 1, "text/plain"), "rank", "9"),
+            with_meta(object("w", 1, "text/plain"), "rank", "9"),
+        ];
+        let order = SortOrder::parse(Some("meta.rank"), None).unwrap();
+        let (page, cursor) = paginate(records.clone(), None, &order, 10, None).unwrap();
+        // 元数据按数字排序（9 < 10），缺失值排在最后
+        assert_eq!(keys(&page), ["w", "x", "y", "z"]);
+        assert!(cursor.is_none());
+
+        let (first, cursor) = paginate(records.clone(), None, &order, 1, None).unwrap();
+        assert_eq!(keys(&first), ["w"]);
+        let (second, _) = paginate(records, None, &order, 2, cursor.as_deref()).unwrap();
+        assert_eq!(keys(&second), ["x", "y"]);
+    }
+
+    #[test]
+    fn paginate_rejects_foreign_or_invalid_cursors() {
+        let records = vec![object("a", 1, "text/plain"), object("b", 2, "text/plain")];
+        let by_size = SortOrder::parse(Some("size"), None).unwrap();
+        let (_, cursor) = paginate(records.clone(), None, &by_size, 1, None).unwrap();
+
+        let by_key = SortOrder::parse(None, None).unwrap();
+        assert!(paginate(records.clone(), None, &by_key, 1, cursor.as_deref()).is_err());
+        assert!(paginate(records, None, &by_key, 1, Some("not a cursor")).is_err());
+    }
+}
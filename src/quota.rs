@@ -0,0 +1,187 @@
+use crate::models::{BucketUsage, QuotaConfiguration, QuotaSizeBasis};
+use anyhow::{Result, anyhow};
+
+/// 桶配额配置文件名（位于 .sevino.meta 目录下）
+pub const QUOTA_CONFIG: &str = "quota";
+
+/// 校验桶配额配置
+pub fn validate_configuration(config: &QuotaConfiguration) -> Result<()> {
+    if let (Some(soft), Some(hard)) = (config.soft_limit_bytes, config.hard_limit_bytes) {
+        if soft > hard {
+            return Err(anyhow!("soft_limit_bytes ({}) must not exceed hard_limit_bytes ({})", soft, hard));
+        }
+    }
+    if let (Some(soft), Some(hard)) = (config.soft_limit_objects, config.hard_limit_objects) {
+        if soft > hard {
+            return Err(anyhow!("soft_limit_objects ({}) must not exceed hard_limit_objects ({})", soft, hard));
+        }
+    }
+    Ok(())
+}
+
+/// 已通过硬配额检查、尚未计入桶用量的写入所增加的用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reservation {
+    pub logical_bytes: u64,
+    pub physical_bytes: u64,
+    pub object_count: u64,
+}
+
+impl Reservation {
+    /// 从 before 到 after 增加的用量（减少的部分不需要预留）
+    pub fn between(before: &BucketUsage, after: &BucketUsage) -> Self {
+        Self {
+            logical_bytes: after.logical_bytes.saturating_sub(before.logical_bytes),
+            physical_bytes: after.physical_bytes.saturating_sub(before.physical_bytes),
+            object_count: after.object_count.saturating_sub(before.object_count),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn add(&mut self, other: &Reservation) {
+        self.logical_bytes += other.logical_bytes;
+        self.physical_bytes += other.physical_bytes;
+        self.object_count += other.object_count;
+    }
+
+    pub fn subtract(&mut self, other: &Reservation) {
+        self.logical_bytes = self.logical_bytes.saturating_sub(other.logical_bytes);
+        self.physical_bytes = self.physical_bytes.saturating_sub(other.physical_bytes);
+        self.object_count = self.object_count.saturating_sub(other.object_count);
+    }
+
+    /// 把预留计入用量
+    pub fn add_to(&self, usage: &mut BucketUsage) {
+        usage.logical_bytes += self.logical_bytes;
+        usage.physical_bytes += self.physical_bytes;
+        usage.object_count += self.object_count;
+    }
+}
+
+/// 按配额的计量方式取用量中的字节数
+fn usage_bytes(config: &QuotaConfiguration, usage: &BucketUsage) -> u64 {
+    match config.size_basis {
+        QuotaSizeBasis::Logical => usage.logical_bytes,
+        QuotaSizeBasis::Physical => usage.physical_bytes,
+    }
+}
+
+/// 检查写入后的用量是否超过硬配额（不增加用量的写入总是允许，便于在降低配额后清理数据）
+pub fn check_hard_limits(bucket_name: &str, config: &QuotaConfiguration, before: &BucketUsage, after: &BucketUsage) -> Result<()> {
+    let (bytes_before, bytes_after) = (usage_bytes(config, before), usage_bytes(config, after));
+    if let Some(limit) = config.hard_limit_bytes {
+        if bytes_after > limit && bytes_after > bytes_before {
+            return Err(anyhow!(
+                "Quota exceeded for bucket '{}': this write would use {} bytes, above the hard limit of {} bytes",
+                bucket_name, bytes_after, limit
+            ));
+        }
+    }
+    if let Some(limit) = config.hard_limit_objects {
+        if after.object_count > limit && after.object_count > before.object_count {
+            return Err(anyhow!(
+                "Quota exceeded for bucket '{}': this write would store {} objects, above the hard limit of {} objects",
+                bucket_name, after.object_count, limit
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 写入使用量从软配额以下变为超过软配额时返回原因
+pub fn soft_limit_crossed(config: &QuotaConfiguration, before: &BucketUsage, after: &BucketUsage) -> Option<String> {
+    let (bytes_before, bytes_after) = (usage_bytes(config, before), usage_bytes(config, after));
+    if let Some(limit) = config.soft_limit_bytes {
+        if bytes_before <= limit && bytes_after > limit {
+            return Some(format!("{} bytes used, soft limit is {} bytes", bytes_after, limit));
+        }
+    }
+    if let Some(limit) = config.soft_limit_objects {
+        if before.object_count <= limit && after.object_count > limit {
+            return Some(format!("{} objects stored, soft limit is {} objects", after.object_count, limit));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(object_count: u64, logical_bytes: u64, physical_bytes: u64) -> BucketUsage {
+        BucketUsage { object_count, logical_bytes, physical_bytes, ..Default::default() }
+    }
+
+    fn limits(hard_limit_bytes: Option<u64>, hard_limit_objects: Option<u64>) -> QuotaConfiguration {
+        QuotaConfiguration { hard_limit_bytes, hard_limit_objects, ..Default::default() }
+    }
+
+    #[test]
+    fn soft_limits_must_not_exceed_hard_limits() {
+        let config = QuotaConfiguration { soft_limit_bytes: Some(10), hard_limit_bytes: Some(5), ..Default::default() };
+        assert!(validate_configuration(&config).is_err());
+        let config = QuotaConfiguration { soft_limit_objects: Some(10), hard_limit_objects: Some(5), ..Default::default() };
+        assert!(validate_configuration(&config).is_err());
+        let config = QuotaConfiguration { soft_limit_bytes: Some(5), hard_limit_bytes: Some(5), soft_limit_objects: Some(1), ..Default::default() };
+        assert!(validate_configuration(&config).is_ok());
+    }
+
+    #[test]
+    fn reservation_only_covers_growth() {
+        let reservation = Reservation::between(&usage(2, 100, 50), &usage(3, 80, 70));
+        assert_eq!(reservation, Reservation { logical_bytes: 0, physical_bytes: 20, object_count: 1 });
+        assert!(Reservation::between(&usage(2, 100, 50), &usage(1, 10, 5)).is_empty());
+    }
+
+    #[test]
+    fn reservations_add_and_subtract() {
+        let one = Reservation { logical_bytes: 10, physical_bytes: 5, object_count: 1 };
+        let mut total = Reservation::default();
+        total.add(&one);
+        total.add(&one);
+
+        let mut projected = usage(1, 1, 1);
+        total.add_to(&mut projected);
+        assert_eq!((projected.object_count, projected.logical_bytes, projected.physical_bytes), (3, 21, 11));
+
+        total.subtract(&one);
+        assert_eq!(total, one);
+        total.subtract(&one);
+        total.subtract(&one);
+        assert!(total.is_empty());
+    }
+
+    #[test]
+    fn hard_limits_reject_growth_past_the_limit() {
+        let config = limits(Some(100), Some(2));
+        assert!(check_hard_limits("b", &config, &usage(1, 50, 50), &usage(2, 100, 100)).is_ok());
+        assert!(check_hard_limits("b", &config, &usage(1, 50, 50), &usage(2, 101, 101)).is_err());
+        assert!(check_hard_limits("b", &config, &usage(2, 50, 50), &usage(3, 60, 60)).is_err());
+    }
+
+    #[test]
+    fn writes_that_do_not_grow_usage_are_allowed_over_the_limit() {
+        let config = limits(Some(100), Some(2));
+        assert!(check_hard_limits("b", &config, &usage(5, 500, 500), &usage(5, 400, 400)).is_ok());
+        assert!(check_hard_limits("b", &config, &usage(5, 500, 500), &usage(5, 500, 500)).is_ok());
+    }
+
+    #[test]
+    fn size_basis_selects_the_byte_count() {
+        let physical = QuotaConfiguration { size_basis: QuotaSizeBasis::Physical, hard_limit_bytes: Some(100), ..Default::default() };
+        assert!(check_hard_limits("b", &physical, &usage(1, 100, 0), &usage(2, 200, 0)).is_ok());
+        assert!(check_hard_limits("b", &limits(Some(100), None), &usage(1, 100, 0), &usage(2, 200, 0)).is_err());
+    }
+
+    #[test]
+    fn soft_limit_is_reported_only_when_crossed() {
+        let config = QuotaConfiguration { soft_limit_bytes: Some(100), soft_limit_objects: Some(3), ..Default::default() };
+        assert!(soft_limit_crossed(&config, &usage(1, 90, 90), &usage(2, 110, 110)).unwrap().contains("bytes"));
+        assert!(soft_limit_crossed(&config, &usage(3, 10, 10), &usage(4, 20, 20)).unwrap().contains("objects"));
+        assert!(soft_limit_crossed(&config, &usage(2, 110, 110), &usage(3, 120, 120)).is_none());
+        assert!(soft_limit_crossed(&config, &usage(1, 10, 10), &usage(2, 20, 20)).is_none());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::config::{BucketRateLimit, Settings};
+use axum::body::{Body, Bytes};
+use futures_util::{StreamExt, stream};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 节流时的最大分块，避免单个大块造成突发流量
+const THROTTLE_CHUNK_SIZE: usize = 64 * 1024;
+/// 令牌桶数量超过该值时清理已经回满的空闲桶
+const MAX_TOKEN_BUCKETS: usize = 10_000;
+
+/// 令牌桶：按速率补充令牌，最多积累一秒的配额
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, capacity: f64, now: Instant) -> Self {
+        Self { rate, capacity, tokens: capacity, last: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        // now 在加锁前取得，可能早于其他线程记录的时间，不能回退
+        self.last = self.last.max(now);
+    }
+}
+
+type TokenBuckets = Arc<Mutex<HashMap<String, TokenBucket>>>;
+
+/// 限速器：按客户端（访问密钥或IP）和桶分别限制每秒请求数和字节数
+#[derive(Clone)]
+pub struct RateLimiter {
+    client_requests_per_sec: f64,
+    client_bytes_per_sec: u64,
+    bucket_defaults: BucketRateLimit,
+    bucket_overrides: Arc<HashMap<String, BucketRateLimit>>,
+    requests: TokenBuckets,
+    bandwidth: TokenBuckets,
+}
+
+impl RateLimiter {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            client_requests_per_sec: settings.client_requests_per_sec,
+            client_bytes_per_sec: settings.client_bytes_per_sec,
+            bucket_defaults: BucketRateLimit {
+                requests_per_sec: settings.bucket_requests_per_sec,
+                bytes_per_sec: settings.bucket_bytes_per_sec,
+            },
+            bucket_overrides: Arc::new(settings.bucket_rate_limits.clone()),
+            requests: Arc::new(Mutex::new(HashMap::new())),
+            bandwidth: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 桶的限速配置（单桶配置优先于默认值）
+    fn bucket_limit(&self, bucket_name: &str) -> &BucketRateLimit {
+        self.bucket_overrides.get(bucket_name).unwrap_or(&self.bucket_defaults)
+    }
+
+    /// 检查请求数限制；超限时返回建议的等待时间，只有所有限制都满足时才扣除令牌
+    pub fn check_request(&self, client: &str, bucket_name: Option<&str>) -> Result<(), Duration> {
+        let mut limits = Vec::new();
+        if self.client_requests_per_sec > 0.0 {
+            limits.push((format!("client:{}", client), self.client_requests_per_sec));
+        }
+        if let Some(bucket_name) = bucket_name {
+            let rate = self.bucket_limit(bucket_name).requests_per_sec;
+            if rate > 0.0 {
+                limits.push((format!("bucket:{}", bucket_name), rate));
+            }
+        }
+        if limits.is_empty() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        prune(&mut buckets, now);
+
+        let mut wait = Duration::ZERO;
+        for (key, rate) in &limits {
+            // 速率低于 1 时也要允许至少一个请求
+            let bucket = buckets
+                .entry(key.clone())
+                .or_insert_with(|| TokenBucket::new(*rate, rate.max(1.0), now));
+            bucket.refill(now);
+            if bucket.tokens < 1.0 {
+                wait = wait.max(Duration::from_secs_f64((1.0 - bucket.tokens) / rate));
+            }
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for (key, _) in &limits {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+
+    /// 请求适用的带宽节流器（没有带宽限制时返回 None）
+    pub fn bandwidth(&self, client: &str, bucket_name: Option<&str>) -> Option<Throttle> {
+        let mut limits = Vec::new();
+        if self.client_bytes_per_sec > 0 {
+            limits.push((format!("client:{}", client), self.client_bytes_per_sec as f64));
+        }
+        if let Some(bucket_name) = bucket_name {
+            let rate = self.bucket_limit(bucket_name).bytes_per_sec;
+            if rate > 0 {
+                limits.push((format!("bucket:{}", bucket_name), rate as f64));
+            }
+        }
+        if limits.is_empty() {
+            return None;
+        }
+
+        Some(Throttle {
+            buckets: self.bandwidth.clone(),
+            limits: Arc::new(limits),
+        })
+    }
+}
+
+/// 清理已经回满（即空闲）的令牌桶，限制内存占用
+fn prune(buckets: &mut HashMap<String, TokenBucket>, now: Instant) {
+    if buckets.len() <= MAX_TOKEN_BUCKETS {
+        return;
+    }
+    buckets.retain(|_, bucket| {
+        bucket.refill(now);
+        bucket.tokens < bucket.capacity
+    });
+}
+
+/// 带宽节流器：同一客户端或桶的所有请求共享令牌
+#[derive(Clone)]
+pub struct Throttle {
+    buckets: TokenBuckets,
+    limits: Arc<Vec<(String, f64)>>,
+}
+
+impl Throttle {
+    /// 扣除字节数对应的令牌；令牌不足时允许透支，并等待透支部分被补回
+    pub async fn consume(&self, bytes: usize) {
+        let wait = {
+            let now = Instant::now();
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            prune(&mut buckets, now);
+
+            let mut wait = Duration::ZERO;
+            for (key, rate) in self.limits.iter() {
+                let bucket = buckets
+                    .entry(key.clone())
+                    .or_insert_with(|| TokenBucket::new(*rate, *rate, now));
+                bucket.refill(now);
+                bucket.tokens -= bytes as f64;
+                if bucket.tokens < 0.0 {
+                    wait = wait.max(Duration::from_secs_f64(-bucket.tokens / rate));
+                }
+            }
+            wait
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 按带宽限制节流消息体：数据拆分为不超过 64KiB 的分块，每块传输前先扣除令牌
+pub fn throttle_body(body: Body, throttle: Throttle) -> Body {
+    let stream = body
+        .into_data_stream()
+        .flat_map(|chunk| stream::iter(split_chunk(chunk)))
+        .then(move |chunk| {
+            let throttle = throttle.clone();
+            async move {
+                if let Ok(data) = &chunk {
+                    throttle.consume(data.len()).await;
+                }
+                chunk
+            }
+        });
+    Body::from_stream(stream)
+}
+
+fn split_chunk(chunk: Result<Bytes, axum::Error>) -> Vec<Result<Bytes, axum::Error>> {
+    match chunk {
+        Ok(mut data) => {
+            let mut chunks = Vec::new();
+            while data.len() > THROTTLE_CHUNK_SIZE {
+                chunks.push(Ok(data.split_to(THROTTLE_CHUNK_SIZE)));
+            }
+            chunks.push(Ok(data));
+            chunks
+        }
+        Err(e) => vec![Err(e)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(client_rps: f64, client_bps: u64, overrides: &[(&str, f64, u64)]) -> RateLimiter {
+        let settings = Settings {
+            client_requests_per_sec: client_rps,
+            client_bytes_per_sec: client_bps,
+            bucket_rate_limits: overrides
+                .iter()
+                .map(|(name, rps, bps)| (name.to_string(), BucketRateLimit { requests_per_sec: *rps, bytes_per_sec: *bps }))
+                .collect(),
+            ..Settings::default()
+        };
+        RateLimiter::new(&settings)
+    }
+
+    fn tokens(buckets: &TokenBuckets, key: &str) -> f64 {
+        buckets.lock().unwrap()[key].tokens
+    }
+
+    #[test]
+    fn token_bucket_refills_at_rate_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, 10.0, start);
+        bucket.tokens = 0.0;
+
+        bucket.refill(start + Duration::from_millis(250));
+        assert!((bucket.tokens - 2.5).abs() < 1e-9);
+
+        bucket.refill(start + Duration::from_millis(500));
+        assert!((bucket.tokens - 5.0).abs() < 1e-9);
+
+        // 最多积累一秒的配额
+        bucket.refill(start + Duration::from_secs(10));
+        assert_eq!(bucket.tokens, 10.0);
+    }
+
+    #[test]
+    fn token_bucket_refills_overdraft_and_ignores_clock_going_backwards() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(100.0, 100.0, start + Duration::from_secs(1));
+        bucket.tokens = -50.0;
+
+        bucket.refill(start);
+        assert_eq!(bucket.tokens, -50.0);
+
+        bucket.refill(start + Duration::from_millis(1250));
+        assert!((bucket.tokens - -25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_limits_always_allow() {
+        let limiter = limiter(0.0, 0, &[]);
+        for _ in 0..100 {
+            assert!(limiter.check_request("alice", Some("photos")).is_ok());
+        }
+        assert!(limiter.bandwidth("alice", Some("photos")).is_none());
+    }
+
+    #[test]
+    fn client_request_limit_allows_burst_then_waits() {
+        let limiter = limiter(2.0, 0, &[]);
+        assert!(limiter.check_request("alice", None).is_ok());
+        assert!(limiter.check_request("alice", None).is_ok());
+
+        let wait = limiter.check_request("alice", None).unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(500), "{:?}", wait);
+
+        // 其他客户端不受影响
+        assert!(limiter.check_request("bob", None).is_ok());
+    }
+
+    #[test]
+    fn fractional_rate_allows_one_request() {
+        let limiter = limiter(0.5, 0, &[]);
+        assert!(limiter.check_request("alice", None).is_ok());
+
+        let wait = limiter.check_request("alice", None).unwrap_err();
+        assert!(wait > Duration::from_millis(1900) && wait <= Duration::from_secs(2), "{:?}", wait);
+    }
+
+    #[test]
+    fn request_refills_after_waiting() {
+        let limiter = limiter(1.0, 0, &[]);
+        assert!(limiter.check_request("alice", None).is_ok());
+        assert!(limiter.check_request("alice", None).is_err());
+
+        // 模拟时间流逝：把桶的上次补充时间往前移
+        limiter.requests.lock().unwrap().get_mut("client:alice").unwrap().last -= Duration::from_secs(1);
+        assert!(limiter.check_request("alice", None).is_ok());
+        assert!(limiter.check_request("alice", None).is_err());
+    }
+
+    #[test]
+    fn rejected_request_does_not_consume_other_limits() {
+        let limiter = limiter(5.0, 0, &[("photos", 1.0, 0)]);
+        assert!(limiter.check_request("alice", Some("photos")).is_ok());
+        assert!(limiter.check_request("alice", Some("photos")).is_err());
+        assert!(limiter.check_request("alice", Some("photos")).is_err());
+
+        // 桶级限制拒绝的请求不扣除客户端令牌
+        assert!((tokens(&limiter.requests, "client:alice") - 4.0).abs() < 0.01);
+        assert!(limiter.check_request("alice", Some("videos")).is_ok());
+    }
+
+    #[test]
+    fn bucket_override_takes_precedence_over_defaults() {
+        let settings = Settings {
+            bucket_requests_per_sec: 1.0,
+            bucket_rate_limits: HashMap::from([(
+                "photos".to_string(),
+                BucketRateLimit { requests_per_sec: 3.0, bytes_per_sec: 0 },
+            )]),
+            ..Settings::default()
+        };
+        let limiter = RateLimiter::new(&settings);
+
+        for _ in 0..3 {
+            assert!(limiter.check_request("alice", Some("photos")).is_ok());
+        }
+        assert!(limiter.check_request("alice", Some("photos")).is_err());
+
+        assert!(limiter.check_request("alice", Some("videos")).is_ok());
+        assert!(limiter.check_request("bob", Some("videos")).is_err());
+    }
+
+    #[tokio::test]
+    async fn throttle_shares_bandwidth_and_allows_overdraft() {
+        let limiter = limiter(0.0, 1_000_000, &[]);
+        let first = limiter.bandwidth("alice", None).unwrap();
+        let second = limiter.bandwidth("alice", None).unwrap();
+
+        first.consume(400_000).await;
+        second.consume(400_000).await;
+        let remaining = tokens(&limiter.bandwidth, "client:alice");
+        assert!((200_000.0..210_000.0).contains(&remaining), "{}", remaining);
+
+        // 透支后等待补回，补回前令牌为负
+        first.consume(250_000).await;
+        let remaining = tokens(&limiter.bandwidth, "client:alice");
+        assert!(remaining < 0.0, "{}", remaining);
+    }
+
+    #[test]
+    fn prune_drops_only_full_buckets() {
+        let now = Instant::now();
+        let mut buckets = HashMap::new();
+        for i in 0..=MAX_TOKEN_BUCKETS {
+            buckets.insert(format!("client:{}", i), TokenBucket::new(1.0, 1.0, now));
+        }
+        buckets.get_mut("client:7").unwrap().tokens = 0.0;
+
+        prune(&mut buckets, now);
+        assert_eq!(buckets.len(), 1);
+        assert!(buckets.contains_key("client:7"));
+    }
+
+    #[test]
+    fn split_chunk_limits_chunk_size() {
+        let data = Bytes::from(vec![7u8; THROTTLE_CHUNK_SIZE * 2 + 10]);
+        let sizes: Vec<usize> = split_chunk(Ok(data)).into_iter().map(|c| c.unwrap().len()).collect();
+        assert_eq!(sizes, vec![THROTTLE_CHUNK_SIZE, THROTTLE_CHUNK_SIZE, 10]);
+
+        let sizes: Vec<usize> = split_chunk(Ok(Bytes::from_static(b"abc"))).into_iter().map(|c| c.unwrap().len()).collect();
+        assert_eq!(sizes, vec![3]);
+    }
+}
@@ -0,0 +1,467 @@
+use crate::config::Settings;
+use crate::content_type;
+use anyhow::Result;
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::io::Write;
+
+/// brotli 压缩质量（0-11），兼顾压缩率和响应延迟
+const BROTLI_QUALITY: u32 = 5;
+/// brotli 窗口大小（log2）
+const BROTLI_WINDOW: u32 = 22;
+/// 超过该大小的响应在阻塞线程池中压缩
+const BLOCKING_THRESHOLD: usize = 64 * 1024;
+
+/// 响应内容编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCoding {
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+impl ContentCoding {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "br" => Some(Self::Brotli),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Brotli => "br",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, BROTLI_QUALITY, BROTLI_WINDOW);
+                encoder.write_all(data)?;
+                encoder.into_inner()
+            }
+            Self::Zstd => zstd::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL)?,
+        })
+    }
+
+    /// 压缩后的表示使用的 ETag：在引号内追加 "-编码名"，与未压缩的表示区分
+    fn encoded_etag(&self, etag: &str) -> String {
+        match etag.strip_suffix('"') {
+            Some(tag) => format!("{}-{}\"", tag, self.name()),
+            None => format!("{}-{}", etag, self.name()),
+        }
+    }
+}
+
+/// 去掉压缩表示 ETag 上的编码后缀（用于条件请求与原始 ETag 比较）
+pub fn strip_encoding_suffix(etag: &str) -> &str {
+    [ContentCoding::Gzip, ContentCoding::Brotli, ContentCoding::Zstd]
+        .iter()
+        .find_map(|coding| etag.strip_suffix(coding.name()).and_then(|tag| tag.strip_suffix('-')))
+        .unwrap_or(etag)
+}
+
+/// 对象内容响应的标记：返回对象数据的处理函数插入到响应扩展中，
+/// 没有该标记的响应按 API 响应处理
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectBody {
+    /// 对象按桶压缩配置压缩存储，不再压缩响应
+    pub stored_compressed: bool,
+}
+
+/// 在请求交给处理函数之前提取的协商结果
+pub struct Negotiation {
+    coding: Option<ContentCoding>,
+    /// Range 请求和 HEAD 请求不压缩
+    skip: bool,
+    if_none_match: Option<String>,
+}
+
+/// 响应压缩：按 Accept-Encoding 协商 gzip/br/zstd，压缩 API 响应和（可选的）对象内容
+#[derive(Clone)]
+pub struct ResponseCompression {
+    api: bool,
+    objects: bool,
+    min_size: u64,
+    /// 服务端优先顺序
+    codings: Vec<ContentCoding>,
+}
+
+impl ResponseCompression {
+    pub fn new(settings: &Settings) -> Self {
+        let mut codings = Vec::new();
+        for name in &settings.response_compression_encodings {
+            match ContentCoding::parse(name) {
+                Some(coding) if !codings.contains(&coding) => codings.push(coding),
+                Some(_) => {}
+                None => tracing::warn!("Ignoring unsupported response encoding '{}'", name),
+            }
+        }
+        Self {
+            api: settings.response_compression,
+            objects: settings.object_response_compression,
+            min_size: settings.response_compression_min_size,
+            codings,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        (self.api || self.objects) && !self.codings.is_empty()
+    }
+
+    /// 按 Accept-Encoding 选择编码：q 值最高的优先，q 值相同时按服务端顺序；
+    /// 未列出的编码只有在 `*` 的 q 值大于0时才可用
+    pub fn negotiate(&self, accept_encoding: &str) -> Option<ContentCoding> {
+        let mut wildcard = None;
+        let mut listed: Vec<(ContentCoding, f32)> = Vec::new();
+        for item in accept_encoding.split(',') {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap_or("").trim();
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q=").or_else(|| param.trim().strip_prefix("Q=")))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if name == "*" {
+                wildcard = Some(q);
+            } else if let Some(coding) = ContentCoding::parse(name) {
+                listed.push((coding, q));
+            }
+        }
+
+        let quality = |coding: ContentCoding| {
+            listed.iter().find(|(listed, _)| *listed == coding).map(|(_, q)| *q).or(wildcard).unwrap_or(0.0)
+        };
+        let mut best: Option<(ContentCoding, f32)> = None;
+        for &coding in &self.codings {
+            let q = quality(coding);
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((coding, q));
+            }
+        }
+        best.map(|(coding, _)| coding)
+    }
+
+    pub fn negotiation(&self, method: &Method, headers: &HeaderMap) -> Negotiation {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        Negotiation {
+            coding: header("accept-encoding").and_then(|value| self.negotiate(value)),
+            skip: method == Method::HEAD || headers.contains_key("range"),
+            if_none_match: header("if-none-match").map(str::to_string),
+        }
+    }
+
+    /// 按协商结果压缩响应。只压缩长度已知、不小于阈值、内容类型可压缩的完整响应；
+    /// 部分内容、已编码或带 `Cache-Control: no-transform` 的响应保持原样
+    pub async fn apply(&self, negotiation: Negotiation, response: Response) -> Response {
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return restore_not_modified_etag(&negotiation, response);
+        }
+
+        let allowed = match response.extensions().get::<ObjectBody>() {
+            Some(object) => self.objects && !object.stored_compressed,
+            None => self.api,
+        };
+        if !allowed || !is_compressible_response(&response) {
+            return response;
+        }
+        match axum::body::HttpBody::size_hint(response.body()).exact() {
+            Some(size) if size >= self.min_size => {}
+            _ => return response,
+        }
+
+        // 响应内容随 Accept-Encoding 变化，缓存需要按该请求头区分
+        let (mut parts, body) = response.into_parts();
+        parts.headers.append("vary", HeaderValue::from_static("accept-encoding"));
+        let coding = match negotiation.coding {
+            Some(coding) if !negotiation.skip => coding,
+            _ => return Response::from_parts(parts, body),
+        };
+
+        let data = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Failed to read response body for compression: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        let encoded = if data.len() > BLOCKING_THRESHOLD {
+            let data = data.clone();
+            tokio::task::spawn_blocking(move || coding.encode(&data)).await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result)
+        } else {
+            coding.encode(&data)
+        };
+        let encoded = match encoded {
+            Ok(encoded) if encoded.len() < data.len() => encoded,
+            Ok(_) => return Response::from_parts(parts, Body::from(data)),
+            Err(e) => {
+                tracing::warn!("Failed to compress response with {}: {}", coding.name(), e);
+                return Response::from_parts(parts, Body::from(data));
+            }
+        };
+
+        parts.headers.insert("content-encoding", HeaderValue::from_static(coding.name()));
+        parts.headers.insert("content-length", encoded.len().into());
+        // 区间请求针对未压缩的表示，压缩后的响应不再声明支持
+        parts.headers.remove("accept-ranges");
+        if let Some(etag) = parts.headers.get("etag").and_then(|v| v.to_str().ok()).map(|etag| coding.encoded_etag(etag)) {
+            if let Ok(etag) = HeaderValue::from_str(&etag) {
+                parts.headers.insert("etag", etag);
+            }
+        }
+        Response::from_parts(parts, Body::from(encoded))
+    }
+}
+
+/// 304 响应的 ETag 应与客户端缓存的表示一致：If-None-Match 中带编码后缀的 ETag 命中时返回该 ETag
+fn restore_not_modified_etag(negotiation: &Negotiation, mut response: Response) -> Response {
+    let Some(condition) = &negotiation.if_none_match else {
+        return response;
+    };
+    let Some(etag) = response.headers().get("etag").and_then(|v| v.to_str().ok()).map(str::to_string) else {
+        return response;
+    };
+    let normalize = |value: &str| value.trim().trim_start_matches("W/").trim_matches('"').to_string();
+    let encoded = condition.split(',').map(str::trim).find(|candidate| {
+        let candidate = normalize(candidate);
+        candidate != normalize(&etag) && strip_encoding_suffix(&candidate) == normalize(&etag)
+    });
+    if let Some(value) = encoded.and_then(|candidate| HeaderValue::from_str(candidate).ok()) {
+        response.headers_mut().insert("etag", value);
+        response.headers_mut().append("vary", HeaderValue::from_static("accept-encoding"));
+    }
+    response
+}
+
+/// 只压缩完整的成功或错误响应，且内容类型基于文本或属于少数未压缩的二进制格式
+fn is_compressible_response(response: &Response) -> bool {
+    let status = response.status();
+    if status.is_informational() || status == StatusCode::NO_CONTENT || status == StatusCode::PARTIAL_CONTENT {
+        return false;
+    }
+    let headers = response.headers();
+    if headers.contains_key("content-encoding") || headers.contains_key("content-range") {
+        return false;
+    }
+    let no_transform = headers.get("cache-control").and_then(|v| v.to_str().ok())
+        .is_some_and(|value| value.to_ascii_lowercase().contains("no-transform"));
+    if no_transform {
+        return false;
+    }
+    let Some(content_type) = headers.get("content-type").and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    content_type::is_textual(&essence)
+        || matches!(essence.as_str(),
+            "application/wasm" | "font/ttf" | "font/otf" | "application/vnd.ms-fontobject" | "image/bmp" | "image/x-icon")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn compression(codings: &[ContentCoding]) -> ResponseCompression {
+        ResponseCompression { api: true, objects: false, min_size: 32, codings: codings.to_vec() }
+    }
+
+    fn all_codings() -> ResponseCompression {
+        compression(&[ContentCoding::Zstd, ContentCoding::Brotli, ContentCoding::Gzip])
+    }
+
+    fn request(headers: &[(&'static str, &'static str)]) -> Negotiation {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        all_codings().negotiation(&Method::GET, &map)
+    }
+
+    fn json_response(body: &str) -> Response {
+        Response::builder()
+            .header("content-type", "application/json")
+            .header("etag", "\"abc\"")
+            .header("accept-ranges", "bytes")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn gunzip(data: &[u8]) -> String {
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(data).read_to_string(&mut text).unwrap();
+        text
+    }
+
+    #[test]
+    fn negotiate_prefers_the_highest_q_value() {
+        let server = all_codings();
+        assert_eq!(server.negotiate("gzip;q=1.0, br;q=0.5"), Some(ContentCoding::Gzip));
+        assert_eq!(server.negotiate("gzip;q=0.2, zstd;q=0.8, br;q=0.5"), Some(ContentCoding::Zstd));
+        assert_eq!(server.negotiate("gzip; Q=0.9, br;q=0.1"), Some(ContentCoding::Gzip));
+    }
+
+    #[test]
+    fn negotiate_breaks_ties_by_server_order() {
+        assert_eq!(all_codings().negotiate("gzip, br, zstd"), Some(ContentCoding::Zstd));
+        let server = compression(&[ContentCoding::Gzip, ContentCoding::Brotli]);
+        assert_eq!(server.negotiate("br, gzip"), Some(ContentCoding::Gzip));
+    }
+
+    #[test]
+    fn negotiate_excludes_zero_q_values_and_unsupported_codings() {
+        let server = all_codings();
+        assert_eq!(server.negotiate("zstd;q=0, br;q=0, gzip"), Some(ContentCoding::Gzip));
+        assert_eq!(server.negotiate("gzip;q=0"), None);
+        assert_eq!(server.negotiate("deflate, identity"), None);
+        assert_eq!(server.negotiate(""), None);
+        assert_eq!(compression(&[ContentCoding::Gzip]).negotiate("br, zstd"), None);
+    }
+
+    #[test]
+    fn negotiate_applies_the_wildcard_to_unlisted_codings() {
+        let server = all_codings();
+        assert_eq!(server.negotiate("*"), Some(ContentCoding::Zstd));
+        assert_eq!(server.negotiate("zstd;q=0, *;q=0.5"), Some(ContentCoding::Brotli));
+        assert_eq!(server.negotiate("gzip;q=0.3, *;q=0.5"), Some(ContentCoding::Zstd));
+        assert_eq!(server.negotiate("gzip, *;q=0"), Some(ContentCoding::Gzip));
+        assert_eq!(server.negotiate("*;q=0"), None);
+    }
+
+    #[test]
+    fn negotiation_skips_head_and_range_requests() {
+        let server = all_codings();
+        let mut headers = HeaderMap::new();
+        headers.insert("accept-encoding", HeaderValue::from_static("gzip"));
+        assert!(!server.negotiation(&Method::GET, &headers).skip);
+        assert!(server.negotiation(&Method::HEAD, &headers).skip);
+        headers.insert("range", HeaderValue::from_static("bytes=0-9"));
+        assert!(server.negotiation(&Method::GET, &headers).skip);
+    }
+
+    #[test]
+    fn encoded_etags_round_trip() {
+        assert_eq!(ContentCoding::Gzip.encoded_etag("\"abc\""), "\"abc-gzip\"");
+        assert_eq!(ContentCoding::Brotli.encoded_etag("abc"), "abc-br");
+        assert_eq!(strip_encoding_suffix("abc-gzip"), "abc");
+        assert_eq!(strip_encoding_suffix("abc-br"), "abc");
+        assert_eq!(strip_encoding_suffix("abc-zstd"), "abc");
+        assert_eq!(strip_encoding_suffix("abc-deflate"), "abc-deflate");
+        assert_eq!(strip_encoding_suffix("abcgzip"), "abcgzip");
+    }
+
+    #[test]
+    fn compressible_responses_are_complete_and_textual() {
+        let response = |status: StatusCode, headers: &[(&str, &str)]| {
+            let mut builder = Response::builder().status(status);
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        assert!(is_compressible_response(&response(StatusCode::OK, &[("content-type", "application/json")])));
+        assert!(is_compressible_response(&response(StatusCode::NOT_FOUND, &[("content-type", "text/html; charset=utf-8")])));
+        assert!(is_compressible_response(&response(StatusCode::OK, &[("content-type", "application/wasm")])));
+        assert!(!is_compressible_response(&response(StatusCode::OK, &[("content-type", "image/png")])));
+        assert!(!is_compressible_response(&response(StatusCode::OK, &[])));
+        assert!(!is_compressible_response(&response(StatusCode::PARTIAL_CONTENT, &[("content-type", "text/plain")])));
+        assert!(!is_compressible_response(&response(StatusCode::NO_CONTENT, &[("content-type", "text/plain")])));
+        assert!(!is_compressible_response(&response(StatusCode::OK, &[("content-type", "text/plain"), ("content-range", "bytes 0-9/20")])));
+        assert!(!is_compressible_response(&response(StatusCode::OK, &[("content-type", "text/plain"), ("content-encoding", "gzip")])));
+        assert!(!is_compressible_response(&response(StatusCode::OK, &[("content-type", "text/plain"), ("cache-control", "public, No-Transform")])));
+    }
+
+    #[tokio::test]
+    async fn apply_compresses_api_responses() {
+        let body = "{\"message\":\"hello\"}".repeat(20);
+        let response = all_codings().apply(request(&[("accept-encoding", "gzip")]), json_response(&body)).await;
+
+        let headers = response.headers().clone();
+        assert_eq!(headers["content-encoding"], "gzip");
+        assert_eq!(headers["vary"], "accept-encoding");
+        assert_eq!(headers["etag"], "\"abc-gzip\"");
+        assert!(!headers.contains_key("accept-ranges"));
+        let data = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(headers["content-length"], data.len().to_string().as_str());
+        assert_eq!(gunzip(&data), body);
+    }
+
+    #[tokio::test]
+    async fn apply_leaves_small_and_range_responses_alone() {
+        let server = all_codings();
+        let small = server.apply(request(&[("accept-encoding", "gzip")]), json_response("{}")).await;
+        assert!(!small.headers().contains_key("content-encoding"));
+        assert!(!small.headers().contains_key("vary"));
+
+        let body = "x".repeat(100);
+        let ranged = server.apply(request(&[("accept-encoding", "gzip"), ("range", "bytes=0-9")]), json_response(&body)).await;
+        assert!(!ranged.headers().contains_key("content-encoding"));
+        assert_eq!(ranged.headers()["vary"], "accept-encoding");
+        assert_eq!(ranged.headers()["etag"], "\"abc\"");
+    }
+
+    #[tokio::test]
+    async fn apply_only_compresses_objects_when_enabled() {
+        let body = "plain text ".repeat(20);
+        let object = |stored_compressed| {
+            let mut response = json_response(&body);
+            response.extensions_mut().insert(ObjectBody { stored_compressed });
+            response
+        };
+
+        let api_only = all_codings();
+        let response = api_only.apply(request(&[("accept-encoding", "gzip")]), object(false)).await;
+        assert!(!response.headers().contains_key("content-encoding"));
+
+        let with_objects = ResponseCompression { objects: true, ..all_codings() };
+        let response = with_objects.apply(request(&[("accept-encoding", "gzip")]), object(false)).await;
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+        let response = with_objects.apply(request(&[("accept-encoding", "gzip")]), object(true)).await;
+        assert!(!response.headers().contains_key("content-encoding"));
+    }
+
+    #[tokio::test]
+    async fn apply_keeps_incompressible_bodies_uncompressed() {
+        // gzip 的头尾开销超过了这段短文本能节省的字节数
+        let body = "{\"id\":\"7f3a9c2e\",\"ok\":true,\"n\":42}";
+        let response = compression(&[ContentCoding::Gzip]).apply(request(&[("accept-encoding", "gzip")]), json_response(body)).await;
+        assert!(!response.headers().contains_key("content-encoding"));
+        assert_eq!(response.headers()["vary"], "accept-encoding");
+        let data = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(data, body.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn apply_restores_encoded_etags_on_not_modified() {
+        let not_modified = || Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header("etag", "\"abc\"")
+            .body(Body::empty())
+            .unwrap();
+        let server = all_codings();
+
+        let response = server.apply(request(&[("if-none-match", "\"abc-br\"")]), not_modified()).await;
+        assert_eq!(response.headers()["etag"], "\"abc-br\"");
+        assert_eq!(response.headers()["vary"], "accept-encoding");
+
+        let response = server.apply(request(&[("if-none-match", "\"abc\"")]), not_modified()).await;
+        assert_eq!(response.headers()["etag"], "\"abc\"");
+        let response = server.apply(request(&[("if-none-match", "\"other-gzip\"")]), not_modified()).await;
+        assert_eq!(response.headers()["etag"], "\"abc\"");
+    }
+}
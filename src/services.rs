@@ -1,1290 +1,3484 @@
-use crate::models::{Bucket, Object, ObjectMetadata};
-use crate::utils::{validate_bucket_name, validate_object_key, generate_etag, get_mime_type, sanitize_path, sha256_hash, is_valid_etag_format};
-use anyhow::{Result, anyhow};
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::fs;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use serde_json;
-use chrono;
-use std::time::{SystemTime, UNIX_EPOCH};
-use regex;
-
-/// 重复数据删除模式
-#[derive(Debug, Clone)]
-pub enum DeduplicationMode {
-    /// 拒绝重复内容
-    Reject,
-    /// 允许重复内容
-    Allow,
-    /// 创建引用（节省存储空间）
-    Reference,
-}
-
-/// 存储服务 - 参考MinIO的存储结构
-#[derive(Clone)]
-pub struct StorageService {
-    data_dir: PathBuf,
-    buckets: Arc<RwLock<HashMap<String, Bucket>>>,
-    object_index: Arc<RwLock<HashMap<String, HashMap<String, String>>>>,
-    etag_index: Arc<RwLock<HashMap<String, HashMap<String, Vec<String>>>>>,
-}
-
-impl StorageService {
-    pub async fn new(data_dir: String) -> Result<Self> {
-        let data_path = PathBuf::from(data_dir);
-        
-        // 创建数据目录
-        if !data_path.exists() {
-            fs::create_dir_all(&data_path)?;
-        }
-        
-        // 加载现有桶
-        let buckets = Self::load_buckets(&data_path).await?;
-        
-        // 构建对象索引
-        let object_index = Self::build_object_index(&data_path).await?;
-        
-        // 构建ETag索引
-        let etag_index = Self::build_etag_index(&data_path).await?;
-        
-        Ok(Self {
-            data_dir: data_path,
-            buckets: Arc::new(RwLock::new(buckets)),
-            object_index: Arc::new(RwLock::new(object_index)),
-            etag_index: Arc::new(RwLock::new(etag_index)),
-        })
-    }
-    
-    async fn load_buckets(data_dir: &Path) -> Result<HashMap<String, Bucket>> {
-        let mut buckets = HashMap::new();
-        
-        if data_dir.exists() {
-            for entry in fs::read_dir(data_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                
-                if path.is_dir() {
-                    let bucket_name = path.file_name()
-                        .and_then(|name| name.to_str())
-                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
-                    
-                    // 跳过系统目录
-                    if bucket_name.starts_with('.') {
-                        continue;
-                    }
-                    
-                    let metadata_path = path.join(".sevino.meta").join("bucket.json");
-                    let bucket = if metadata_path.exists() {
-                        let content = fs::read_to_string(metadata_path)?;
-                        serde_json::from_str(&content)?
-                    } else {
-                        Bucket::new(bucket_name.to_string())
-                    };
-                    
-                    buckets.insert(bucket_name.to_string(), bucket);
-                }
-            }
-        }
-        
-        Ok(buckets)
-    }
-    
-    async fn build_object_index(data_dir: &Path) -> Result<HashMap<String, HashMap<String, String>>> {
-        let mut index = HashMap::new();
-        
-        if data_dir.exists() {
-            for entry in fs::read_dir(data_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                
-                if path.is_dir() {
-                    let bucket_name = path.file_name()
-                        .and_then(|name| name.to_str())
-                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
-                    
-                    // 跳过系统目录
-                    if bucket_name.starts_with('.') {
-                        continue;
-                    }
-                    
-                    let mut bucket_index = HashMap::new();
-                    let meta_dir = path.join(".sevino.meta").join("objects");
-                    
-                    if meta_dir.exists() {
-                        for meta_entry in fs::read_dir(meta_dir)? {
-                            let meta_entry = meta_entry?;
-                            let meta_path = meta_entry.path();
-                            
-                            if meta_path.is_file() && meta_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
-                                if let Ok(content) = fs::read_to_string(&meta_path) {
-                                    if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
-                                        let object_id = Self::generate_object_id(bucket_name, &metadata.key);
-                                        bucket_index.insert(metadata.key, object_id);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                    if !bucket_index.is_empty() {
-                        index.insert(bucket_name.to_string(), bucket_index);
-                    }
-                }
-            }
-        }
-        
-        Ok(index)
-    }
-    
-    async fn build_etag_index(data_dir: &Path) -> Result<HashMap<String, HashMap<String, Vec<String>>>> {
-        let mut etag_index = HashMap::new();
-        
-        if data_dir.exists() {
-            for entry in fs::read_dir(data_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                
-                if path.is_dir() {
-                    let bucket_name = path.file_name()
-                        .and_then(|name| name.to_str())
-                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
-                    
-                    // 跳过系统目录
-                    if bucket_name.starts_with('.') {
-                        continue;
-                    }
-                    
-                    let mut bucket_etag_index = HashMap::new();
-                    let meta_dir = path.join(".sevino.meta").join("objects");
-                    
-                    if meta_dir.exists() {
-                        for meta_entry in fs::read_dir(meta_dir)? {
-                            let meta_entry = meta_entry?;
-                            let meta_path = meta_entry.path();
-                            
-                            if meta_path.is_file() && meta_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
-                                if let Ok(content) = fs::read_to_string(&meta_path) {
-                                    if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
-                                        let object_id = Self::generate_object_id(bucket_name, &metadata.key);
-                                        bucket_etag_index
-                                            .entry(metadata.etag)
-                                            .or_insert_with(Vec::new)
-                                            .push(object_id);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                    if !bucket_etag_index.is_empty() {
-                        etag_index.insert(bucket_name.to_string(), bucket_etag_index);
-                    }
-                }
-            }
-        }
-        
-        Ok(etag_index)
-    }
-    
-    /// 生成对象ID（类似MinIO的哈希化文件名）
-    pub fn generate_object_id(bucket_name: &str, key: &str) -> String {
-        let combined = format!("{}:{}", bucket_name, key);
-        sha256_hash(combined.as_bytes())
-    }
-    
-    /// 获取对象存储路径（使用哈希化文件名）
-    fn get_object_data_path(&self, bucket_name: &str, object_id: &str) -> PathBuf {
-        // 使用前4个字符作为目录名，避免单个目录文件过多
-        let prefix = &object_id[..4];
-        let sub_prefix = &object_id[4..6];
-        
-        self.data_dir
-            .join(bucket_name)
-            .join(prefix)
-            .join(sub_prefix)
-            .join(object_id)
-    }
-    
-    /// 获取对象元数据路径
-    fn get_object_metadata_path(&self, bucket_name: &str, object_id: &str) -> PathBuf {
-        self.data_dir
-            .join(bucket_name)
-            .join(".sevino.meta")
-            .join("objects")
-            .join(format!("{}.json", object_id))
-    }
-    
-    /// 获取桶元数据路径
-    fn get_bucket_metadata_path(&self, bucket_name: &str) -> PathBuf {
-        self.data_dir
-            .join(bucket_name)
-            .join(".sevino.meta")
-            .join("bucket.json")
-    }
-    
-    pub async fn save_bucket_metadata(&self, bucket: &Bucket) -> Result<()> {
-        let bucket_dir = self.data_dir.join(&bucket.name);
-        if !bucket_dir.exists() {
-            fs::create_dir_all(&bucket_dir)?;
-        }
-        
-        // 创建.sevino.meta目录
-        let meta_dir = bucket_dir.join(".sevino.meta");
-        if !meta_dir.exists() {
-            fs::create_dir_all(&meta_dir)?;
-        }
-        
-        let metadata_path = self.get_bucket_metadata_path(&bucket.name);
-        let content = serde_json::to_string_pretty(bucket)?;
-        fs::write(metadata_path, content)?;
-        
-        Ok(())
-    }
-    
-    pub async fn delete_bucket_directory(&self, bucket_name: &str) -> Result<()> {
-        let bucket_dir = self.data_dir.join(bucket_name);
-        if bucket_dir.exists() {
-            fs::remove_dir_all(bucket_dir)?;
-        }
-        Ok(())
-    }
-    
-    pub async fn save_object_metadata(&self, bucket_name: &str, object_id: &str, metadata: &ObjectMetadata) -> Result<()> {
-        let meta_dir = self.data_dir
-            .join(bucket_name)
-            .join(".sevino.meta")
-            .join("objects");
-        
-        if !meta_dir.exists() {
-            fs::create_dir_all(&meta_dir)?;
-        }
-        
-        let metadata_path = self.get_object_metadata_path(bucket_name, object_id);
-        let content = serde_json::to_string_pretty(metadata)?;
-        fs::write(metadata_path, content)?;
-        
-        Ok(())
-    }
-    
-    pub async fn load_object_metadata(&self, bucket_name: &str, object_id: &str) -> Result<Option<ObjectMetadata>> {
-        let metadata_path = self.get_object_metadata_path(bucket_name, object_id);
-        
-        if metadata_path.exists() {
-            let content = fs::read_to_string(metadata_path)?;
-            let metadata: ObjectMetadata = serde_json::from_str(&content)?;
-            Ok(Some(metadata))
-        } else {
-            Ok(None)
-        }
-    }
-    
-    pub async fn delete_object_metadata(&self, bucket_name: &str, object_id: &str) -> Result<()> {
-        let metadata_path = self.get_object_metadata_path(bucket_name, object_id);
-        if metadata_path.exists() {
-            fs::remove_file(metadata_path)?;
-        }
-        Ok(())
-    }
-    
-    pub async fn list_object_metadata(&self, bucket_name: &str) -> Result<Vec<ObjectMetadata>> {
-        self.list_object_metadata_with_pagination(bucket_name, None, None).await
-    }
-    
-    pub async fn list_object_metadata_with_pagination(
-        &self,
-        bucket_name: &str,
-        max_keys: Option<usize>,
-        marker: Option<String>,
-    ) -> Result<Vec<ObjectMetadata>> {
-        let meta_dir = self.data_dir
-            .join(bucket_name)
-            .join(".sevino.meta")
-            .join("objects");
-        
-        let mut objects = Vec::new();
-        let mut count = 0;
-        let max_keys = max_keys.unwrap_or(usize::MAX);
-        
-        if meta_dir.exists() {
-            let mut entries: Vec<_> = fs::read_dir(meta_dir)?
-                .filter_map(|entry| entry.ok())
-                .collect();
-            
-            // 按文件名排序，确保一致性
-            entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-            
-            let mut started = marker.is_none();
-            
-            for entry in entries {
-                if count >= max_keys {
-                    break;
-                }
-                
-                let path = entry.path();
-                
-                if path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("json") {
-                    // 处理marker逻辑
-                    if !started {
-                        if let Some(marker_val) = &marker {
-                            let file_name = path.file_name()
-                                .and_then(|name| name.to_str())
-                                .unwrap_or("");
-                            if file_name == marker_val {
-                                started = true;
-                            }
-                            continue;
-                        }
-                    }
-                    
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
-                            objects.push(metadata);
-                            count += 1;
-                        }
-                    }
-                }
-            }
-        }
-        
-        Ok(objects)
-    }
-    
-    /// 根据key查找对象ID
-    pub async fn find_object_id_by_key(&self, bucket_name: &str, key: &str) -> Result<Option<String>> {
-        let index = self.object_index.read().await;
-        
-        if let Some(bucket_index) = index.get(bucket_name) {
-            if let Some(object_id) = bucket_index.get(key) {
-                return Ok(Some(object_id.clone()));
-            }
-        }
-        
-        Ok(None)
-    }
-    
-    /// 添加对象到索引
-    pub async fn add_object_to_index(&self, bucket_name: &str, key: &str, object_id: &str) -> Result<()> {
-        let mut index = self.object_index.write().await;
-        
-        let bucket_index = index.entry(bucket_name.to_string())
-            .or_insert_with(HashMap::new);
-        
-        bucket_index.insert(key.to_string(), object_id.to_string());
-        
-        Ok(())
-    }
-    
-    /// 从索引中删除对象
-    pub async fn remove_object_from_index(&self, bucket_name: &str, key: &str) -> Result<()> {
-        let mut index = self.object_index.write().await;
-        
-        if let Some(bucket_index) = index.get_mut(bucket_name) {
-            bucket_index.remove(key);
-            
-            // 如果桶索引为空，删除整个桶索引
-            if bucket_index.is_empty() {
-                index.remove(bucket_name);
-            }
-        }
-        
-        Ok(())
-    }
-    
-    /// 获取桶中对象数量（使用索引，O(1)性能）
-    pub async fn get_bucket_object_count(&self, bucket_name: &str) -> usize {
-        let index = self.object_index.read().await;
-        
-        if let Some(bucket_index) = index.get(bucket_name) {
-            bucket_index.len()
-        } else {
-            0
-        }
-    }
-    
-    /// 检查桶是否为空（使用索引，O(1)性能）
-    pub async fn is_bucket_empty(&self, bucket_name: &str) -> bool {
-        self.get_bucket_object_count(bucket_name).await == 0
-    }
-    
-    /// 重建对象索引（用于修复索引不一致问题）
-    pub async fn rebuild_object_index(&self) -> Result<()> {
-        let new_index = Self::build_object_index(&self.data_dir).await?;
-        let mut index = self.object_index.write().await;
-        *index = new_index;
-        Ok(())
-    }
-    
-    /// 验证索引一致性
-    pub async fn validate_index_consistency(&self, bucket_name: &str) -> Result<bool> {
-        let index_count = self.get_bucket_object_count(bucket_name).await;
-        let disk_objects = self.list_object_metadata(bucket_name).await?;
-        let disk_count = disk_objects.len();
-        
-        Ok(index_count == disk_count)
-    }
-    
-    /// 添加ETag到索引
-    pub async fn add_etag_to_index(&self, bucket_name: &str, etag: &str, object_id: &str) -> Result<()> {
-        let mut etag_index = self.etag_index.write().await;
-        
-        let bucket_etag_index = etag_index.entry(bucket_name.to_string())
-            .or_insert_with(HashMap::new);
-        
-        bucket_etag_index
-            .entry(etag.to_string())
-            .or_insert_with(Vec::new)
-            .push(object_id.to_string());
-        
-        Ok(())
-    }
-    
-    /// 从ETag索引中删除
-    pub async fn remove_etag_from_index(&self, bucket_name: &str, etag: &str, object_id: &str) -> Result<()> {
-        let mut etag_index = self.etag_index.write().await;
-        
-        if let Some(bucket_etag_index) = etag_index.get_mut(bucket_name) {
-            if let Some(object_ids) = bucket_etag_index.get_mut(etag) {
-                object_ids.retain(|id| id != object_id);
-                
-                // 如果没有对象引用这个ETag，删除整个ETag条目
-                if object_ids.is_empty() {
-                    bucket_etag_index.remove(etag);
-                }
-            }
-            
-            // 如果桶的ETag索引为空，删除整个桶索引
-            if bucket_etag_index.is_empty() {
-                etag_index.remove(bucket_name);
-            }
-        }
-        
-        Ok(())
-    }
-    
-    /// 根据ETag查找所有对象
-    pub async fn find_objects_by_etag(&self, bucket_name: &str, etag: &str) -> Result<Vec<String>> {
-        let etag_index = self.etag_index.read().await;
-        
-        if let Some(bucket_etag_index) = etag_index.get(bucket_name) {
-            if let Some(object_ids) = bucket_etag_index.get(etag) {
-                return Ok(object_ids.clone());
-            }
-        }
-        
-        Ok(Vec::new())
-    }
-    
-    /// 检查ETag是否已存在（跨key检测）
-    pub async fn is_etag_exists(&self, bucket_name: &str, etag: &str) -> Result<bool> {
-        let object_ids = self.find_objects_by_etag(bucket_name, etag).await?;
-        Ok(!object_ids.is_empty())
-    }
-}
-
-/// 桶服务
-#[derive(Clone)]
-pub struct BucketService {
-    storage: StorageService,
-}
-
-impl BucketService {
-    pub fn new(storage: StorageService) -> Self {
-        Self { storage }
-    }
-    
-    pub async fn list_buckets(&self) -> Vec<Bucket> {
-        let buckets = self.storage.buckets.read().await;
-        buckets.values().cloned().collect()
-    }
-    
-    pub async fn create_bucket(&self, name: String) -> Result<Bucket> {
-        validate_bucket_name(&name).map_err(|e| anyhow!(e))?;
-        
-        let mut buckets = self.storage.buckets.write().await;
-        
-        if buckets.contains_key(&name) {
-            return Err(anyhow!("Bucket '{}' already exists", name));
-        }
-        
-        let bucket = Bucket::new(name.clone());
-        self.storage.save_bucket_metadata(&bucket).await?;
-        buckets.insert(name, bucket.clone());
-        
-        Ok(bucket)
-    }
-    
-    pub async fn get_bucket(&self, name: &str) -> Option<Bucket> {
-        let buckets = self.storage.buckets.read().await;
-        buckets.get(name).cloned()
-    }
-    
-    pub async fn delete_bucket(&self, name: &str) -> Result<()> {
-        let mut buckets = self.storage.buckets.write().await;
-        
-        if !buckets.contains_key(name) {
-            return Err(anyhow!("Bucket '{}' not found", name));
-        }
-        
-        // 检查桶是否为空（使用索引，O(1)性能）
-        if !self.storage.is_bucket_empty(name).await {
-            return Err(anyhow!("Cannot delete non-empty bucket '{}'", name));
-        }
-        
-        self.storage.delete_bucket_directory(name).await?;
-        buckets.remove(name);
-        
-        Ok(())
-    }
-}
-
-/// 对象服务
-#[derive(Clone)]
-pub struct ObjectService {
-    storage: StorageService,
-}
-
-impl ObjectService {
-    pub fn new(storage: StorageService) -> Self {
-        Self { storage }
-    }
-    
-    pub async fn put_object(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-    ) -> Result<Object> {
-        self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, None).await
-    }
-    
-    pub async fn put_object_with_custom_etag(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-        custom_etag: Option<String>,
-    ) -> Result<Object> {
-        self.put_object_with_versioning_and_custom_etag(bucket_name, key, data, content_type, user_metadata, false, custom_etag).await
-    }
-    
-    pub async fn put_object_with_versioning_and_custom_etag(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-        enable_versioning: bool,
-        custom_etag: Option<String>,
-    ) -> Result<Object> {
-        validate_object_key(key).map_err(|e| anyhow!(e))?;
-        
-        // 检查桶是否存在
-        let bucket = self.storage.buckets.read().await;
-        if !bucket.contains_key(bucket_name) {
-            return Err(anyhow!("Bucket '{}' not found", bucket_name));
-        }
-        drop(bucket);
-        
-        // 使用自定义ETag或生成默认ETag
-        let etag = custom_etag.unwrap_or_else(|| generate_etag(&data));
-        let mime_type = if content_type == "application/octet-stream" {
-            get_mime_type(key)
-        } else {
-            content_type.to_string()
-        };
-        
-        // 注意：这里移除了硬编码的重复内容检查逻辑
-        // 重复内容检查现在由 put_object_with_deduplication 方法处理
-        
-        // 检查是否存在相同内容的文件
-        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? {
-            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
-                // 如果ETag相同，说明内容相同
-                if existing_metadata.etag == etag {
-                    // 更新元数据（时间戳等），但不重新存储数据
-                    let mut updated_metadata = existing_metadata.clone();
-                    updated_metadata.last_modified = chrono::Utc::now();
-                    updated_metadata.user_metadata = user_metadata;
-                    
-                    self.storage.save_object_metadata(bucket_name, &existing_object_id, &updated_metadata).await?;
-                    
-                    return Ok(Object::new(
-                        key.to_string(),
-                        bucket_name.to_string(),
-                        updated_metadata.size,
-                        updated_metadata.content_type,
-                        updated_metadata.etag,
-                        updated_metadata.user_metadata,
-                    ));
-                }
-            }
-        }
-        
-        // 生成版本ID（如果启用版本控制）
-        let version_id = if enable_versioning {
-            Some(self.generate_version_id())
-        } else {
-            None
-        };
-        
-        let object = Object::new(
-            key.to_string(),
-            bucket_name.to_string(),
-            data.len() as u64,
-            mime_type,
-            etag.clone(),
-            user_metadata,
-        );
-        
-        // 生成对象ID（包含版本信息）
-        let object_id = if let Some(vid) = &version_id {
-            format!("{}_{}", StorageService::generate_object_id(bucket_name, key), vid)
-        } else {
-            StorageService::generate_object_id(bucket_name, key)
-        };
-        
-        // 保存对象数据（使用哈希化文件名）
-        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
-        if let Some(parent) = object_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-        }
-        fs::write(&object_path, data)?;
-        
-        // 保存元数据
-        let mut metadata: ObjectMetadata = object.clone().into();
-        if let Some(vid) = version_id {
-            metadata.version_id = Some(vid);
-        }
-        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
-        
-        // 更新索引
-        self.storage.add_object_to_index(bucket_name, key, &object_id).await?;
-        self.storage.add_etag_to_index(bucket_name, &etag, &object_id).await?;
-        
-        Ok(object)
-    }
-    
-    /// 生成版本ID
-    fn generate_version_id(&self) -> String {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        format!("{:016x}", now)
-    }
-    
-    /// 检查文件是否重复（基于ETag）
-    pub async fn is_duplicate_content(&self, bucket_name: &str, key: &str, etag: &str) -> Result<bool> {
-        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? {
-            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
-                return Ok(existing_metadata.etag == etag);
-            }
-        }
-        Ok(false)
-    }
-    
-    /// 检查是否存在相同内容的其他文件（跨key检测）
-    pub async fn find_duplicate_content_keys(&self, bucket_name: &str, etag: &str, exclude_key: Option<&str>) -> Result<Vec<String>> {
-        let object_ids = self.storage.find_objects_by_etag(bucket_name, etag).await?;
-        let mut duplicate_keys = Vec::new();
-        
-        for object_id in object_ids {
-            if let Some(metadata) = self.storage.load_object_metadata(bucket_name, &object_id).await? {
-                // 排除指定的key
-                if let Some(exclude) = exclude_key {
-                    if metadata.key != exclude {
-                        duplicate_keys.push(metadata.key);
-                    }
-                } else {
-                    duplicate_keys.push(metadata.key);
-                }
-            }
-        }
-        
-        Ok(duplicate_keys)
-    }
-    
-    /// 条件上传（只有当文件不存在或内容不同时才上传）
-    pub async fn put_object_if_not_exists(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-    ) -> Result<Object> {
-        let etag = generate_etag(&data);
-        
-        // 检查文件是否已存在且内容相同
-        if self.is_duplicate_content(bucket_name, key, &etag).await? {
-            return Err(anyhow!("Object '{}' already exists with same content", key));
-        }
-        
-        self.put_object(bucket_name, key, data, content_type, user_metadata).await
-    }
-    
-    /// 条件上传（只有当ETag不匹配时才上传）
-    pub async fn put_object_if_etag_mismatch(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-        expected_etag: &str,
-    ) -> Result<Object> {
-        let etag = generate_etag(&data);
-        
-        // 检查当前ETag是否与期望的ETag匹配
-        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? {
-            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
-                if existing_metadata.etag == expected_etag {
-                    return Err(anyhow!("ETag precondition failed: expected '{}', got '{}'", expected_etag, existing_metadata.etag));
-                }
-            }
-        }
-        
-        self.put_object(bucket_name, key, data, content_type, user_metadata).await
-    }
-    
-    /// 智能上传：如果内容已存在，可以选择创建引用或拒绝上传
-    pub async fn put_object_with_deduplication(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-        deduplication_mode: DeduplicationMode,
-    ) -> Result<Object> {
-        self.put_object_with_deduplication_and_custom_etag(bucket_name, key, data, content_type, user_metadata, deduplication_mode, None).await
-    }
-    
-    /// 智能上传：如果内容已存在，可以选择创建引用或拒绝上传（支持自定义ETag）
-    pub async fn put_object_with_deduplication_and_custom_etag(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-        deduplication_mode: DeduplicationMode,
-        custom_etag: Option<String>,
-    ) -> Result<Object> {
-        let etag = custom_etag.clone().unwrap_or_else(|| generate_etag(&data));
-        
-        // 检查是否存在相同内容的其他文件
-        let duplicate_keys = self.find_duplicate_content_keys(bucket_name, &etag, Some(key)).await?;
-        
-        match deduplication_mode {
-            DeduplicationMode::Reject => {
-                if !duplicate_keys.is_empty() {
-                    return Err(anyhow!(
-                        "Content already exists with keys: {}. Use different content or enable deduplication.",
-                        duplicate_keys.join(", ")
-                    ));
-                }
-                self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
-            },
-            DeduplicationMode::Allow => {
-                // 允许重复，正常上传
-                self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
-            },
-            DeduplicationMode::Reference => {
-                if !duplicate_keys.is_empty() {
-                    // 找到重复内容，创建引用
-                    if let Some(first_duplicate_key) = duplicate_keys.first() {
-                        // 找到第一个重复对象的ID
-                        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, first_duplicate_key).await? {
-                            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
-                                // 创建引用对象
-                                let new_object = Object::new(
-                                    key.to_string(),
-                                    bucket_name.to_string(),
-                                    existing_metadata.size,
-                                    existing_metadata.content_type.clone(),
-                                    existing_metadata.etag.clone(),
-                                    user_metadata,
-                                );
-                                
-                                // 生成新的对象ID
-                                let new_object_id = StorageService::generate_object_id(bucket_name, key);
-                                
-                                // 创建引用元数据
-                                let mut new_metadata: ObjectMetadata = new_object.clone().into();
-                                new_metadata.data_holder_id = Some(existing_object_id.clone());
-                                new_metadata.reference_count = 0; // 引用对象本身不计数
-                                
-                                // 保存引用元数据
-                                self.storage.save_object_metadata(bucket_name, &new_object_id, &new_metadata).await?;
-                                
-                                // 更新数据持有者的引用计数
-                                let mut updated_existing_metadata = existing_metadata.clone();
-                                updated_existing_metadata.reference_count += 1;
-                                self.storage.save_object_metadata(bucket_name, &existing_object_id, &updated_existing_metadata).await?;
-                                
-                                // 更新索引
-                                self.storage.add_object_to_index(bucket_name, key, &new_object_id).await?;
-                                self.storage.add_etag_to_index(bucket_name, &etag, &new_object_id).await?;
-                                
-                                Ok(new_object)
-                            } else {
-                                // 如果找不到现有元数据，正常上传
-                                self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
-                            }
-                        } else {
-                            // 如果找不到现有对象ID，正常上传
-                            self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
-                        }
-                    } else {
-                        // 没有重复，正常上传
-                        self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
-                    }
-                } else {
-                    // 没有重复，正常上传
-                    self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
-                }
-            }
-        }
-    }
-    
-    /// 获取对象的所有版本
-    pub async fn list_object_versions(
-        &self,
-        bucket_name: &str,
-        key: &str,
-    ) -> Result<Vec<ObjectMetadata>> {
-        let all_objects = self.storage.list_object_metadata(bucket_name).await?;
-        
-        let mut versions: Vec<ObjectMetadata> = all_objects
-            .into_iter()
-            .filter(|obj| obj.key == key)
-            .collect();
-        
-        // 按创建时间排序（最新的在前）
-        versions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-        
-        Ok(versions)
-    }
-    
-    /// 获取特定版本的对象
-    pub async fn get_object_version(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        version_id: &str,
-    ) -> Result<(Vec<u8>, ObjectMetadata)> {
-        let object_id = format!("{}_{}", StorageService::generate_object_id(bucket_name, key), version_id);
-        
-        // 加载元数据
-        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
-            .ok_or_else(|| anyhow!("Object version not found"))?;
-        
-        // 读取对象数据
-        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
-        if !object_path.exists() {
-            return Err(anyhow!("Object data not found"));
-        }
-        
-        let data = fs::read(object_path)?;
-        
-        Ok((data, metadata))
-    }
-    
-    pub async fn get_object(&self, bucket_name: &str, key: &str) -> Result<(Vec<u8>, ObjectMetadata)> {
-        // 检查桶是否存在
-        let bucket = self.storage.buckets.read().await;
-        if !bucket.contains_key(bucket_name) {
-            return Err(anyhow!("Bucket '{}' not found", bucket_name));
-        }
-        drop(bucket);
-        
-        // 查找对象ID
-        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
-            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
-        
-        // 加载元数据
-        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
-            .ok_or_else(|| anyhow!("Object metadata not found"))?;
-        
-        // 确定数据持有者ID
-        let data_object_id = if let Some(holder_id) = &metadata.data_holder_id {
-            // 检查数据持有者是否还存在
-            if let Some(_holder_metadata) = self.storage.load_object_metadata(bucket_name, holder_id).await? {
-                holder_id.clone()
-            } else {
-                return Err(anyhow!("Data holder for object '{}' not found", key));
-            }
-        } else {
-            // 自己是数据持有者
-            object_id
-        };
-        
-        // 读取对象数据
-        let object_path = self.storage.get_object_data_path(bucket_name, &data_object_id);
-        if !object_path.exists() {
-            return Err(anyhow!("Object data not found"));
-        }
-        
-        let data = fs::read(object_path)?;
-        
-        Ok((data, metadata))
-    }
-    
-    pub async fn delete_object(&self, bucket_name: &str, key: &str) -> Result<()> {
-        // 检查桶是否存在
-        let bucket = self.storage.buckets.read().await;
-        if !bucket.contains_key(bucket_name) {
-            return Err(anyhow!("Bucket '{}' not found", bucket_name));
-        }
-        drop(bucket);
-        
-        // 查找对象ID
-        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
-            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
-        
-        // 获取对象元数据
-        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
-            .ok_or_else(|| anyhow!("Object metadata not found"))?;
-        
-        if let Some(data_holder_id) = &metadata.data_holder_id {
-            // 删除引用对象
-            self.storage.delete_object_metadata(bucket_name, &object_id).await?;
-            self.storage.remove_object_from_index(bucket_name, key).await?;
-            self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
-            
-            // 减少数据持有者的引用计数
-            if let Some(mut holder_metadata) = self.storage.load_object_metadata(bucket_name, data_holder_id).await? {
-                if holder_metadata.reference_count > 0 {
-                    holder_metadata.reference_count -= 1;
-                    self.storage.save_object_metadata(bucket_name, data_holder_id, &holder_metadata).await?;
-                }
-            }
-        } else {
-            // 自己是数据持有者，检查是否有其他对象引用
-            if metadata.reference_count > 0 {
-                return Err(anyhow!("Cannot delete object '{}' because it has {} reference(s). Delete all references first.", key, metadata.reference_count));
-            }
-            
-            // 删除对象数据
-            let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
-            if object_path.exists() {
-                fs::remove_file(object_path)?;
-            }
-            
-            // 删除元数据
-            self.storage.delete_object_metadata(bucket_name, &object_id).await?;
-            
-            // 更新索引
-            self.storage.remove_object_from_index(bucket_name, key).await?;
-            self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
-        }
-        
-        Ok(())
-    }
-    
-    pub async fn get_object_metadata(&self, bucket_name: &str, key: &str) -> Result<ObjectMetadata> {
-        // 检查桶是否存在
-        let bucket = self.storage.buckets.read().await;
-        if !bucket.contains_key(bucket_name) {
-            return Err(anyhow!("Bucket '{}' not found", bucket_name));
-        }
-        drop(bucket);
-        
-        // 查找对象ID
-        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
-            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
-        
-        self.storage.load_object_metadata(bucket_name, &object_id).await?
-            .ok_or_else(|| anyhow!("Object metadata not found"))
-    }
-    
-    pub async fn list_objects(
-        &self,
-        bucket_name: &str,
-        prefix: Option<String>,
-        delimiter: Option<String>,
-        max_keys: Option<u32>,
-        marker: Option<String>,
-        etag_filter: Option<String>,
-    ) -> Result<Vec<Object>> {
-        let all_objects = self.storage.list_object_metadata(bucket_name).await?;
-        
-        let mut objects: Vec<Object> = all_objects
-            .into_iter()
-            .map(|metadata| Object::new(
-                metadata.key,
-                metadata.bucket_name,
-                metadata.size,
-                metadata.content_type,
-                metadata.etag,
-                metadata.user_metadata,
-            ))
-            .collect();
-        
-        // 应用前缀过滤
-        if let Some(prefix) = prefix {
-            objects.retain(|obj| obj.key.starts_with(&prefix));
-        }
-        
-        // 应用ETag过滤
-        if let Some(etag_filter) = etag_filter {
-            objects.retain(|obj| self.matches_etag_filter(&obj.etag, &etag_filter));
-        }
-        
-        // 应用分页
-        if let Some(marker) = marker {
-            objects.retain(|obj| obj.key > marker);
-        }
-        
-        // 应用最大数量限制
-        if let Some(max_keys) = max_keys {
-            objects.truncate(max_keys as usize);
-        }
-        
-        // 应用分隔符过滤
-        if let Some(delimiter) = delimiter {
-            let mut filtered_objects = Vec::new();
-            let mut seen_prefixes = std::collections::HashSet::new();
-            
-            for obj in objects {
-                if let Some(pos) = obj.key.find(&delimiter) {
-                    let prefix = obj.key[..pos + delimiter.len()].to_string();
-                    if !seen_prefixes.contains(&prefix) {
-                        seen_prefixes.insert(prefix.clone());
-                        // 创建一个虚拟对象来表示公共前缀
-                        let virtual_obj = Object::new(
-                            prefix,
-                            bucket_name.to_string(),
-                            0,
-                            "application/x-directory".to_string(),
-                            "".to_string(),
-                            HashMap::new(),
-                        );
-                        filtered_objects.push(virtual_obj);
-                    }
-                } else {
-                    filtered_objects.push(obj);
-                }
-            }
-            objects = filtered_objects;
-        }
-        
-        Ok(objects)
-    }
-    
-    /// 检查ETag是否匹配过滤条件（支持通配符）
-    fn matches_etag_filter(&self, etag: &str, filter: &str) -> bool {
-        // 简单的通配符匹配：* 和 ?
-        if filter.contains('*') || filter.contains('?') {
-            // 将通配符模式转换为正则表达式
-            let pattern = filter
-                .replace(".", "\\.")
-                .replace("*", ".*")
-                .replace("?", ".");
-            
-            if let Ok(regex) = regex::Regex::new(&pattern) {
-                return regex.is_match(etag);
-            }
-        }
-        
-        // 精确匹配
-        etag == filter
-    }
-    
-    /// 测试重复文件处理
-    pub async fn test_duplicate_handling(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        data: Vec<u8>,
-        content_type: &str,
-        user_metadata: HashMap<String, String>,
-    ) -> Result<String> {
-        let etag = generate_etag(&data);
-        let mut result = String::new();
-        
-        // 测试1：检查是否重复
-        result.push_str(&format!("1. 检查文件是否重复 (ETag: {})\n", etag));
-        let is_duplicate = self.is_duplicate_content(bucket_name, key, &etag).await?;
-        result.push_str(&format!("   结果: {}\n\n", if is_duplicate { "重复" } else { "不重复" }));
-        
-        // 测试2：尝试条件上传
-        result.push_str("2. 尝试条件上传（如果不存在）\n");
-        match self.put_object_if_not_exists(bucket_name, key, data.clone(), content_type, user_metadata.clone()).await {
-            Ok(_) => result.push_str("   结果: 上传成功\n\n"),
-            Err(e) => result.push_str(&format!("   结果: {}\n\n", e)),
-        }
-        
-        // 测试3：再次检查重复
-        result.push_str("3. 再次检查文件是否重复\n");
-        let is_duplicate_after = self.is_duplicate_content(bucket_name, key, &etag).await?;
-        result.push_str(&format!("   结果: {}\n\n", if is_duplicate_after { "重复" } else { "不重复" }));
-        
-        // 测试4：尝试上传相同内容
-        result.push_str("4. 尝试上传相同内容\n");
-        match self.put_object_if_not_exists(bucket_name, key, data, content_type, user_metadata).await {
-            Ok(_) => result.push_str("   结果: 上传成功\n\n"),
-            Err(e) => result.push_str(&format!("   结果: {}\n\n", e)),
-        }
-        
-        // 测试5：列出所有版本
-        result.push_str("5. 列出所有版本\n");
-        match self.list_object_versions(bucket_name, key).await {
-            Ok(versions) => {
-                result.push_str(&format!("   版本数量: {}\n", versions.len()));
-                for (i, version) in versions.iter().enumerate() {
-                    result.push_str(&format!("   版本 {}: ETag={}, 大小={}, 时间={}\n", 
-                        i + 1, 
-                        version.etag, 
-                        version.size,
-                        version.created_at.format("%Y-%m-%d %H:%M:%S")
-                    ));
-                }
-            },
-            Err(e) => result.push_str(&format!("   结果: {}\n", e)),
-        }
-        
-        Ok(result)
-    }
-    
-    /// 查找引用某个对象的所有引用对象
-    pub async fn find_references_to_object(&self, bucket_name: &str, object_id: &str) -> Result<Vec<ObjectMetadata>> {
-        let all_objects = self.storage.list_object_metadata(bucket_name).await?;
-        
-        let references: Vec<ObjectMetadata> = all_objects
-            .into_iter()
-            .filter(|obj| obj.data_holder_id.as_ref() == Some(&object_id.to_string()))
-            .collect();
-        
-        Ok(references)
-    }
-    
-    /// 强制删除对象及其所有引用（危险操作）
-    pub async fn force_delete_object_with_references(&self, bucket_name: &str, key: &str) -> Result<()> {
-        // 查找对象ID
-        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
-            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
-        
-        // 查找所有引用
-        let references = self.find_references_to_object(bucket_name, &object_id).await?;
-        
-        // 删除所有引用
-        for reference in references {
-            self.storage.delete_object_metadata(bucket_name, &StorageService::generate_object_id(bucket_name, &reference.key)).await?;
-            self.storage.remove_object_from_index(bucket_name, &reference.key).await?;
-            self.storage.remove_etag_from_index(bucket_name, &reference.etag, &StorageService::generate_object_id(bucket_name, &reference.key)).await?;
-        }
-        
-        // 删除原始对象
-        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
-        if object_path.exists() {
-            fs::remove_file(object_path)?;
-        }
-        
-        self.storage.delete_object_metadata(bucket_name, &object_id).await?;
-        self.storage.remove_object_from_index(bucket_name, key).await?;
-        
-        Ok(())
-    }
-
-    pub async fn list_objects_with_custom_filter(
-        &self,
-        bucket_name: &str,
-        prefix: Option<String>,
-        delimiter: Option<String>,
-        max_keys: Option<u32>,
-        marker: Option<String>,
-        etag_filter: Option<String>,
-        custom_filters: Vec<(String, String)>,
-    ) -> Result<Vec<Object>> {
-        let mut objects = self.list_objects(bucket_name, prefix, delimiter, max_keys, marker, etag_filter).await?;
-        if !custom_filters.is_empty() {
-            objects.retain(|obj| {
-                custom_filters.iter().all(|(k, v)| {
-                    obj.user_metadata.get(k).map(|val| val == v).unwrap_or(false)
-                })
-            });
-        }
-        Ok(objects)
-    }
-
-    /// 更新对象元数据（不重新上传文件内容）
-    pub async fn update_object_metadata(
-        &self,
-        bucket_name: &str,
-        key: &str,
-        content_type: Option<String>,
-        user_metadata: Option<HashMap<String, String>>,
-        custom_etag: Option<String>,
-    ) -> Result<Object> {
-        // 检查桶是否存在
-        let bucket = self.storage.buckets.read().await;
-        if !bucket.contains_key(bucket_name) {
-            return Err(anyhow!("Bucket '{}' not found", bucket_name));
-        }
-        drop(bucket);
-
-        // 查找对象ID
-        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
-            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
-
-        // 加载现有元数据
-        let mut metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
-            .ok_or_else(|| anyhow!("Object metadata not found for '{}' in bucket '{}'", key, bucket_name))?;
-
-        // 更新内容类型（如果提供）
-        if let Some(ct) = content_type {
-            metadata.content_type = ct;
-        }
-
-        // 更新用户元数据（如果提供）- 只更新传递过来的字段，不覆盖未传递的字段
-        if let Some(um) = user_metadata {
-            for (key, value) in um {
-                metadata.user_metadata.insert(key, value);
-            }
-        }
-
-        // 更新ETag（如果提供）
-        if let Some(etag) = custom_etag {
-            // 验证ETag格式
-            if !is_valid_etag_format(&etag) {
-                return Err(anyhow!("Invalid ETag format: {}", etag));
-            }
-            
-            // 如果ETag发生变化，需要更新索引
-            if metadata.etag != etag {
-                // 从旧ETag索引中移除
-                self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
-                // 添加到新ETag索引中
-                self.storage.add_etag_to_index(bucket_name, &etag, &object_id).await?;
-                metadata.etag = etag;
-            }
-        }
-
-        // 更新最后修改时间
-        metadata.last_modified = chrono::Utc::now();
-
-        // 保存更新后的元数据
-        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
-
-        // 返回更新后的对象
-        Ok(Object::new(
-            key.to_string(),
-            bucket_name.to_string(),
-            metadata.size,
-            metadata.content_type,
-            metadata.etag,
-            metadata.user_metadata,
-        ))
-    }
-}
\ No newline at end of file
+use crate::models::{Bucket, BucketUsage, Object, ObjectMetadata, EventType, NotificationConfiguration, LifecycleConfiguration, ObjectLockConfiguration, ObjectLockMode, ObjectRetention, BucketPolicy, EncryptionConfiguration, CompressionConfiguration, ContentTypeConfiguration, QuotaConfiguration, BucketQuotaStatus, CopyObjectRequest, MetadataDirective, RenameReport, RenamedObject, DeleteObjectEntry, DeleteObjectError, BatchDeleteResult, ObjectSearchResult, MetadataIndexConfiguration, MetadataIndexStatus, MetadataIndexStats, WebsiteConfiguration};
+use crate::notifications::{NotificationService, NOTIFICATION_CONFIG};
+use crate::lifecycle::{LifecycleService, LIFECYCLE_CONFIG};
+use crate::object_lock::{self, OBJECT_LOCK_CONFIG};
+use crate::policy::{PolicyService, POLICY_CONFIG};
+use crate::encryption::{self, CustomerKeyError, EncryptionService, ENCRYPTION_CONFIG};
+use crate::compression::{self, COMPRESSION_CONFIG};
+use crate::content_type::{self, CONTENT_TYPE_CONFIG};
+use crate::quota::{self, QUOTA_CONFIG};
+use crate::tagging;
+use crate::query::{self, CompareOp, Condition, Expr, Field, SortOrder, Value, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+use crate::metadata_index::{self, BucketMetadataIndex, METADATA_INDEX_CONFIG};
+use crate::website::{self, WEBSITE_CONFIG};
+use crate::metrics::{Metrics, TimedGuard};
+use crate::audit::AuditLog;
+use crate::utils::{validate_bucket_name, validate_object_key, generate_etag, sha256_hash, is_valid_etag_format, etag_condition_matches};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::borrow::Cow;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, de::DeserializeOwned};
+
+/// 重复数据删除模式
+#[derive(Debug, Clone)]
+pub enum DeduplicationMode {
+    /// 拒绝重复内容
+    Reject,
+    /// 允许重复内容
+    Allow,
+    /// 创建引用（节省存储空间）
+    Reference,
+}
+
+/// 通过硬配额检查的写入：配额配置、写入前的用量（含其他写入的预留）和本次写入的预留
+struct QuotaState {
+    config: QuotaConfiguration,
+    before: BucketUsage,
+    reservation: UsageReservation,
+}
+
+/// 写入对象的附加选项
+#[derive(Default)]
+struct PutObjectOptions<'a> {
+    /// 保留旧版本
+    enable_versioning: bool,
+    /// 自定义ETag（默认按内容生成）
+    custom_etag: Option<String>,
+    /// 客户提供的 SSE-C 密钥
+    customer_key: Option<&'a [u8; 32]>,
+}
+
+/// 条件请求不满足（对应 HTTP 412）
+#[derive(Debug, thiserror::Error)]
+#[error("Precondition failed: {0}")]
+pub struct PreconditionFailed(pub String);
+
+/// 请求的区间超出对象数据（对应 HTTP 416），例如解析 Range 之后对象被覆盖为更小的内容
+#[derive(Debug, thiserror::Error)]
+#[error("Range {start}-{end} not satisfiable for object of size {size}")]
+pub struct RangeNotSatisfiable {
+    pub start: u64,
+    pub end: u64,
+    pub size: u64,
+}
+
+/// 批量删除一次最多删除的对象数
+pub const MAX_BATCH_DELETE_OBJECTS: usize = 1000;
+
+/// 对象索引：桶名 -> (对象键 -> 对象ID)
+type ObjectIndex = HashMap<String, HashMap<String, String>>;
+
+/// ETag索引：桶名 -> (ETag -> 对象ID列表)
+type EtagIndex = HashMap<String, HashMap<String, Vec<String>>>;
+
+/// 用量索引：桶名 -> 桶用量
+type UsageIndex = HashMap<String, BucketUsage>;
+
+/// 元数据二级索引：桶名 -> 桶的元数据索引（只包含配置了索引的桶）
+type MetadataIndexes = HashMap<String, BucketMetadataIndex>;
+
+/// 配额预留：桶名 -> 已通过配额检查、尚未写入元数据的用量
+type QuotaReservations = HashMap<String, quota::Reservation>;
+
+/// 一次写入的配额预留，释放（drop）时从桶的预留中扣除
+pub struct UsageReservation {
+    reservations: Arc<std::sync::Mutex<QuotaReservations>>,
+    bucket_name: String,
+    amount: quota::Reservation,
+}
+
+impl Drop for UsageReservation {
+    fn drop(&mut self) {
+        let mut reservations = self.reservations.lock().unwrap();
+        if let Some(reserved) = reservations.get_mut(&self.bucket_name) {
+            reserved.subtract(&self.amount);
+            if reserved.is_empty() {
+                reservations.remove(&self.bucket_name);
+            }
+        }
+    }
+}
+
+/// 存储服务 - 参考MinIO的存储结构
+#[derive(Clone)]
+pub struct StorageService {
+    data_dir: PathBuf,
+    buckets: Arc<RwLock<HashMap<String, Bucket>>>,
+    object_index: Arc<RwLock<ObjectIndex>>,
+    etag_index: Arc<RwLock<EtagIndex>>,
+    usage: Arc<RwLock<UsageIndex>>,
+    quota_reservations: Arc<std::sync::Mutex<QuotaReservations>>,
+    metadata_indexes: Arc<RwLock<MetadataIndexes>>,
+    metrics: Option<Metrics>,
+}
+
+impl StorageService {
+    pub async fn new(data_dir: String) -> Result<Self> {
+        let data_path = PathBuf::from(data_dir);
+        
+        // 创建数据目录
+        if !data_path.exists() {
+            fs::create_dir_all(&data_path)?;
+        }
+        
+        // 加载现有桶
+        let buckets = Self::load_buckets(&data_path).await?;
+        
+        // 构建对象索引
+        let object_index = Self::build_object_index(&data_path).await?;
+        
+        // 构建ETag索引
+        let etag_index = Self::build_etag_index(&data_path).await?;
+        
+        // 统计桶用量（之后随对象元数据的写入和删除增量更新）
+        let usage = Self::build_usage_index(&data_path).await?;
+        
+        let storage = Self {
+            data_dir: data_path,
+            buckets: Arc::new(RwLock::new(buckets)),
+            object_index: Arc::new(RwLock::new(object_index)),
+            etag_index: Arc::new(RwLock::new(etag_index)),
+            usage: Arc::new(RwLock::new(usage)),
+            quota_reservations: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            metadata_indexes: Arc::new(RwLock::new(HashMap::new())),
+            metrics: None,
+        };
+        
+        // 构建配置了元数据索引的桶的二级索引
+        for bucket_name in storage.bucket_names().await {
+            storage.rebuild_metadata_index(&bucket_name).await?;
+        }
+        
+        Ok(storage)
+    }
+    
+    /// 启用指标收集（记录索引锁的持有时间）
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+    
+    async fn read_object_index(&self) -> TimedGuard<RwLockReadGuard<'_, ObjectIndex>> {
+        Metrics::time_lock(self.metrics.as_ref(), "object", "read", self.object_index.read().await)
+    }
+    
+    async fn write_object_index(&self) -> TimedGuard<RwLockWriteGuard<'_, ObjectIndex>> {
+        Metrics::time_lock(self.metrics.as_ref(), "object", "write", self.object_index.write().await)
+    }
+    
+    async fn read_etag_index(&self) -> TimedGuard<RwLockReadGuard<'_, EtagIndex>> {
+        Metrics::time_lock(self.metrics.as_ref(), "etag", "read", self.etag_index.read().await)
+    }
+    
+    async fn write_etag_index(&self) -> TimedGuard<RwLockWriteGuard<'_, EtagIndex>> {
+        Metrics::time_lock(self.metrics.as_ref(), "etag", "write", self.etag_index.write().await)
+    }
+    
+    async fn load_buckets(data_dir: &Path) -> Result<HashMap<String, Bucket>> {
+        let mut buckets = HashMap::new();
+        
+        if data_dir.exists() {
+            for entry in fs::read_dir(data_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                
+                if path.is_dir() {
+                    let bucket_name = path.file_name()
+                        .and_then(|name| name.to_str())
+                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
+                    
+                    // 跳过系统目录
+                    if bucket_name.starts_with('.') {
+                        continue;
+                    }
+                    
+                    let metadata_path = path.join(".sevino.meta").join("bucket.json");
+                    let bucket = if metadata_path.exists() {
+                        let content = fs::read_to_string(metadata_path)?;
+                        serde_json::from_str(&content)?
+                    } else {
+                        Bucket::new(bucket_name.to_string())
+                    };
+                    
+                    buckets.insert(bucket_name.to_string(), bucket);
+                }
+            }
+        }
+        
+        Ok(buckets)
+    }
+    
+    async fn build_object_index(data_dir: &Path) -> Result<ObjectIndex> {
+        let mut index = HashMap::new();
+        
+        if data_dir.exists() {
+            for entry in fs::read_dir(data_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                
+                if path.is_dir() {
+                    let bucket_name = path.file_name()
+                        .and_then(|name| name.to_str())
+                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
+                    
+                    // 跳过系统目录
+                    if bucket_name.starts_with('.') {
+                        continue;
+                    }
+                    
+                    let mut bucket_index = HashMap::new();
+                    let meta_dir = path.join(".sevino.meta").join("objects");
+                    
+                    if meta_dir.exists() {
+                        for meta_entry in fs::read_dir(meta_dir)? {
+                            let meta_entry = meta_entry?;
+                            let meta_path = meta_entry.path();
+                            
+                            if meta_path.is_file() && meta_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                                if let Ok(content) = fs::read_to_string(&meta_path) {
+                                    if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
+                                        let object_id = Self::generate_object_id(bucket_name, &metadata.key);
+                                        bucket_index.insert(metadata.key, object_id);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    
+                    if !bucket_index.is_empty() {
+                        index.insert(bucket_name.to_string(), bucket_index);
+                    }
+                }
+            }
+        }
+        
+        Ok(index)
+    }
+    
+    async fn build_etag_index(data_dir: &Path) -> Result<EtagIndex> {
+        let mut etag_index = HashMap::new();
+        
+        if data_dir.exists() {
+            for entry in fs::read_dir(data_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                
+                if path.is_dir() {
+                    let bucket_name = path.file_name()
+                        .and_then(|name| name.to_str())
+                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
+                    
+                    // 跳过系统目录
+                    if bucket_name.starts_with('.') {
+                        continue;
+                    }
+                    
+                    let mut bucket_etag_index = HashMap::new();
+                    let meta_dir = path.join(".sevino.meta").join("objects");
+                    
+                    if meta_dir.exists() {
+                        for meta_entry in fs::read_dir(meta_dir)? {
+                            let meta_entry = meta_entry?;
+                            let meta_path = meta_entry.path();
+                            
+                            if meta_path.is_file() && meta_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                                if let Ok(content) = fs::read_to_string(&meta_path) {
+                                    if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
+                                        let object_id = Self::generate_object_id(bucket_name, &metadata.key);
+                                        bucket_etag_index
+                                            .entry(metadata.etag)
+                                            .or_insert_with(Vec::new)
+                                            .push(object_id);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    
+                    if !bucket_etag_index.is_empty() {
+                        etag_index.insert(bucket_name.to_string(), bucket_etag_index);
+                    }
+                }
+            }
+        }
+        
+        Ok(etag_index)
+    }
+    
+    async fn build_usage_index(data_dir: &Path) -> Result<UsageIndex> {
+        let mut usage_index = HashMap::new();
+        
+        if data_dir.exists() {
+            for entry in fs::read_dir(data_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                
+                if path.is_dir() {
+                    let bucket_name = path.file_name()
+                        .and_then(|name| name.to_str())
+                        .ok_or_else(|| anyhow!("Invalid bucket name"))?;
+                    
+                    // 跳过系统目录
+                    if bucket_name.starts_with('.') {
+                        continue;
+                    }
+                    
+                    let mut usage = BucketUsage::default();
+                    let meta_dir = path.join(".sevino.meta").join("objects");
+                    
+                    if meta_dir.exists() {
+                        for meta_entry in fs::read_dir(meta_dir)? {
+                            let meta_entry = meta_entry?;
+                            let meta_path = meta_entry.path();
+                            
+                            if meta_path.is_file() && meta_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                                if let Ok(content) = fs::read_to_string(&meta_path) {
+                                    if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
+                                        usage.add(&metadata);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    
+                    usage_index.insert(bucket_name.to_string(), usage);
+                }
+            }
+        }
+        
+        Ok(usage_index)
+    }
+    
+    /// 生成对象ID（类似MinIO的哈希化文件名）
+    pub fn generate_object_id(bucket_name: &str, key: &str) -> String {
+        let combined = format!("{}:{}", bucket_name, key);
+        sha256_hash(combined.as_bytes())
+    }
+    
+    /// 对象记录的ID（版本化的记录带有版本后缀）
+    pub fn object_record_id(bucket_name: &str, metadata: &ObjectMetadata) -> String {
+        match &metadata.version_id {
+            Some(version_id) => format!("{}_{}", Self::generate_object_id(bucket_name, &metadata.key), version_id),
+            None => Self::generate_object_id(bucket_name, &metadata.key),
+        }
+    }
+    
+    /// 获取对象存储路径（使用哈希化文件名）
+    fn get_object_data_path(&self, bucket_name: &str, object_id: &str) -> PathBuf {
+        // 使用前4个字符作为目录名，避免单个目录文件过多
+        let prefix = &object_id[..4];
+        let sub_prefix = &object_id[4..6];
+        
+        self.data_dir
+            .join(bucket_name)
+            .join(prefix)
+            .join(sub_prefix)
+            .join(object_id)
+    }
+    
+    /// 获取对象元数据路径
+    fn get_object_metadata_path(&self, bucket_name: &str, object_id: &str) -> PathBuf {
+        self.data_dir
+            .join(bucket_name)
+            .join(".sevino.meta")
+            .join("objects")
+            .join(format!("{}.json", object_id))
+    }
+    
+    /// 对象派生数据（如图片变换结果）的缓存目录，源对象变化时整体清除
+    pub fn derived_object_dir(&self, bucket_name: &str, key: &str) -> PathBuf {
+        self.data_dir
+            .join(bucket_name)
+            .join(".sevino.meta")
+            .join("derived")
+            .join(sha256_hash(key.as_bytes()))
+    }
+
+    fn purge_derived_objects(&self, bucket_name: &str, key: &str) {
+        let dir = self.derived_object_dir(bucket_name, key);
+        if dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                tracing::warn!("Failed to purge derived objects for {}/{}: {}", bucket_name, key, e);
+            }
+        }
+    }
+
+    /// 获取桶元数据路径
+    fn get_bucket_metadata_path(&self, bucket_name: &str) -> PathBuf {
+        self.data_dir
+            .join(bucket_name)
+            .join(".sevino.meta")
+            .join("bucket.json")
+    }
+    
+    pub async fn save_bucket_metadata(&self, bucket: &Bucket) -> Result<()> {
+        let bucket_dir = self.data_dir.join(&bucket.name);
+        if !bucket_dir.exists() {
+            fs::create_dir_all(&bucket_dir)?;
+        }
+        
+        // 创建.sevino.meta目录
+        let meta_dir = bucket_dir.join(".sevino.meta");
+        if !meta_dir.exists() {
+            fs::create_dir_all(&meta_dir)?;
+        }
+        
+        let metadata_path = self.get_bucket_metadata_path(&bucket.name);
+        let content = serde_json::to_string_pretty(bucket)?;
+        fs::write(metadata_path, content)?;
+        
+        Ok(())
+    }
+    
+    /// 获取桶配置文件路径（如通知配置 notification.json）
+    fn get_bucket_config_path(&self, bucket_name: &str, config_name: &str) -> PathBuf {
+        self.data_dir
+            .join(bucket_name)
+            .join(".sevino.meta")
+            .join(format!("{}.json", config_name))
+    }
+
+    /// 加载桶配置
+    pub async fn load_bucket_config<T: DeserializeOwned>(&self, bucket_name: &str, config_name: &str) -> Result<Option<T>> {
+        let config_path = self.get_bucket_config_path(bucket_name, config_name);
+
+        if config_path.exists() {
+            let content = fs::read_to_string(config_path)?;
+            Ok(Some(serde_json::from_str(&content)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 保存桶配置
+    pub async fn save_bucket_config<T: Serialize>(&self, bucket_name: &str, config_name: &str, config: &T) -> Result<()> {
+        let meta_dir = self.data_dir.join(bucket_name).join(".sevino.meta");
+        if !meta_dir.exists() {
+            fs::create_dir_all(&meta_dir)?;
+        }
+
+        // 先写临时文件再重命名，并发读取不会读到写了一半的配置
+        let config_path = self.get_bucket_config_path(bucket_name, config_name);
+        let content = serde_json::to_string_pretty(config)?;
+        let temp_path = config_path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        fs::write(&temp_path, content)?;
+        if let Err(e) = fs::rename(&temp_path, &config_path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    /// 删除桶配置
+    pub async fn delete_bucket_config(&self, bucket_name: &str, config_name: &str) -> Result<()> {
+        let config_path = self.get_bucket_config_path(bucket_name, config_name);
+        if config_path.exists() {
+            fs::remove_file(config_path)?;
+        }
+        Ok(())
+    }
+
+    /// 数据根目录
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+    
+    /// 检查桶是否存在
+    pub async fn bucket_exists(&self, bucket_name: &str) -> bool {
+        self.buckets.read().await.contains_key(bucket_name)
+    }
+
+    pub async fn delete_bucket_directory(&self, bucket_name: &str) -> Result<()> {
+        let bucket_dir = self.data_dir.join(bucket_name);
+        if bucket_dir.exists() {
+            fs::remove_dir_all(bucket_dir)?;
+        }
+        self.usage.write().await.remove(bucket_name);
+        self.metadata_indexes.write().await.remove(bucket_name);
+        Ok(())
+    }
+    
+    /// 获取桶的当前用量
+    pub async fn bucket_usage(&self, bucket_name: &str) -> BucketUsage {
+        self.usage.read().await.get(bucket_name).cloned().unwrap_or_default()
+    }
+    
+    /// 桶的当前用量加上尚未完成的写入预留的用量
+    pub async fn projected_usage(&self, bucket_name: &str) -> BucketUsage {
+        let usage = self.usage.read().await;
+        let mut projected = usage.get(bucket_name).cloned().unwrap_or_default();
+        if let Some(reserved) = self.quota_reservations.lock().unwrap().get(bucket_name) {
+            reserved.add_to(&mut projected);
+        }
+        projected
+    }
+    
+    /// 在用量锁内按当前用量和其他写入的预留检查一次写入（check 收到写入前后的用量），
+    /// 通过后预留其增加的用量。预留在写入完成并计入用量或写入失败后释放（drop）
+    pub async fn reserve_usage(
+        &self,
+        bucket_name: &str,
+        replaced: Option<&ObjectMetadata>,
+        candidate: &ObjectMetadata,
+        check: impl FnOnce(&BucketUsage, &BucketUsage) -> Result<()>,
+    ) -> Result<(BucketUsage, UsageReservation)> {
+        let usage = self.usage.read().await;
+        let mut reservations = self.quota_reservations.lock().unwrap();
+        let mut before = usage.get(bucket_name).cloned().unwrap_or_default();
+        if let Some(reserved) = reservations.get(bucket_name) {
+            reserved.add_to(&mut before);
+        }
+        let mut after = before.clone();
+        if let Some(replaced) = replaced {
+            after.remove(replaced);
+        }
+        after.add(candidate);
+        check(&before, &after)?;
+        
+        let amount = quota::Reservation::between(&before, &after);
+        reservations.entry(bucket_name.to_string()).or_default().add(&amount);
+        let reservation = UsageReservation {
+            reservations: self.quota_reservations.clone(),
+            bucket_name: bucket_name.to_string(),
+            amount,
+        };
+        Ok((before, reservation))
+    }
+    
+    /// 保存对象元数据，并按新旧记录的差异更新桶用量
+    pub async fn save_object_metadata(&self, bucket_name: &str, object_id: &str, metadata: &ObjectMetadata) -> Result<()> {
+        // 持有用量锁直到写入完成，保证同一记录的并发写入不会重复计数
+        let mut usage = self.usage.write().await;
+        let previous = self.load_object_metadata(bucket_name, object_id).await.ok().flatten();
+        
+        let meta_dir = self.data_dir
+            .join(bucket_name)
+            .join(".sevino.meta")
+            .join("objects");
+        
+        if !meta_dir.exists() {
+            fs::create_dir_all(&meta_dir)?;
+        }
+        
+        let metadata_path = self.get_object_metadata_path(bucket_name, object_id);
+        let content = serde_json::to_string_pretty(metadata)?;
+        fs::write(metadata_path, content)?;
+        
+        // 内容或键变化时清除派生数据缓存
+        match &previous {
+            Some(previous) if previous.key != metadata.key => {
+                self.purge_derived_objects(bucket_name, &previous.key);
+                self.purge_derived_objects(bucket_name, &metadata.key);
+            }
+            Some(previous) if previous.etag == metadata.etag => {}
+            _ => self.purge_derived_objects(bucket_name, &metadata.key),
+        }
+        
+        let bucket_usage = usage.entry(bucket_name.to_string()).or_default();
+        if let Some(previous) = &previous {
+            bucket_usage.remove(previous);
+        }
+        bucket_usage.add(metadata);
+        
+        if let Some(index) = self.metadata_indexes.write().await.get_mut(bucket_name) {
+            index.insert(object_id, metadata);
+        }
+        
+        Ok(())
+    }
+    
+    pub async fn load_object_metadata(&self, bucket_name: &str, object_id: &str) -> Result<Option<ObjectMetadata>> {
+        let metadata_path = self.get_object_metadata_path(bucket_name, object_id);
+        
+        if metadata_path.exists() {
+            let content = fs::read_to_string(metadata_path)?;
+            let metadata: ObjectMetadata = serde_json::from_str(&content)?;
+            Ok(Some(metadata))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// 删除对象元数据，并从桶用量中扣除
+    pub async fn delete_object_metadata(&self, bucket_name: &str, object_id: &str) -> Result<()> {
+        let mut usage = self.usage.write().await;
+        let previous = self.load_object_metadata(bucket_name, object_id).await.ok().flatten();
+        
+        let metadata_path = self.get_object_metadata_path(bucket_name, object_id);
+        if metadata_path.exists() {
+            fs::remove_file(metadata_path)?;
+        }
+        
+        if let Some(previous) = &previous {
+            self.purge_derived_objects(bucket_name, &previous.key);
+        }
+        if let (Some(previous), Some(bucket_usage)) = (previous, usage.get_mut(bucket_name)) {
+            bucket_usage.remove(&previous);
+        }
+        
+        if let Some(index) = self.metadata_indexes.write().await.get_mut(bucket_name) {
+            index.remove(object_id);
+        }
+        Ok(())
+    }
+    
+    pub async fn list_object_metadata(&self, bucket_name: &str) -> Result<Vec<ObjectMetadata>> {
+        self.list_object_metadata_with_pagination(bucket_name, None, None).await
+    }
+    
+    pub async fn list_object_metadata_with_pagination(
+        &self,
+        bucket_name: &str,
+        max_keys: Option<usize>,
+        marker: Option<String>,
+    ) -> Result<Vec<ObjectMetadata>> {
+        let meta_dir = self.data_dir
+            .join(bucket_name)
+            .join(".sevino.meta")
+            .join("objects");
+        
+        let mut objects = Vec::new();
+        let mut count = 0;
+        let max_keys = max_keys.unwrap_or(usize::MAX);
+        
+        if meta_dir.exists() {
+            let mut entries: Vec<_> = fs::read_dir(meta_dir)?
+                .filter_map(|entry| entry.ok())
+                .collect();
+            
+            // 按文件名排序，确保一致性
+            entries.sort_by_key(|a| a.file_name());
+            
+            let mut started = marker.is_none();
+            
+            for entry in entries {
+                if count >= max_keys {
+                    break;
+                }
+                
+                let path = entry.path();
+                
+                if path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                    // 处理marker逻辑
+                    if !started {
+                        if let Some(marker_val) = &marker {
+                            let file_name = path.file_name()
+                                .and_then(|name| name.to_str())
+                                .unwrap_or("");
+                            if file_name == marker_val {
+                                started = true;
+                            }
+                            continue;
+                        }
+                    }
+                    
+                    if let Ok(content) = fs::read_to_string(&path) {
+                        if let Ok(metadata) = serde_json::from_str::<ObjectMetadata>(&content) {
+                            objects.push(metadata);
+                            count += 1;
+                        }
+                    }
+                }
+            }
+        }
+        
+        Ok(objects)
+    }
+    
+    /// 按记录ID加载对象记录（跳过不存在的记录）
+    pub async fn load_object_records(&self, bucket_name: &str, object_ids: impl IntoIterator<Item = String>) -> Result<Vec<ObjectMetadata>> {
+        let mut records = Vec::new();
+        for object_id in object_ids {
+            if let Some(metadata) = self.load_object_metadata(bucket_name, &object_id).await? {
+                records.push(metadata);
+            }
+        }
+        Ok(records)
+    }
+    
+    /// 只保留各对象键的当前记录（去掉历史版本和删除标记）
+    pub async fn retain_current_records(&self, bucket_name: &str, records: Vec<ObjectMetadata>) -> Vec<ObjectMetadata> {
+        let index = self.read_object_index().await;
+        let Some(bucket_index) = index.get(bucket_name) else {
+            return Vec::new();
+        };
+        records.into_iter()
+            .filter(|m| !m.is_delete_marker)
+            .filter(|m| bucket_index.get(&m.key).is_some_and(|id| *id == Self::object_record_id(bucket_name, m)))
+            .collect()
+    }
+    
+    /// 用元数据索引求可能满足表达式的记录ID；桶没有可用的索引时返回 None
+    pub async fn metadata_index_candidates(&self, bucket_name: &str, expr: &Expr) -> Option<std::collections::BTreeSet<String>> {
+        self.metadata_indexes.read().await.get(bucket_name)?.candidates(expr)
+    }
+    
+    /// 元数据索引的统计（桶没有配置索引时为空）
+    pub async fn metadata_index_stats(&self, bucket_name: &str) -> Vec<MetadataIndexStats> {
+        self.metadata_indexes.read().await.get(bucket_name).map(|index| index.stats()).unwrap_or_default()
+    }
+    
+    /// 按桶的配置重建元数据索引（用于修改配置后或修复索引不一致问题）
+    pub async fn rebuild_metadata_index(&self, bucket_name: &str) -> Result<()> {
+        let config: MetadataIndexConfiguration = self.load_bucket_config(bucket_name, METADATA_INDEX_CONFIG).await?.unwrap_or_default();
+        // 重建期间持有索引写锁，并发写入会在重建完成后再更新索引
+        let mut indexes = self.metadata_indexes.write().await;
+        if config.keys.is_empty() {
+            indexes.remove(bucket_name);
+            return Ok(());
+        }
+        
+        let mut index = BucketMetadataIndex::new(&config.keys);
+        for metadata in self.list_object_metadata(bucket_name).await? {
+            index.insert(&Self::object_record_id(bucket_name, &metadata), &metadata);
+        }
+        indexes.insert(bucket_name.to_string(), index);
+        Ok(())
+    }
+    
+    /// 根据key查找对象ID
+    pub async fn find_object_id_by_key(&self, bucket_name: &str, key: &str) -> Result<Option<String>> {
+        let index = self.read_object_index().await;
+        
+        if let Some(bucket_index) = index.get(bucket_name) {
+            if let Some(object_id) = bucket_index.get(key) {
+                return Ok(Some(object_id.clone()));
+            }
+        }
+        
+        Ok(None)
+    }
+    
+    /// 添加对象到索引
+    pub async fn add_object_to_index(&self, bucket_name: &str, key: &str, object_id: &str) -> Result<()> {
+        let mut index = self.write_object_index().await;
+        
+        let bucket_index = index.entry(bucket_name.to_string())
+            .or_insert_with(HashMap::new);
+        
+        bucket_index.insert(key.to_string(), object_id.to_string());
+        
+        Ok(())
+    }
+    
+    /// 从索引中删除对象
+    pub async fn remove_object_from_index(&self, bucket_name: &str, key: &str) -> Result<()> {
+        let mut index = self.write_object_index().await;
+        
+        if let Some(bucket_index) = index.get_mut(bucket_name) {
+            bucket_index.remove(key);
+            
+            // 如果桶索引为空，删除整个桶索引
+            if bucket_index.is_empty() {
+                index.remove(bucket_name);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 获取桶中对象数量（使用索引，O(1)性能）
+    pub async fn get_bucket_object_count(&self, bucket_name: &str) -> usize {
+        let index = self.read_object_index().await;
+        
+        if let Some(bucket_index) = index.get(bucket_name) {
+            bucket_index.len()
+        } else {
+            0
+        }
+    }
+    
+    /// 获取所有桶的对象数量（使用索引）
+    pub async fn object_counts(&self) -> HashMap<String, usize> {
+        let index = self.read_object_index().await;
+        index.iter().map(|(bucket_name, bucket_index)| (bucket_name.clone(), bucket_index.len())).collect()
+    }
+    
+    /// 获取所有桶名称
+    pub async fn bucket_names(&self) -> Vec<String> {
+        self.buckets.read().await.keys().cloned().collect()
+    }
+    
+    /// 检查桶是否为空（使用索引，O(1)性能）
+    pub async fn is_bucket_empty(&self, bucket_name: &str) -> bool {
+        self.get_bucket_object_count(bucket_name).await == 0
+    }
+    
+    /// 重建对象索引（用于修复索引不一致问题）
+    #[allow(dead_code)]
+    pub async fn rebuild_object_index(&self) -> Result<()> {
+        let new_index = Self::build_object_index(&self.data_dir).await?;
+        let mut index = self.write_object_index().await;
+        **index = new_index;
+        Ok(())
+    }
+    
+    /// 验证索引一致性
+    #[allow(dead_code)]
+    pub async fn validate_index_consistency(&self, bucket_name: &str) -> Result<bool> {
+        let index_count = self.get_bucket_object_count(bucket_name).await;
+        let disk_objects = self.list_object_metadata(bucket_name).await?;
+        let disk_count = disk_objects.len();
+        
+        Ok(index_count == disk_count)
+    }
+    
+    /// 添加ETag到索引
+    pub async fn add_etag_to_index(&self, bucket_name: &str, etag: &str, object_id: &str) -> Result<()> {
+        let mut etag_index = self.write_etag_index().await;
+        
+        let bucket_etag_index = etag_index.entry(bucket_name.to_string())
+            .or_insert_with(HashMap::new);
+        
+        bucket_etag_index
+            .entry(etag.to_string())
+            .or_insert_with(Vec::new)
+            .push(object_id.to_string());
+        
+        Ok(())
+    }
+    
+    /// 从ETag索引中删除
+    pub async fn remove_etag_from_index(&self, bucket_name: &str, etag: &str, object_id: &str) -> Result<()> {
+        let mut etag_index = self.write_etag_index().await;
+        
+        if let Some(bucket_etag_index) = etag_index.get_mut(bucket_name) {
+            if let Some(object_ids) = bucket_etag_index.get_mut(etag) {
+                object_ids.retain(|id| id != object_id);
+                
+                // 如果没有对象引用这个ETag，删除整个ETag条目
+                if object_ids.is_empty() {
+                    bucket_etag_index.remove(etag);
+                }
+            }
+            
+            // 如果桶的ETag索引为空，删除整个桶索引
+            if bucket_etag_index.is_empty() {
+                etag_index.remove(bucket_name);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 根据ETag查找所有对象
+    pub async fn find_objects_by_etag(&self, bucket_name: &str, etag: &str) -> Result<Vec<String>> {
+        let etag_index = self.read_etag_index().await;
+        
+        if let Some(bucket_etag_index) = etag_index.get(bucket_name) {
+            if let Some(object_ids) = bucket_etag_index.get(etag) {
+                return Ok(object_ids.clone());
+            }
+        }
+        
+        Ok(Vec::new())
+    }
+    
+    /// 检查ETag是否已存在（跨key检测）
+    #[allow(dead_code)]
+    pub async fn is_etag_exists(&self, bucket_name: &str, etag: &str) -> Result<bool> {
+        let object_ids = self.find_objects_by_etag(bucket_name, etag).await?;
+        Ok(!object_ids.is_empty())
+    }
+}
+
+/// 桶服务
+#[derive(Clone)]
+pub struct BucketService {
+    storage: StorageService,
+    encryption: Option<EncryptionService>,
+    audit: Option<AuditLog>,
+    notifications: Option<NotificationService>,
+}
+
+impl BucketService {
+    pub fn new(storage: StorageService) -> Self {
+        Self { storage, encryption: None, audit: None, notifications: None }
+    }
+    
+    /// 启用事件通知配置
+    pub fn with_notifications(mut self, notifications: NotificationService) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+    
+    /// 启用服务端加密配置
+    pub fn with_encryption(mut self, encryption: EncryptionService) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+    
+    /// 启用审计日志
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+    
+    /// 记录桶变更的审计日志（失败只记录错误日志，操作本身已经完成）
+    fn audit(&self, action: &str, name: &str) {
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.record(action, name, None, None, None) {
+                tracing::error!("Failed to write audit entry {} for bucket '{}': {}", action, name, e);
+            }
+        }
+    }
+    
+    pub async fn list_buckets(&self) -> Vec<Bucket> {
+        let buckets = self.storage.buckets.read().await;
+        buckets.values().cloned().collect()
+    }
+    
+    pub async fn create_bucket(&self, name: String) -> Result<Bucket> {
+        validate_bucket_name(&name).map_err(|e| anyhow!(e))?;
+        
+        let mut buckets = self.storage.buckets.write().await;
+        
+        if buckets.contains_key(&name) {
+            return Err(anyhow!("Bucket '{}' already exists", name));
+        }
+        
+        let bucket = Bucket::new(name.clone());
+        self.storage.save_bucket_metadata(&bucket).await?;
+        buckets.insert(name, bucket.clone());
+        self.audit("CreateBucket", &bucket.name);
+        
+        Ok(bucket)
+    }
+    
+    pub async fn get_bucket(&self, name: &str) -> Option<Bucket> {
+        let buckets = self.storage.buckets.read().await;
+        buckets.get(name).cloned()
+    }
+    
+    pub async fn delete_bucket(&self, name: &str) -> Result<()> {
+        let mut buckets = self.storage.buckets.write().await;
+        
+        if !buckets.contains_key(name) {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        // 检查桶是否为空（使用索引，O(1)性能）
+        if !self.storage.is_bucket_empty(name).await {
+            return Err(anyhow!("Cannot delete non-empty bucket '{}'", name));
+        }
+        
+        self.storage.delete_bucket_directory(name).await?;
+        buckets.remove(name);
+        self.audit("DeleteBucket", name);
+        
+        Ok(())
+    }
+    
+    /// 获取桶的用量和去重统计（增量维护，不扫描桶）
+    pub async fn get_bucket_stats(&self, name: &str) -> Result<BucketUsage> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.bucket_usage(name).await)
+    }
+    
+    /// 获取桶的事件通知配置
+    pub async fn get_notification_configuration(&self, name: &str) -> Result<NotificationConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.load_bucket_config(name, NOTIFICATION_CONFIG).await?.unwrap_or_default())
+    }
+    
+    /// 设置桶的事件通知配置
+    pub async fn put_notification_configuration(&self, name: &str, mut config: NotificationConfiguration) -> Result<NotificationConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        let enabled = self.notifications.as_ref().is_some_and(|notifications| notifications.enabled());
+        if !config.rules.is_empty() && !enabled {
+            return Err(anyhow!("Webhook notifications are disabled, set SEVINO_WEBHOOK_SECRET to sign deliveries"));
+        }
+        if let Some(notifications) = &self.notifications {
+            notifications.validate_configuration(&mut config)?;
+        }
+        self.storage.save_bucket_config(name, NOTIFICATION_CONFIG, &config).await?;
+        self.audit("PutBucketNotification", name);
+        
+        Ok(config)
+    }
+    
+    /// 删除桶的事件通知配置
+    pub async fn delete_notification_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, NOTIFICATION_CONFIG).await?;
+        self.audit("DeleteBucketNotification", name);
+        Ok(())
+    }
+    
+    /// 获取桶的生命周期配置
+    pub async fn get_lifecycle_configuration(&self, name: &str) -> Result<LifecycleConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.load_bucket_config(name, LIFECYCLE_CONFIG).await?.unwrap_or_default())
+    }
+    
+    /// 设置桶的生命周期配置
+    pub async fn put_lifecycle_configuration(&self, name: &str, mut config: LifecycleConfiguration) -> Result<LifecycleConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        LifecycleService::validate_configuration(&mut config)?;
+        self.storage.save_bucket_config(name, LIFECYCLE_CONFIG, &config).await?;
+        self.audit("PutLifecycleConfiguration", name);
+        
+        Ok(config)
+    }
+    
+    /// 删除桶的生命周期配置
+    pub async fn delete_lifecycle_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, LIFECYCLE_CONFIG).await?;
+        self.audit("DeleteLifecycleConfiguration", name);
+        Ok(())
+    }
+    
+    /// 获取桶的对象锁定配置
+    pub async fn get_object_lock_configuration(&self, name: &str) -> Result<ObjectLockConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.load_bucket_config(name, OBJECT_LOCK_CONFIG).await?.unwrap_or_default())
+    }
+    
+    /// 设置桶的对象锁定配置（启用后不能关闭）
+    pub async fn put_object_lock_configuration(&self, name: &str, config: ObjectLockConfiguration) -> Result<ObjectLockConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        let existing: Option<ObjectLockConfiguration> = self.storage.load_bucket_config(name, OBJECT_LOCK_CONFIG).await?;
+        object_lock::validate_configuration(existing.as_ref(), &config)?;
+        self.storage.save_bucket_config(name, OBJECT_LOCK_CONFIG, &config).await?;
+        self.audit("PutBucketObjectLockConfiguration", name);
+        
+        Ok(config)
+    }
+    
+    /// 获取桶策略
+    pub async fn get_bucket_policy(&self, name: &str) -> Result<BucketPolicy> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.load_bucket_config(name, POLICY_CONFIG).await?
+            .ok_or_else(|| anyhow!("Bucket '{}' has no policy", name))
+    }
+    
+    /// 设置桶策略
+    pub async fn put_bucket_policy(&self, name: &str, policy: BucketPolicy) -> Result<BucketPolicy> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        PolicyService::validate_policy(name, &policy)?;
+        self.storage.save_bucket_config(name, POLICY_CONFIG, &policy).await?;
+        self.audit("PutBucketPolicy", name);
+        
+        Ok(policy)
+    }
+    
+    /// 删除桶策略
+    pub async fn delete_bucket_policy(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, POLICY_CONFIG).await?;
+        self.audit("DeleteBucketPolicy", name);
+        Ok(())
+    }
+    
+    /// 获取桶的服务端加密配置
+    pub async fn get_encryption_configuration(&self, name: &str) -> Result<EncryptionConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.load_bucket_config(name, ENCRYPTION_CONFIG).await?.unwrap_or_default())
+    }
+    
+    /// 设置桶的服务端加密配置（只影响之后写入的对象）
+    pub async fn put_encryption_configuration(&self, name: &str, config: EncryptionConfiguration) -> Result<EncryptionConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        let encryption = self.encryption.as_ref()
+            .ok_or_else(|| anyhow!("Server-side encryption is not available"))?;
+        encryption.validate_configuration(&config)?;
+        self.storage.save_bucket_config(name, ENCRYPTION_CONFIG, &config).await?;
+        self.audit("PutBucketEncryption", name);
+        
+        Ok(config)
+    }
+    
+    /// 获取桶的压缩配置
+    pub async fn get_compression_configuration(&self, name: &str) -> Result<CompressionConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.load_bucket_config(name, COMPRESSION_CONFIG).await?.unwrap_or_default())
+    }
+    
+    /// 设置桶的压缩配置（只影响之后写入的对象）
+    pub async fn put_compression_configuration(&self, name: &str, config: CompressionConfiguration) -> Result<CompressionConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        compression::validate_configuration(&config)?;
+        self.storage.save_bucket_config(name, COMPRESSION_CONFIG, &config).await?;
+        self.audit("PutBucketCompression", name);
+        
+        Ok(config)
+    }
+    
+    /// 删除桶的压缩配置
+    pub async fn delete_compression_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, COMPRESSION_CONFIG).await?;
+        self.audit("DeleteBucketCompression", name);
+        Ok(())
+    }
+    
+    /// 获取桶的内容类型检测配置
+    pub async fn get_content_type_configuration(&self, name: &str) -> Result<ContentTypeConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(self.storage.load_bucket_config(name, CONTENT_TYPE_CONFIG).await?.unwrap_or_default())
+    }
+    
+    /// 设置桶的内容类型检测配置（只影响之后写入的对象）
+    pub async fn put_content_type_configuration(&self, name: &str, config: ContentTypeConfiguration) -> Result<ContentTypeConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.save_bucket_config(name, CONTENT_TYPE_CONFIG, &config).await?;
+        self.audit("PutBucketContentType", name);
+        
+        Ok(config)
+    }
+    
+    /// 删除桶的内容类型检测配置（恢复为 trust 模式）
+    pub async fn delete_content_type_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, CONTENT_TYPE_CONFIG).await?;
+        self.audit("DeleteBucketContentType", name);
+        Ok(())
+    }
+    
+    /// 获取桶的配额配置和当前用量
+    pub async fn get_quota(&self, name: &str) -> Result<BucketQuotaStatus> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(BucketQuotaStatus {
+            configuration: self.storage.load_bucket_config(name, QUOTA_CONFIG).await?.unwrap_or_default(),
+            usage: self.storage.bucket_usage(name).await,
+        })
+    }
+    
+    /// 设置桶的配额配置（已有数据超过新配额时不会被删除，只会阻止继续增长）
+    pub async fn put_quota_configuration(&self, name: &str, config: QuotaConfiguration) -> Result<QuotaConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        quota::validate_configuration(&config)?;
+        self.storage.save_bucket_config(name, QUOTA_CONFIG, &config).await?;
+        self.audit("PutBucketQuota", name);
+        
+        Ok(config)
+    }
+    
+    /// 删除桶的配额配置
+    pub async fn delete_quota_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, QUOTA_CONFIG).await?;
+        self.audit("DeleteBucketQuota", name);
+        Ok(())
+    }
+    
+    /// 获取桶的元数据索引配置和索引统计
+    pub async fn get_metadata_index(&self, name: &str) -> Result<MetadataIndexStatus> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        Ok(MetadataIndexStatus {
+            configuration: self.storage.load_bucket_config(name, METADATA_INDEX_CONFIG).await?.unwrap_or_default(),
+            indexes: self.storage.metadata_index_stats(name).await,
+        })
+    }
+    
+    /// 设置桶的元数据索引配置，并立即为已有对象建立索引
+    pub async fn put_metadata_index_configuration(&self, name: &str, config: MetadataIndexConfiguration) -> Result<MetadataIndexStatus> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        metadata_index::validate_configuration(&config)?;
+        self.storage.save_bucket_config(name, METADATA_INDEX_CONFIG, &config).await?;
+        self.storage.rebuild_metadata_index(name).await?;
+        self.audit("PutBucketMetadataIndex", name);
+        
+        self.get_metadata_index(name).await
+    }
+    
+    /// 删除桶的元数据索引配置和索引
+    pub async fn delete_metadata_index_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, METADATA_INDEX_CONFIG).await?;
+        self.storage.rebuild_metadata_index(name).await?;
+        self.audit("DeleteBucketMetadataIndex", name);
+        Ok(())
+    }
+    
+    /// 获取桶的静态网站配置
+    pub async fn get_website_configuration(&self, name: &str) -> Result<WebsiteConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.load_bucket_config(name, WEBSITE_CONFIG).await?
+            .ok_or_else(|| anyhow!("Bucket '{}' is not configured as a website", name))
+    }
+    
+    /// 设置桶的静态网站配置（启用网站托管）
+    pub async fn put_website_configuration(&self, name: &str, config: WebsiteConfiguration) -> Result<WebsiteConfiguration> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        website::validate_configuration(&config)?;
+        self.storage.save_bucket_config(name, WEBSITE_CONFIG, &config).await?;
+        self.audit("PutBucketWebsite", name);
+        
+        Ok(config)
+    }
+    
+    /// 删除桶的静态网站配置（停止网站托管）
+    pub async fn delete_website_configuration(&self, name: &str) -> Result<()> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.delete_bucket_config(name, WEBSITE_CONFIG).await?;
+        self.audit("DeleteBucketWebsite", name);
+        Ok(())
+    }
+    
+    /// 从磁盘上的对象元数据重建桶的元数据索引（用于修复索引不一致问题）
+    pub async fn rebuild_metadata_index(&self, name: &str) -> Result<MetadataIndexStatus> {
+        if !self.storage.bucket_exists(name).await {
+            return Err(anyhow!("Bucket '{}' not found", name));
+        }
+        
+        self.storage.rebuild_metadata_index(name).await?;
+        self.audit("RebuildBucketMetadataIndex", name);
+        self.get_metadata_index(name).await
+    }
+}
+
+/// 重命名过程中已完成的步骤，失败时按相反顺序撤销
+enum RenameUndo {
+    /// 数据文件已从 from 移动到 to
+    MoveData { from: PathBuf, to: PathBuf },
+    /// 已写入新的元数据记录
+    CreatedMetadata(String),
+    /// 已改写或删除的元数据记录（保存原内容）
+    ReplacedMetadata(String, Box<ObjectMetadata>),
+}
+
+/// 待重命名的对象记录
+struct RenameStep {
+    old_id: String,
+    new_id: String,
+    metadata: ObjectMetadata,
+    renamed: ObjectMetadata,
+}
+
+/// 对象服务
+#[derive(Clone)]
+pub struct ObjectService {
+    storage: StorageService,
+    notifications: Option<NotificationService>,
+    encryption: Option<EncryptionService>,
+    metrics: Option<Metrics>,
+    audit: Option<AuditLog>,
+    rename_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ObjectService {
+    pub fn new(storage: StorageService) -> Self {
+        Self { storage, notifications: None, encryption: None, metrics: None, audit: None, rename_lock: Arc::new(tokio::sync::Mutex::new(())) }
+    }
+    
+    /// 启用事件通知
+    pub fn with_notifications(mut self, notifications: NotificationService) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+    
+    /// 启用服务端加密
+    pub fn with_encryption(mut self, encryption: EncryptionService) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+    
+    /// 启用指标收集
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+    
+    fn record_operation(&self, operation: &str, bytes: u64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_object_operation(operation, bytes);
+        }
+    }
+    
+    /// 启用审计日志
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+    
+    /// 记录对象变更的审计日志（失败只记录错误日志，操作本身已经完成）
+    fn audit(&self, action: &str, metadata: &ObjectMetadata, details: Option<String>) {
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.record(action, &metadata.bucket_name, Some(&metadata.key), metadata.version_id.as_deref(), details) {
+                tracing::error!("Failed to write audit entry {} for '{}/{}': {}", action, metadata.bucket_name, metadata.key, e);
+            }
+        }
+    }
+    
+    /// 检查写入是否超过桶的硬配额并预留用量（replaced 为被覆盖的对象记录，candidate 的物理大小按未压缩大小估算），
+    /// 返回配额配置、写入前的用量和预留；写入失败时丢弃返回值即释放预留
+    async fn enforce_quota(&self, bucket_name: &str, replaced: Option<&ObjectMetadata>, candidate: &ObjectMetadata) -> Result<Option<QuotaState>> {
+        let config: Option<QuotaConfiguration> = self.storage.load_bucket_config(bucket_name, QUOTA_CONFIG).await?;
+        let Some(config) = config else {
+            return Ok(None);
+        };
+        
+        let (before, reservation) = self.storage.reserve_usage(bucket_name, replaced, candidate, |before, after| {
+            quota::check_hard_limits(bucket_name, &config, before, after)
+        }).await?;
+        
+        Ok(Some(QuotaState { config, before, reservation }))
+    }
+    
+    /// 写入后用量首次超过软配额时发出警告事件（写入已计入用量，先释放预留）
+    async fn check_soft_quota(&self, quota_state: Option<QuotaState>, metadata: &ObjectMetadata) {
+        let Some(QuotaState { config, before, reservation }) = quota_state else {
+            return;
+        };
+        drop(reservation);
+        
+        let after = self.storage.projected_usage(&metadata.bucket_name).await;
+        if let Some(reason) = quota::soft_limit_crossed(&config, &before, &after) {
+            tracing::warn!("Bucket '{}' exceeded its soft quota: {}", metadata.bucket_name, reason);
+            self.emit_event(EventType::QuotaSoftLimitExceeded, metadata).await;
+        }
+    }
+    
+    /// 写入对象数据并在元数据中记录压缩和加密信息：先按桶配置压缩，
+    /// 再在提供了客户密钥时使用 SSE-C，否则在桶启用加密时使用服务端密钥
+    async fn write_object_data(&self, object_path: &Path, data: &[u8], customer_key: Option<&[u8; 32]>, metadata: &mut ObjectMetadata) -> Result<()> {
+        let bucket_name = metadata.bucket_name.clone();
+        if let Some(parent) = object_path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        
+        let config: Option<CompressionConfiguration> = self.storage.load_bucket_config(&bucket_name, COMPRESSION_CONFIG).await?;
+        let compressed = match config {
+            Some(config) if compression::should_compress(&config, &metadata.content_type) => compression::compress(&config, data)?,
+            _ => None,
+        };
+        let payload = match compressed {
+            Some((compressed, info)) => {
+                metadata.compression = Some(info);
+                Cow::Owned(compressed)
+            }
+            None => Cow::Borrowed(data),
+        };
+        
+        let stored = if let Some(customer_key) = customer_key {
+            let (ciphertext, info) = encryption::encrypt_with_customer_key(customer_key, &payload)?;
+            metadata.customer_encryption = Some(info);
+            Cow::Owned(ciphertext)
+        } else {
+            match &self.encryption {
+                Some(encryption) if encryption.is_enabled(&bucket_name).await? => {
+                    let (ciphertext, info) = encryption.encrypt(&bucket_name, &payload).await?;
+                    metadata.encryption = Some(info);
+                    Cow::Owned(ciphertext)
+                }
+                _ => payload,
+            }
+        };
+        
+        // 先写临时文件再重命名：写入是原子的，也不会改动与其他对象共享的硬链接
+        let tmp_path = temporary_path(object_path);
+        fs::write(&tmp_path, &stored)?;
+        fs::rename(&tmp_path, object_path)?;
+        metadata.stored_size = Some(stored.len() as u64);
+        Ok(())
+    }
+    
+    /// 读取数据持有者的对象数据，range 为闭区间 [start, end]（压缩对象需要完整解压后再截取）
+    async fn read_object_data(&self, bucket_name: &str, data_object_id: &str, data_metadata: &ObjectMetadata, range: Option<(u64, u64)>, customer_key: Option<&[u8; 32]>) -> Result<Vec<u8>> {
+        let data = match &data_metadata.compression {
+            Some(info) => {
+                let payload = self.read_stored_payload(bucket_name, data_object_id, data_metadata, None, customer_key).await?;
+                let data = compression::decompress(info, &payload, data_metadata.size)?;
+                match range {
+                    Some((start, end)) => {
+                        let size = data.len() as u64;
+                        if start > end || end >= size {
+                            return Err(RangeNotSatisfiable { start, end, size }.into());
+                        }
+                        data[start as usize..=end as usize].to_vec()
+                    }
+                    None => data,
+                }
+            }
+            None => self.read_stored_payload(bucket_name, data_object_id, data_metadata, range, customer_key).await?,
+        };
+        
+        self.record_operation("get", data.len() as u64);
+        Ok(data)
+    }
+    
+    /// 读取解密后（未解压）的存储数据，range 为闭区间 [start, end]
+    async fn read_stored_payload(&self, bucket_name: &str, data_object_id: &str, data_metadata: &ObjectMetadata, range: Option<(u64, u64)>, customer_key: Option<&[u8; 32]>) -> Result<Vec<u8>> {
+        let object_path = self.storage.get_object_data_path(bucket_name, data_object_id);
+        if !object_path.exists() {
+            return Err(anyhow!("Object data not found"));
+        }
+        
+        if data_metadata.customer_encryption.is_some() {
+            let customer_key = customer_key.ok_or(CustomerKeyError::Missing)?;
+            let (start, end) = range.unwrap_or((0, data_metadata.payload_size().saturating_sub(1)));
+            return encryption::decrypt_with_customer_key(customer_key, data_metadata, &object_path, start, end);
+        }
+        if customer_key.is_some() {
+            return Err(CustomerKeyError::NotEncrypted.into());
+        }
+        
+        if data_metadata.encryption.is_some() {
+            let encryption = self.encryption.as_ref()
+                .ok_or_else(|| anyhow!("Object is encrypted but server-side encryption is not configured"))?;
+            let (start, end) = range.unwrap_or((0, data_metadata.payload_size().saturating_sub(1)));
+            return encryption.decrypt_range(data_metadata, &object_path, start, end).await;
+        }
+        
+        match range {
+            Some((start, end)) => {
+                let mut file = File::open(object_path)?;
+                let size = file.metadata()?.len();
+                if start > end || end >= size {
+                    return Err(RangeNotSatisfiable { start, end, size }.into());
+                }
+                let mut buffer = vec![0u8; (end - start + 1) as usize];
+                file.seek(SeekFrom::Start(start))?;
+                file.read_exact(&mut buffer)?;
+                Ok(buffer)
+            }
+            None => Ok(fs::read(object_path)?),
+        }
+    }
+    
+    /// 检查对象锁定，治理模式被绕过时记录审计日志
+    fn enforce_object_lock(&self, metadata: &ObjectMetadata, operation: &str, bypass_governance: bool) -> Result<()> {
+        if object_lock::check_object_mutable(metadata, bypass_governance)? {
+            self.record_governance_bypass(metadata, operation)?;
+        }
+        Ok(())
+    }
+    
+    /// 治理模式绕过写入哈希链审计日志；写入失败时拒绝操作
+    fn record_governance_bypass(&self, metadata: &ObjectMetadata, operation: &str) -> Result<()> {
+        let audit = self.audit.as_ref()
+            .ok_or_else(|| anyhow!("Bypassing governance retention requires the audit log"))?;
+        let retain_until = metadata.retain_until.map(|until| until.to_rfc3339()).unwrap_or_default();
+        audit.record(
+            "BypassGovernanceRetention",
+            &metadata.bucket_name,
+            Some(&metadata.key),
+            metadata.version_id.as_deref(),
+            Some(format!("operation={} retain_until={}", operation, retain_until)),
+        )?;
+        tracing::warn!(
+            "Governance retention bypassed for '{}/{}' ({})",
+            metadata.bucket_name, metadata.key, operation
+        );
+        Ok(())
+    }
+    
+    /// 为新对象应用桶的默认保留策略
+    async fn apply_default_retention(&self, bucket_name: &str, metadata: &mut ObjectMetadata) -> Result<()> {
+        let config: Option<ObjectLockConfiguration> = self.storage.load_bucket_config(bucket_name, OBJECT_LOCK_CONFIG).await?;
+        if let Some(retention) = config.filter(|c| c.enabled).and_then(|c| c.default_retention) {
+            metadata.retention_mode = Some(retention.mode);
+            metadata.retain_until = Some(metadata.created_at + chrono::Duration::days(retention.days as i64));
+        }
+        Ok(())
+    }
+    
+    /// 设置对象的保留期（缩短或降级治理模式保留需要绕过权限，合规模式不能缩短）
+    pub async fn put_object_retention(&self, bucket_name: &str, key: &str, retention: ObjectRetention, bypass_governance: bool) -> Result<ObjectMetadata> {
+        let (object_id, mut metadata) = self.load_lockable_object(bucket_name, key).await?;
+        
+        if retention.retain_until <= chrono::Utc::now() {
+            return Err(anyhow!("Retain-until date must be in the future"));
+        }
+        
+        if let Some(current_until) = metadata.retain_until.filter(|until| *until > chrono::Utc::now()) {
+            let weakened = retention.retain_until < current_until
+                || (metadata.retention_mode == Some(ObjectLockMode::Compliance) && retention.mode == ObjectLockMode::Governance);
+            if weakened {
+                match metadata.retention_mode {
+                    Some(ObjectLockMode::Governance) if bypass_governance => {
+                        self.record_governance_bypass(&metadata, "PutObjectRetention")?;
+                    }
+                    Some(ObjectLockMode::Governance) => {
+                        return Err(anyhow!("Shortening governance retention of '{}' requires a governance bypass", key));
+                    }
+                    _ => {
+                        return Err(anyhow!("Retention of '{}' is in compliance mode and cannot be shortened", key));
+                    }
+                }
+            }
+        }
+        
+        metadata.retention_mode = Some(retention.mode);
+        metadata.retain_until = Some(retention.retain_until);
+        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        self.audit("PutObjectRetention", &metadata, Some(format!("mode={:?} retain_until={}", retention.mode, retention.retain_until.to_rfc3339())));
+        
+        Ok(metadata)
+    }
+    
+    /// 设置或解除对象的合规保留（Legal Hold）
+    pub async fn put_object_legal_hold(&self, bucket_name: &str, key: &str, enabled: bool) -> Result<ObjectMetadata> {
+        let (object_id, mut metadata) = self.load_lockable_object(bucket_name, key).await?;
+        
+        metadata.legal_hold = enabled;
+        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        self.audit("PutObjectLegalHold", &metadata, Some(format!("enabled={}", enabled)));
+        
+        Ok(metadata)
+    }
+    
+    /// 获取对象（或指定版本）的标签
+    pub async fn get_object_tagging(&self, bucket_name: &str, key: &str, version_id: Option<&str>) -> Result<HashMap<String, String>> {
+        let (_, metadata) = self.load_object_record(bucket_name, key, version_id).await?;
+        Ok(metadata.tags)
+    }
+    
+    /// 替换对象（或指定版本）的标签集，不改变 last_modified 和 ETag
+    pub async fn put_object_tagging(&self, bucket_name: &str, key: &str, version_id: Option<&str>, tags: HashMap<String, String>) -> Result<HashMap<String, String>> {
+        tagging::validate_tags(&tags)?;
+        let (object_id, mut metadata) = self.load_object_record(bucket_name, key, version_id).await?;
+        
+        metadata.tags = tags;
+        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        let mut tag_keys: Vec<&str> = metadata.tags.keys().map(|k| k.as_str()).collect();
+        tag_keys.sort_unstable();
+        self.audit("PutObjectTagging", &metadata, Some(format!("tags={}", tag_keys.join(","))));
+        
+        Ok(metadata.tags)
+    }
+    
+    /// 删除对象（或指定版本）的所有标签
+    pub async fn delete_object_tagging(&self, bucket_name: &str, key: &str, version_id: Option<&str>) -> Result<()> {
+        let (object_id, mut metadata) = self.load_object_record(bucket_name, key, version_id).await?;
+        
+        if !metadata.tags.is_empty() {
+            metadata.tags.clear();
+            self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        }
+        self.audit("DeleteObjectTagging", &metadata, None);
+        
+        Ok(())
+    }
+    
+    /// 加载对象的当前记录或指定版本的记录
+    async fn load_object_record(&self, bucket_name: &str, key: &str, version_id: Option<&str>) -> Result<(String, ObjectMetadata)> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        
+        let object_id = match version_id {
+            Some(version_id) => format!("{}_{}", StorageService::generate_object_id(bucket_name, key), version_id),
+            None => self.storage.find_object_id_by_key(bucket_name, key).await?
+                .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?,
+        };
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| match version_id {
+                Some(version_id) => anyhow!("Version '{}' of object '{}' not found", version_id, key),
+                None => anyhow!("Object metadata not found"),
+            })?;
+        
+        Ok((object_id, metadata))
+    }
+    
+    /// 加载启用了对象锁定的桶中的对象
+    async fn load_lockable_object(&self, bucket_name: &str, key: &str) -> Result<(String, ObjectMetadata)> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        
+        let config: Option<ObjectLockConfiguration> = self.storage.load_bucket_config(bucket_name, OBJECT_LOCK_CONFIG).await?;
+        if !config.map(|c| c.enabled).unwrap_or(false) {
+            return Err(anyhow!("Object lock is not enabled for bucket '{}'", bucket_name));
+        }
+        
+        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object metadata not found"))?;
+        
+        Ok((object_id, metadata))
+    }
+    
+    /// 发布对象事件（失败只记录日志，不影响对象操作本身）
+    async fn emit_event(&self, event_type: EventType, metadata: &ObjectMetadata) {
+        if let Some(notifications) = &self.notifications {
+            if let Err(e) = notifications.publish(event_type, metadata).await {
+                tracing::warn!("Failed to publish {:?} event for '{}/{}': {}", event_type, metadata.bucket_name, metadata.key, e);
+            }
+        }
+    }
+    
+    pub async fn put_object(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+    ) -> Result<Object> {
+        self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, None).await
+    }
+    
+    pub async fn put_object_with_custom_etag(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        custom_etag: Option<String>,
+    ) -> Result<Object> {
+        self.put_object_with_versioning_and_custom_etag(bucket_name, key, data, content_type, user_metadata, false, custom_etag).await
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub async fn put_object_with_versioning_and_custom_etag(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        enable_versioning: bool,
+        custom_etag: Option<String>,
+    ) -> Result<Object> {
+        let options = PutObjectOptions { enable_versioning, custom_etag, customer_key: None };
+        self.put_object_with_options(bucket_name, key, data, content_type, user_metadata, options).await
+    }
+
+    /// 用客户提供的 SSE-C 密钥上传对象；SSE-C 对象不参与去重，也不复用已有数据
+    pub async fn put_object_with_customer_key(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        customer_key: &[u8; 32],
+    ) -> Result<Object> {
+        let options = PutObjectOptions { customer_key: Some(customer_key), ..Default::default() };
+        self.put_object_with_options(bucket_name, key, data, content_type, user_metadata, options).await
+    }
+    
+    async fn put_object_with_options(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        options: PutObjectOptions<'_>,
+    ) -> Result<Object> {
+        let PutObjectOptions { enable_versioning, custom_etag, customer_key } = options;
+        validate_object_key(key).map_err(|e| anyhow!(e))?;
+        
+        // 检查桶是否存在
+        let bucket = self.storage.buckets.read().await;
+        if !bucket.contains_key(bucket_name) {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        drop(bucket);
+        
+        // 使用自定义ETag或生成默认ETag
+        let etag = custom_etag.unwrap_or_else(|| generate_etag(&data));
+        let content_type_config: ContentTypeConfiguration = self.storage.load_bucket_config(bucket_name, CONTENT_TYPE_CONFIG).await?.unwrap_or_default();
+        let (mime_type, detected_content_type) = content_type::resolve(&content_type_config, key, content_type, &data)?;
+        
+        // 注意：这里移除了硬编码的重复内容检查逻辑
+        // 重复内容检查现在由 put_object_with_deduplication 方法处理
+        
+        // 检查是否存在相同内容的文件
+        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? {
+            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
+                // 如果ETag相同，说明内容相同（涉及客户密钥加密时必须重新写入数据，受对象锁定保护的记录不能原地修改）
+                let involves_customer_key = customer_key.is_some() || existing_metadata.customer_encryption.is_some();
+                let mutable = object_lock::check_object_mutable(&existing_metadata, false).is_ok();
+                if existing_metadata.etag == etag && !involves_customer_key && mutable {
+                    // 更新元数据（时间戳等），但不重新存储数据
+                    let mut updated_metadata = existing_metadata.clone();
+                    updated_metadata.last_modified = chrono::Utc::now();
+                    updated_metadata.user_metadata = user_metadata;
+                    updated_metadata.detected_content_type = detected_content_type;
+                    
+                    self.storage.save_object_metadata(bucket_name, &existing_object_id, &updated_metadata).await?;
+                    self.emit_event(EventType::ObjectCreated, &updated_metadata).await;
+                    self.record_operation("put", data.len() as u64);
+                    self.audit("PutObject", &updated_metadata, Some(format!("etag={} size={}", updated_metadata.etag, updated_metadata.size)));
+                    
+                    return Ok(Object::new(
+                        key.to_string(),
+                        bucket_name.to_string(),
+                        updated_metadata.size,
+                        updated_metadata.content_type,
+                        updated_metadata.etag,
+                        updated_metadata.user_metadata,
+                    ));
+                }
+            }
+        }
+        
+        // 生成版本ID（如果启用版本控制）
+        let version_id = if enable_versioning {
+            Some(self.generate_version_id())
+        } else {
+            None
+        };
+        
+        let object = Object::new(
+            key.to_string(),
+            bucket_name.to_string(),
+            data.len() as u64,
+            mime_type,
+            etag.clone(),
+            user_metadata,
+        );
+        
+        // 生成对象ID（包含版本信息）
+        let object_id = if let Some(vid) = &version_id {
+            format!("{}_{}", StorageService::generate_object_id(bucket_name, key), vid)
+        } else {
+            StorageService::generate_object_id(bucket_name, key)
+        };
+        
+        // 保存对象数据（使用哈希化文件名）
+        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
+        let mut metadata: ObjectMetadata = object.clone().into();
+        metadata.version_id = version_id;
+        metadata.detected_content_type = detected_content_type;
+        
+        // 只有被覆盖的记录受对象锁定保护；启用版本控制时写入新版本，锁定的版本保持不变
+        let replaced = self.storage.load_object_metadata(bucket_name, &object_id).await?;
+        if let Some(replaced) = &replaced {
+            object_lock::check_object_mutable(replaced, false)?;
+        }
+        
+        // 检查配额（覆盖同一记录时先扣除旧记录的用量）
+        let quota_state = self.enforce_quota(bucket_name, replaced.as_ref(), &metadata).await?;
+        
+        self.write_object_data(&object_path, &data, customer_key, &mut metadata).await?;
+        
+        // 保存元数据
+        self.apply_default_retention(bucket_name, &mut metadata).await?;
+        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        
+        // 更新索引
+        self.storage.add_object_to_index(bucket_name, key, &object_id).await?;
+        self.storage.add_etag_to_index(bucket_name, &etag, &object_id).await?;
+        self.emit_event(EventType::ObjectCreated, &metadata).await;
+        self.record_operation("put", metadata.size);
+        self.audit("PutObject", &metadata, Some(format!("etag={} size={}", metadata.etag, metadata.size)));
+        self.check_soft_quota(quota_state, &metadata).await;
+        
+        Ok(object)
+    }
+    
+    /// 生成版本ID
+    fn generate_version_id(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        format!("{:016x}", now)
+    }
+    
+    /// 检查文件是否重复（基于ETag）
+    pub async fn is_duplicate_content(&self, bucket_name: &str, key: &str, etag: &str) -> Result<bool> {
+        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? {
+            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
+                return Ok(existing_metadata.etag == etag);
+            }
+        }
+        Ok(false)
+    }
+    
+    /// 检查是否存在相同内容的其他文件（跨key检测）
+    pub async fn find_duplicate_content_keys(&self, bucket_name: &str, etag: &str, exclude_key: Option<&str>) -> Result<Vec<String>> {
+        let object_ids = self.storage.find_objects_by_etag(bucket_name, etag).await?;
+        let mut duplicate_keys = Vec::new();
+        
+        for object_id in object_ids {
+            if let Some(metadata) = self.storage.load_object_metadata(bucket_name, &object_id).await? {
+                // SSE-C 对象的密文不能被共享
+                if metadata.customer_encryption.is_some() {
+                    continue;
+                }
+                
+                // 排除指定的key
+                if let Some(exclude) = exclude_key {
+                    if metadata.key != exclude {
+                        duplicate_keys.push(metadata.key);
+                    }
+                } else {
+                    duplicate_keys.push(metadata.key);
+                }
+            }
+        }
+        
+        Ok(duplicate_keys)
+    }
+    
+    /// 条件上传（只有当文件不存在或内容不同时才上传）
+    pub async fn put_object_if_not_exists(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+    ) -> Result<Object> {
+        let etag = generate_etag(&data);
+        
+        // 检查文件是否已存在且内容相同
+        if self.is_duplicate_content(bucket_name, key, &etag).await? {
+            return Err(anyhow!("Object '{}' already exists with same content", key));
+        }
+        
+        self.put_object(bucket_name, key, data, content_type, user_metadata).await
+    }
+    
+    /// 条件上传（只有当ETag不匹配时才上传）
+    #[allow(dead_code)]
+    pub async fn put_object_if_etag_mismatch(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        expected_etag: &str,
+    ) -> Result<Object> {
+        let _etag = generate_etag(&data);
+        
+        // 检查当前ETag是否与期望的ETag匹配
+        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, key).await? {
+            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
+                if existing_metadata.etag == expected_etag {
+                    return Err(anyhow!("ETag precondition failed: expected '{}', got '{}'", expected_etag, existing_metadata.etag));
+                }
+            }
+        }
+        
+        self.put_object(bucket_name, key, data, content_type, user_metadata).await
+    }
+    
+    /// 智能上传：如果内容已存在，可以选择创建引用或拒绝上传
+    pub async fn put_object_with_deduplication(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        deduplication_mode: DeduplicationMode,
+    ) -> Result<Object> {
+        self.put_object_with_deduplication_and_custom_etag(bucket_name, key, data, content_type, user_metadata, deduplication_mode, None).await
+    }
+    
+    /// 智能上传：如果内容已存在，可以选择创建引用或拒绝上传（支持自定义ETag）
+    #[allow(clippy::too_many_arguments)]
+    pub async fn put_object_with_deduplication_and_custom_etag(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+        deduplication_mode: DeduplicationMode,
+        custom_etag: Option<String>,
+    ) -> Result<Object> {
+        let etag = custom_etag.clone().unwrap_or_else(|| generate_etag(&data));
+        
+        // 检查是否存在相同内容的其他文件
+        let duplicate_keys = self.find_duplicate_content_keys(bucket_name, &etag, Some(key)).await?;
+        
+        match deduplication_mode {
+            DeduplicationMode::Reject => {
+                if !duplicate_keys.is_empty() {
+                    return Err(anyhow!(
+                        "Content already exists with keys: {}. Use different content or enable deduplication.",
+                        duplicate_keys.join(", ")
+                    ));
+                }
+                self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
+            },
+            DeduplicationMode::Allow => {
+                // 允许重复，正常上传
+                self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
+            },
+            DeduplicationMode::Reference => {
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_dedup_reference_attempt();
+                }
+                if !duplicate_keys.is_empty() {
+                    // 找到重复内容，创建引用
+                    if let Some(first_duplicate_key) = duplicate_keys.first() {
+                        // 找到第一个重复对象的ID
+                        if let Some(existing_object_id) = self.storage.find_object_id_by_key(bucket_name, first_duplicate_key).await? {
+                            if let Some(existing_metadata) = self.storage.load_object_metadata(bucket_name, &existing_object_id).await? {
+                                // 创建引用对象
+                                let new_object = Object::new(
+                                    key.to_string(),
+                                    bucket_name.to_string(),
+                                    existing_metadata.size,
+                                    existing_metadata.content_type.clone(),
+                                    existing_metadata.etag.clone(),
+                                    user_metadata,
+                                );
+                                
+                                // 生成新的对象ID
+                                let new_object_id = StorageService::generate_object_id(bucket_name, key);
+                                
+                                // 创建引用元数据
+                                let mut new_metadata: ObjectMetadata = new_object.clone().into();
+                                new_metadata.data_holder_id = Some(existing_object_id.clone());
+                                new_metadata.reference_count = 0; // 引用对象本身不计数
+                                new_metadata.stored_size = Some(0);
+                                new_metadata.detected_content_type = existing_metadata.detected_content_type.clone();
+                                self.apply_default_retention(bucket_name, &mut new_metadata).await?;
+                                
+                                // 受对象锁定保护的记录不允许覆盖
+                                let replaced = self.storage.load_object_metadata(bucket_name, &new_object_id).await?;
+                                if let Some(replaced) = &replaced {
+                                    object_lock::check_object_mutable(replaced, false)?;
+                                }
+                                
+                                // 检查配额（引用对象只增加逻辑大小）
+                                let quota_state = self.enforce_quota(bucket_name, replaced.as_ref(), &new_metadata).await?;
+                                
+                                // 保存引用元数据
+                                self.storage.save_object_metadata(bucket_name, &new_object_id, &new_metadata).await?;
+                                
+                                // 更新数据持有者的引用计数
+                                let mut updated_existing_metadata = existing_metadata.clone();
+                                updated_existing_metadata.reference_count += 1;
+                                self.storage.save_object_metadata(bucket_name, &existing_object_id, &updated_existing_metadata).await?;
+                                
+                                // 更新索引
+                                self.storage.add_object_to_index(bucket_name, key, &new_object_id).await?;
+                                self.storage.add_etag_to_index(bucket_name, &etag, &new_object_id).await?;
+                                self.emit_event(EventType::ObjectCreated, &new_metadata).await;
+                                self.record_operation("put", new_metadata.size);
+                                self.audit("PutObject", &new_metadata, Some(format!("etag={} size={} reference", new_metadata.etag, new_metadata.size)));
+                                if let Some(metrics) = &self.metrics {
+                                    metrics.record_dedup_reference_hit();
+                                }
+                                self.check_soft_quota(quota_state, &new_metadata).await;
+                                
+                                Ok(new_object)
+                            } else {
+                                // 如果找不到现有元数据，正常上传
+                                self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
+                            }
+                        } else {
+                            // 如果找不到现有对象ID，正常上传
+                            self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
+                        }
+                    } else {
+                        // 没有重复，正常上传
+                        self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
+                    }
+                } else {
+                    // 没有重复，正常上传
+                    self.put_object_with_custom_etag(bucket_name, key, data, content_type, user_metadata, custom_etag.clone()).await
+                }
+            }
+        }
+    }
+    
+    /// 服务端复制对象（if_match / if_none_match 为源对象ETag的条件）。
+    /// 同一个桶内创建指向源数据持有者的引用，不复制数据；跨桶时未加密的数据使用硬链接（不支持时复制文件），
+    /// 加密数据或目标桶启用了加密时解密后按目标桶的配置重新写入
+    pub async fn copy_object(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        request: CopyObjectRequest,
+        if_match: Option<&str>,
+        if_none_match: Option<&str>,
+    ) -> Result<Object> {
+        validate_object_key(key).map_err(|e| anyhow!(e))?;
+        for name in [request.source_bucket.as_str(), bucket_name] {
+            if !self.storage.bucket_exists(name).await {
+                return Err(anyhow!("Bucket '{}' not found", name));
+            }
+        }
+        let source_bucket = request.source_bucket.as_str();
+        
+        // 加载源对象及其数据持有者
+        let source_id = match &request.source_version_id {
+            Some(version_id) => format!("{}_{}", StorageService::generate_object_id(source_bucket, &request.source_key), version_id),
+            None => self.storage.find_object_id_by_key(source_bucket, &request.source_key).await?
+                .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", request.source_key, source_bucket))?,
+        };
+        let source = self.storage.load_object_metadata(source_bucket, &source_id).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", request.source_key, source_bucket))?;
+        
+        if let Some(condition) = if_match {
+            if !etag_condition_matches(condition, &source.etag) {
+                return Err(PreconditionFailed(format!("source ETag {} does not match {}", source.etag, condition)).into());
+            }
+        }
+        if let Some(condition) = if_none_match {
+            if etag_condition_matches(condition, &source.etag) {
+                return Err(PreconditionFailed(format!("source ETag {} matches {}", source.etag, condition)).into());
+            }
+        }
+        
+        let (holder_id, holder) = match &source.data_holder_id {
+            Some(holder_id) => {
+                let holder = self.storage.load_object_metadata(source_bucket, holder_id).await?
+                    .ok_or_else(|| anyhow!("Data holder for object '{}' not found", request.source_key))?;
+                (holder_id.clone(), holder)
+            }
+            None => (source_id.clone(), source.clone()),
+        };
+        if holder.customer_encryption.is_some() {
+            return Err(anyhow!("Objects encrypted with a customer-provided key cannot be copied on the server"));
+        }
+        
+        let object_id = StorageService::generate_object_id(bucket_name, key);
+        let same_bucket = source_bucket == bucket_name;
+        if same_bucket && object_id == source_id {
+            return Err(anyhow!("Cannot copy '{}' onto itself; use the metadata API to change its metadata", key));
+        }
+        
+        // 受对象锁定保护的记录不允许覆盖，仍被引用的数据持有者也不允许覆盖
+        let replaced = self.storage.load_object_metadata(bucket_name, &object_id).await?;
+        if let Some(replaced) = &replaced {
+            object_lock::check_object_mutable(replaced, false)?;
+            if replaced.reference_count > 0 {
+                return Err(anyhow!("Cannot overwrite object '{}' because it has {} reference(s)", key, replaced.reference_count));
+            }
+        }
+        
+        let (content_type, user_metadata, tags) = match request.metadata_directive {
+            MetadataDirective::Copy => (source.content_type.clone(), source.user_metadata.clone(), source.tags.clone()),
+            MetadataDirective::Replace => {
+                tagging::validate_tags(&request.tags)?;
+                if let Some(content_type) = &request.content_type {
+                    let config = self.storage.load_bucket_config(bucket_name, CONTENT_TYPE_CONFIG).await?.unwrap_or_default();
+                    content_type::check_declared(&config, content_type, source.detected_content_type.as_deref())?;
+                }
+                (request.content_type.clone().unwrap_or_else(|| source.content_type.clone()), request.user_metadata.clone(), request.tags.clone())
+            }
+        };
+        let mut object = Object::new(
+            key.to_string(),
+            bucket_name.to_string(),
+            source.size,
+            content_type,
+            source.etag.clone(),
+            user_metadata,
+        );
+        object.tags = tags;
+        let mut metadata: ObjectMetadata = object.clone().into();
+        metadata.detected_content_type = source.detected_content_type.clone();
+        if same_bucket {
+            metadata.data_holder_id = Some(holder_id.clone());
+            metadata.stored_size = Some(0);
+        }
+        self.apply_default_retention(bucket_name, &mut metadata).await?;
+        let quota_state = self.enforce_quota(bucket_name, replaced.as_ref(), &metadata).await?;
+        
+        // 先准备新数据，此时被覆盖的对象仍然完整：同桶复制增加数据持有者的引用计数，跨桶复制把数据写入暂存文件
+        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
+        let staging_path = temporary_path(&object_path);
+        if same_bucket {
+            let mut holder = self.storage.load_object_metadata(bucket_name, &holder_id).await?
+                .ok_or_else(|| anyhow!("Data holder for object '{}' not found", request.source_key))?;
+            holder.reference_count += 1;
+            self.storage.save_object_metadata(bucket_name, &holder_id, &holder).await?;
+        } else {
+            let source_path = self.storage.get_object_data_path(source_bucket, &holder_id);
+            let encrypt_destination = match &self.encryption {
+                Some(encryption) => encryption.is_enabled(bucket_name).await?,
+                None => false,
+            };
+            if holder.encryption.is_none() && !encrypt_destination {
+                // 存储的数据不依赖桶密钥，可以直接共享（压缩信息随数据一起沿用）
+                link_or_copy(&source_path, &staging_path)?;
+                metadata.compression = holder.compression.clone();
+                metadata.stored_size = Some(holder.physical_size());
+            } else {
+                let data = self.read_object_data(source_bucket, &holder_id, &holder, None, None).await?;
+                self.write_object_data(&staging_path, &data, None, &mut metadata).await?;
+            }
+            // 暂存的数据原子地替换被覆盖对象的数据文件
+            if let Err(e) = fs::rename(&staging_path, &object_path) {
+                let _ = fs::remove_file(&staging_path);
+                return Err(e.into());
+            }
+        }
+        
+        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        
+        // 新元数据保存后再释放被覆盖对象的数据或引用（跨桶复制时它的数据文件已被替换）
+        if let Some(replaced) = &replaced {
+            if replaced.data_holder_id.is_some() || same_bucket {
+                self.release_object_data(bucket_name, &object_id, replaced).await?;
+            }
+            self.storage.remove_etag_from_index(bucket_name, &replaced.etag, &object_id).await?;
+        }
+        self.storage.add_object_to_index(bucket_name, key, &object_id).await?;
+        self.storage.add_etag_to_index(bucket_name, &metadata.etag, &object_id).await?;
+        self.emit_event(EventType::ObjectCreated, &metadata).await;
+        self.record_operation("copy", metadata.size);
+        let source_version = request.source_version_id.as_deref().map(|v| format!(" version={}", v)).unwrap_or_default();
+        self.audit("CopyObject", &metadata, Some(format!("source={}/{}{}", source_bucket, request.source_key, source_version)));
+        self.check_soft_quota(quota_state, &metadata).await;
+        
+        Ok(object)
+    }
+    
+    /// 释放即将被覆盖的对象记录占用的数据：引用对象减少数据持有者的引用计数，否则删除数据文件
+    async fn release_object_data(&self, bucket_name: &str, object_id: &str, metadata: &ObjectMetadata) -> Result<()> {
+        match &metadata.data_holder_id {
+            Some(holder_id) => {
+                if let Some(mut holder) = self.storage.load_object_metadata(bucket_name, holder_id).await? {
+                    if holder.reference_count > 0 {
+                        holder.reference_count -= 1;
+                        self.storage.save_object_metadata(bucket_name, holder_id, &holder).await?;
+                    }
+                }
+            }
+            None => {
+                let object_path = self.storage.get_object_data_path(bucket_name, object_id);
+                if object_path.exists() {
+                    fs::remove_file(object_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// 重命名对象（包括其所有历史版本），数据文件移动而不复制
+    pub async fn rename_object(&self, bucket_name: &str, key: &str, destination_key: &str) -> Result<RenameReport> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        if self.storage.find_object_id_by_key(bucket_name, key).await?.is_none() {
+            return Err(anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name));
+        }
+        if key == destination_key {
+            return Err(anyhow!("Destination key must differ from the source key"));
+        }
+        
+        self.rename_keys(bucket_name, |k| (k == key).then(|| destination_key.to_string())).await
+    }
+    
+    /// 前缀下所有对象记录（包括历史版本）的键，去重并排序
+    pub async fn prefix_keys(&self, bucket_name: &str, prefix: &str) -> Result<Vec<String>> {
+        let keys: std::collections::BTreeSet<String> = self.storage.list_object_metadata(bucket_name).await?
+            .into_iter()
+            .filter(|m| m.key.starts_with(prefix))
+            .map(|m| m.key)
+            .collect();
+        Ok(keys.into_iter().collect())
+    }
+    
+    /// 把前缀下的对象移动到新前缀下；只移动 keys 中的对象（调用方已逐个检查过权限），之后新增的对象不受影响
+    pub async fn rename_prefix(&self, bucket_name: &str, source_prefix: &str, destination_prefix: &str, keys: &std::collections::HashSet<String>) -> Result<RenameReport> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        if source_prefix.is_empty() {
+            return Err(anyhow!("Source prefix must not be empty"));
+        }
+        if source_prefix == destination_prefix {
+            return Err(anyhow!("Destination prefix must differ from the source prefix"));
+        }
+        
+        let report = self.rename_keys(bucket_name, |k| {
+            if !keys.contains(k) {
+                return None;
+            }
+            k.strip_prefix(source_prefix).map(|rest| format!("{}{}", destination_prefix, rest))
+        }).await?;
+        if report.renamed.is_empty() {
+            return Err(anyhow!("No objects found under prefix '{}' in bucket '{}'", source_prefix, bucket_name));
+        }
+        Ok(report)
+    }
+    
+    /// 按 map_key 重命名对象记录：先校验全部目标，再逐步执行，任一步失败时撤销已完成的步骤
+    async fn rename_keys<F>(&self, bucket_name: &str, map_key: F) -> Result<RenameReport>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        // 同一时间只执行一个重命名，避免两个重命名交叉改写同一批记录
+        let _guard = self.rename_lock.lock().await;
+        
+        let records = self.storage.list_object_metadata(bucket_name).await?;
+        let existing_keys: std::collections::HashSet<&str> = records.iter().map(|m| m.key.as_str()).collect();
+        
+        let mut steps = Vec::new();
+        let mut key_map: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+        for metadata in &records {
+            let Some(new_key) = map_key(&metadata.key) else { continue };
+            if !key_map.contains_key(&metadata.key) {
+                validate_object_key(&new_key).map_err(|e| anyhow!("Invalid destination key '{}': {}", new_key, e))?;
+                if existing_keys.contains(new_key.as_str()) {
+                    return Err(anyhow!("Object '{}' already exists in bucket '{}'", new_key, bucket_name));
+                }
+                key_map.insert(metadata.key.clone(), new_key.clone());
+            }
+            // 受对象锁定保护的记录不能改变键
+            object_lock::check_object_mutable(metadata, false)?;
+            
+            let mut renamed = metadata.clone();
+            renamed.key = new_key;
+            steps.push(RenameStep {
+                old_id: StorageService::object_record_id(bucket_name, metadata),
+                new_id: StorageService::object_record_id(bucket_name, &renamed),
+                metadata: metadata.clone(),
+                renamed,
+            });
+        }
+        if steps.is_empty() {
+            return Ok(RenameReport::default());
+        }
+        
+        // 引用关系跟随数据持有者的新ID
+        let id_map: HashMap<String, String> = steps.iter().map(|s| (s.old_id.clone(), s.new_id.clone())).collect();
+        for step in &mut steps {
+            if let Some(new_holder) = step.renamed.data_holder_id.as_ref().and_then(|h| id_map.get(h)) {
+                step.renamed.data_holder_id = Some(new_holder.clone());
+            }
+        }
+        let referrers: Vec<&ObjectMetadata> = records.iter()
+            .filter(|m| !key_map.contains_key(&m.key))
+            .filter(|m| m.data_holder_id.as_ref().is_some_and(|h| id_map.contains_key(h)))
+            .collect();
+        
+        // 重命名前各个键的当前记录
+        let mut current_ids = Vec::new();
+        for (old_key, new_key) in &key_map {
+            if let Some(current_id) = self.storage.find_object_id_by_key(bucket_name, old_key).await? {
+                current_ids.push((old_key.clone(), new_key.clone(), current_id));
+            }
+        }
+        
+        let mut undo = Vec::new();
+        if let Err(e) = self.apply_rename_steps(bucket_name, &steps, &referrers, &id_map, &mut undo).await {
+            self.rollback_rename(bucket_name, undo).await;
+            return Err(e);
+        }
+        
+        // 所有文件操作都已成功，更新内存索引
+        for step in &steps {
+            self.storage.remove_etag_from_index(bucket_name, &step.metadata.etag, &step.old_id).await?;
+            self.storage.add_etag_to_index(bucket_name, &step.renamed.etag, &step.new_id).await?;
+        }
+        for (old_key, new_key, current_id) in &current_ids {
+            self.storage.remove_object_from_index(bucket_name, old_key).await?;
+            let new_id = id_map.get(current_id).unwrap_or(current_id);
+            self.storage.add_object_to_index(bucket_name, new_key, new_id).await?;
+        }
+        
+        for step in &steps {
+            self.audit("RenameObject", &step.renamed, Some(format!("from={}", step.metadata.key)));
+            if current_ids.iter().any(|(_, _, current_id)| *current_id == step.old_id) {
+                self.emit_event(EventType::ObjectRemoved, &step.metadata).await;
+                self.emit_event(EventType::ObjectCreated, &step.renamed).await;
+            }
+        }
+        self.record_operation("rename", 0);
+        
+        Ok(RenameReport {
+            renamed: key_map.into_iter()
+                .map(|(source_key, destination_key)| RenamedObject { source_key, destination_key })
+                .collect(),
+            records: steps.len() as u64,
+        })
+    }
+    
+    /// 执行重命名的文件操作：移动数据文件、写入新元数据、改写外部引用、删除旧元数据
+    async fn apply_rename_steps(
+        &self,
+        bucket_name: &str,
+        steps: &[RenameStep],
+        referrers: &[&ObjectMetadata],
+        id_map: &HashMap<String, String>,
+        undo: &mut Vec<RenameUndo>,
+    ) -> Result<()> {
+        for step in steps {
+            if self.storage.load_object_metadata(bucket_name, &step.new_id).await?.is_some() {
+                return Err(anyhow!("Object record '{}' already exists in bucket '{}'", step.renamed.key, bucket_name));
+            }
+            
+            let old_path = self.storage.get_object_data_path(bucket_name, &step.old_id);
+            if old_path.exists() {
+                let new_path = self.storage.get_object_data_path(bucket_name, &step.new_id);
+                if let Some(parent) = new_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&old_path, &new_path)?;
+                undo.push(RenameUndo::MoveData { from: new_path, to: old_path });
+            }
+            
+            self.storage.save_object_metadata(bucket_name, &step.new_id, &step.renamed).await?;
+            undo.push(RenameUndo::CreatedMetadata(step.new_id.clone()));
+        }
+        
+        for referrer in referrers {
+            let referrer_id = StorageService::object_record_id(bucket_name, referrer);
+            let mut updated = (*referrer).clone();
+            updated.data_holder_id = updated.data_holder_id.as_ref().and_then(|h| id_map.get(h)).cloned();
+            self.storage.save_object_metadata(bucket_name, &referrer_id, &updated).await?;
+            undo.push(RenameUndo::ReplacedMetadata(referrer_id, Box::new((*referrer).clone())));
+        }
+        
+        for step in steps {
+            self.storage.delete_object_metadata(bucket_name, &step.old_id).await?;
+            undo.push(RenameUndo::ReplacedMetadata(step.old_id.clone(), Box::new(step.metadata.clone())));
+        }
+        Ok(())
+    }
+    
+    /// 撤销重命名已完成的步骤（尽力而为，失败只记录日志）
+    async fn rollback_rename(&self, bucket_name: &str, undo: Vec<RenameUndo>) {
+        for step in undo.into_iter().rev() {
+            let result = match step {
+                RenameUndo::MoveData { from, to } => fs::rename(&from, &to).map_err(anyhow::Error::from),
+                RenameUndo::CreatedMetadata(object_id) => self.storage.delete_object_metadata(bucket_name, &object_id).await,
+                RenameUndo::ReplacedMetadata(object_id, metadata) => self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await,
+            };
+            if let Err(e) = result {
+                tracing::error!("Failed to roll back rename step in bucket '{}': {}", bucket_name, e);
+            }
+        }
+    }
+    
+    /// 获取对象的所有版本
+    pub async fn list_object_versions(
+        &self,
+        bucket_name: &str,
+        key: &str,
+    ) -> Result<Vec<ObjectMetadata>> {
+        let all_objects = self.storage.list_object_metadata(bucket_name).await?;
+        
+        let mut versions: Vec<ObjectMetadata> = all_objects
+            .into_iter()
+            .filter(|obj| obj.key == key)
+            .collect();
+        
+        // 按创建时间排序（最新的在前）
+        versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+        
+        Ok(versions)
+    }
+    
+    /// 获取特定版本的对象
+    #[allow(dead_code)]
+    pub async fn get_object_version(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        version_id: &str,
+    ) -> Result<(Vec<u8>, ObjectMetadata)> {
+        let object_id = format!("{}_{}", StorageService::generate_object_id(bucket_name, key), version_id);
+        
+        // 加载元数据
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object version not found"))?;
+        
+        // 读取对象数据
+        let data = self.read_object_data(bucket_name, &object_id, &metadata, None, None).await?;
+        
+        Ok((data, metadata))
+    }
+    
+    /// 获取对象指定版本的元数据
+    pub async fn get_object_version_metadata(&self, bucket_name: &str, key: &str, version_id: &str) -> Result<ObjectMetadata> {
+        let object_id = format!("{}_{}", StorageService::generate_object_id(bucket_name, key), version_id);
+        
+        self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object version not found"))
+    }
+    
+    /// 删除对象的非当前版本（当前版本请使用 delete_object）
+    pub async fn delete_object_version(&self, bucket_name: &str, key: &str, version_id: &str) -> Result<()> {
+        let object_id = format!("{}_{}", StorageService::generate_object_id(bucket_name, key), version_id);
+        
+        if self.storage.find_object_id_by_key(bucket_name, key).await?.as_deref() == Some(object_id.as_str()) {
+            return Err(anyhow!("Version '{}' is the current version of '{}'", version_id, key));
+        }
+        
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object version not found"))?;
+        
+        if metadata.reference_count > 0 {
+            return Err(anyhow!("Cannot delete version '{}' of '{}' because it has {} reference(s)", version_id, key, metadata.reference_count));
+        }
+        
+        object_lock::check_object_mutable(&metadata, false)?;
+        
+        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
+        if object_path.exists() {
+            fs::remove_file(object_path)?;
+        }
+        
+        self.storage.delete_object_metadata(bucket_name, &object_id).await?;
+        self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
+        self.record_operation("delete", metadata.size);
+        self.audit("DeleteObjectVersion", &metadata, None);
+        
+        Ok(())
+    }
+    
+    pub async fn get_object(&self, bucket_name: &str, key: &str) -> Result<(Vec<u8>, ObjectMetadata)> {
+        self.get_object_range(bucket_name, key, None).await
+    }
+    
+    /// 读取对象数据，range 为闭区间 [start, end]（调用方需保证区间在对象大小之内）
+    pub async fn get_object_range(&self, bucket_name: &str, key: &str, range: Option<(u64, u64)>) -> Result<(Vec<u8>, ObjectMetadata)> {
+        self.get_object_range_with_customer_key(bucket_name, key, range, None).await
+    }
+    
+    /// 读取对象数据（SSE-C 对象需要提供加密时使用的客户密钥）
+    pub async fn get_object_range_with_customer_key(&self, bucket_name: &str, key: &str, range: Option<(u64, u64)>, customer_key: Option<&[u8; 32]>) -> Result<(Vec<u8>, ObjectMetadata)> {
+        // 检查桶是否存在
+        let bucket = self.storage.buckets.read().await;
+        if !bucket.contains_key(bucket_name) {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        drop(bucket);
+        
+        // 查找对象ID
+        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
+        
+        // 加载元数据
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object metadata not found"))?;
+        
+        // 确定数据持有者ID（加密信息保存在数据持有者的元数据中）
+        let (data_object_id, data_metadata) = if let Some(holder_id) = &metadata.data_holder_id {
+            // 检查数据持有者是否还存在
+            if let Some(holder_metadata) = self.storage.load_object_metadata(bucket_name, holder_id).await? {
+                (holder_id.clone(), holder_metadata)
+            } else {
+                return Err(anyhow!("Data holder for object '{}' not found", key));
+            }
+        } else {
+            // 自己是数据持有者
+            (object_id, metadata.clone())
+        };
+        
+        // 读取对象数据
+        let data = self.read_object_data(bucket_name, &data_object_id, &data_metadata, range, customer_key).await?;
+        
+        Ok((data, metadata))
+    }
+    
+    pub async fn delete_object(&self, bucket_name: &str, key: &str) -> Result<()> {
+        self.delete_object_with_governance_bypass(bucket_name, key, false).await
+    }
+    
+    /// 删除对象（bypass_governance 为 true 时允许绕过治理模式保留，并记录审计日志）
+    pub async fn delete_object_with_governance_bypass(&self, bucket_name: &str, key: &str, bypass_governance: bool) -> Result<()> {
+        // 检查桶是否存在
+        let bucket = self.storage.buckets.read().await;
+        if !bucket.contains_key(bucket_name) {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        drop(bucket);
+        
+        // 查找对象ID
+        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
+        
+        // 获取对象元数据
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object metadata not found"))?;
+        
+        // 检查对象锁定
+        self.enforce_object_lock(&metadata, "DeleteObject", bypass_governance)?;
+        
+        if let Some(data_holder_id) = &metadata.data_holder_id {
+            // 删除引用对象
+            self.storage.delete_object_metadata(bucket_name, &object_id).await?;
+            self.storage.remove_object_from_index(bucket_name, key).await?;
+            self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
+            
+            // 减少数据持有者的引用计数
+            if let Some(mut holder_metadata) = self.storage.load_object_metadata(bucket_name, data_holder_id).await? {
+                if holder_metadata.reference_count > 0 {
+                    holder_metadata.reference_count -= 1;
+                    self.storage.save_object_metadata(bucket_name, data_holder_id, &holder_metadata).await?;
+                }
+            }
+        } else {
+            // 自己是数据持有者，检查是否有其他对象引用
+            if metadata.reference_count > 0 {
+                return Err(anyhow!("Cannot delete object '{}' because it has {} reference(s). Delete all references first.", key, metadata.reference_count));
+            }
+            
+            // 删除对象数据
+            let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
+            if object_path.exists() {
+                fs::remove_file(object_path)?;
+            }
+            
+            // 删除元数据
+            self.storage.delete_object_metadata(bucket_name, &object_id).await?;
+            
+            // 更新索引
+            self.storage.remove_object_from_index(bucket_name, key).await?;
+            self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
+        }
+        
+        self.emit_event(EventType::ObjectRemoved, &metadata).await;
+        self.record_operation("delete", metadata.size);
+        self.audit("DeleteObject", &metadata, bypass_governance.then(|| "bypass_governance".to_string()));
+        
+        Ok(())
+    }
+    
+    /// 批量删除对象并逐个返回结果；仍被引用的数据持有者推迟到最后删除，
+    /// 这样同一批中指向它的引用对象会先被删除
+    pub async fn delete_objects(&self, bucket_name: &str, entries: Vec<DeleteObjectEntry>, bypass_governance: bool) -> Result<BatchDeleteResult> {
+        if entries.len() > MAX_BATCH_DELETE_OBJECTS {
+            return Err(anyhow!("At most {} objects can be deleted in one request", MAX_BATCH_DELETE_OBJECTS));
+        }
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        
+        let mut ordered = Vec::with_capacity(entries.len());
+        let mut deferred = Vec::new();
+        for entry in entries {
+            let object_id = match &entry.version_id {
+                Some(version_id) => Some(format!("{}_{}", StorageService::generate_object_id(bucket_name, &entry.key), version_id)),
+                None => self.storage.find_object_id_by_key(bucket_name, &entry.key).await.ok().flatten(),
+            };
+            let referenced = match object_id {
+                Some(object_id) => self.storage.load_object_metadata(bucket_name, &object_id).await.ok().flatten()
+                    .is_some_and(|m| m.data_holder_id.is_none() && m.reference_count > 0),
+                None => false,
+            };
+            if referenced {
+                deferred.push(entry);
+            } else {
+                ordered.push(entry);
+            }
+        }
+        
+        let mut result = BatchDeleteResult::default();
+        for entry in ordered.into_iter().chain(deferred) {
+            let outcome = match &entry.version_id {
+                Some(version_id) => self.delete_object_version(bucket_name, &entry.key, version_id).await,
+                None => self.delete_object_with_governance_bypass(bucket_name, &entry.key, bypass_governance).await,
+            };
+            match outcome {
+                Ok(()) => result.deleted.push(entry),
+                Err(e) => result.errors.push(DeleteObjectError { key: entry.key, version_id: entry.version_id, message: e.to_string() }),
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    pub async fn get_object_metadata(&self, bucket_name: &str, key: &str) -> Result<ObjectMetadata> {
+        // 检查桶是否存在
+        let bucket = self.storage.buckets.read().await;
+        if !bucket.contains_key(bucket_name) {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        drop(bucket);
+        
+        // 查找对象ID
+        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
+        
+        self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object metadata not found"))
+    }
+    
+    pub async fn list_objects(
+        &self,
+        bucket_name: &str,
+        prefix: Option<String>,
+        delimiter: Option<String>,
+        max_keys: Option<u32>,
+        marker: Option<String>,
+        etag_filter: Option<String>,
+    ) -> Result<Vec<Object>> {
+        let all_objects = self.storage.list_object_metadata(bucket_name).await?;
+        let objects = self.build_object_listing(all_objects, prefix, marker, etag_filter);
+        Ok(Self::limit_object_listing(bucket_name, objects, max_keys, delimiter))
+    }
+    
+    /// 把对象记录转换为列举结果，并应用前缀、ETag 和分页标记
+    fn build_object_listing(
+        &self,
+        records: Vec<ObjectMetadata>,
+        prefix: Option<String>,
+        marker: Option<String>,
+        etag_filter: Option<String>,
+    ) -> Vec<Object> {
+        let mut objects: Vec<Object> = records
+            .into_iter()
+            .map(|metadata| {
+                let mut object = Object::new(
+                    metadata.key,
+                    metadata.bucket_name,
+                    metadata.size,
+                    metadata.content_type,
+                    metadata.etag,
+                    metadata.user_metadata,
+                );
+                object.tags = metadata.tags;
+                object
+            })
+            .collect();
+        
+        // 应用前缀过滤
+        if let Some(prefix) = prefix {
+            objects.retain(|obj| obj.key.starts_with(&prefix));
+        }
+        
+        // 应用ETag过滤
+        if let Some(etag_filter) = etag_filter {
+            objects.retain(|obj| self.matches_etag_filter(&obj.etag, &etag_filter));
+        }
+        
+        // 应用分页
+        if let Some(marker) = marker {
+            objects.retain(|obj| obj.key > marker);
+        }
+        
+        objects
+    }
+    
+    /// 截断到最大数量，再按分隔符合并公共前缀
+    fn limit_object_listing(bucket_name: &str, mut objects: Vec<Object>, max_keys: Option<u32>, delimiter: Option<String>) -> Vec<Object> {
+        // 应用最大数量限制
+        if let Some(max_keys) = max_keys {
+            objects.truncate(max_keys as usize);
+        }
+        
+        // 应用分隔符过滤
+        if let Some(delimiter) = delimiter {
+            objects = Self::group_common_prefixes(bucket_name, objects, &delimiter);
+        }
+        
+        objects
+    }
+    
+    /// 把包含分隔符的对象键合并为公共前缀
+    fn group_common_prefixes(bucket_name: &str, objects: Vec<Object>, delimiter: &str) -> Vec<Object> {
+        let mut filtered_objects = Vec::new();
+        let mut seen_prefixes = std::collections::HashSet::new();
+        
+        for obj in objects {
+            if let Some(pos) = obj.key.find(delimiter) {
+                let prefix = obj.key[..pos + delimiter.len()].to_string();
+                if !seen_prefixes.contains(&prefix) {
+                    seen_prefixes.insert(prefix.clone());
+                    // 创建一个虚拟对象来表示公共前缀
+                    let virtual_obj = Object::new(
+                        prefix,
+                        bucket_name.to_string(),
+                        0,
+                        "application/x-directory".to_string(),
+                        "".to_string(),
+                        HashMap::new(),
+                    );
+                    filtered_objects.push(virtual_obj);
+                }
+            } else {
+                filtered_objects.push(obj);
+            }
+        }
+        filtered_objects
+    }
+    
+    /// 检查ETag是否匹配过滤条件（支持通配符）
+    fn matches_etag_filter(&self, etag: &str, filter: &str) -> bool {
+        // 简单的通配符匹配：* 和 ?
+        if filter.contains('*') || filter.contains('?') {
+            // 将通配符模式转换为正则表达式
+            let pattern = filter
+                .replace(".", "\\.")
+                .replace("*", ".*")
+                .replace("?", ".");
+            
+            if let Ok(regex) = regex::Regex::new(&pattern) {
+                return regex.is_match(etag);
+            }
+        }
+        
+        // 精确匹配
+        etag == filter
+    }
+    
+    /// 测试重复文件处理
+    pub async fn test_duplicate_handling(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        data: Vec<u8>,
+        content_type: &str,
+        user_metadata: HashMap<String, String>,
+    ) -> Result<String> {
+        let etag = generate_etag(&data);
+        let mut result = String::new();
+        
+        // 测试1：检查是否重复
+        result.push_str(&format!("1. 检查文件是否重复 (ETag: {})\n", etag));
+        let is_duplicate = self.is_duplicate_content(bucket_name, key, &etag).await?;
+        result.push_str(&format!("   结果: {}\n\n", if is_duplicate { "重复" } else { "不重复" }));
+        
+        // 测试2：尝试条件上传
+        result.push_str("2. 尝试条件上传（如果不存在）\n");
+        match self.put_object_if_not_exists(bucket_name, key, data.clone(), content_type, user_metadata.clone()).await {
+            Ok(_) => result.push_str("   结果: 上传成功\n\n"),
+            Err(e) => result.push_str(&format!("   结果: {}\n\n", e)),
+        }
+        
+        // 测试3：再次检查重复
+        result.push_str("3. 再次检查文件是否重复\n");
+        let is_duplicate_after = self.is_duplicate_content(bucket_name, key, &etag).await?;
+        result.push_str(&format!("   结果: {}\n\n", if is_duplicate_after { "重复" } else { "不重复" }));
+        
+        // 测试4：尝试上传相同内容
+        result.push_str("4. 尝试上传相同内容\n");
+        match self.put_object_if_not_exists(bucket_name, key, data, content_type, user_metadata).await {
+            Ok(_) => result.push_str("   结果: 上传成功\n\n"),
+            Err(e) => result.push_str(&format!("   结果: {}\n\n", e)),
+        }
+        
+        // 测试5：列出所有版本
+        result.push_str("5. 列出所有版本\n");
+        match self.list_object_versions(bucket_name, key).await {
+            Ok(versions) => {
+                result.push_str(&format!("   版本数量: {}\n", versions.len()));
+                for (i, version) in versions.iter().enumerate() {
+                    result.push_str(&format!("   版本 {}: ETag={}, 大小={}, 时间={}\n", 
+                        i + 1, 
+                        version.etag, 
+                        version.size,
+                        version.created_at.format("%Y-%m-%d %H:%M:%S")
+                    ));
+                }
+            },
+            Err(e) => result.push_str(&format!("   结果: {}\n", e)),
+        }
+        
+        Ok(result)
+    }
+    
+    /// 查找引用某个对象的所有引用对象
+    pub async fn find_references_to_object(&self, bucket_name: &str, object_id: &str) -> Result<Vec<ObjectMetadata>> {
+        let all_objects = self.storage.list_object_metadata(bucket_name).await?;
+        
+        let references: Vec<ObjectMetadata> = all_objects
+            .into_iter()
+            .filter(|obj| obj.data_holder_id.as_ref() == Some(&object_id.to_string()))
+            .collect();
+        
+        Ok(references)
+    }
+    
+    /// 强制删除对象及其所有引用（危险操作）
+    pub async fn force_delete_object_with_references(&self, bucket_name: &str, key: &str, bypass_governance: bool) -> Result<()> {
+        // 查找对象ID
+        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
+        
+        let metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object metadata not found"))?;
+        
+        // 查找所有引用
+        let references = self.find_references_to_object(bucket_name, &object_id).await?;
+        
+        // 对象及其所有引用都必须允许删除，避免只删除一部分
+        let mut bypassed = Vec::new();
+        for candidate in std::iter::once(&metadata).chain(references.iter()) {
+            if object_lock::check_object_mutable(candidate, bypass_governance)? {
+                bypassed.push(candidate);
+            }
+        }
+        for candidate in bypassed {
+            self.record_governance_bypass(candidate, "ForceDeleteObject")?;
+        }
+        
+        // 删除所有引用
+        for reference in &references {
+            self.storage.delete_object_metadata(bucket_name, &StorageService::generate_object_id(bucket_name, &reference.key)).await?;
+            self.storage.remove_object_from_index(bucket_name, &reference.key).await?;
+            self.storage.remove_etag_from_index(bucket_name, &reference.etag, &StorageService::generate_object_id(bucket_name, &reference.key)).await?;
+        }
+        
+        // 删除原始对象
+        let object_path = self.storage.get_object_data_path(bucket_name, &object_id);
+        if object_path.exists() {
+            fs::remove_file(object_path)?;
+        }
+        
+        self.storage.delete_object_metadata(bucket_name, &object_id).await?;
+        self.storage.remove_object_from_index(bucket_name, key).await?;
+        self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
+        
+        for removed in references.iter().chain(std::iter::once(&metadata)) {
+            self.emit_event(EventType::ObjectRemoved, removed).await;
+            self.record_operation("delete", removed.size);
+            self.audit("ForceDeleteObject", removed, bypass_governance.then(|| "bypass_governance".to_string()));
+        }
+        
+        Ok(())
+    }
+
+    /// 按自定义元数据（Field::Metadata）和标签（Field::Tag）的等值条件过滤后列举对象
+    #[allow(clippy::too_many_arguments)]
+    pub async fn list_objects_with_custom_filter(
+        &self,
+        bucket_name: &str,
+        prefix: Option<String>,
+        delimiter: Option<String>,
+        max_keys: Option<u32>,
+        marker: Option<String>,
+        etag_filter: Option<String>,
+        filters: Vec<(Field, String)>,
+    ) -> Result<Vec<Object>> {
+        let filters = filters.into_iter()
+            .map(|(field, value)| Expr::Condition(Condition::Compare(field, CompareOp::Eq, Value::Text(value))))
+            .reduce(|a, b| Expr::And(Box::new(a), Box::new(b)));
+        let Some(filters) = filters else {
+            return self.list_objects(bucket_name, prefix, delimiter, max_keys, marker, etag_filter).await;
+        };
+
+        // 先过滤再截断和按分隔符分组，否则一页中被过滤掉的对象会让分页结果不完整
+        let records: Vec<ObjectMetadata> = self.load_candidate_records(bucket_name, Some(&filters)).await?
+            .into_iter()
+            .filter(|m| filters.evaluate(m))
+            .collect();
+        let objects = self.build_object_listing(records, prefix, marker, etag_filter);
+        Ok(Self::limit_object_listing(bucket_name, objects, max_keys, delimiter))
+    }
+
+    /// 加载可能满足表达式的对象记录（按记录ID排序）：元数据索引可用时只加载候选记录，否则扫描整个桶
+    async fn load_candidate_records(&self, bucket_name: &str, expr: Option<&Expr>) -> Result<Vec<ObjectMetadata>> {
+        if let Some(expr) = expr {
+            if let Some(object_ids) = self.storage.metadata_index_candidates(bucket_name, expr).await {
+                return self.storage.load_object_records(bucket_name, object_ids).await;
+            }
+        }
+        self.storage.list_object_metadata(bucket_name).await
+    }
+
+    /// 按查询语句搜索桶中各对象键的当前版本，支持排序和游标分页
+    pub async fn search_objects(
+        &self,
+        bucket_name: &str,
+        query: &str,
+        sort: Option<&str>,
+        order: Option<&str>,
+        limit: Option<u32>,
+        cursor: Option<&str>,
+    ) -> Result<ObjectSearchResult> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+
+        let expr = query::parse(query)?;
+        let order = SortOrder::parse(sort, order)?;
+        let limit = limit.map(|l| l as usize).unwrap_or(DEFAULT_SEARCH_LIMIT);
+        if limit == 0 || limit > MAX_SEARCH_LIMIT {
+            return Err(anyhow!("limit must be between 1 and {}", MAX_SEARCH_LIMIT));
+        }
+
+        let records = self.load_candidate_records(bucket_name, expr.as_ref()).await?;
+        let records = self.storage.retain_current_records(bucket_name, records).await;
+        let (records, next_cursor) = query::paginate(records, expr.as_ref(), &order, limit, cursor)?;
+        Ok(ObjectSearchResult {
+            objects: records.into_iter().map(Object::from).collect(),
+            next_cursor,
+        })
+    }
+
+    /// 更新对象元数据（不重新上传文件内容）
+    #[allow(dead_code)]
+    pub async fn update_object_metadata(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        content_type: Option<String>,
+        user_metadata: Option<HashMap<String, String>>,
+        custom_etag: Option<String>,
+    ) -> Result<Object> {
+        self.update_object_metadata_with_governance_bypass(bucket_name, key, content_type, user_metadata, custom_etag, false).await
+    }
+
+    /// 更新对象元数据（bypass_governance 为 true 时允许绕过治理模式保留，并记录审计日志）
+    pub async fn update_object_metadata_with_governance_bypass(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        content_type: Option<String>,
+        user_metadata: Option<HashMap<String, String>>,
+        custom_etag: Option<String>,
+        bypass_governance: bool,
+    ) -> Result<Object> {
+        // 检查桶是否存在
+        let bucket = self.storage.buckets.read().await;
+        if !bucket.contains_key(bucket_name) {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        drop(bucket);
+
+        // 查找对象ID
+        let object_id = self.storage.find_object_id_by_key(bucket_name, key).await?
+            .ok_or_else(|| anyhow!("Object '{}' not found in bucket '{}'", key, bucket_name))?;
+
+        // 加载现有元数据
+        let mut metadata = self.storage.load_object_metadata(bucket_name, &object_id).await?
+            .ok_or_else(|| anyhow!("Object metadata not found for '{}' in bucket '{}'", key, bucket_name))?;
+
+        // 检查对象锁定
+        self.enforce_object_lock(&metadata, "UpdateObjectMetadata", bypass_governance)?;
+
+        // 更新内容类型（如果提供）
+        if let Some(ct) = content_type {
+            let config = self.storage.load_bucket_config(bucket_name, CONTENT_TYPE_CONFIG).await?.unwrap_or_default();
+            content_type::check_declared(&config, &ct, metadata.detected_content_type.as_deref())?;
+            metadata.content_type = ct;
+        }
+
+        // 更新用户元数据（如果提供）- 只更新传递过来的字段，不覆盖未传递的字段
+        if let Some(um) = user_metadata {
+            for (key, value) in um {
+                metadata.user_metadata.insert(key, value);
+            }
+        }
+
+        // 更新ETag（如果提供）
+        if let Some(etag) = custom_etag {
+            // 验证ETag格式
+            if !is_valid_etag_format(&etag) {
+                return Err(anyhow!("Invalid ETag format: {}", etag));
+            }
+            
+            // 如果ETag发生变化，需要更新索引
+            if metadata.etag != etag {
+                // 从旧ETag索引中移除
+                self.storage.remove_etag_from_index(bucket_name, &metadata.etag, &object_id).await?;
+                // 添加到新ETag索引中
+                self.storage.add_etag_to_index(bucket_name, &etag, &object_id).await?;
+                metadata.etag = etag;
+            }
+        }
+
+        // 更新最后修改时间
+        metadata.last_modified = chrono::Utc::now();
+
+        // 保存更新后的元数据
+        self.storage.save_object_metadata(bucket_name, &object_id, &metadata).await?;
+        self.emit_event(EventType::MetadataUpdated, &metadata).await;
+        self.audit("UpdateObjectMetadata", &metadata, None);
+
+        // 返回更新后的对象
+        Ok(Object::new(
+            key.to_string(),
+            bucket_name.to_string(),
+            metadata.size,
+            metadata.content_type,
+            metadata.etag,
+            metadata.user_metadata,
+        ))
+    }
+}
+
+/// 同目录下的临时文件路径
+fn temporary_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4().simple()))
+}
+
+/// 创建指向源文件的硬链接，文件系统不支持时复制文件
+fn link_or_copy(source: &Path, destination: &Path) -> Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = temporary_path(destination);
+    if fs::hard_link(source, &tmp_path).is_err() {
+        fs::copy(source, &tmp_path)?;
+    }
+    fs::rename(&tmp_path, destination)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> (tempfile::TempDir, StorageService, ObjectService) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = StorageService::new(dir.path().to_string_lossy().into_owned()).await.unwrap();
+        let buckets = BucketService::new(storage.clone());
+        for name in ["src", "dst"] {
+            buckets.create_bucket(name.to_string()).await.unwrap();
+        }
+        let objects = ObjectService::new(storage.clone());
+        (dir, storage, objects)
+    }
+
+    fn copy_request(source_bucket: &str, source_key: &str) -> CopyObjectRequest {
+        CopyObjectRequest {
+            source_bucket: source_bucket.to_string(),
+            source_key: source_key.to_string(),
+            source_version_id: None,
+            metadata_directive: MetadataDirective::Copy,
+            content_type: None,
+            user_metadata: HashMap::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    async fn put(objects: &ObjectService, bucket: &str, key: &str, data: &[u8]) {
+        objects.put_object(bucket, key, data.to_vec(), "text/plain", HashMap::new()).await.unwrap();
+    }
+
+    async fn record(storage: &StorageService, bucket: &str, key: &str) -> ObjectMetadata {
+        let id = storage.find_object_id_by_key(bucket, key).await.unwrap().unwrap();
+        storage.load_object_metadata(bucket, &id).await.unwrap().unwrap()
+    }
+
+    async fn set_quota(storage: &StorageService, bucket: &str, hard_limit_bytes: u64) {
+        let config = QuotaConfiguration { hard_limit_bytes: Some(hard_limit_bytes), ..Default::default() };
+        BucketService::new(storage.clone()).put_quota_configuration(bucket, config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn quota_rejects_writes_past_the_hard_limit() {
+        let (_dir, storage, objects) = setup().await;
+        set_quota(&storage, "src", 10).await;
+        put(&objects, "src", "a", b"123456").await;
+
+        assert!(objects.put_object("src", "b", b"12345".to_vec(), "text/plain", HashMap::new()).await.is_err());
+        // 覆盖为更小的内容不增加用量
+        put(&objects, "src", "a", b"1234").await;
+        put(&objects, "src", "b", b"abcdef").await;
+        assert_eq!(storage.bucket_usage("src").await.logical_bytes, 10);
+    }
+
+    #[tokio::test]
+    async fn reservations_count_against_other_writes() {
+        let (_dir, storage, objects) = setup().await;
+        set_quota(&storage, "src", 10).await;
+        put(&objects, "src", "a", b"1234").await;
+        let candidate = record(&storage, "src", "a").await;
+
+        let (_, reservation) = storage.reserve_usage("src", None, &candidate, |_, _| Ok(())).await.unwrap();
+        assert_eq!(storage.projected_usage("src").await.logical_bytes, 8);
+        assert!(objects.put_object("src", "b", b"abcde".to_vec(), "text/plain", HashMap::new()).await.is_err());
+
+        drop(reservation);
+        assert_eq!(storage.projected_usage("src").await.logical_bytes, 4);
+        put(&objects, "src", "b", b"abcde").await;
+    }
+
+    #[tokio::test]
+    async fn reservations_are_released_after_each_write() {
+        let (_dir, storage, objects) = setup().await;
+        set_quota(&storage, "src", 10).await;
+        put(&objects, "src", "a", b"1234").await;
+        assert!(objects.put_object("src", "b", b"1234567".to_vec(), "text/plain", HashMap::new()).await.is_err());
+
+        assert!(storage.quota_reservations.lock().unwrap().is_empty());
+        assert_eq!(storage.projected_usage("src").await.logical_bytes, 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_writes_cannot_overshoot_the_quota() {
+        let (_dir, storage, objects) = setup().await;
+        set_quota(&storage, "src", 50).await;
+
+        let writes = (0..10).map(|i| {
+            let objects = objects.clone();
+            tokio::spawn(async move {
+                let data = format!("object-{:03}", i).into_bytes();
+                objects.put_object("src", &format!("k{}", i), data, "text/plain", HashMap::new()).await.is_ok()
+            })
+        }).collect::<Vec<_>>();
+        let mut stored = 0;
+        for write in writes {
+            stored += write.await.unwrap() as u64;
+        }
+
+        assert_eq!(stored, 5);
+        assert_eq!(storage.bucket_usage("src").await.logical_bytes, 50);
+        assert!(storage.quota_reservations.lock().unwrap().is_empty());
+    }
+
+    /// 为桶启用对象锁定（可选默认保留策略）
+    async fn enable_object_lock(storage: &StorageService, bucket: &str, default_retention: Option<crate::models::DefaultRetention>) {
+        let config = ObjectLockConfiguration { enabled: true, default_retention };
+        storage.save_bucket_config(bucket, OBJECT_LOCK_CONFIG, &config).await.unwrap();
+    }
+
+    fn retention(mode: ObjectLockMode, days: i64) -> ObjectRetention {
+        ObjectRetention { mode, retain_until: chrono::Utc::now() + chrono::Duration::days(days) }
+    }
+
+    #[tokio::test]
+    async fn default_retention_is_applied_to_new_objects() {
+        let (_dir, storage, objects) = setup().await;
+        enable_object_lock(&storage, "src", Some(crate::models::DefaultRetention { mode: ObjectLockMode::Compliance, days: 7 })).await;
+        put(&objects, "src", "a", b"locked").await;
+
+        let metadata = record(&storage, "src", "a").await;
+        assert_eq!(metadata.retention_mode, Some(ObjectLockMode::Compliance));
+        assert_eq!(metadata.retain_until.unwrap() - metadata.created_at, chrono::Duration::days(7));
+        assert!(objects.delete_object("src", "a").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn locked_objects_cannot_be_deleted_overwritten_or_renamed() {
+        let (_dir, storage, objects) = setup().await;
+        enable_object_lock(&storage, "src", None).await;
+        put(&objects, "src", "a", b"locked").await;
+        put(&objects, "src", "other", b"other").await;
+        objects.put_object_retention("src", "a", retention(ObjectLockMode::Governance, 1), false).await.unwrap();
+
+        assert!(objects.delete_object("src", "a").await.is_err());
+        assert!(objects.put_object("src", "a", b"new".to_vec(), "text/plain", HashMap::new()).await.is_err());
+        assert!(objects.copy_object("src", "a", copy_request("src", "other"), None, None).await.is_err());
+        assert!(objects.rename_object("src", "a", "b").await.is_err());
+        assert_eq!(objects.get_object("src", "a").await.unwrap().0, b"locked");
+
+        // 启用版本控制时写入新版本，锁定的版本保持不变
+        objects.put_object_with_versioning_and_custom_etag("src", "a", b"v2".to_vec(), "text/plain", HashMap::new(), true, None).await.unwrap();
+        assert_eq!(objects.get_object("src", "a").await.unwrap().0, b"v2");
+    }
+
+    #[tokio::test]
+    async fn legal_hold_blocks_deletion_until_released() {
+        let (_dir, storage, objects) = setup().await;
+        enable_object_lock(&storage, "src", None).await;
+        put(&objects, "src", "a", b"held").await;
+
+        objects.put_object_legal_hold("src", "a", true).await.unwrap();
+        assert!(objects.delete_object("src", "a").await.unwrap_err().to_string().contains("legal hold"));
+        assert!(objects.delete_object_with_governance_bypass("src", "a", true).await.is_err());
+
+        objects.put_object_legal_hold("src", "a", false).await.unwrap();
+        objects.delete_object("src", "a").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn retention_can_be_extended_but_not_weakened() {
+        let (_dir, storage, objects) = setup().await;
+        enable_object_lock(&storage, "src", None).await;
+        put(&objects, "src", "a", b"data").await;
+
+        objects.put_object_retention("src", "a", retention(ObjectLockMode::Compliance, 2), false).await.unwrap();
+        objects.put_object_retention("src", "a", retention(ObjectLockMode::Compliance, 3), false).await.unwrap();
+        assert!(objects.put_object_retention("src", "a", retention(ObjectLockMode::Compliance, 1), true).await.is_err());
+        assert!(objects.put_object_retention("src", "a", retention(ObjectLockMode::Governance, 5), true).await.is_err());
+        assert!(objects.delete_object_with_governance_bypass("src", "a", true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn governance_bypass_is_audited() {
+        let (dir, storage, objects) = setup().await;
+        let data_dir = dir.path().to_string_lossy().into_owned();
+        let objects = objects.with_audit(AuditLog::open(&data_dir, 1024 * 1024).unwrap());
+        enable_object_lock(&storage, "src", None).await;
+        put(&objects, "src", "a", b"data").await;
+        objects.put_object_retention("src", "a", retention(ObjectLockMode::Governance, 2), false).await.unwrap();
+
+        assert!(objects.put_object_retention("src", "a", retention(ObjectLockMode::Governance, 1), false).await.is_err());
+        objects.put_object_retention("src", "a", retention(ObjectLockMode::Governance, 1), true).await.unwrap();
+        objects.delete_object_with_governance_bypass("src", "a", true).await.unwrap();
+
+        let log = fs::read_dir(dir.path().join(".sevino.audit")).unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<String>();
+        assert_eq!(log.matches("BypassGovernanceRetention").count(), 2);
+        assert!(crate::audit::verify(&data_dir).unwrap().error.is_none());
+    }
+
+    #[tokio::test]
+    async fn governance_bypass_requires_the_audit_log() {
+        let (_dir, storage, objects) = setup().await;
+        enable_object_lock(&storage, "src", None).await;
+        put(&objects, "src", "a", b"data").await;
+        objects.put_object_retention("src", "a", retention(ObjectLockMode::Governance, 1), false).await.unwrap();
+
+        assert!(objects.delete_object_with_governance_bypass("src", "a", true).await.is_err());
+        assert_eq!(objects.get_object("src", "a").await.unwrap().0, b"data");
+    }
+
+    #[tokio::test]
+    async fn same_bucket_copy_references_the_source_data() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"hello").await;
+
+        objects.copy_object("src", "b", copy_request("src", "a"), None, None).await.unwrap();
+        objects.copy_object("src", "c", copy_request("src", "b"), None, None).await.unwrap();
+
+        let holder_id = StorageService::generate_object_id("src", "a");
+        assert_eq!(record(&storage, "src", "a").await.reference_count, 2);
+        assert_eq!(record(&storage, "src", "b").await.data_holder_id.as_deref(), Some(holder_id.as_str()));
+        assert_eq!(record(&storage, "src", "c").await.data_holder_id.as_deref(), Some(holder_id.as_str()));
+        assert_eq!(objects.get_object("src", "c").await.unwrap().0, b"hello");
+
+        objects.delete_object("src", "b").await.unwrap();
+        assert_eq!(record(&storage, "src", "a").await.reference_count, 1);
+    }
+
+    #[tokio::test]
+    async fn copy_over_a_reference_releases_the_old_holder() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"first").await;
+        put(&objects, "src", "b", b"second").await;
+        objects.copy_object("src", "c", copy_request("src", "a"), None, None).await.unwrap();
+
+        objects.copy_object("src", "c", copy_request("src", "b"), None, None).await.unwrap();
+        assert_eq!(record(&storage, "src", "a").await.reference_count, 0);
+        assert_eq!(record(&storage, "src", "b").await.reference_count, 1);
+        assert_eq!(objects.get_object("src", "c").await.unwrap().0, b"second");
+    }
+
+    #[tokio::test]
+    async fn copy_over_an_object_with_data_removes_the_old_file() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"source").await;
+        put(&objects, "src", "b", b"overwritten").await;
+        let old_path = storage.get_object_data_path("src", &StorageService::generate_object_id("src", "b"));
+        assert!(old_path.exists());
+
+        objects.copy_object("src", "b", copy_request("src", "a"), None, None).await.unwrap();
+        assert!(!old_path.exists());
+        assert_eq!(objects.get_object("src", "b").await.unwrap().0, b"source");
+    }
+
+    #[tokio::test]
+    async fn copy_refuses_to_overwrite_a_referenced_holder() {
+        let (_dir, _storage, objects) = setup().await;
+        put(&objects, "src", "a", b"holder").await;
+        put(&objects, "src", "other", b"other").await;
+        objects.copy_object("src", "ref", copy_request("src", "a"), None, None).await.unwrap();
+
+        let err = objects.copy_object("src", "a", copy_request("src", "other"), None, None).await.unwrap_err();
+        assert!(err.to_string().contains("reference"));
+        assert_eq!(objects.get_object("src", "ref").await.unwrap().0, b"holder");
+    }
+
+    #[tokio::test]
+    async fn cross_bucket_copy_writes_independent_data() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"payload").await;
+
+        objects.copy_object("dst", "a", copy_request("src", "a"), None, None).await.unwrap();
+        objects.delete_object("src", "a").await.unwrap();
+
+        assert!(record(&storage, "dst", "a").await.data_holder_id.is_none());
+        assert_eq!(objects.get_object("dst", "a").await.unwrap().0, b"payload");
+    }
+
+    #[tokio::test]
+    async fn failed_cross_bucket_copy_keeps_the_destination() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"new").await;
+        put(&objects, "dst", "a", b"old").await;
+
+        // 源数据文件丢失时复制失败，目标对象不能受影响
+        fs::remove_file(storage.get_object_data_path("src", &StorageService::generate_object_id("src", "a"))).unwrap();
+        assert!(objects.copy_object("dst", "a", copy_request("src", "a"), None, None).await.is_err());
+
+        let (data, metadata) = objects.get_object("dst", "a").await.unwrap();
+        assert_eq!(data, b"old");
+        assert_eq!(metadata.etag, record(&storage, "dst", "a").await.etag);
+    }
+
+    fn keys(keys: &[&str]) -> std::collections::HashSet<String> {
+        keys.iter().map(|k| k.to_string()).collect()
+    }
+
+    async fn stored_keys(storage: &StorageService, bucket: &str) -> Vec<String> {
+        let mut keys: Vec<String> = storage.list_object_metadata(bucket).await.unwrap().into_iter().map(|m| m.key).collect();
+        keys.sort();
+        keys
+    }
+
+    #[tokio::test]
+    async fn rename_prefix_moves_only_the_authorized_keys() {
+        let (_dir, storage, objects) = setup().await;
+        for key in ["logs/a", "logs/b", "logs/c", "other"] {
+            put(&objects, "src", key, key.as_bytes()).await;
+        }
+
+        let report = objects.rename_prefix("src", "logs/", "archive/", &keys(&["logs/a", "logs/b"])).await.unwrap();
+        assert_eq!(report.renamed.len(), 2);
+        assert_eq!(stored_keys(&storage, "src").await, ["archive/a", "archive/b", "logs/c", "other"]);
+        assert_eq!(objects.get_object("src", "archive/b").await.unwrap().0, b"logs/b");
+        assert!(objects.get_object("src", "logs/a").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rename_is_rejected_when_a_destination_exists() {
+        let (_dir, storage, objects) = setup().await;
+        for key in ["logs/a", "logs/b", "archive/b"] {
+            put(&objects, "src", key, key.as_bytes()).await;
+        }
+
+        let all = keys(&["logs/a", "logs/b"]);
+        assert!(objects.rename_prefix("src", "logs/", "archive/", &all).await.unwrap_err().to_string().contains("already exists"));
+        assert_eq!(stored_keys(&storage, "src").await, ["archive/b", "logs/a", "logs/b"]);
+        assert_eq!(objects.get_object("src", "archive/b").await.unwrap().0, b"archive/b");
+    }
+
+    #[tokio::test]
+    async fn rename_is_rejected_when_any_record_is_locked() {
+        let (_dir, storage, objects) = setup().await;
+        enable_object_lock(&storage, "src", None).await;
+        put(&objects, "src", "logs/a", b"a").await;
+        put(&objects, "src", "logs/b", b"b").await;
+        objects.put_object_legal_hold("src", "logs/b", true).await.unwrap();
+
+        assert!(objects.rename_prefix("src", "logs/", "archive/", &keys(&["logs/a", "logs/b"])).await.is_err());
+        assert_eq!(stored_keys(&storage, "src").await, ["logs/a", "logs/b"]);
+        assert_eq!(objects.get_object("src", "logs/a").await.unwrap().0, b"a");
+    }
+
+    #[tokio::test]
+    async fn failed_rename_step_rolls_back_completed_steps() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "logs/a", b"a").await;
+        put(&objects, "src", "logs/b", b"b").await;
+
+        // 让按执行顺序的最后一步无法移动数据文件
+        let last = storage.list_object_metadata("src").await.unwrap().pop().unwrap();
+        let blocked = storage.get_object_data_path("src", &StorageService::generate_object_id("src", &last.key.replace("logs/", "archive/")));
+        fs::create_dir_all(blocked.join("occupied")).unwrap();
+
+        assert!(objects.rename_prefix("src", "logs/", "archive/", &keys(&["logs/a", "logs/b"])).await.is_err());
+        assert_eq!(stored_keys(&storage, "src").await, ["logs/a", "logs/b"]);
+        assert_eq!(objects.get_object("src", "logs/a").await.unwrap().0, b"a");
+        assert_eq!(objects.get_object("src", "logs/b").await.unwrap().0, b"b");
+        assert!(objects.get_object("src", "archive/a").await.is_err());
+        assert!(!storage.get_object_data_path("src", &StorageService::generate_object_id("src", "archive/a")).is_file());
+    }
+
+    #[tokio::test]
+    async fn renaming_a_data_holder_relinks_its_references() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"shared").await;
+        objects.copy_object("src", "ref", copy_request("src", "a"), None, None).await.unwrap();
+
+        objects.rename_object("src", "a", "moved").await.unwrap();
+
+        let holder_id = StorageService::generate_object_id("src", "moved");
+        assert_eq!(record(&storage, "src", "ref").await.data_holder_id.as_deref(), Some(holder_id.as_str()));
+        assert_eq!(record(&storage, "src", "moved").await.reference_count, 1);
+        assert_eq!(objects.get_object("src", "ref").await.unwrap().0, b"shared");
+        assert_eq!(objects.get_object("src", "moved").await.unwrap().0, b"shared");
+    }
+
+    #[tokio::test]
+    async fn renaming_a_reference_keeps_its_holder() {
+        let (_dir, storage, objects) = setup().await;
+        put(&objects, "src", "a", b"shared").await;
+        objects.copy_object("src", "ref", copy_request("src", "a"), None, None).await.unwrap();
+
+        objects.rename_object("src", "ref", "renamed").await.unwrap();
+
+        let holder_id = StorageService::generate_object_id("src", "a");
+        assert_eq!(record(&storage, "src", "renamed").await.data_holder_id.as_deref(), Some(holder_id.as_str()));
+        assert_eq!(objects.get_object("src", "renamed").await.unwrap().0, b"shared");
+        objects.delete_object("src", "renamed").await.unwrap();
+        assert_eq!(record(&storage, "src", "a").await.reference_count, 0);
+    }
+}
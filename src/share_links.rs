@@ -0,0 +1,236 @@
+use crate::models::{CreateShareLinkRequest, ShareLink};
+use crate::services::ObjectService;
+use crate::utils::load_or_create_secret;
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// 默认有效期（秒）
+const DEFAULT_EXPIRES_IN_SECS: u64 = 3600;
+/// 最长有效期（秒）
+const MAX_EXPIRES_IN_SECS: u64 = 7 * 24 * 3600;
+
+/// 分享链接兑换失败原因
+#[derive(Debug, thiserror::Error)]
+pub enum ShareLinkError {
+    #[error("Share link not found")]
+    NotFound,
+    #[error("Invalid share link signature")]
+    InvalidSignature,
+    #[error("Share link has expired")]
+    Expired,
+    #[error("Share link has been revoked")]
+    Revoked,
+    #[error("Share link download limit reached")]
+    DownloadLimitReached,
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+/// 分享链接服务 - 生成带HMAC签名和过期时间的下载链接，链接记录保存在 {data_dir}/.sevino.shares
+#[derive(Clone)]
+pub struct ShareLinkService {
+    object_service: ObjectService,
+    links_dir: PathBuf,
+    secret: Vec<u8>,
+    /// 串行化下载计数的读-改-写
+    lock: Arc<Mutex<()>>,
+}
+
+impl ShareLinkService {
+    /// 未配置密钥时自动生成一个并保存到 .sevino.shares/secret，保证重启后链接仍然有效
+    pub fn new(object_service: ObjectService, data_dir: &str, secret: &str) -> Result<Self> {
+        let links_dir = PathBuf::from(data_dir).join(".sevino.shares");
+        fs::create_dir_all(&links_dir)?;
+
+        let secret = load_or_create_secret(&links_dir, secret)?;
+
+        Ok(Self {
+            object_service,
+            links_dir,
+            secret: secret.into_bytes(),
+            lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// 创建分享链接（对象或指定版本必须存在）
+    pub async fn create(&self, bucket_name: &str, key: &str, request: CreateShareLinkRequest, created_by: &str) -> Result<ShareLink> {
+        let expires_in = request.expires_in_secs.unwrap_or(DEFAULT_EXPIRES_IN_SECS);
+        if expires_in == 0 || expires_in > MAX_EXPIRES_IN_SECS {
+            return Err(anyhow!("expires_in_secs must be between 1 and {}", MAX_EXPIRES_IN_SECS));
+        }
+        if request.max_downloads == Some(0) {
+            return Err(anyhow!("max_downloads must be greater than 0"));
+        }
+        if let Some(filename) = &request.filename {
+            Self::validate_filename(filename)?;
+        }
+
+        match &request.version_id {
+            Some(version_id) => {
+                self.object_service.get_object_version_metadata(bucket_name, key, version_id).await?;
+            }
+            None => {
+                self.object_service.get_object_metadata(bucket_name, key).await?;
+            }
+        }
+
+        let now = Utc::now();
+        let id = uuid::Uuid::new_v4().to_string();
+        let expires_at = now + Duration::seconds(expires_in as i64);
+
+        let mut link = ShareLink {
+            id,
+            url: String::new(),
+            bucket_name: bucket_name.to_string(),
+            key: key.to_string(),
+            version_id: request.version_id,
+            filename: request.filename,
+            max_downloads: request.max_downloads,
+            downloads: 0,
+            created_by: created_by.to_string(),
+            created_at: now,
+            expires_at,
+            revoked: false,
+        };
+        let signature = self.sign(&link, expires_at.timestamp())?;
+        link.url = format!("/share/{}?expires={}&signature={}", link.id, expires_at.timestamp(), signature);
+
+        self.save(&link)?;
+        Ok(link)
+    }
+
+    /// 获取分享链接
+    pub fn get(&self, id: &str) -> Result<ShareLink, ShareLinkError> {
+        let path = self.link_path(id)?;
+        if !path.exists() {
+            return Err(ShareLinkError::NotFound);
+        }
+        let content = fs::read_to_string(path).map_err(anyhow::Error::from)?;
+        Ok(serde_json::from_str(&content).map_err(anyhow::Error::from)?)
+    }
+
+    /// 撤销分享链接
+    pub async fn revoke(&self, id: &str) -> Result<ShareLink, ShareLinkError> {
+        let _guard = self.lock.lock().await;
+        let mut link = self.get(id)?;
+        link.revoked = true;
+        self.save(&link)?;
+        Ok(link)
+    }
+
+    /// 校验签名、有效期和下载次数；下载次数在对象读取成功后由 record_download 记录
+    pub async fn redeem(&self, id: &str, expires: i64, signature: &str) -> Result<ShareLink, ShareLinkError> {
+        let link = self.get(id)?;
+
+        let signature = hex_decode(signature).ok_or(ShareLinkError::InvalidSignature)?;
+        let mac = self.mac(&link, expires)?;
+        if mac.verify_slice(&signature).is_err() || expires != link.expires_at.timestamp() {
+            return Err(ShareLinkError::InvalidSignature);
+        }
+        Self::check_usable(&link)?;
+        Ok(link)
+    }
+
+    /// 记录一次成功的下载；并发下载时重新检查，保证不超过下载次数上限
+    pub async fn record_download(&self, id: &str) -> Result<ShareLink, ShareLinkError> {
+        let _guard = self.lock.lock().await;
+        let mut link = self.get(id)?;
+        Self::check_usable(&link)?;
+
+        link.downloads += 1;
+        self.save(&link)?;
+        Ok(link)
+    }
+
+    fn check_usable(link: &ShareLink) -> Result<(), ShareLinkError> {
+        if link.revoked {
+            return Err(ShareLinkError::Revoked);
+        }
+        if Utc::now() >= link.expires_at {
+            return Err(ShareLinkError::Expired);
+        }
+        if link.max_downloads.map(|max| link.downloads >= max).unwrap_or(false) {
+            return Err(ShareLinkError::DownloadLimitReached);
+        }
+        Ok(())
+    }
+
+    /// 读取分享链接指向的对象
+    pub async fn fetch(&self, link: &ShareLink) -> Result<(Vec<u8>, crate::models::ObjectMetadata)> {
+        match &link.version_id {
+            Some(version_id) => self.object_service.get_object_version(&link.bucket_name, &link.key, version_id).await,
+            None => self.object_service.get_object(&link.bucket_name, &link.key).await,
+        }
+    }
+
+    /// 生成 Content-Disposition 头
+    pub fn content_disposition(filename: &str) -> String {
+        let encoded = percent_encoding::utf8_percent_encode(filename, percent_encoding::NON_ALPHANUMERIC);
+        let ascii: String = filename.chars().map(|c| if c.is_ascii() { c } else { '_' }).collect();
+        format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", ascii, encoded)
+    }
+
+    fn validate_filename(filename: &str) -> Result<()> {
+        if filename.is_empty() || filename.len() > 255 {
+            return Err(anyhow!("filename must be between 1 and 255 bytes"));
+        }
+        if filename.chars().any(|c| c.is_control() || matches!(c, '/' | '\\' | '"')) {
+            return Err(anyhow!("filename must not contain control characters, slashes or quotes"));
+        }
+        Ok(())
+    }
+
+    /// 签名内容：ID、桶、key、版本、过期时间和文件名
+    fn mac(&self, link: &ShareLink, expires: i64) -> Result<Hmac<Sha256>> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret)
+            .map_err(|e| anyhow!("Invalid share link secret: {}", e))?;
+        let canonical = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            link.id,
+            link.bucket_name,
+            link.key,
+            link.version_id.as_deref().unwrap_or(""),
+            expires,
+            link.filename.as_deref().unwrap_or("")
+        );
+        mac.update(canonical.as_bytes());
+        Ok(mac)
+    }
+
+    fn sign(&self, link: &ShareLink, expires: i64) -> Result<String> {
+        Ok(format!("{:x}", self.mac(link, expires)?.finalize().into_bytes()))
+    }
+
+    fn link_path(&self, id: &str) -> Result<PathBuf, ShareLinkError> {
+        // ID为UUID，拒绝其他字符以防路径穿越
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            return Err(ShareLinkError::NotFound);
+        }
+        Ok(self.links_dir.join(format!("{}.json", id)))
+    }
+
+    /// 原子写入链接记录
+    fn save(&self, link: &ShareLink) -> Result<()> {
+        let path = self.links_dir.join(format!("{}.json", link.id));
+        let tmp_path = self.links_dir.join(format!(".{}.tmp", link.id));
+        fs::write(&tmp_path, serde_json::to_string_pretty(link)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
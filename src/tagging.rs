@@ -0,0 +1,40 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+/// 每个对象最多的标签数
+pub const MAX_OBJECT_TAGS: usize = 10;
+/// 标签键的最大长度（字符）
+pub const MAX_TAG_KEY_LENGTH: usize = 128;
+/// 标签值的最大长度（字符）
+pub const MAX_TAG_VALUE_LENGTH: usize = 256;
+/// 保留给系统使用的标签键前缀
+const RESERVED_TAG_PREFIX: &str = "sevino:";
+
+/// 校验对象标签集：数量、长度和字符（字母、数字、空格和 + - = . _ : / @）
+pub fn validate_tags(tags: &HashMap<String, String>) -> Result<()> {
+    if tags.len() > MAX_OBJECT_TAGS {
+        return Err(anyhow!("An object can have at most {} tags, got {}", MAX_OBJECT_TAGS, tags.len()));
+    }
+
+    for (key, value) in tags {
+        let key_length = key.chars().count();
+        if key_length == 0 || key_length > MAX_TAG_KEY_LENGTH {
+            return Err(anyhow!("Tag key '{}' must be 1 to {} characters long", key, MAX_TAG_KEY_LENGTH));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LENGTH {
+            return Err(anyhow!("Value of tag '{}' must be at most {} characters long", key, MAX_TAG_VALUE_LENGTH));
+        }
+        if key.to_ascii_lowercase().starts_with(RESERVED_TAG_PREFIX) {
+            return Err(anyhow!("Tag key '{}' uses the reserved prefix '{}'", key, RESERVED_TAG_PREFIX));
+        }
+        if let Some(c) = key.chars().chain(value.chars()).find(|c| !is_valid_tag_char(*c)) {
+            return Err(anyhow!("Tag '{}' contains invalid character {:?}", key, c));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_valid_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == ' ' || "+-=._:/@".contains(c)
+}
@@ -1,128 +1,259 @@
-use sha2::{Sha256, Digest};
-use std::path::Path;
-
-/// 计算SHA256哈希
-pub fn sha256_hash(data: &[u8]) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    format!("{:x}", hasher.finalize())
-}
-
-/// 计算MD5哈希
-pub fn md5_hash(data: &[u8]) -> String {
-    let digest = md5::compute(data);
-    format!("{:x}", digest)
-}
-
-/// 清理路径，防止路径遍历攻击
-pub fn sanitize_path(path: &str) -> String {
-    path_clean::clean(path)
-}
-
-/// 验证桶名称
-pub fn validate_bucket_name(name: &str) -> Result<(), String> {
-    if name.is_empty() {
-        return Err("Bucket name cannot be empty".to_string());
-    }
-    
-    if name.len() > 63 {
-        return Err("Bucket name cannot be longer than 63 characters".to_string());
-    }
-    
-    if !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
-        return Err("Bucket name can only contain alphanumeric characters and hyphens".to_string());
-    }
-    
-    if name.starts_with('-') || name.ends_with('-') {
-        return Err("Bucket name cannot start or end with a hyphen".to_string());
-    }
-    
-    if name.chars().next().unwrap().is_numeric() {
-        return Err("Bucket name cannot start with a number".to_string());
-    }
-    
-    Ok(())
-}
-
-/// 验证对象键
-pub fn validate_object_key(key: &str) -> Result<(), String> {
-    if key.is_empty() {
-        return Err("Object key cannot be empty".to_string());
-    }
-    
-    if key.len() > 1024 {
-        return Err("Object key cannot be longer than 1024 characters".to_string());
-    }
-    
-    if key.contains("..") {
-        return Err("Object key cannot contain '..'".to_string());
-    }
-    
-    Ok(())
-}
-
-/// 生成ETag
-pub fn generate_etag(data: &[u8]) -> String {
-    format!("\"{}\"", md5_hash(data))
-}
-
-/// 获取文件扩展名对应的MIME类型
-pub fn get_mime_type(filename: &str) -> String {
-    let ext = Path::new(filename)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    
-    match ext.as_str() {
-        "html" | "htm" => "text/html",
-        "css" => "text/css",
-        "js" => "application/javascript",
-        "json" => "application/json",
-        "xml" => "application/xml",
-        "txt" => "text/plain",
-        "pdf" => "application/pdf",
-        "jpg" | "jpeg" => "image/jpeg",
-        "png" => "image/png",
-        "gif" => "image/gif",
-        "svg" => "image/svg+xml",
-        "ico" => "image/x-icon",
-        "zip" => "application/zip",
-        "tar" => "application/x-tar",
-        "gz" => "application/gzip",
-        "mp4" => "video/mp4",
-        "mp3" => "audio/mpeg",
-        "wav" => "audio/wav",
-        _ => "application/octet-stream",
-    }.to_string()
-}
-
-/// 验证ETag格式
-pub fn is_valid_etag_format(etag: &str) -> bool {
-    // 支持以下格式：
-    // 1. "hash" - 标准ETag
-    // 2. W/"weak-etag" - 弱ETag
-    // 3. "sha256-hash" - SHA256格式
-    // 4. "version-id" - 版本标识
-    
-    if etag.is_empty() {
-        return false;
-    }
-    
-    // 弱ETag格式: W/"weak-etag"
-    if etag.starts_with("W/\"") && etag.ends_with("\"") {
-        return true;
-    }
-    
-    // 标准ETag格式: "hash"
-    if etag.starts_with("\"") && etag.ends_with("\"") {
-        return true;
-    }
-    
-    // 无引号格式（向后兼容）
-    if !etag.contains('"') && !etag.contains(' ') {
-        return true;
-    }
-    
-    false
-} 
\ No newline at end of file
+use axum::http::{HeaderMap, header};
+use sha2::{Sha256, Digest};
+use subtle::ConstantTimeEq;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// 计算SHA256哈希
+pub fn sha256_hash(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+/// 读取签名密钥：已配置时直接使用，否则读取 {dir}/secret，不存在时生成一个并保存，保证重启后签名仍然有效
+pub fn load_or_create_secret(dir: &Path, configured: &str) -> std::io::Result<String> {
+    if !configured.is_empty() {
+        return Ok(configured.to_string());
+    }
+    let secret_path = dir.join("secret");
+    match std::fs::read_to_string(&secret_path) {
+        Ok(secret) => Ok(secret.trim().to_string()),
+        Err(_) => {
+            let secret = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+            std::fs::write(&secret_path, &secret)?;
+            Ok(secret)
+        }
+    }
+}
+
+/// 计算MD5哈希
+pub fn md5_hash(data: &[u8]) -> String {
+    let digest = md5::compute(data);
+    format!("{:x}", digest)
+}
+
+/// 清理路径，防止路径遍历攻击
+#[allow(dead_code)]
+pub fn sanitize_path(path: &str) -> String {
+    path_clean::clean(path)
+}
+
+/// 验证桶名称
+pub fn validate_bucket_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Bucket name cannot be empty".to_string());
+    }
+    
+    if name.len() > 63 {
+        return Err("Bucket name cannot be longer than 63 characters".to_string());
+    }
+    
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        return Err("Bucket name can only contain alphanumeric characters and hyphens".to_string());
+    }
+    
+    if name.starts_with('-') || name.ends_with('-') {
+        return Err("Bucket name cannot start or end with a hyphen".to_string());
+    }
+    
+    if name.chars().next().unwrap().is_numeric() {
+        return Err("Bucket name cannot start with a number".to_string());
+    }
+    
+    Ok(())
+}
+
+/// 验证对象键
+pub fn validate_object_key(key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Err("Object key cannot be empty".to_string());
+    }
+    
+    if key.len() > 1024 {
+        return Err("Object key cannot be longer than 1024 characters".to_string());
+    }
+    
+    if key.contains("..") {
+        return Err("Object key cannot contain '..'".to_string());
+    }
+    
+    Ok(())
+}
+
+/// 生成ETag
+pub fn generate_etag(data: &[u8]) -> String {
+    format!("\"{}\"", md5_hash(data))
+}
+
+/// 获取文件扩展名对应的MIME类型
+pub fn get_mime_type(filename: &str) -> String {
+    crate::content_type::from_extension(filename)
+        .unwrap_or(crate::content_type::OCTET_STREAM)
+        .to_string()
+}
+
+/// 验证ETag格式
+pub fn is_valid_etag_format(etag: &str) -> bool {
+    // 支持以下格式：
+    // 1. "hash" - 标准ETag
+    // 2. W/"weak-etag" - 弱ETag
+    // 3. "sha256-hash" - SHA256格式
+    // 4. "version-id" - 版本标识
+    
+    if etag.is_empty() {
+        return false;
+    }
+    
+    // 弱ETag格式: W/"weak-etag"
+    if etag.starts_with("W/\"") && etag.ends_with("\"") {
+        return true;
+    }
+    
+    // 标准ETag格式: "hash"
+    if etag.starts_with("\"") && etag.ends_with("\"") {
+        return true;
+    }
+    
+    // 无引号格式（向后兼容）
+    if !etag.contains('"') && !etag.contains(' ') {
+        return true;
+    }
+    
+    false
+} 
+
+/// 通配符匹配（* 匹配任意字符序列，? 匹配单个字符）
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 以常数时间比较两个密钥字符串（只泄露长度），用于令牌校验
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// 检查IP是否属于指定网段（支持 CIDR 或单个IP）
+pub fn ip_in_cidr(ip: &IpAddr, cidr: &str) -> Result<bool, String> {
+    let (network, prefix_len) = match cidr.split_once('/') {
+        Some((network, len)) => (network, Some(len)),
+        None => (cidr, None),
+    };
+    let network: IpAddr = network.trim().parse().map_err(|_| format!("Invalid IP address: {}", cidr))?;
+
+    let (ip_bits, network_bits, max_len) = match (ip, &network) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => (u32::from(*ip) as u128, u32::from(*net) as u128, 32),
+        (IpAddr::V6(ip), IpAddr::V6(net)) => (u128::from(*ip), u128::from(*net), 128),
+        (IpAddr::V6(ip), IpAddr::V4(net)) => match ip.to_ipv4_mapped() {
+            Some(ip) => (u32::from(ip) as u128, u32::from(*net) as u128, 32),
+            None => return Ok(false),
+        },
+        _ => return Ok(false),
+    };
+
+    let prefix_len: u32 = match prefix_len {
+        Some(len) => len.trim().parse().map_err(|_| format!("Invalid CIDR prefix: {}", cidr))?,
+        None => max_len,
+    };
+    if prefix_len > max_len {
+        return Err(format!("Invalid CIDR prefix: {}", cidr));
+    }
+    if prefix_len == 0 {
+        return Ok(true);
+    }
+
+    let shift = max_len - prefix_len;
+    Ok(ip_bits >> shift == network_bits >> shift)
+}
+
+/// 解析 Range 请求头，返回闭区间 [start, end]
+///
+/// 只支持单个 `bytes` 区间；格式不支持时返回 `Ok(None)`（按完整内容响应），区间无法满足时返回错误。
+pub fn parse_range_header(value: &str, size: u64) -> Result<Option<(u64, u64)>, String> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", "") => return Ok(None),
+        // bytes=-N：最后N个字节
+        ("", suffix) => {
+            let Ok(suffix) = suffix.parse::<u64>() else { return Ok(None) };
+            if suffix == 0 || size == 0 {
+                return Err(format!("Range not satisfiable: {}", value));
+            }
+            (size.saturating_sub(suffix), size - 1)
+        }
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else { return Ok(None) };
+            let end = if end.is_empty() {
+                size.saturating_sub(1)
+            } else {
+                let Ok(end) = end.parse::<u64>() else { return Ok(None) };
+                if end < start {
+                    return Ok(None);
+                }
+                end.min(size.saturating_sub(1))
+            };
+            if start >= size {
+                return Err(format!("Range not satisfiable: {}", value));
+            }
+            (start, end)
+        }
+    };
+
+    Ok(Some((start, end)))
+}
+
+/// 优先使用已知的消息体长度，否则使用 Content-Length
+pub fn body_length(headers: &HeaderMap, exact: Option<u64>) -> u64 {
+    exact
+        .or_else(|| {
+            headers
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+/// 判断 If-Match / If-None-Match 条件是否匹配ETag（支持 *、逗号分隔的列表和弱ETag，比较时忽略引号；
+/// 压缩响应的 ETag 带有 "-gzip" 等编码后缀，与原始 ETag 视为相同）
+pub fn etag_condition_matches(condition: &str, etag: &str) -> bool {
+    let normalize = |value: &str| value.trim().trim_start_matches("W/").trim_matches('"').to_string();
+    let etag = normalize(etag);
+    condition
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| {
+            let candidate = normalize(candidate);
+            candidate == "*" || candidate == etag || crate::response_compression::strip_encoding_suffix(&candidate) == etag
+        })
+}
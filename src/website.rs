@@ -0,0 +1,183 @@
+use crate::models::{ObjectMetadata, WebsiteConfiguration, WebsiteRedirectRule};
+use crate::services::{ObjectService, StorageService};
+use crate::utils::get_mime_type;
+use anyhow::{Result, anyhow};
+
+/// 静态网站配置文件名（位于 .sevino.meta 目录下）
+pub const WEBSITE_CONFIG: &str = "website";
+/// 允许的重定向状态码
+const REDIRECT_CODES: [u16; 5] = [301, 302, 303, 307, 308];
+
+/// 校验静态网站配置
+pub fn validate_configuration(config: &WebsiteConfiguration) -> Result<()> {
+    if config.index_document.is_empty() || config.index_document.contains('/') {
+        return Err(anyhow!("index_document must be a non-empty file name without '/'"));
+    }
+    if config.error_document.as_deref().is_some_and(|d| d.is_empty() || d.starts_with('/')) {
+        return Err(anyhow!("error_document must be an object key without a leading '/'"));
+    }
+
+    for (i, rule) in config.redirect_rules.iter().enumerate() {
+        if !REDIRECT_CODES.contains(&rule.http_redirect_code) {
+            return Err(anyhow!("Redirect rule {}: http_redirect_code must be one of 301, 302, 303, 307 or 308", i + 1));
+        }
+        if rule.http_error_code.is_some_and(|code| code != 404) {
+            return Err(anyhow!("Redirect rule {}: http_error_code only supports 404", i + 1));
+        }
+        if rule.replace_key_prefix_with.is_some() && rule.replace_key_with.is_some() {
+            return Err(anyhow!("Redirect rule {}: replace_key_prefix_with and replace_key_with are mutually exclusive", i + 1));
+        }
+        if rule.protocol.as_deref().is_some_and(|p| p != "http" && p != "https") {
+            return Err(anyhow!("Redirect rule {}: protocol must be 'http' or 'https'", i + 1));
+        }
+        if rule.host_name.as_deref().is_some_and(|h| h.is_empty() || h.contains('/')) {
+            return Err(anyhow!("Redirect rule {}: host_name must be a host name without a path", i + 1));
+        }
+    }
+    Ok(())
+}
+
+/// 网站请求
+pub struct WebsiteRequest<'a> {
+    /// 去掉网站前缀后的路径（已解码，不含开头的 /）
+    pub path: &'a str,
+    /// 本站URL路径前缀（基于路径的访问为 /website/{bucket}，基于主机名的访问为空）
+    pub base: &'a str,
+    /// 请求的 Host 头
+    pub host: Option<&'a str>,
+    /// 请求是否通过HTTPS到达
+    pub secure: bool,
+}
+
+/// 网站请求的处理结果
+pub enum WebsiteResponse {
+    /// 返回对象内容（索引、错误和回退页面也以对象返回）
+    Object {
+        status: u16,
+        data: Vec<u8>,
+        metadata: Box<ObjectMetadata>,
+        content_type: String,
+        cache_control: Option<String>,
+    },
+    Redirect { status: u16, location: String },
+    NotFound,
+}
+
+/// 静态网站托管服务
+#[derive(Clone)]
+pub struct WebsiteService {
+    storage: StorageService,
+    object_service: ObjectService,
+}
+
+impl WebsiteService {
+    pub fn new(storage: StorageService, object_service: ObjectService) -> Self {
+        Self { storage, object_service }
+    }
+
+    /// 处理网站请求：先应用无条件的重定向规则，再依次查找对象、目录索引、404 重定向规则、SPA 回退和错误文档
+    pub async fn serve(&self, bucket_name: &str, request: &WebsiteRequest<'_>) -> Result<WebsiteResponse> {
+        if !self.storage.bucket_exists(bucket_name).await {
+            return Err(anyhow!("Bucket '{}' not found", bucket_name));
+        }
+        let config: WebsiteConfiguration = self.storage.load_bucket_config(bucket_name, WEBSITE_CONFIG).await?
+            .ok_or_else(|| anyhow!("Bucket '{}' is not configured as a website", bucket_name))?;
+
+        let path = request.path;
+        if let Some(rule) = find_rule(&config, path, false) {
+            return Ok(redirect(rule, path, request));
+        }
+
+        let key = if path.is_empty() || path.ends_with('/') {
+            format!("{}{}", path, config.index_document)
+        } else {
+            path.to_string()
+        };
+        if let Some(response) = self.load(bucket_name, &key, 200, &config).await? {
+            return Ok(response);
+        }
+
+        // 请求的是目录但缺少结尾的 /：重定向到目录，页面中的相对链接才能正确解析
+        if !path.is_empty() && !path.ends_with('/') && self.exists(bucket_name, &format!("{}/{}", path, config.index_document)).await {
+            let location = format!("{}/{}/", request.base, percent_encoding::utf8_percent_encode(path, PATH_SEGMENT));
+            return Ok(WebsiteResponse::Redirect { status: 302, location });
+        }
+
+        if let Some(rule) = find_rule(&config, path, true) {
+            return Ok(redirect(rule, path, request));
+        }
+
+        let last_segment = path.rsplit('/').next().unwrap_or("");
+        if config.spa_fallback && !last_segment.contains('.') {
+            if let Some(response) = self.load(bucket_name, &config.index_document, 200, &config).await? {
+                return Ok(response);
+            }
+        }
+
+        if let Some(error_document) = &config.error_document {
+            if let Some(response) = self.load(bucket_name, error_document, 404, &config).await? {
+                return Ok(response);
+            }
+        }
+
+        Ok(WebsiteResponse::NotFound)
+    }
+
+    async fn exists(&self, bucket_name: &str, key: &str) -> bool {
+        matches!(self.object_service.get_object_metadata(bucket_name, key).await, Ok(metadata) if !metadata.is_delete_marker)
+    }
+
+    /// 读取对象（不存在或为删除标记时返回 None）
+    async fn load(&self, bucket_name: &str, key: &str, status: u16, config: &WebsiteConfiguration) -> Result<Option<WebsiteResponse>> {
+        if !self.exists(bucket_name, key).await {
+            return Ok(None);
+        }
+        let (data, metadata) = self.object_service.get_object(bucket_name, key).await?;
+
+        // 未指定内容类型上传的对象按扩展名推断
+        let content_type = if metadata.content_type.is_empty() || metadata.content_type == "application/octet-stream" {
+            get_mime_type(key)
+        } else {
+            metadata.content_type.clone()
+        };
+        let cache_control = if content_type.starts_with("text/html") {
+            config.html_cache_control.clone()
+        } else {
+            config.cache_control.clone()
+        };
+
+        Ok(Some(WebsiteResponse::Object { status, data, metadata: Box::new(metadata), content_type, cache_control }))
+    }
+}
+
+fn find_rule<'a>(config: &'a WebsiteConfiguration, path: &str, not_found: bool) -> Option<&'a WebsiteRedirectRule> {
+    config.redirect_rules.iter()
+        .filter(|rule| rule.http_error_code.is_some() == not_found)
+        .find(|rule| path.starts_with(&rule.key_prefix))
+}
+
+/// 按规则生成重定向地址
+fn redirect(rule: &WebsiteRedirectRule, path: &str, request: &WebsiteRequest<'_>) -> WebsiteResponse {
+    let key = match (&rule.replace_key_with, &rule.replace_key_prefix_with) {
+        (Some(key), _) => key.clone(),
+        (None, Some(prefix)) => format!("{}{}", prefix, &path[rule.key_prefix.len()..]),
+        (None, None) => path.to_string(),
+    };
+    let encoded = percent_encoding::utf8_percent_encode(&key, PATH_SEGMENT).to_string();
+
+    let location = match (&rule.host_name, &rule.protocol) {
+        (None, None) => format!("{}/{}", request.base, encoded),
+        (host_name, protocol) => {
+            let protocol = protocol.as_deref().unwrap_or(if request.secure { "https" } else { "http" });
+            match host_name {
+                Some(host) => format!("{}://{}/{}", protocol, host, encoded),
+                None => format!("{}://{}{}/{}", protocol, request.host.unwrap_or("localhost"), request.base, encoded),
+            }
+        }
+    };
+    WebsiteResponse::Redirect { status: rule.http_redirect_code, location }
+}
+
+/// 重定向地址中需要编码的字符（保留 /）
+const PATH_SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ').add(b'"').add(b'#').add(b'<').add(b'>').add(b'?').add(b'`').add(b'{').add(b'}').add(b'%');